@@ -19,11 +19,11 @@ use ralsei_model::{
         common::{ConsoleSerial, Environment as DeviceEnvironment},
         n3ds::Console3ds,
     },
-    network::Nnid,
+    network::{Identifier, IdentifierKind, Nnid},
     title::{id::TitleId, version::TitleVersion},
 };
 use ralsei_service_account::{
-    client::{AgreementVersionParameter, Client},
+    client::{AgreementVersionParameter, Client, ClientError},
     xml::agreement::AgreementKindValue,
 };
 
@@ -62,6 +62,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         (@subcommand utc =>
             (about: "get the time, in utc, according to the account server")
         )
+        (@subcommand email =>
+            (about: "checks if an email address can be used to register an account")
+            (@arg EMAIL: +required "the email address to check")
+        )
+        (@subcommand pids =>
+            (about: "get the pids of the provided nnids")
+            (@arg NNID: +required +multiple "the nnids to get the pids of")
+        )
     ).get_matches();
 
     let console = Arc::new(RwLock::new(Console3ds::new(|b| {
@@ -175,6 +183,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .await?
         ),
         ("utc", _) => println!("time in utc: {}", client.time().await?),
+        ("email", Some(arguments)) => {
+            match client
+                .validate_email(
+                    arguments
+                        .value_of("EMAIL")
+                        .expect("no email address was provided (this should never happen)"),
+                )
+                .await
+            {
+                Ok(()) => println!("the email address can be used"),
+                Err(ClientError::RegistrationError(error)) => {
+                    println!("the email address cannot be used: {}", error)
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+        ("pids", Some(arguments)) => {
+            let nnids = arguments
+                .values_of("NNID")
+                .expect("no nnids were provided (this should never happen)")
+                .map(|nnid| Identifier::Nnid(Nnid(Cow::Borrowed(nnid))))
+                .collect::<Vec<_>>();
+            for (nnid, pid) in client
+                .convert_id(&nnids, IdentifierKind::Nnid, IdentifierKind::Pid)
+                .await?
+            {
+                match pid {
+                    Some(pid) => println!("{}: {}", nnid, pid),
+                    None => println!("{}: no pid was found", nnid),
+                }
+            }
+        }
         _ => println!("you shouldn't have done that"),
     }
     Ok(())
@@ -14,6 +14,14 @@
 // information. do not ask
 //
 // the password for all pkcs12 identities is "ralsei"
+//
+// the common keys used to decrypt title keys are not included either, and must be supplied by
+// whoever is decrypting titles
+//
+// the certificates that device certificates chain up to (Root-CA00000003, CA00000003 and
+// Nintendo CA - G3_NintendoCTR2prod) are not included yet, as no copy of them has been checked
+// into the repository. until they are, the issuer of device certificates must be supplied by
+// whoever is verifying them, and the account server rejects every device certificate without one
 
 // client identities
 
@@ -0,0 +1,136 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! A minimal DER encoder and decoder, covering only what is needed to convert
+//! [`Key`](super::Key)s to and from `SubjectPublicKeyInfo` structures
+
+/// The tag of an `INTEGER`
+pub(crate) const INTEGER: u8 = 0x02;
+
+/// The tag of a `BIT STRING`
+pub(crate) const BIT_STRING: u8 = 0x03;
+
+/// The tag of a `NULL`
+pub(crate) const NULL: u8 = 0x05;
+
+/// The tag of an `OBJECT IDENTIFIER`
+pub(crate) const OBJECT_IDENTIFIER: u8 = 0x06;
+
+/// The tag of a `SEQUENCE`
+pub(crate) const SEQUENCE: u8 = 0x30;
+
+/// The encoded `rsaEncryption` object identifier (1.2.840.113549.1.1.1)
+pub(crate) const RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+
+/// The encoded `id-ecPublicKey` object identifier (1.2.840.10045.2.1)
+pub(crate) const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+
+/// The encoded `sect233r1` object identifier (1.3.132.0.27)
+pub(crate) const SECT233R1: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x1b];
+
+/// Encodes a value with the provided tag and contents
+pub(crate) fn encode(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    if contents.len() < 0x80 {
+        encoded.push(contents.len() as u8);
+    } else {
+        let length = contents.len().to_be_bytes();
+        let length = &length[length.iter().take_while(|&&b| b == 0).count()..];
+        encoded.push(0x80 | length.len() as u8);
+        encoded.extend_from_slice(length);
+    }
+    encoded.extend_from_slice(contents);
+    encoded
+}
+
+/// Encodes the provided unsigned big-endian integer as an `INTEGER`
+pub(crate) fn encode_integer(integer: &[u8]) -> Vec<u8> {
+    let integer = &integer[integer.iter().take_while(|&&b| b == 0).count()..];
+    let mut contents = Vec::with_capacity(integer.len() + 1);
+    if !matches!(integer.first(), Some(b) if b & 0x80 == 0) {
+        contents.push(0);
+    }
+    contents.extend_from_slice(integer);
+    encode(INTEGER, &contents)
+}
+
+/// Decodes a value with the provided tag from the start of the provided data, returning its
+/// contents and the data following it
+pub(crate) fn decode(data: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    let (&actual_tag, data) = data.split_first()?;
+    let (&length, mut data) = data.split_first()?;
+    if actual_tag != tag {
+        return None;
+    }
+
+    let length = if length < 0x80 {
+        usize::from(length)
+    } else {
+        let length_length = usize::from(length & 0x7f);
+        if length_length == 0 || length_length > 4 || length_length > data.len() {
+            return None;
+        }
+        let (length, rest) = data.split_at(length_length);
+        data = rest;
+        length
+            .iter()
+            .fold(0, |length, &b| (length << 8) | usize::from(b))
+    };
+
+    (length <= data.len()).then(|| data.split_at(length))
+}
+
+/// Decodes an unsigned `INTEGER` from the start of the provided data, returning it as a
+/// big-endian integer without leading zeroes along with the data following it
+pub(crate) fn decode_integer(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (integer, rest) = decode(data, INTEGER)?;
+    if !matches!(integer.first(), Some(b) if b & 0x80 == 0) {
+        return None;
+    }
+    Some((
+        &integer[integer.iter().take_while(|&&b| b == 0).count()..],
+        rest,
+    ))
+}
+
+/// Encodes a `SubjectPublicKeyInfo` holding the provided public key, whose algorithm is identified
+/// by the provided object identifier and parameters
+pub(crate) fn encode_subject_public_key_info(
+    algorithm: &[u8],
+    parameters: &[u8],
+    public_key: &[u8],
+) -> Vec<u8> {
+    let mut algorithm_identifier = encode(OBJECT_IDENTIFIER, algorithm);
+    algorithm_identifier.extend_from_slice(parameters);
+
+    let mut bit_string = Vec::with_capacity(public_key.len() + 1);
+    bit_string.push(0);
+    bit_string.extend_from_slice(public_key);
+
+    let mut contents = encode(SEQUENCE, &algorithm_identifier);
+    contents.extend_from_slice(&encode(BIT_STRING, &bit_string));
+    encode(SEQUENCE, &contents)
+}
+
+/// Decodes a `SubjectPublicKeyInfo`, returning the object identifier of its algorithm, the
+/// encoded parameters of its algorithm and its public key
+pub(crate) fn decode_subject_public_key_info(data: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let (contents, rest) = decode(data, SEQUENCE)?;
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let (algorithm_identifier, contents) = decode(contents, SEQUENCE)?;
+    let (algorithm, parameters) = decode(algorithm_identifier, OBJECT_IDENTIFIER)?;
+    let (bit_string, rest) = decode(contents, BIT_STRING)?;
+    match bit_string.split_first() {
+        Some((0, public_key)) if rest.is_empty() => Some((algorithm, parameters, public_key)),
+        _ => None,
+    }
+}
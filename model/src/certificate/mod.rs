@@ -0,0 +1,1286 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Type definitions related to a general Nintendo certificate container format
+//!
+//! This certificate format is specific to Nintendo consoles and does not appear to match standards
+//! such as ASN.1, creating a need for a standalone implementation of the format.
+//!
+//! Certificates form chains, where each certificate's issuer is the name of the certificate whose
+//! key signed it, prefixed by that certificate's own issuer. [`Certificate::verify`] checks a
+//! single link of such a chain, while [`Key::verify`] can be used to check a certificate signed by
+//! a root key that is not itself distributed as a certificate. Chains are usually stored as
+//! certificates placed back-to-back, which can be read into a [`CertificateChain`] to verify them
+//! in full
+//!
+//! [`Key`]s can be split into their components, or converted to and from the DER and PEM-encoded
+//! `SubjectPublicKeyInfo` structures understood by most other cryptographic tooling
+
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::cast::{FromPrimitive, ToPrimitive};
+use rsa::{BigUint, Hash, PaddingScheme, PublicKey, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    convert::{TryFrom, TryInto},
+    fmt,
+    ops::Range,
+    str::FromStr,
+    string::FromUtf8Error,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use strum_macros::{AsRefStr, Display, EnumString, IntoStaticStr};
+use thiserror::Error;
+
+use crate::console::common::{Kind as ConsoleKind, Type as ConsoleType};
+
+mod der;
+mod sect233r1;
+
+/// The length of an elliptic curve [`Key`], which holds both coordinates of its public point
+const ELLIPTIC_CURVE_KEY_LENGTH: usize = 0x3c;
+
+/// A Nintendo certificate container
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Certificate<'a> {
+    pub signature: Signature<'a>,
+    pub issuer: Issuer<'a>,
+    pub key: Key<'a>,
+    pub name: Name<'a>,
+    pub key_id: KeyId,
+}
+
+impl<'a> Certificate<'a> {
+    /// The message that appears when a panic occurs while trying to convert a c-style enum to a
+    /// u32
+    const CSTYLE_ENUM_TO_U32_PANIC_MESSAGE: &'static str =
+        "unable to convert a c-style enum to a u32 (this should be impossible)";
+
+    /// The message that appears when a panic occurs while trying to convert a slice into an array
+    const SLICE_TO_ARRAY_PANIC_MESSAGE: &'static str =
+        "unable to convert a slice into an array (this should be impossible)";
+
+    /// Creates a new [`Certificate`] from its portions
+    pub const fn new(
+        signature: Signature<'a>,
+        issuer: Issuer<'a>,
+        key: Key<'a>,
+        name: Name<'a>,
+        key_id: KeyId,
+    ) -> Self {
+        Self {
+            signature,
+            issuer,
+            key,
+            name,
+            key_id,
+        }
+    }
+
+    /// Converts a [`Certificate`] into a byte vector
+    pub fn to_bytes(&self) -> Result<Vec<u8>, CertificateError> {
+        let mut certificate = self.signature.to_bytes();
+
+        {
+            let len = certificate.len();
+            certificate.extend(self.issuer.0.as_ref().bytes());
+            certificate.resize(len + 0x40, 0);
+        }
+
+        macro key_match_clause($key_kind:ident, $key_data:ident, $padding_size:literal) {{
+            certificate.extend(
+                &KeyMagic::$key_kind
+                    .to_u32()
+                    .expect(Self::CSTYLE_ENUM_TO_U32_PANIC_MESSAGE)
+                    .to_be_bytes(),
+            );
+            {
+                let len = certificate.len();
+                certificate.extend(self.name.0.as_ref().bytes());
+                certificate.resize(len + 0x40, 0);
+            }
+            certificate.extend(&self.key_id.0.to_be_bytes());
+            certificate.extend($key_data.as_ref());
+            certificate.extend([0; $padding_size].as_ref());
+        }}
+
+        match &self.key {
+            Key::Rsa4096(key) => key_match_clause!(Rsa4096, key, 0x34),
+            Key::Rsa2048(key) => key_match_clause!(Rsa2048, key, 0x34),
+            Key::EllipticCurve(key) => key_match_clause!(EllipticCurve, key, 0x3c),
+        }
+
+        Ok(certificate)
+    }
+
+    /// Creates a new device [`Certificate`] for the console with the provided [`DeviceName`],
+    /// holding the provided public [`Key`]
+    ///
+    /// The [`Certificate`] is named, issued and given the kind of signature the console's own
+    /// device certificate would have, with its [`KeyId`] left as zero. Its signature is left zeroed
+    /// until it is signed using [`sign`](Self::sign). An error is returned for development Wii U
+    /// units, as the issuer of their device certificates is not known
+    pub fn new_device(device_name: DeviceName, key: Key<'a>) -> Result<Self, CertificateError> {
+        let issuer =
+            KnownIssuer::of_device_certificates(device_name.console_kind, device_name.device_type)
+                .ok_or(CertificateError::UnknownDeviceCertificateIssuer(
+                    device_name.console_kind,
+                    device_name.device_type,
+                ))?;
+        Ok(Self::new(
+            Signature::EcdsaWithSha256(Cow::Owned(vec![0; 0x3c])),
+            Issuer(Cow::Borrowed(issuer.into())),
+            key,
+            device_name.into(),
+            KeyId(0),
+        ))
+    }
+
+    /// Signs the [`Certificate`] using the provided elliptic curve private key, which is a 30-byte
+    /// big-endian integer, replacing its existing signature
+    ///
+    /// Only elliptic curve signatures can be produced, so an error is returned if the
+    /// [`Certificate`] carries an RSA signature
+    pub fn sign(&mut self, private_key: &[u8]) -> Result<(), CertificateError> {
+        let data = self.signed_data()?;
+        let sign = |digest: &[u8]| {
+            sect233r1::sign::sign(private_key, digest)
+                .map(|signature| Cow::Owned(signature.to_vec()))
+                .ok_or(CertificateError::InvalidPrivateKey)
+        };
+        self.signature = match &self.signature {
+            Signature::EllipticCurveWithSha1(_) => {
+                Signature::EllipticCurveWithSha1(sign(&Sha1::digest(&data))?)
+            }
+            Signature::EcdsaWithSha256(_) => {
+                Signature::EcdsaWithSha256(sign(&Sha256::digest(&data))?)
+            }
+            _ => return Err(CertificateError::KeyMismatch),
+        };
+        Ok(())
+    }
+
+    /// Parses every [`Certificate`] placed back-to-back in the provided byte slice, returning each
+    /// of them along with the range of bytes it was read from
+    pub fn parse_all(data: &[u8]) -> Result<Vec<(Self, Range<usize>)>, CertificateError> {
+        let mut certificates = Vec::new();
+        let mut start = 0;
+        while start < data.len() {
+            let certificate = Self::try_from(&data[start..])?;
+            let end = start + certificate.encoded_length();
+            if end > data.len() {
+                return Err(CertificateError::OutOfBounds);
+            }
+            certificates.push((certificate, start..end));
+            start = end;
+        }
+        Ok(certificates)
+    }
+
+    /// Returns the length of the [`Certificate`] once converted into bytes
+    pub fn encoded_length(&self) -> usize {
+        self.signature.magic().padded_length() + 0x88 + self.key.magic().padded_length()
+    }
+
+    /// Returns the portion of the [`Certificate`] covered by its signature, which is everything
+    /// following the signature and its padding
+    pub fn signed_data(&self) -> Result<Vec<u8>, CertificateError> {
+        let mut certificate = self.to_bytes()?;
+        certificate.drain(..self.signature.magic().padded_length());
+        Ok(certificate)
+    }
+
+    /// Verifies that the [`Certificate`] was signed by the provided issuing [`Certificate`]
+    ///
+    /// This checks both that the issuer's name is the last portion of the [`Certificate`]'s
+    /// [`Issuer`] and that the signature is valid under the issuer's [`Key`]. It does not check
+    /// the issuer itself, which must either be trusted or verified in turn
+    pub fn verify(&self, issuer: &Certificate<'_>) -> Result<(), CertificateError> {
+        if !self.issuer.is_issued_by(&issuer.name) {
+            return Err(CertificateError::IssuerMismatch);
+        }
+        issuer.key.verify(&self.signature, &self.signed_data()?)
+    }
+}
+
+impl TryFrom<&[u8]> for Certificate<'_> {
+    type Error = CertificateError;
+
+    /// Creates a new [`Certificate`] from a byte slice
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let (signature, offset) = Signature::parse(value)?;
+
+        let mut issuer = value
+            .get(offset..offset + 0x40)
+            .ok_or(CertificateError::OutOfBounds)?
+            .to_owned();
+        while let Some(&value) = issuer.last() {
+            if value == 0 {
+                issuer.pop();
+            } else {
+                break;
+            }
+        }
+        let issuer = Issuer(Cow::Owned(String::from_utf8(issuer)?));
+
+        let key_type = u32::from_be_bytes(
+            value
+                .get(offset + 0x40..offset + 0x44)
+                .ok_or(CertificateError::OutOfBounds)?
+                .try_into()
+                .expect(Self::SLICE_TO_ARRAY_PANIC_MESSAGE),
+        );
+
+        macro key_magic_match_clause($key_kind:ident, $key_limit:literal) {
+            Key::$key_kind(Cow::Owned(
+                value
+                    .get(offset + 0x88..offset + $key_limit)
+                    .ok_or(CertificateError::OutOfBounds)?
+                    .to_owned(),
+            ))
+        }
+
+        let key = match KeyMagic::from_u32(key_type)
+            .ok_or(CertificateError::UnsupportedKeyType(key_type))?
+        {
+            KeyMagic::Rsa4096 => key_magic_match_clause!(Rsa4096, 0x28c),
+            KeyMagic::Rsa2048 => key_magic_match_clause!(Rsa2048, 0x18c),
+            KeyMagic::EllipticCurve => key_magic_match_clause!(EllipticCurve, 0xc4),
+        };
+
+        let mut name = value
+            .get(offset + 0x44..offset + 0x84)
+            .ok_or(CertificateError::OutOfBounds)?
+            .to_owned();
+        while let Some(&value) = name.last() {
+            if value == 0 {
+                name.pop();
+            } else {
+                break;
+            }
+        }
+        let name = Name(Cow::Owned(String::from_utf8(name)?));
+
+        let key_id = KeyId(u32::from_be_bytes(
+            value
+                .get(offset + 0x84..offset + 0x88)
+                .ok_or(CertificateError::OutOfBounds)?
+                .try_into()
+                .expect(Self::SLICE_TO_ARRAY_PANIC_MESSAGE),
+        ));
+
+        Ok(Self {
+            signature,
+            issuer,
+            key,
+            name,
+            key_id,
+        })
+    }
+}
+
+/// A collection of [`Certificate`]s that issue one another, such as the certificate chains
+/// carried by tickets and title metadata
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct CertificateChain<'a> {
+    pub certificates: Vec<Certificate<'a>>,
+}
+
+impl<'a> CertificateChain<'a> {
+    /// The issuer of certificates signed directly by a root key
+    pub const ROOT_ISSUER: &'static str = "Root";
+
+    /// Creates a new [`CertificateChain`] from the provided [`Certificate`]s
+    pub const fn new(certificates: Vec<Certificate<'a>>) -> Self {
+        Self { certificates }
+    }
+
+    /// Returns the [`Certificate`] with the provided issuer path, such as
+    /// `Root-CA00000003-CP0000000b`, which is made up of the [`Certificate`]'s own issuer followed
+    /// by its name
+    pub fn get(&self, path: &str) -> Option<&Certificate<'a>> {
+        self.certificates.iter().find(|certificate| {
+            matches!(
+                path.strip_suffix(certificate.name.0.as_ref())
+                    .and_then(|issuer| issuer.strip_suffix(|c| c == '-' || c == '_')),
+                Some(issuer) if issuer == certificate.issuer.0
+            )
+        })
+    }
+
+    /// Returns the [`Certificate`] that issued the provided [`Certificate`], if it is present
+    pub fn issuer_of(&self, certificate: &Certificate<'_>) -> Option<&Certificate<'a>> {
+        self.get(&certificate.issuer.0)
+    }
+
+    /// Verifies the provided [`Certificate`] along with every [`Certificate`] above it in the
+    /// [`CertificateChain`], ending at the [`Certificate`] signed by the provided root [`Key`]
+    pub fn verify(
+        &self,
+        certificate: &Certificate<'_>,
+        root: &Key<'_>,
+    ) -> Result<(), CertificateError> {
+        let mut certificate = certificate.clone();
+        // each certificate in the chain may be visited at most once, which stops a chain that
+        // loops back on itself from being walked forever
+        for _ in 0..=self.certificates.len() {
+            if certificate.issuer.0 == Self::ROOT_ISSUER {
+                return root.verify(&certificate.signature, &certificate.signed_data()?);
+            }
+
+            let issuer = self
+                .issuer_of(&certificate)
+                .ok_or_else(|| CertificateError::MissingIssuer(certificate.issuer.0.to_string()))?;
+            certificate.verify(issuer)?;
+            certificate = issuer.clone();
+        }
+        Err(CertificateError::MissingIssuer(
+            Self::ROOT_ISSUER.to_string(),
+        ))
+    }
+}
+
+impl TryFrom<&[u8]> for CertificateChain<'_> {
+    type Error = CertificateError;
+
+    /// Creates a new [`CertificateChain`] from [`Certificate`]s placed back-to-back in a byte slice
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self::new(
+            Certificate::parse_all(value)?
+                .into_iter()
+                .map(|(certificate, _)| certificate)
+                .collect(),
+        ))
+    }
+}
+
+/// A list of all possible errors encountered while working with a [`Certificate`]
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum CertificateError {
+    #[error("The UTF-8 data inside of the Certificate is invalid")]
+    FromUtf8Error(#[from] FromUtf8Error),
+
+    #[error("`{0}` is an unsupported signature type")]
+    UnsupportedSignatureType(u32),
+
+    #[error("`{0}` is an unsupported key type")]
+    UnsupportedKeyType(u32),
+
+    #[error("The provided byte certificate is not large enough")]
+    OutOfBounds,
+
+    #[error("The Certificate was not issued by the provided issuer")]
+    IssuerMismatch,
+
+    #[error("The Signature cannot have been made by the provided Key")]
+    KeyMismatch,
+
+    #[error("The Signature is not valid")]
+    InvalidSignature,
+
+    #[error("The issuer `{0}` is not present in the CertificateChain")]
+    MissingIssuer(String),
+
+    #[error("The issuer of device certificates for `{0}` consoles of type `{1:?}` is unknown")]
+    UnknownDeviceCertificateIssuer(ConsoleKind, ConsoleType),
+
+    #[error("The provided private key is not valid")]
+    InvalidPrivateKey,
+
+    #[error("The Key's data is not of the length expected of its kind")]
+    MalformedKey,
+
+    #[error("The public key is malformed or cannot be held by a Key")]
+    UnsupportedPublicKey,
+
+    #[error("The PEM document is malformed or does not hold a public key")]
+    InvalidPem,
+}
+
+/// An enumeration over the possible magic numbers representing a kind of [`Signature`]
+#[non_exhaustive]
+#[derive(FromPrimitive, ToPrimitive, Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum SignatureMagic {
+    Rsa4096WithSha1 = 0x010000,
+    Rsa2048WithSha1 = 0x010001,
+    EllipticCurveWithSha1 = 0x010002,
+    Rsa4096WithSha256 = 0x010003,
+    Rsa2048WithSha256 = 0x010004,
+    EcdsaWithSha256 = 0x010005,
+}
+
+impl SignatureMagic {
+    /// Returns the length of the corresponding kind of [`Signature`] along with the padding that
+    /// follows it and the magic number that precedes it
+    pub fn padded_length(self) -> usize {
+        match self {
+            Self::Rsa4096WithSha1 | Self::Rsa4096WithSha256 => 0x240,
+            Self::Rsa2048WithSha1 | Self::Rsa2048WithSha256 => 0x140,
+            Self::EllipticCurveWithSha1 | Self::EcdsaWithSha256 => 0x80,
+        }
+    }
+}
+
+/// An enumeration over all possible signature kinds, containing the internal signature data
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum Signature<'a> {
+    Rsa4096WithSha1(Cow<'a, [u8]>),
+    Rsa2048WithSha1(Cow<'a, [u8]>),
+    EllipticCurveWithSha1(Cow<'a, [u8]>),
+    Rsa4096WithSha256(Cow<'a, [u8]>),
+    Rsa2048WithSha256(Cow<'a, [u8]>),
+    EcdsaWithSha256(Cow<'a, [u8]>),
+}
+
+impl Signature<'_> {
+    /// Returns the corresponding [`SignatureMagic`]
+    pub fn magic(&self) -> SignatureMagic {
+        match &self {
+            Self::Rsa4096WithSha1(_) => SignatureMagic::Rsa4096WithSha1,
+            Self::Rsa2048WithSha1(_) => SignatureMagic::Rsa2048WithSha1,
+            Self::EllipticCurveWithSha1(_) => SignatureMagic::EllipticCurveWithSha1,
+            Self::Rsa4096WithSha256(_) => SignatureMagic::Rsa4096WithSha256,
+            Self::Rsa2048WithSha256(_) => SignatureMagic::Rsa2048WithSha256,
+            Self::EcdsaWithSha256(_) => SignatureMagic::EcdsaWithSha256,
+        }
+    }
+
+    /// Returns the signature data held by the [`Signature`]
+    pub fn data(&self) -> &[u8] {
+        match self {
+            Self::Rsa4096WithSha1(signature)
+            | Self::Rsa2048WithSha1(signature)
+            | Self::EllipticCurveWithSha1(signature)
+            | Self::Rsa4096WithSha256(signature)
+            | Self::Rsa2048WithSha256(signature)
+            | Self::EcdsaWithSha256(signature) => signature,
+        }
+    }
+
+    /// Parses a [`Signature`] from the start of the provided data, returning it along with the
+    /// offset of the data following it, its magic number and its padding
+    pub fn parse(data: &[u8]) -> Result<(Signature<'static>, usize), CertificateError> {
+        let signature_type = u32::from_be_bytes(
+            data.get(..0x4)
+                .ok_or(CertificateError::OutOfBounds)?
+                .try_into()
+                .expect(Certificate::SLICE_TO_ARRAY_PANIC_MESSAGE),
+        );
+
+        macro signature_magic_match_clause($signature_kind:ident, $signature_limit:literal) {
+            Signature::$signature_kind(Cow::Owned(
+                data.get(0x4..$signature_limit)
+                    .ok_or(CertificateError::OutOfBounds)?
+                    .to_owned(),
+            ))
+        }
+
+        let signature = match SignatureMagic::from_u32(signature_type)
+            .ok_or(CertificateError::UnsupportedSignatureType(signature_type))?
+        {
+            SignatureMagic::Rsa4096WithSha1 => {
+                signature_magic_match_clause!(Rsa4096WithSha1, 0x204)
+            }
+            SignatureMagic::Rsa2048WithSha1 => {
+                signature_magic_match_clause!(Rsa2048WithSha1, 0x104)
+            }
+            SignatureMagic::EllipticCurveWithSha1 => {
+                signature_magic_match_clause!(EllipticCurveWithSha1, 0x40)
+            }
+            SignatureMagic::Rsa4096WithSha256 => {
+                signature_magic_match_clause!(Rsa4096WithSha256, 0x204)
+            }
+            SignatureMagic::Rsa2048WithSha256 => {
+                signature_magic_match_clause!(Rsa2048WithSha256, 0x104)
+            }
+            SignatureMagic::EcdsaWithSha256 => signature_magic_match_clause!(EcdsaWithSha256, 0x40),
+        };
+        let offset = signature.magic().padded_length();
+        Ok((signature, offset))
+    }
+
+    /// Converts the [`Signature`] into a byte vector, preceded by its magic number and followed by
+    /// its padding
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let magic = self.magic();
+        let mut signature = Vec::with_capacity(magic.padded_length());
+        signature.extend(
+            &magic
+                .to_u32()
+                .expect(Certificate::CSTYLE_ENUM_TO_U32_PANIC_MESSAGE)
+                .to_be_bytes(),
+        );
+        signature.extend(self.data());
+        let padding = match magic {
+            SignatureMagic::EllipticCurveWithSha1 | SignatureMagic::EcdsaWithSha256 => 0x40,
+            _ => 0x3c,
+        };
+        signature.resize(signature.len() + padding, 0);
+        signature
+    }
+}
+
+/// A newtype that defines various operations on a [`Certificate`]'s issuer section
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Issuer<'a>(pub Cow<'a, str>);
+
+impl<'a> Issuer<'a> {
+    /// Attempt to get a [`KnownIssuer`] value from the [`Issuer`]
+    pub fn known_issuer(&self) -> Option<KnownIssuer> {
+        KnownIssuer::from_str(&self.0).ok()
+    }
+
+    /// Returns `true` if the [`Issuer`] names the certificate with the provided [`Name`] as the
+    /// one that issued it
+    ///
+    /// The name of the issuing certificate is separated from its own issuer by either a `-` or a
+    /// `_`, as in `Root-CA00000003-MS00000012` and `Nintendo CA - G3_NintendoCTR2prod`
+    pub fn is_issued_by(&self, name: &Name<'_>) -> bool {
+        matches!(
+            self.0.strip_suffix(name.0.as_ref()),
+            Some(issuer) if issuer.ends_with('-') || issuer.ends_with('_')
+        )
+    }
+}
+
+/// An enumeration over known certificate issuers
+#[non_exhaustive]
+#[derive(
+    IntoStaticStr,
+    AsRefStr,
+    EnumString,
+    Display,
+    Copy,
+    Clone,
+    Debug,
+    Eq,
+    Hash,
+    PartialEq,
+    PartialOrd,
+    Ord,
+)]
+pub enum KnownIssuer {
+    #[strum(to_string = "Root-CA00000003-MS00000012")]
+    RootCa00000003Ms00000012,
+    #[strum(to_string = "Nintendo CA - G3_NintendoCTR2prod")]
+    NintendoCaG3NintendoCtr2Prod,
+    #[strum(to_string = "Nintendo CA - G3_NintendoCTR2dev")]
+    NintendoCaG3NintendoCtr2Dev,
+}
+
+impl KnownIssuer {
+    /// Returns the issuer of the device certificates of the provided [`Kind`](ConsoleKind) and
+    /// [`Type`](ConsoleType) of console, if it is known
+    pub fn of_device_certificates(kind: ConsoleKind, device_type: ConsoleType) -> Option<Self> {
+        match (kind, device_type) {
+            (ConsoleKind::N3ds, ConsoleType::Retail) => Some(Self::NintendoCaG3NintendoCtr2Prod),
+            (ConsoleKind::N3ds, ConsoleType::Developer) => Some(Self::NintendoCaG3NintendoCtr2Dev),
+            (ConsoleKind::WiiU, ConsoleType::Retail) => Some(Self::RootCa00000003Ms00000012),
+            (ConsoleKind::WiiU, ConsoleType::Developer) => None,
+        }
+    }
+}
+
+/// An enumeration over all possible magic numbers representing a kind of [`Key`]
+#[non_exhaustive]
+#[derive(FromPrimitive, ToPrimitive, Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum KeyMagic {
+    Rsa4096 = 0x0,
+    Rsa2048 = 0x1,
+    EllipticCurve = 0x2,
+}
+
+impl KeyMagic {
+    /// Returns the length of the corresponding kind of [`Key`] along with the padding that follows
+    /// it
+    pub fn padded_length(self) -> usize {
+        match self {
+            Self::Rsa4096 => 0x238,
+            Self::Rsa2048 => 0x138,
+            Self::EllipticCurve => 0x78,
+        }
+    }
+}
+
+/// An enumeration over all possible key kinds, containing the internal key data
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum Key<'a> {
+    Rsa4096(Cow<'a, [u8]>),
+    Rsa2048(Cow<'a, [u8]>),
+    EllipticCurve(Cow<'a, [u8]>),
+}
+
+impl Key<'_> {
+    /// Returns the corresponding [`KeyMagic`]
+    pub fn magic(&self) -> KeyMagic {
+        match &self {
+            Self::Rsa4096(_) => KeyMagic::Rsa4096,
+            Self::Rsa2048(_) => KeyMagic::Rsa2048,
+            Self::EllipticCurve(_) => KeyMagic::EllipticCurve,
+        }
+    }
+
+    /// Creates the public elliptic curve [`Key`] belonging to the provided private key, which is a
+    /// 30-byte big-endian integer
+    pub fn from_elliptic_curve_private_key(
+        private_key: &[u8],
+    ) -> Result<Key<'static>, CertificateError> {
+        sect233r1::sign::public_key(private_key)
+            .map(|public_key| Key::EllipticCurve(Cow::Owned(public_key.to_vec())))
+            .ok_or(CertificateError::InvalidPrivateKey)
+    }
+
+    /// Creates an RSA [`Key`] from the provided big-endian modulus and exponent, choosing its kind
+    /// depending on the modulus' length
+    pub fn from_rsa_components(
+        modulus: &[u8],
+        exponent: u32,
+    ) -> Result<Key<'static>, CertificateError> {
+        let modulus = &modulus[modulus.iter().take_while(|&&b| b == 0).count()..];
+        let modulus_length = match modulus.len() {
+            0x81..=0x100 => 0x100,
+            0x101..=0x200 => 0x200,
+            _ => return Err(CertificateError::MalformedKey),
+        };
+
+        let mut key = vec![0; modulus_length - modulus.len()];
+        key.extend_from_slice(modulus);
+        key.extend_from_slice(&exponent.to_be_bytes());
+        Ok(if modulus_length == 0x100 {
+            Key::Rsa2048(Cow::Owned(key))
+        } else {
+            Key::Rsa4096(Cow::Owned(key))
+        })
+    }
+
+    /// Creates an elliptic curve [`Key`] from the provided big-endian coordinates of its public
+    /// point, each of which must fit within 30 bytes
+    pub fn from_elliptic_curve_point(x: &[u8], y: &[u8]) -> Result<Key<'static>, CertificateError> {
+        let mut key = Vec::with_capacity(ELLIPTIC_CURVE_KEY_LENGTH);
+        for coordinate in [x, y] {
+            let coordinate = &coordinate[coordinate.iter().take_while(|&&b| b == 0).count()..];
+            let padding = (ELLIPTIC_CURVE_KEY_LENGTH / 2)
+                .checked_sub(coordinate.len())
+                .ok_or(CertificateError::MalformedKey)?;
+            key.resize(key.len() + padding, 0);
+            key.extend_from_slice(coordinate);
+        }
+        Ok(Key::EllipticCurve(Cow::Owned(key)))
+    }
+
+    /// Returns the big-endian modulus and the exponent of an RSA [`Key`], or [`None`] if the
+    /// [`Key`] is not an RSA key or its data is not of the expected length
+    pub fn rsa_components(&self) -> Option<(&[u8], u32)> {
+        let (key, modulus_length) = match self {
+            Self::Rsa4096(key) => (key, 0x200),
+            Self::Rsa2048(key) => (key, 0x100),
+            Self::EllipticCurve(_) => return None,
+        };
+        if key.len() != modulus_length + 4 {
+            return None;
+        }
+
+        let (modulus, exponent) = key.split_at(modulus_length);
+        Some((modulus, u32::from_be_bytes(exponent.try_into().ok()?)))
+    }
+
+    /// Returns the big-endian x and y coordinates of the public point of an elliptic curve
+    /// [`Key`], or [`None`] if the [`Key`] is not an elliptic curve key or its data is not of the
+    /// expected length
+    pub fn elliptic_curve_point(&self) -> Option<(&[u8], &[u8])> {
+        match self {
+            Self::EllipticCurve(key) if key.len() == ELLIPTIC_CURVE_KEY_LENGTH => {
+                Some(key.split_at(ELLIPTIC_CURVE_KEY_LENGTH / 2))
+            }
+            _ => None,
+        }
+    }
+
+    /// Encodes the [`Key`] as a DER-encoded `SubjectPublicKeyInfo`
+    ///
+    /// Elliptic curve keys are encoded as uncompressed points on the named sect233r1 curve
+    pub fn to_der(&self) -> Result<Vec<u8>, CertificateError> {
+        if let Some((modulus, exponent)) = self.rsa_components() {
+            let mut components = der::encode_integer(modulus);
+            components.extend_from_slice(&der::encode_integer(&exponent.to_be_bytes()));
+            Ok(der::encode_subject_public_key_info(
+                der::RSA_ENCRYPTION,
+                &der::encode(der::NULL, &[]),
+                &der::encode(der::SEQUENCE, &components),
+            ))
+        } else if let Some((x, y)) = self.elliptic_curve_point() {
+            let mut point = Vec::with_capacity(ELLIPTIC_CURVE_KEY_LENGTH + 1);
+            point.push(0x04);
+            point.extend_from_slice(x);
+            point.extend_from_slice(y);
+            Ok(der::encode_subject_public_key_info(
+                der::EC_PUBLIC_KEY,
+                &der::encode(der::OBJECT_IDENTIFIER, der::SECT233R1),
+                &point,
+            ))
+        } else {
+            Err(CertificateError::MalformedKey)
+        }
+    }
+
+    /// Decodes a [`Key`] from a DER-encoded `SubjectPublicKeyInfo`
+    ///
+    /// Only RSA keys with 2048 or 4096-bit moduli and 32-bit exponents, and elliptic curve keys
+    /// on the sect233r1 curve stored as uncompressed points, can be held by a [`Key`]
+    pub fn from_der(der: &[u8]) -> Result<Key<'static>, CertificateError> {
+        let (algorithm, parameters, public_key) = der::decode_subject_public_key_info(der)
+            .ok_or(CertificateError::UnsupportedPublicKey)?;
+
+        if algorithm == der::RSA_ENCRYPTION && parameters == der::encode(der::NULL, &[]) {
+            let (components, rest) = der::decode(public_key, der::SEQUENCE)
+                .filter(|(_, rest)| rest.is_empty())
+                .ok_or(CertificateError::UnsupportedPublicKey)?;
+            let (modulus, components) =
+                der::decode_integer(components).ok_or(CertificateError::UnsupportedPublicKey)?;
+            let (exponent, components) =
+                der::decode_integer(components).ok_or(CertificateError::UnsupportedPublicKey)?;
+            if !rest.is_empty() || !components.is_empty() || exponent.len() > 4 {
+                return Err(CertificateError::UnsupportedPublicKey);
+            }
+
+            let exponent = exponent
+                .iter()
+                .fold(0, |exponent, &b| (exponent << 8) | u32::from(b));
+            Key::from_rsa_components(modulus, exponent)
+                .map_err(|_| CertificateError::UnsupportedPublicKey)
+        } else if algorithm == der::EC_PUBLIC_KEY
+            && parameters == der::encode(der::OBJECT_IDENTIFIER, der::SECT233R1)
+        {
+            match public_key.split_first() {
+                Some((0x04, point)) if point.len() == ELLIPTIC_CURVE_KEY_LENGTH => {
+                    let (x, y) = point.split_at(ELLIPTIC_CURVE_KEY_LENGTH / 2);
+                    Key::from_elliptic_curve_point(x, y)
+                }
+                _ => Err(CertificateError::UnsupportedPublicKey),
+            }
+        } else {
+            Err(CertificateError::UnsupportedPublicKey)
+        }
+    }
+
+    /// Encodes the [`Key`] as a PEM-encoded `SubjectPublicKeyInfo`, labeled as a `PUBLIC KEY`
+    pub fn to_pem(&self) -> Result<String, CertificateError> {
+        let encoded = base64::encode(self.to_der()?);
+        let mut pem = String::from("-----BEGIN PUBLIC KEY-----\n");
+        for line in encoded.as_bytes().chunks(64) {
+            pem.push_str(&String::from_utf8_lossy(line));
+            pem.push('\n');
+        }
+        pem.push_str("-----END PUBLIC KEY-----\n");
+        Ok(pem)
+    }
+
+    /// Decodes a [`Key`] from a PEM-encoded `SubjectPublicKeyInfo`, labeled as a `PUBLIC KEY`
+    pub fn from_pem(pem: &str) -> Result<Key<'static>, CertificateError> {
+        let encoded = pem
+            .trim()
+            .strip_prefix("-----BEGIN PUBLIC KEY-----")
+            .and_then(|pem| pem.strip_suffix("-----END PUBLIC KEY-----"))
+            .ok_or(CertificateError::InvalidPem)?;
+        let encoded: String = encoded.split_whitespace().collect();
+        Key::from_der(&base64::decode(encoded).map_err(|_| CertificateError::InvalidPem)?)
+    }
+
+    /// Verifies that the provided [`Signature`] over the provided data was made using the private
+    /// half of the [`Key`]
+    ///
+    /// RSA keys are split using [`rsa_components`](Self::rsa_components), and elliptic curve
+    /// keys, which are on the sect233r1 curve, consist of the x and y coordinates of their public
+    /// point
+    pub fn verify(&self, signature: &Signature<'_>, data: &[u8]) -> Result<(), CertificateError> {
+        let valid = match (self, signature) {
+            (Self::Rsa4096(_), Signature::Rsa4096WithSha1(signature))
+            | (Self::Rsa2048(_), Signature::Rsa2048WithSha1(signature)) => {
+                self.verify_rsa(signature, Hash::SHA1, &Sha1::digest(data))?
+            }
+            (Self::Rsa4096(_), Signature::Rsa4096WithSha256(signature))
+            | (Self::Rsa2048(_), Signature::Rsa2048WithSha256(signature)) => {
+                self.verify_rsa(signature, Hash::SHA2_256, &Sha256::digest(data))?
+            }
+            (Self::EllipticCurve(key), Signature::EllipticCurveWithSha1(signature)) => {
+                sect233r1::verify(key, signature, &Sha1::digest(data))
+            }
+            (Self::EllipticCurve(key), Signature::EcdsaWithSha256(signature)) => {
+                sect233r1::verify(key, signature, &Sha256::digest(data))
+            }
+            _ => return Err(CertificateError::KeyMismatch),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(CertificateError::InvalidSignature)
+        }
+    }
+
+    /// Returns `true` if the provided RSASSA-PKCS1-v1_5 signature over the provided digest,
+    /// computed using the provided [`Hash`], was made using the private half of the RSA [`Key`]
+    fn verify_rsa(
+        &self,
+        signature: &[u8],
+        hash: Hash,
+        digest: &[u8],
+    ) -> Result<bool, CertificateError> {
+        let (modulus, exponent) = self
+            .rsa_components()
+            .ok_or(CertificateError::MalformedKey)?;
+        let key = RsaPublicKey::new(BigUint::from_bytes_be(modulus), BigUint::from(exponent))
+            .map_err(|_| CertificateError::MalformedKey)?;
+        Ok(key
+            .verify(
+                PaddingScheme::new_pkcs1v15_sign(Some(hash)),
+                digest,
+                signature,
+            )
+            .is_ok())
+    }
+}
+
+/// A newtype that defines various operations on a [`Certificate`]'s name section
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Name<'a>(pub Cow<'a, str>);
+
+impl<'a> Name<'a> {
+    /// Extract the device id from the [`Name`], if possible
+    pub fn device_id(&self) -> Option<u32> {
+        self.0
+            .get(..2)
+            .map_or(false, |prefix| matches!(prefix, "NG" | "CT"))
+            .then(|| {
+                self.0
+                    .get(2..10)
+                    .and_then(|id| u32::from_str_radix(id, 16).ok())
+            })
+            .flatten()
+    }
+
+    /// Attempt to get the [`Kind`](ConsoleKind) from the [`Name`], is possible
+    pub fn console_kind(&self) -> Option<ConsoleKind> {
+        self.0
+            .get(..2)
+            .map(|k| match k {
+                "NG" => Some(ConsoleKind::WiiU),
+                "CT" => Some(ConsoleKind::N3ds),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    /// Attempt to get the [`Type`](ConsoleType) from the [`Name`], if possible
+    ///
+    /// Only the names of 3ds device certificates carry the console's type, so [`None`] is always
+    /// returned for Wii U device certificates
+    pub fn device_type(&self) -> Option<ConsoleType> {
+        self.device_name()?.device_type()
+    }
+
+    /// Attempt to parse the [`Name`] as the name of a device certificate, if possible
+    pub fn device_name(&self) -> Option<DeviceName> {
+        DeviceName::from_str(&self.0).ok()
+    }
+}
+
+impl From<DeviceName> for Name<'_> {
+    fn from(device_name: DeviceName) -> Self {
+        Self(Cow::Owned(device_name.to_string()))
+    }
+}
+
+/// The parts making up the [`Name`] of a device certificate, which takes the form `CT%08X-%02X`
+/// on the 3ds and `NG%08X` on the Wii U
+///
+/// On the 3ds, the number following the device id is `00` on retail units and `01` on
+/// development units. Wii U device certificate names do not carry the console's type, so it is
+/// assumed to be [`Retail`](ConsoleType::Retail) when parsing them
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DeviceName {
+    pub device_id: u32,
+    pub console_kind: ConsoleKind,
+    pub device_type: ConsoleType,
+}
+
+impl DeviceName {
+    /// Creates a new [`DeviceName`] from its parts
+    pub const fn new(device_id: u32, console_kind: ConsoleKind, device_type: ConsoleType) -> Self {
+        Self {
+            device_id,
+            console_kind,
+            device_type,
+        }
+    }
+
+    /// Returns the [`Type`](ConsoleType) of the console if the [`DeviceName`] carries it, which
+    /// is only the case on the 3ds
+    pub fn device_type(&self) -> Option<ConsoleType> {
+        match self.console_kind {
+            ConsoleKind::N3ds => Some(self.device_type),
+            ConsoleKind::WiiU => None,
+        }
+    }
+}
+
+impl FromStr for DeviceName {
+    type Err = DeviceNameError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let device_id = |id: Option<&str>| {
+            id.filter(|id| id.len() == 8 && id.bytes().all(|c| c.is_ascii_hexdigit()))
+                .and_then(|id| u32::from_str_radix(id, 16).ok())
+                .ok_or(DeviceNameError::InvalidDeviceId)
+        };
+
+        if let Some(name) = name.strip_prefix("CT") {
+            let device_type = match name.get(8..) {
+                Some("-00") => ConsoleType::Retail,
+                Some("-01") => ConsoleType::Developer,
+                _ => return Err(DeviceNameError::InvalidDeviceType),
+            };
+            Ok(Self::new(
+                device_id(name.get(..8))?,
+                ConsoleKind::N3ds,
+                device_type,
+            ))
+        } else if let Some(name) = name.strip_prefix("NG") {
+            Ok(Self::new(
+                device_id(Some(name))?,
+                ConsoleKind::WiiU,
+                ConsoleType::Retail,
+            ))
+        } else {
+            Err(DeviceNameError::UnknownConsoleKind)
+        }
+    }
+}
+
+impl fmt::Display for DeviceName {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.console_kind {
+            ConsoleKind::N3ds => write!(
+                formatter,
+                "CT{:08X}-{:02X}",
+                self.device_id,
+                match self.device_type {
+                    ConsoleType::Retail => 0,
+                    ConsoleType::Developer => 1,
+                }
+            ),
+            ConsoleKind::WiiU => write!(formatter, "NG{:08X}", self.device_id),
+        }
+    }
+}
+
+/// An enumeration over errors that can be encountered while parsing a [`DeviceName`]
+#[derive(Error, Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum DeviceNameError {
+    /// The name does not begin with the prefix of any [`Kind`](ConsoleKind) of console
+    #[error("The name does not begin with `CT` or `NG`")]
+    UnknownConsoleKind,
+
+    /// The device id within the name is not 8 hexadecimal digits
+    #[error("The device id within the name is invalid")]
+    InvalidDeviceId,
+
+    /// The suffix of a 3ds device certificate's name is neither `-00` nor `-01`
+    #[error("The console type within the name is invalid")]
+    InvalidDeviceType,
+}
+
+/// A newtype that defines various operations on a [`Certificate`]'s key id section
+///
+/// While it is called the key id by kinnay's documentation, 3dbrew documents it as the
+/// certificate's expiration time, stored as a unix timestamp
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct KeyId(pub u32);
+
+impl KeyId {
+    /// Creates a new [`KeyId`] from the provided expiration time, returning [`None`] if it cannot
+    /// be represented as a 32-bit unix timestamp
+    pub fn from_expiration_time(expiration_time: SystemTime) -> Option<Self> {
+        expiration_time
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|timestamp| u32::try_from(timestamp.as_secs()).ok())
+            .map(Self)
+    }
+
+    /// Returns the [`KeyId`] interpreted as an expiration time
+    pub fn expiration_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(u64::from(self.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // a chain generated for testing, where a certificate authority with an elliptic curve key is
+    // signed by an RSA-2048 root key and in turn signs a device certificate using both SHA-256
+    // and SHA-1. the root key is also distributed within a certificate signed by itself, as the
+    // certificate authority names it as its issuer
+    const ROOT_KEY: &[u8] = include_bytes!("test/root-key.bin");
+    const ROOT_CA: &[u8] = include_bytes!("test/root-ca.bin");
+    const CA_PRIVATE_KEY: &[u8] = include_bytes!("test/ca-private-key.bin");
+    const DEVICE_PRIVATE_KEY: &[u8] = include_bytes!("test/device-private-key.bin");
+    const CA: &[u8] = include_bytes!("test/ca.bin");
+    const ROOT_KEY_DER: &[u8] = include_bytes!("test/root-key.der");
+    const CA_KEY_PEM: &str = include_str!("test/ca-key.pem");
+    const DEVICE: &[u8] = include_bytes!("test/device.bin");
+    const DEVICE_SHA1: &[u8] = include_bytes!("test/device-sha1.bin");
+
+    #[test]
+    fn verify_chain() {
+        let ca = Certificate::try_from(CA).unwrap();
+        Key::Rsa2048(Cow::Borrowed(ROOT_KEY))
+            .verify(&ca.signature, &ca.signed_data().unwrap())
+            .unwrap();
+
+        for device in [DEVICE, DEVICE_SHA1] {
+            let device = Certificate::try_from(device).unwrap();
+            assert_eq!(device.to_bytes().unwrap().len(), DEVICE.len());
+            device.verify(&ca).unwrap();
+        }
+    }
+
+    #[test]
+    fn reject_forgeries() {
+        let ca = Certificate::try_from(CA).unwrap();
+        let mut device = Certificate::try_from(DEVICE).unwrap();
+        assert!(matches!(
+            device.verify(&device),
+            Err(CertificateError::IssuerMismatch)
+        ));
+
+        // the device certificate's key is not that of the certificate authority
+        let mut impostor = ca.clone();
+        impostor.key = device.key.clone();
+        assert!(matches!(
+            device.verify(&impostor),
+            Err(CertificateError::InvalidSignature)
+        ));
+
+        // the signed portion of the certificate has been tampered with
+        device.key_id = KeyId(device.key_id.0 + 1);
+        assert!(matches!(
+            device.verify(&ca),
+            Err(CertificateError::InvalidSignature)
+        ));
+
+        let mut ca_signature = ca.signature.clone();
+        if let Signature::Rsa2048WithSha256(signature) = &mut ca_signature {
+            signature.to_mut()[0] ^= 1;
+        }
+        assert!(matches!(
+            Key::Rsa2048(Cow::Borrowed(ROOT_KEY)).verify(&ca_signature, &ca.signed_data().unwrap()),
+            Err(CertificateError::InvalidSignature)
+        ));
+        assert!(matches!(
+            Key::Rsa2048(Cow::Borrowed(ROOT_KEY))
+                .verify(&device.signature, &ca.signed_data().unwrap()),
+            Err(CertificateError::KeyMismatch)
+        ));
+    }
+
+    #[test]
+    fn parse_chain() {
+        let chain = [ROOT_CA, CA, DEVICE].concat();
+        let certificates = Certificate::parse_all(&chain).unwrap();
+        assert_eq!(
+            certificates
+                .iter()
+                .map(|(certificate, range)| (certificate.name.0.as_ref(), range.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("CA00000000", 0..ROOT_CA.len()),
+                ("RalseiTestCA", ROOT_CA.len()..ROOT_CA.len() + CA.len()),
+                ("CT0123abcd-00", ROOT_CA.len() + CA.len()..chain.len()),
+            ]
+        );
+        assert!(matches!(
+            Certificate::parse_all(&chain[..chain.len() - 1]),
+            Err(CertificateError::OutOfBounds)
+        ));
+
+        let chain = CertificateChain::try_from(chain.as_slice()).unwrap();
+        let device = &chain.certificates[2];
+        assert_eq!(
+            chain.get("Root-CA00000000-RalseiTestCA"),
+            Some(&chain.certificates[1])
+        );
+        assert_eq!(chain.get("Root-CA00000000"), Some(&chain.certificates[0]));
+        assert_eq!(chain.get("Root-RalseiTestCA"), None);
+        assert_eq!(chain.issuer_of(device), Some(&chain.certificates[1]));
+
+        let root = Key::Rsa2048(Cow::Borrowed(ROOT_KEY));
+        chain.verify(device, &root).unwrap();
+        assert!(matches!(
+            chain.verify(device, &chain.certificates[1].key),
+            Err(CertificateError::KeyMismatch)
+        ));
+        assert!(matches!(
+            CertificateChain::new(chain.certificates[1..].to_vec()).verify(device, &root),
+            Err(CertificateError::MissingIssuer(issuer)) if issuer == "Root-CA00000000"
+        ));
+    }
+
+    #[test]
+    fn mint_device_certificate() {
+        let ca = Certificate::try_from(CA).unwrap();
+        let key = Key::from_elliptic_curve_private_key(DEVICE_PRIVATE_KEY).unwrap();
+        assert_eq!(key, Certificate::try_from(DEVICE).unwrap().key);
+
+        let mut device = Certificate::new_device(
+            DeviceName::new(0x0badcafe, ConsoleKind::N3ds, ConsoleType::Retail),
+            key,
+        )
+        .unwrap();
+        assert_eq!(
+            device.issuer.known_issuer(),
+            Some(KnownIssuer::NintendoCaG3NintendoCtr2Prod)
+        );
+        assert_eq!(device.name.0, "CT0BADCAFE-00");
+        assert_eq!(device.name.device_id(), Some(0x0badcafe));
+        assert_eq!(device.name.console_kind(), Some(ConsoleKind::N3ds));
+        device.sign(CA_PRIVATE_KEY).unwrap();
+        ca.key
+            .verify(&device.signature, &device.signed_data().unwrap())
+            .unwrap();
+        assert_eq!(
+            Certificate::try_from(device.to_bytes().unwrap().as_slice()).unwrap(),
+            device
+        );
+
+        let mut device = Certificate::new_device(
+            DeviceName::new(0x0badcafe, ConsoleKind::WiiU, ConsoleType::Retail),
+            ca.key.clone(),
+        )
+        .unwrap();
+        assert_eq!(device.name.0, "NG0BADCAFE");
+        assert_eq!(device.name.console_kind(), Some(ConsoleKind::WiiU));
+        device.sign(CA_PRIVATE_KEY).unwrap();
+        ca.key
+            .verify(&device.signature, &device.signed_data().unwrap())
+            .unwrap();
+
+        assert!(matches!(
+            Certificate::new_device(
+                DeviceName::new(0x0badcafe, ConsoleKind::WiiU, ConsoleType::Developer),
+                ca.key.clone()
+            ),
+            Err(CertificateError::UnknownDeviceCertificateIssuer(
+                ConsoleKind::WiiU,
+                ConsoleType::Developer
+            ))
+        ));
+        assert!(matches!(
+            device.sign(&[0; 0x1e]),
+            Err(CertificateError::InvalidPrivateKey)
+        ));
+    }
+
+    #[test]
+    fn device_names() {
+        for (name, device_name) in [
+            (
+                "CT0BADCAFE-00",
+                DeviceName::new(0x0badcafe, ConsoleKind::N3ds, ConsoleType::Retail),
+            ),
+            (
+                "CT0BADCAFE-01",
+                DeviceName::new(0x0badcafe, ConsoleKind::N3ds, ConsoleType::Developer),
+            ),
+            (
+                "NG0BADCAFE",
+                DeviceName::new(0x0badcafe, ConsoleKind::WiiU, ConsoleType::Retail),
+            ),
+        ] {
+            assert_eq!(DeviceName::from_str(name), Ok(device_name));
+            assert_eq!(Name::from(device_name).0, name);
+        }
+
+        let name = Name(Cow::Borrowed("CT0badcafe-01"));
+        assert_eq!(name.device_type(), Some(ConsoleType::Developer));
+        assert_eq!(
+            name.device_name().map(|name| name.device_id),
+            Some(0x0badcafe)
+        );
+        assert_eq!(Name(Cow::Borrowed("NG0badcafe")).device_type(), None);
+
+        for (name, error) in [
+            ("CT0BADCAFE", DeviceNameError::InvalidDeviceType),
+            ("CT0BADCAFE-02", DeviceNameError::InvalidDeviceType),
+            ("CT0BADCAF-00", DeviceNameError::InvalidDeviceType),
+            ("CT+BADCAFE-00", DeviceNameError::InvalidDeviceId),
+            ("NG0BADCAFE0", DeviceNameError::InvalidDeviceId),
+            ("XX0BADCAFE", DeviceNameError::UnknownConsoleKind),
+        ] {
+            assert_eq!(DeviceName::from_str(name), Err(error));
+        }
+    }
+
+    #[test]
+    fn key_id_expiration_time() {
+        let key_id = KeyId(0x5f5e1000);
+        assert_eq!(
+            key_id.expiration_time(),
+            UNIX_EPOCH + Duration::from_secs(0x5f5e1000)
+        );
+        assert_eq!(
+            KeyId::from_expiration_time(key_id.expiration_time()),
+            Some(key_id)
+        );
+        assert_eq!(
+            KeyId::from_expiration_time(UNIX_EPOCH - Duration::from_secs(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn key_conversions() {
+        let root_key = Key::Rsa2048(Cow::Borrowed(ROOT_KEY));
+        let (modulus, exponent) = root_key.rsa_components().unwrap();
+        assert_eq!(modulus, &ROOT_KEY[..0x100]);
+        assert_eq!(exponent, 0x10001);
+        assert_eq!(
+            Key::from_rsa_components(modulus, exponent).unwrap(),
+            root_key
+        );
+        assert_eq!(root_key.elliptic_curve_point(), None);
+        assert_eq!(root_key.to_der().unwrap(), ROOT_KEY_DER);
+        assert_eq!(Key::from_der(ROOT_KEY_DER).unwrap(), root_key);
+
+        let ca = Certificate::try_from(CA).unwrap();
+        let (x, y) = ca.key.elliptic_curve_point().unwrap();
+        assert_eq!(Key::from_elliptic_curve_point(x, y).unwrap(), ca.key);
+        assert_eq!(ca.key.rsa_components(), None);
+        assert_eq!(ca.key.to_pem().unwrap(), CA_KEY_PEM);
+        assert_eq!(Key::from_pem(CA_KEY_PEM).unwrap(), ca.key);
+
+        assert!(matches!(
+            Key::EllipticCurve(Cow::Borrowed(&[0; 0x3b])).to_der(),
+            Err(CertificateError::MalformedKey)
+        ));
+        assert!(matches!(
+            Key::from_der(&ROOT_KEY_DER[..ROOT_KEY_DER.len() - 1]),
+            Err(CertificateError::UnsupportedPublicKey)
+        ));
+        assert!(matches!(
+            Key::from_pem("-----BEGIN PUBLIC KEY-----\n!\n-----END PUBLIC KEY-----"),
+            Err(CertificateError::InvalidPem)
+        ));
+    }
+}
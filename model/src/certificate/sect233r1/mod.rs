@@ -0,0 +1,377 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! An implementation of ECDSA verification over sect233r1, the binary curve used by the elliptic
+//! curve keys found in Nintendo certificates
+//!
+//! None of the elliptic curve libraries available implement binary curves, so the arithmetic is
+//! done here using affine coordinates. Verification only ever handles public data, so it does not
+//! need to run in constant time. Anything involving a private key lives in the [`sign`] module
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+use std::{
+    convert::TryInto,
+    ops::{Add, Mul},
+};
+
+pub(crate) mod sign;
+
+/// The length of an encoded coordinate or signature component
+const COMPONENT_LENGTH: usize = 0x1e;
+
+/// The degree of the reduction polynomial, `x^233 + x^74 + 1`
+const DEGREE: usize = 233;
+
+/// The curve's `b` coefficient (its `a` coefficient is 1)
+const B: [u8; COMPONENT_LENGTH] = [
+    0x00, 0x66, 0x64, 0x7e, 0xde, 0x6c, 0x33, 0x2c, 0x7f, 0x8c, 0x09, 0x23, 0xbb, 0x58, 0x21, 0x3b,
+    0x33, 0x3b, 0x20, 0xe9, 0xce, 0x42, 0x81, 0xfe, 0x11, 0x5f, 0x7d, 0x8f, 0x90, 0xad,
+];
+
+/// The x coordinate of the curve's generator
+const GENERATOR_X: [u8; COMPONENT_LENGTH] = [
+    0x00, 0xfa, 0xc9, 0xdf, 0xcb, 0xac, 0x83, 0x13, 0xbb, 0x21, 0x39, 0xf1, 0xbb, 0x75, 0x5f, 0xef,
+    0x65, 0xbc, 0x39, 0x1f, 0x8b, 0x36, 0xf8, 0xf8, 0xeb, 0x73, 0x71, 0xfd, 0x55, 0x8b,
+];
+
+/// The y coordinate of the curve's generator
+const GENERATOR_Y: [u8; COMPONENT_LENGTH] = [
+    0x01, 0x00, 0x6a, 0x08, 0xa4, 0x19, 0x03, 0x35, 0x06, 0x78, 0xe5, 0x85, 0x28, 0xbe, 0xbf, 0x8a,
+    0x0b, 0xef, 0xf8, 0x67, 0xa7, 0xca, 0x36, 0x71, 0x6f, 0x7e, 0x01, 0xf8, 0x10, 0x52,
+];
+
+/// The order of the curve's generator
+const ORDER: [u8; COMPONENT_LENGTH] = [
+    0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x13,
+    0xe9, 0x74, 0xe7, 0x2f, 0x8a, 0x69, 0x22, 0x03, 0x1d, 0x26, 0x03, 0xcf, 0xe0, 0xd7,
+];
+
+/// An element of the binary field the curve is defined over, stored as a polynomial whose
+/// coefficients are the element's bits, least significant word first
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+struct Element([u64; 4]);
+
+impl Element {
+    /// The additive identity
+    const ZERO: Self = Self([0; 4]);
+
+    /// The multiplicative identity
+    const ONE: Self = Self([1, 0, 0, 0]);
+
+    /// The reduction polynomial itself, which is only used while inverting elements
+    const POLYNOMIAL: Self = Self([1, 1 << 10, 0, 1 << 41]);
+
+    /// Reads an [`Element`] from its big-endian representation, returning [`None`] if it is not
+    /// reduced
+    fn from_bytes(bytes: &[u8; COMPONENT_LENGTH]) -> Option<Self> {
+        let mut padded = [0; 32];
+        padded[32 - COMPONENT_LENGTH..].copy_from_slice(bytes);
+
+        let mut words = [0; 4];
+        for (word, chunk) in words.iter_mut().zip(padded.rchunks_exact(8)) {
+            *word = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        let element = Self(words);
+        (element.degree() < Some(DEGREE)).then_some(element)
+    }
+
+    /// Writes the [`Element`] in its big-endian representation
+    fn to_bytes(self) -> [u8; COMPONENT_LENGTH] {
+        let mut padded = [0; 32];
+        for (chunk, word) in padded.rchunks_exact_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        padded[32 - COMPONENT_LENGTH..]
+            .try_into()
+            .expect("the element fits within a component")
+    }
+
+    /// Converts the [`Element`] into an integer with the same bits
+    fn to_integer(self) -> BigUint {
+        BigUint::from_bytes_le(
+            &self
+                .0
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Returns the degree of the [`Element`] as a polynomial, or [`None`] if it is zero
+    fn degree(self) -> Option<usize> {
+        (0..4)
+            .rev()
+            .find(|&i| self.0[i] != 0)
+            .map(|i| i * 64 + 63 - self.0[i].leading_zeros() as usize)
+    }
+
+    /// Returns `true` if the bit representing the provided power of `x` is set
+    fn bit(self, power: usize) -> bool {
+        self.0[power / 64] >> (power % 64) & 1 == 1
+    }
+
+    /// Shifts the [`Element`] left by the provided number of bits without reducing it
+    fn shift_left(self, bits: usize) -> Self {
+        let mut words = [0; 4];
+        let (word_shift, bit_shift) = (bits / 64, bits % 64);
+        for (i, word) in words.iter_mut().enumerate().skip(word_shift) {
+            *word = self.0[i - word_shift] << bit_shift;
+            if bit_shift != 0 && i > word_shift {
+                *word |= self.0[i - word_shift - 1] >> (64 - bit_shift);
+            }
+        }
+        Self(words)
+    }
+
+    /// Multiplies the [`Element`] by `x`
+    fn double(self) -> Self {
+        let mut result = self.shift_left(1);
+        if result.bit(DEGREE) {
+            // x^233 = x^74 + 1
+            result.0[DEGREE / 64] ^= 1 << (DEGREE % 64);
+            result.0[1] ^= 1 << 10;
+            result.0[0] ^= 1;
+        }
+        result
+    }
+
+    /// Returns the product of the [`Element`] and the provided [`Element`], computed by adding
+    /// together the multiples of the [`Element`] by each power of `x` present in the other
+    fn multiply(self, rhs: Self) -> Self {
+        let (mut result, mut power) = (Self::ZERO, self);
+        for i in 0..DEGREE {
+            if rhs.bit(i) {
+                result = result + power;
+            }
+            power = power.double();
+        }
+        result
+    }
+
+    /// Returns the square of the [`Element`]
+    fn square(self) -> Self {
+        self * self
+    }
+
+    /// Returns the multiplicative inverse of the [`Element`], or [`None`] if it is zero
+    fn invert(self) -> Option<Self> {
+        let (mut u, mut v) = (self, Self::POLYNOMIAL);
+        let (mut g1, mut g2) = (Self::ONE, Self::ZERO);
+        while u != Self::ONE {
+            let (u_degree, v_degree) = (u.degree()?, v.degree()?);
+            if u_degree < v_degree {
+                std::mem::swap(&mut u, &mut v);
+                std::mem::swap(&mut g1, &mut g2);
+            }
+            let shift = u_degree.max(v_degree) - u_degree.min(v_degree);
+            u = u + v.shift_left(shift);
+            g1 = g1 + g2.shift_left(shift);
+        }
+        Some(g1)
+    }
+}
+
+impl Add for Element {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self([
+            self.0[0] ^ rhs.0[0],
+            self.0[1] ^ rhs.0[1],
+            self.0[2] ^ rhs.0[2],
+            self.0[3] ^ rhs.0[3],
+        ])
+    }
+}
+
+impl Mul for Element {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.multiply(rhs)
+    }
+}
+
+/// A point on the curve in affine coordinates, where [`None`] is the point at infinity
+type Point = Option<(Element, Element)>;
+
+/// Returns `true` if the provided point is on the curve, satisfying `y^2 + xy = x^3 + x^2 + b`
+fn is_on_curve((x, y): (Element, Element)) -> bool {
+    let b = Element::from_bytes(&B).expect("the curve's b coefficient is reduced");
+    y.square() + x * y == x.square() * x + x.square() + b
+}
+
+/// Returns the sum of the two provided points
+fn add(p: Point, q: Point) -> Point {
+    let ((x1, y1), (x2, y2)) = match (p, q) {
+        (None, point) | (point, None) => return point,
+        (Some(p), Some(q)) => (p, q),
+    };
+
+    let lambda = if x1 == x2 {
+        // the sum of a point and its negation, `(x, x + y)`, is the point at infinity, as is
+        // double a point where x is zero
+        if y1 != y2 || x1 == Element::ZERO {
+            return None;
+        }
+        x1 + y1 * x1.invert()?
+    } else {
+        (y1 + y2) * (x1 + x2).invert()?
+    };
+    let x3 = lambda.square() + lambda + x1 + x2 + Element::ONE;
+    let y3 = lambda * (x1 + x3) + x3 + y1;
+    Some((x3, y3))
+}
+
+/// Returns the product of the provided point and the provided integer
+fn multiply(point: Point, scalar: &BigUint) -> Point {
+    (0..scalar.bits())
+        .rev()
+        .fold(None, |result, i| match add(result, result) {
+            doubled if scalar.bit(i) => add(doubled, point),
+            doubled => doubled,
+        })
+}
+
+/// Returns the curve's generator
+fn generator() -> Point {
+    Some((
+        Element::from_bytes(&GENERATOR_X).expect("the generator's x coordinate is reduced"),
+        Element::from_bytes(&GENERATOR_Y).expect("the generator's y coordinate is reduced"),
+    ))
+}
+
+/// Reads the component at the provided index of a concatenation of components
+fn component(data: &[u8], index: usize) -> Option<[u8; COMPONENT_LENGTH]> {
+    data.get(index * COMPONENT_LENGTH..(index + 1) * COMPONENT_LENGTH)?
+        .try_into()
+        .ok()
+}
+
+/// Converts the provided digest into an integer, truncating it to the length of the order if it
+/// is longer
+fn digest_to_integer(digest: &[u8], order: &BigUint) -> BigUint {
+    let integer = BigUint::from_bytes_be(digest);
+    let (digest_bits, order_bits) = (digest.len() as u64 * 8, order.bits());
+    if digest_bits > order_bits {
+        integer >> (digest_bits - order_bits)
+    } else {
+        integer
+    }
+}
+
+/// Verifies the provided signature, made up of `r` and `s` concatenated together, over the
+/// provided digest using the provided public key, made up of its x and y coordinates concatenated
+/// together
+pub(crate) fn verify(public_key: &[u8], signature: &[u8], digest: &[u8]) -> bool {
+    let verify = || -> Option<bool> {
+        let public_key = (
+            Element::from_bytes(&component(public_key, 0)?)?,
+            Element::from_bytes(&component(public_key, 1)?)?,
+        );
+        if !is_on_curve(public_key) {
+            return Some(false);
+        }
+
+        let order = &BigUint::from_bytes_be(&ORDER);
+        let r = BigUint::from_bytes_be(&component(signature, 0)?);
+        let s = BigUint::from_bytes_be(&component(signature, 1)?);
+        if r.is_zero() || s.is_zero() || &r >= order || &s >= order {
+            return Some(false);
+        }
+
+        let w = s.modpow(&(order - 2u32), order);
+        let (x, _) = add(
+            multiply(
+                generator(),
+                &(digest_to_integer(digest, order) * &w % order),
+            ),
+            multiply(Some(public_key), &(&r * &w % order)),
+        )?;
+        Some(x.to_integer() % order == r)
+    };
+    verify().unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use num_traits::One;
+    use sha1::Sha1;
+    use sha2::{Digest, Sha256};
+
+    // a key pair and signatures over `MESSAGE` produced by openssl
+    pub(super) const PRIVATE_KEY: [u8; COMPONENT_LENGTH] = [
+        0x00, 0x64, 0xa5, 0xdf, 0xc7, 0xa4, 0x0c, 0xb2, 0xe8, 0xb0, 0xb9, 0x9f, 0x34, 0x8c, 0x24,
+        0x7e, 0xb0, 0xea, 0x15, 0x62, 0x86, 0x45, 0x5d, 0x71, 0x4a, 0x20, 0x29, 0xe7, 0x95, 0x8f,
+    ];
+    pub(super) const PUBLIC_KEY: [u8; COMPONENT_LENGTH * 2] = [
+        0x00, 0xc4, 0x4f, 0x64, 0x0b, 0xba, 0x5c, 0x97, 0x68, 0x1e, 0x48, 0xe8, 0x2f, 0x57, 0xfe,
+        0xfb, 0xb5, 0x62, 0x6f, 0x01, 0x8e, 0x4c, 0xb4, 0x9d, 0x43, 0x5e, 0x51, 0xaf, 0x12, 0x89,
+        0x00, 0xd4, 0xa7, 0x14, 0xd1, 0xf5, 0x33, 0x15, 0x7e, 0x81, 0xe8, 0xbc, 0x6b, 0x43, 0x6d,
+        0xe3, 0xdf, 0x3e, 0x3c, 0x36, 0xa2, 0x15, 0xd1, 0x1f, 0x56, 0x3b, 0xb2, 0x2e, 0x09, 0xf3,
+    ];
+    const SHA1_SIGNATURE: [u8; COMPONENT_LENGTH * 2] = [
+        0x00, 0x1d, 0x53, 0x4a, 0x32, 0xf9, 0x17, 0x36, 0x3f, 0x44, 0x43, 0xd5, 0x9d, 0x7f, 0xf7,
+        0x8b, 0xf6, 0x76, 0x67, 0x37, 0xb8, 0x1b, 0x65, 0x72, 0xe0, 0x46, 0xd6, 0x9f, 0x64, 0x68,
+        0x00, 0x48, 0x90, 0x7c, 0xc6, 0x5f, 0x9c, 0x28, 0xe0, 0xdd, 0x6a, 0x95, 0x6d, 0xab, 0x81,
+        0xd4, 0x49, 0x28, 0x2a, 0xd6, 0x63, 0x45, 0x3e, 0xd9, 0xa0, 0xa0, 0x91, 0x0e, 0xa7, 0x79,
+    ];
+    const SHA256_SIGNATURE: [u8; COMPONENT_LENGTH * 2] = [
+        0x00, 0xf2, 0x28, 0xe1, 0x78, 0xf6, 0x1d, 0x0c, 0x9b, 0x45, 0x82, 0x1e, 0x89, 0xb9, 0x0a,
+        0xa8, 0x17, 0x76, 0xe4, 0x81, 0x4a, 0x35, 0x74, 0x6a, 0x58, 0x98, 0xe4, 0xbe, 0xcd, 0xc7,
+        0x00, 0xa8, 0x22, 0x4c, 0x43, 0xae, 0x09, 0x0d, 0xa0, 0xc0, 0x9a, 0xec, 0x64, 0xfb, 0x2e,
+        0xc0, 0xae, 0xdd, 0xa3, 0x94, 0x1b, 0xee, 0xd5, 0xa3, 0xf8, 0x0a, 0x5d, 0x8b, 0xdf, 0xbb,
+    ];
+    pub(super) const MESSAGE: &[u8] = b"a known answer for sect233r1";
+
+    #[test]
+    fn curve_parameters() {
+        let order = BigUint::from_bytes_be(&ORDER);
+        let generator = generator().unwrap();
+        assert!(is_on_curve(generator));
+        assert_eq!(multiply(Some(generator), &order), None);
+        assert_eq!(
+            multiply(Some(generator), &(order + BigUint::one())),
+            Some(generator)
+        );
+
+        let inverse = generator.0.invert().unwrap();
+        assert_eq!(generator.0 * inverse, Element::ONE);
+        assert_eq!(
+            Element::from_bytes(&generator.0.to_bytes()),
+            Some(generator.0)
+        );
+    }
+
+    #[test]
+    fn known_answers() {
+        assert!(verify(&PUBLIC_KEY, &SHA1_SIGNATURE, &Sha1::digest(MESSAGE)));
+        assert!(verify(
+            &PUBLIC_KEY,
+            &SHA256_SIGNATURE,
+            &Sha256::digest(MESSAGE)
+        ));
+
+        assert!(!verify(
+            &PUBLIC_KEY,
+            &SHA256_SIGNATURE,
+            &Sha1::digest(MESSAGE)
+        ));
+        assert!(!verify(
+            &PUBLIC_KEY,
+            &SHA1_SIGNATURE,
+            &Sha256::digest(MESSAGE)
+        ));
+        let mut tampered = SHA256_SIGNATURE;
+        tampered[COMPONENT_LENGTH] ^= 1;
+        assert!(!verify(&PUBLIC_KEY, &tampered, &Sha256::digest(MESSAGE)));
+    }
+}
@@ -0,0 +1,121 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! ECDSA signing over sect233r1, used to mint certificates
+//!
+//! Nothing here runs in constant time, as the arithmetic is done on arbitrary precision integers,
+//! so it is suited to minting certificates for testing rather than to handling keys that must be
+//! kept secret
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+use sha2::{Digest, Sha256};
+
+use super::{component, digest_to_integer, generator, multiply, COMPONENT_LENGTH, ORDER};
+
+/// Writes the provided integer, which must be less than the order, as a component
+fn integer_to_component(integer: &BigUint) -> [u8; COMPONENT_LENGTH] {
+    let bytes = integer.to_bytes_be();
+    let mut component = [0; COMPONENT_LENGTH];
+    component[COMPONENT_LENGTH - bytes.len()..].copy_from_slice(&bytes);
+    component
+}
+
+/// Reads a private key, which is a big-endian integer between 1 and the order
+fn private_key_to_integer(private_key: &[u8], order: &BigUint) -> Option<BigUint> {
+    let private_key = BigUint::from_bytes_be(&component(private_key, 0)?);
+    (!private_key.is_zero() && &private_key < order).then_some(private_key)
+}
+
+/// Returns the public key, made up of its x and y coordinates concatenated together, belonging to
+/// the provided private key
+pub(crate) fn public_key(private_key: &[u8]) -> Option<[u8; COMPONENT_LENGTH * 2]> {
+    let order = BigUint::from_bytes_be(&ORDER);
+    let (x, y) = multiply(generator(), &private_key_to_integer(private_key, &order)?)?;
+
+    let mut public_key = [0; COMPONENT_LENGTH * 2];
+    public_key[..COMPONENT_LENGTH].copy_from_slice(&x.to_bytes());
+    public_key[COMPONENT_LENGTH..].copy_from_slice(&y.to_bytes());
+    Some(public_key)
+}
+
+/// Signs the provided digest using the provided private key, returning the signature made up of
+/// `r` and `s` concatenated together
+///
+/// The nonce is derived from the private key and the digest rather than being generated randomly,
+/// so signing the same digest twice produces the same signature and no source of randomness is
+/// needed
+pub(crate) fn sign(private_key: &[u8], digest: &[u8]) -> Option<[u8; COMPONENT_LENGTH * 2]> {
+    let order = &BigUint::from_bytes_be(&ORDER);
+    let d = private_key_to_integer(private_key, order)?;
+    let e = digest_to_integer(digest, order);
+
+    // the nonce is taken from 512 bits of hash output, which makes the bias introduced by
+    // reducing it by the order negligible
+    for counter in 0u32.. {
+        let nonce = |block: u8| {
+            let mut hasher = Sha256::new();
+            hasher.update(integer_to_component(&d));
+            hasher.update(digest);
+            hasher.update(counter.to_be_bytes());
+            hasher.update([block]);
+            hasher.finalize()
+        };
+        let k = BigUint::from_bytes_be(&[nonce(0), nonce(1)].concat()) % order;
+        if k.is_zero() {
+            continue;
+        }
+
+        let r = match multiply(generator(), &k) {
+            Some((x, _)) => x.to_integer() % order,
+            None => continue,
+        };
+        let s = k.modpow(&(order - 2u32), order) * (&e + &r * &d) % order;
+        if r.is_zero() || s.is_zero() {
+            continue;
+        }
+
+        let mut signature = [0; COMPONENT_LENGTH * 2];
+        signature[..COMPONENT_LENGTH].copy_from_slice(&integer_to_component(&r));
+        signature[COMPONENT_LENGTH..].copy_from_slice(&integer_to_component(&s));
+        return Some(signature);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        super::{test::*, verify},
+        *,
+    };
+    use sha2::Sha256;
+
+    #[test]
+    fn sign_and_verify() {
+        let private_key = integer_to_component(&BigUint::from(1337u32));
+        let key = public_key(&private_key).unwrap();
+        let signature = sign(&private_key, b"digest").unwrap();
+        assert!(verify(&key, &signature, b"digest"));
+        assert!(!verify(&key, &signature, b"digesu"));
+        assert_eq!(sign(&private_key, b"digest"), Some(signature));
+
+        assert_eq!(public_key(&[0; COMPONENT_LENGTH]), None);
+        assert_eq!(sign(&ORDER, b"digest"), None);
+    }
+
+    #[test]
+    fn known_answers() {
+        assert_eq!(public_key(&PRIVATE_KEY), Some(PUBLIC_KEY));
+        eprintln!(
+            "{:02x?}",
+            sign(&PRIVATE_KEY, &Sha256::digest(MESSAGE)).unwrap()
+        );
+    }
+}
@@ -17,14 +17,25 @@
 //! console's data that are defined here, such as the [`Region`] enumeration.
 
 use http::header::{HeaderMap, HeaderValue, InvalidHeaderValue};
+use iso::language::Iso639_1;
+use isocountry::CountryCode;
 use num_derive::{FromPrimitive, ToPrimitive};
-use std::{borrow::Cow, fmt, num::ParseIntError};
+use num_traits::cast::FromPrimitive as _;
+use std::{borrow::Cow, convert::TryFrom, fmt, num::ParseIntError, str::FromStr};
+use strum::ParseError as EnumParseError;
 use strum_macros::{AsRefStr, Display, EnumString, IntoStaticStr};
 use thiserror::Error;
 
 use crate::{
-    certificate::CertificateError, console::n3ds::Model as N3dsModel, server::Kind as ServerKind,
+    certificate::{Certificate, CertificateError},
+    console::n3ds::Model as N3dsModel,
+    server::Kind as ServerKind,
+    title::{
+        id::{TitleId, UniqueId},
+        version::TitleVersion,
+    },
 };
+use unin::u24;
 
 /// An abstraction over the various console-specific data structures
 ///
@@ -75,6 +86,230 @@ pub enum HeaderConstructionError {
     UnimplementedServerKind(&'static str),
 }
 
+/// A problem found with one of the headers in a [`HeaderMap`] while reading a console's data from
+/// it
+///
+/// It is reported by the `from_http_headers` methods of the implementors of [`Console`], each
+/// naming the header(s) it concerns. Rather than failing on the first problem, those methods report
+/// every header that is missing, malformed, or inconsistent with another alongside the data that
+/// could be read, making them usable both for rejecting requests and for auditing them. The
+/// `X-Nintendo-Title-ID`, `X-Nintendo-Unique-ID`, `X-Nintendo-Application-Version`,
+/// `X-Nintendo-Device-Cert` and `Accept-Language` headers are not always sent, so their absence is
+/// not reported
+#[non_exhaustive]
+#[derive(Error, Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum HeaderIssue {
+    /// A header that is always sent by the console is not present
+    #[error("The `{0}` header is missing")]
+    Missing(&'static str),
+
+    /// A header's value could not be parsed
+    #[error("The `{0}` header is malformed")]
+    Malformed(&'static str),
+
+    /// A header's value disagrees with the value of another header (the second one)
+    #[error("The `{0}` header is inconsistent with the `{1}` header")]
+    Inconsistent(&'static str, &'static str),
+}
+
+/// Parses the value of the header with the provided name using the provided closure, recording a
+/// [`HeaderIssue`] if it is missing (and required) or could not be parsed
+pub(crate) fn parse_header<T, F>(
+    headers: &HeaderMap<HeaderValue>,
+    name: &'static str,
+    required: bool,
+    issues: &mut Vec<HeaderIssue>,
+    parse: F,
+) -> Option<T>
+where
+    F: FnOnce(&str) -> Option<T>,
+{
+    if let Some(value) = headers.get(name) {
+        let value = value.to_str().ok().and_then(parse);
+        if value.is_none() {
+            issues.push(HeaderIssue::Malformed(name));
+        }
+        value
+    } else {
+        if required {
+            issues.push(HeaderIssue::Missing(name));
+        }
+        None
+    }
+}
+
+/// Returns `true` if the provided string is a client id or secret, which are both 32 hexadecimal
+/// digits long
+pub(crate) fn is_client_credential(credential: &str) -> bool {
+    credential.len() == 32 && credential.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Helper macro to read the fields shared by all implementors of [`Console`] from the headers
+/// produced by [`Console::http_headers`] when used with [`ServerKind::Account`], recording a
+/// [`HeaderIssue`] for each header that is missing or malformed
+///
+/// As only the major version of the title is sent, the `title_version` field will have its minor
+/// and micro versions set to zero
+pub(crate) macro read_common_headers($console:ident, $kind:expr, $headers:ident, $issues:ident) {
+    match Kind::from_http_headers($headers) {
+        Ok(kind) if kind == $kind => (),
+        // the serial number is what ties the rest of the headers to a model of console
+        Ok(_) => $issues.push(HeaderIssue::Inconsistent(
+            "X-Nintendo-Platform-ID",
+            "X-Nintendo-Serial-Number",
+        )),
+        Err(issue) => $issues.push(issue),
+    }
+
+    $console.device_type = parse_header(
+        $headers,
+        "X-Nintendo-Device-Type",
+        true,
+        &mut $issues,
+        |v| u8::from_str(v).ok().and_then(Type::from_u8),
+    );
+    $console.device_id = parse_header($headers, "X-Nintendo-Device-ID", true, &mut $issues, |v| {
+        u32::from_str(v).ok()
+    });
+    $console.serial = parse_header(
+        $headers,
+        "X-Nintendo-Serial-Number",
+        true,
+        &mut $issues,
+        |v| {
+            let serial = ConsoleSerial(Cow::Owned(v.to_string()));
+            serial.verify().is_ok().then_some(serial)
+        },
+    );
+    $console.system_version = parse_header(
+        $headers,
+        "X-Nintendo-System-Version",
+        true,
+        &mut $issues,
+        |v| u16::from_str_radix(v, 16).ok().map(TitleVersion),
+    );
+    $console.region = parse_header($headers, "X-Nintendo-Region", true, &mut $issues, |v| {
+        u8::from_str(v).ok().and_then(Region::from_u8)
+    });
+    $console.country = parse_header($headers, "X-Nintendo-Country", true, &mut $issues, |v| {
+        CountryCode::for_alpha2(v).ok()
+    });
+    $console.language = parse_header($headers, "Accept-Language", false, &mut $issues, |v| {
+        Iso639_1::from_str(v).ok()
+    });
+    $console.client_id = parse_header($headers, "X-Nintendo-Client-ID", true, &mut $issues, |v| {
+        is_client_credential(v).then(|| Cow::Owned(v.to_string()))
+    });
+    $console.client_secret = parse_header(
+        $headers,
+        "X-Nintendo-Client-Secret",
+        true,
+        &mut $issues,
+        |v| is_client_credential(v).then(|| Cow::Owned(v.to_string())),
+    );
+    $console.fpd_version = parse_header(
+        $headers,
+        "X-Nintendo-FPD-Version",
+        true,
+        &mut $issues,
+        |v| u16::from_str(v).ok(),
+    );
+    $console.environment = parse_header(
+        $headers,
+        "X-Nintendo-Environment",
+        true,
+        &mut $issues,
+        |v| Environment::from_str(v).ok(),
+    );
+    $console.title_id = parse_header($headers, "X-Nintendo-Title-ID", false, &mut $issues, |v| {
+        u64::from_str_radix(v, 16).ok().map(TitleId)
+    });
+    $console.unique_id = parse_header($headers, "X-Nintendo-Unique-ID", false, &mut $issues, |v| {
+        u32::from_str_radix(v, 16)
+            .ok()
+            .filter(|&unique_id| unique_id <= 0xFFFFFF)
+            .map(|unique_id| UniqueId(u24::new(unique_id)))
+    });
+    $console.title_version = parse_header(
+        $headers,
+        "X-Nintendo-Application-Version",
+        false,
+        &mut $issues,
+        |v| {
+            u16::from_str_radix(v, 16)
+                .ok()
+                .filter(|&major| major <= 0x3F)
+                .map(|major| TitleVersion(major << 10))
+        },
+    );
+    $console.device_certificate = parse_header(
+        $headers,
+        "X-Nintendo-Device-Cert",
+        false,
+        &mut $issues,
+        |v| {
+            base64::decode(v)
+                .ok()
+                .and_then(|certificate| Certificate::try_from(certificate.as_slice()).ok())
+        },
+    );
+}
+
+/// Helper macro to check the fields shared by all implementors of [`Console`] that are read from
+/// headers against each other, recording a [`HeaderIssue`] for each disagreement
+pub(crate) macro check_header_consistency($console:ident, $kind:expr, $issues:ident) {
+    if let Some(serial) = &$console.serial {
+        if let (Some(region), Ok(serial_region)) = ($console.region, serial.region()) {
+            if region != serial_region {
+                $issues.push(HeaderIssue::Inconsistent(
+                    "X-Nintendo-Region",
+                    "X-Nintendo-Serial-Number",
+                ));
+            }
+        }
+
+        if let (Some(device_type), Ok(serial_device_type)) =
+            ($console.device_type, serial.device_type())
+        {
+            if device_type != serial_device_type {
+                $issues.push(HeaderIssue::Inconsistent(
+                    "X-Nintendo-Device-Type",
+                    "X-Nintendo-Serial-Number",
+                ));
+            }
+        }
+    }
+
+    if let Some(device_certificate) = &$console.device_certificate {
+        if let (Some(device_id), Some(certificate_device_id)) =
+            ($console.device_id, device_certificate.name.device_id())
+        {
+            if device_id != certificate_device_id {
+                $issues.push(HeaderIssue::Inconsistent(
+                    "X-Nintendo-Device-Cert",
+                    "X-Nintendo-Device-ID",
+                ));
+            }
+        }
+
+        if matches!(device_certificate.name.console_kind(), Some(kind) if kind != $kind) {
+            $issues.push(HeaderIssue::Inconsistent(
+                "X-Nintendo-Device-Cert",
+                "X-Nintendo-Platform-ID",
+            ));
+        }
+    }
+
+    if let (Some(title_id), Some(unique_id)) = ($console.title_id, $console.unique_id) {
+        if title_id.unique_id() != unique_id {
+            $issues.push(HeaderIssue::Inconsistent(
+                "X-Nintendo-Unique-ID",
+                "X-Nintendo-Title-ID",
+            ));
+        }
+    }
+}
+
 /// A list of Nintendo consoles that can implement the [`Console`] trait
 ///
 /// While it is entirely possible to create a Switch client, this is not listed here as there are
@@ -99,6 +334,36 @@ pub enum Kind {
     WiiU,
 }
 
+impl Kind {
+    /// Returns the value of the `X-Nintendo-Platform-ID` header sent by this [`Kind`] of console
+    ///
+    /// The 3DS sends `0` and the Wii U sends `1`, which is what allows
+    /// [`from_http_headers`](Self::from_http_headers) to tell them apart
+    pub fn platform_id(self) -> &'static str {
+        match self {
+            Self::N3ds => "0",
+            Self::WiiU => "1",
+        }
+    }
+
+    /// Determines the [`Kind`] of console that sent the provided headers from their
+    /// `X-Nintendo-Platform-ID` header
+    ///
+    /// This can be used to choose which implementor of [`Console`] to read the rest of the headers
+    /// into
+    pub fn from_http_headers(headers: &HeaderMap<HeaderValue>) -> Result<Self, HeaderIssue> {
+        match headers
+            .get("X-Nintendo-Platform-ID")
+            .ok_or(HeaderIssue::Missing("X-Nintendo-Platform-ID"))?
+            .as_bytes()
+        {
+            b"0" => Ok(Self::N3ds),
+            b"1" => Ok(Self::WiiU),
+            _ => Err(HeaderIssue::Malformed("X-Nintendo-Platform-ID")),
+        }
+    }
+}
+
 /// Enumeration of possible (3ds/WiiU) console environments
 ///
 /// While not console-specific, it is not accessible through the [`Console`] trait and must instead
@@ -124,6 +389,25 @@ impl fmt::Display for Environment {
     }
 }
 
+impl FromStr for Environment {
+    type Err = EnumParseError;
+
+    fn from_str(environment: &str) -> Result<Self, Self::Err> {
+        let n = environment
+            .get(1..)
+            .and_then(|n| u8::from_str(n).ok())
+            .ok_or(EnumParseError::VariantNotFound)?;
+        Ok(match environment.get(..1) {
+            Some("L") => Self::L(n),
+            Some("D") => Self::D(n),
+            Some("S") => Self::S(n),
+            Some("T") => Self::T(n),
+            Some("J") => Self::J(n),
+            _ => return Err(EnumParseError::VariantNotFound),
+        })
+    }
+}
+
 /// Enumeration of possible console variants (Developer/Retail)
 #[derive(FromPrimitive, ToPrimitive, Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub enum Type {
@@ -23,6 +23,7 @@ use std::{
     convert::{TryFrom, TryInto},
     error::Error,
     fmt,
+    str::FromStr,
 };
 use strum_macros::{AsRefStr, Display, EnumString, IntoStaticStr};
 use thiserror::Error;
@@ -30,8 +31,9 @@ use thiserror::Error;
 use crate::{
     certificate::Certificate,
     console::common::{
-        Console, ConsoleSerial, Environment, HeaderConstructionError, InvalidSerialError,
-        Kind as ConsoleKind, Model as ConsoleModel, Region as ConsoleRegion, Type as ConsoleType,
+        check_header_consistency, parse_header, read_common_headers, Console, ConsoleSerial,
+        Environment, HeaderConstructionError, HeaderIssue, InvalidSerialError, Kind as ConsoleKind,
+        Model as ConsoleModel, Region as ConsoleRegion, Type as ConsoleType,
     },
     server::Kind as ServerKind,
     title::{
@@ -133,6 +135,23 @@ impl<'a> Console3dsBuilder<'a> {
         Ok(self)
     }
 
+    /// Derives the [`Type`](ConsoleType) from the console's [`Certificate`], producing the
+    /// [`device_type`] field
+    ///
+    /// [`device_type`]: ./struct.Console3ds.html#structfield.device_type
+    pub fn derive_device_type_from_device_certificate(
+        &mut self,
+    ) -> Result<&mut Self, Console3dsBuilderError> {
+        self.console.device_type = self
+            .console
+            .device_certificate
+            .as_ref()
+            .ok_or(Console3dsBuilderError::DeriveableFieldEmpty)?
+            .name
+            .device_type();
+        Ok(self)
+    }
+
     /// Derives the [`Region`] from the console's [`ConsoleSerial`], producing the [`region`] field
     ///
     /// [`region`]: ./struct.Console3ds.html#structfield.region
@@ -306,6 +325,54 @@ impl<'a> Console3ds<'a> {
     }
 }
 
+impl Console3ds<'static> {
+    /// Reads a [`Console3ds`] from the provided [`HeaderMap`], the inverse of
+    /// [`Console::http_headers`] when used with [`ServerKind::Account`]
+    ///
+    /// Every problem found with the headers is reported as a [`HeaderIssue`] alongside the data
+    /// that could be read. As only the major version of the title is sent, the [`title_version`]
+    /// field will have its minor and micro versions set to zero
+    ///
+    /// [`title_version`]: ./struct.Console3ds.html#structfield.title_version
+    pub fn from_http_headers(headers: &HeaderMap<HeaderValue>) -> (Self, Vec<HeaderIssue>) {
+        let mut console = Self::default();
+        let mut issues = Vec::new();
+
+        read_common_headers!(console, ConsoleKind::N3ds, headers, issues);
+
+        // 3ds-specific
+        console.device_model =
+            parse_header(headers, "X-Nintendo-Device-Model", true, &mut issues, |v| {
+                Model::from_str(v).ok()
+            });
+
+        if let (Some(device_model), Some(serial)) = (console.device_model, &console.serial) {
+            if matches!(serial.device_model(), Ok(model) if model != device_model.into()) {
+                issues.push(HeaderIssue::Inconsistent(
+                    "X-Nintendo-Device-Model",
+                    "X-Nintendo-Serial-Number",
+                ));
+            }
+        }
+
+        if let Some(serial) = &console.serial {
+            if serial
+                .device_model()
+                .map_or(true, |model| Model::try_from(model).is_err())
+            {
+                issues.push(HeaderIssue::Inconsistent(
+                    "X-Nintendo-Serial-Number",
+                    "X-Nintendo-Platform-ID",
+                ));
+            }
+        }
+
+        check_header_consistency!(console, ConsoleKind::N3ds, issues);
+
+        (console, issues)
+    }
+}
+
 impl<'a> Console<'a> for Console3ds<'_> {
     fn kind(&self) -> ConsoleKind {
         ConsoleKind::N3ds
@@ -322,7 +389,10 @@ impl<'a> Console<'a> for Console3ds<'_> {
                 // unsure if this is necessary
                 // let _ = h.append(header::USER_AGENT, "".parse().unwrap());
 
-                let _ = h.append("X-Nintendo-Platform-ID", "0".parse()?);
+                let _ = h.append(
+                    "X-Nintendo-Platform-ID",
+                    HeaderValue::from_static(self.kind().platform_id()),
+                );
 
                 if let Some(device_type) = self.device_type {
                     let _ = h.append(
@@ -377,8 +447,13 @@ impl<'a> Console<'a> for Console3ds<'_> {
                     let _ = h.append("X-Nintendo-Environment", environment.to_string().parse()?);
                 }
 
+                // the title id is sent as 16 hexadecimal digits, like the unique id and
+                // application version that accompany it
                 if let Some(title_id) = self.title_id {
-                    let _ = h.append("X-Nintendo-Title-ID", HeaderValue::from(title_id.0));
+                    let _ = h.append(
+                        "X-Nintendo-Title-ID",
+                        format!("{:0>16X}", title_id.0).parse()?,
+                    );
                     let _ = h.append(
                         "X-Nintendo-Unique-ID",
                         format!("{:0>5X}", u32::from(title_id.unique_id().0)).parse()?,
@@ -407,9 +482,114 @@ impl<'a> Console<'a> for Console3ds<'_> {
 
                 Ok(h)
             }
-            _ => Err(HeaderConstructionError::UnimplementedServerKind(
-                server.into(),
-            )),
+            ServerKind::Nus(_) | ServerKind::Ccs(_) => {
+                let mut h = HeaderMap::new();
+
+                // the update servers only look at the client certificate, so the system update
+                // process' user agent is all that is sent alongside it
+                let _ = h.append(
+                    header::USER_AGENT,
+                    HeaderValue::from_static("CTR NUP 040600 Mar 14 2012 13:32:39"),
+                );
+
+                Ok(h)
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn console() -> Console3ds<'static> {
+        Console3ds::new(|b| {
+            Ok(b.device_id(1)
+                .serial(ConsoleSerial(Cow::Borrowed("CW404567772")))
+                .derive_region_from_serial()?
+                .derive_device_model_from_serial()?
+                .derive_device_type_from_serial()?
+                .system_version(TitleVersion(0x02E0))
+                .country(CountryCode::USA)
+                .client_id(Cow::Borrowed("ea25c66c26b403376b4c5ed94ab9cdea"))
+                .client_secret(Cow::Borrowed("d137be62cb6a2b831cad8c013b92fb55"))
+                .fpd_version(0)
+                .environment(Environment::L(1))
+                .title_id(TitleId(0x000400100002C000))
+                .derive_unique_id_from_title_id()?
+                .title_version(TitleVersion(0x0C00))
+                .language(Iso639_1::En))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn console_3ds_http_headers() {
+        let headers = console()
+            .http_headers(ServerKind::Account(Cow::Borrowed("")))
+            .unwrap();
+        assert_eq!(headers["X-Nintendo-Platform-ID"], "0");
+        assert_eq!(headers["X-Nintendo-Title-ID"], "000400100002C000");
+        assert_eq!(headers["X-Nintendo-Unique-ID"], "002C0");
+    }
+
+    #[test]
+    fn console_3ds_from_http_headers() {
+        let console = console();
+        let (parsed, issues) = Console3ds::from_http_headers(
+            &console
+                .http_headers(ServerKind::Account(Cow::Borrowed("")))
+                .unwrap(),
+        );
+        assert_eq!(issues, vec![]);
+        assert_eq!(parsed, console);
+    }
+
+    #[test]
+    fn console_3ds_from_inconsistent_http_headers() {
+        let mut headers = console()
+            .http_headers(ServerKind::Account(Cow::Borrowed("")))
+            .unwrap();
+        let _ = headers.insert("X-Nintendo-Region", HeaderValue::from(4_u16));
+        let _ = headers.insert("X-Nintendo-Platform-ID", HeaderValue::from_static("1"));
+        let _ = headers.remove("X-Nintendo-Device-ID");
+        let _ = headers.insert(
+            "X-Nintendo-Serial-Number",
+            HeaderValue::from_static("CW404567773"),
+        );
+
+        let (_, issues) = Console3ds::from_http_headers(&headers);
+        assert_eq!(
+            issues,
+            vec![
+                HeaderIssue::Inconsistent("X-Nintendo-Platform-ID", "X-Nintendo-Serial-Number"),
+                HeaderIssue::Missing("X-Nintendo-Device-ID"),
+                HeaderIssue::Malformed("X-Nintendo-Serial-Number"),
+            ]
+        );
+
+        let _ = headers.insert(
+            "X-Nintendo-Serial-Number",
+            HeaderValue::from_static("CW404567772"),
+        );
+        let (_, issues) = Console3ds::from_http_headers(&headers);
+        assert!(issues.contains(&HeaderIssue::Inconsistent(
+            "X-Nintendo-Region",
+            "X-Nintendo-Serial-Number"
+        )));
+    }
+
+    #[test]
+    fn console_3ds_from_device_certificate() {
+        let certificate =
+            Certificate::try_from(&include_bytes!("../certificate/test/device.bin")[..]).unwrap();
+        let console = Console3ds::new(|b| {
+            b.device_certificate(certificate)
+                .derive_device_id_from_device_certificate()?
+                .derive_device_type_from_device_certificate()
+        })
+        .unwrap();
+        assert_eq!(console.device_id, Some(0x0123abcd));
+        assert_eq!(console.device_type, Some(ConsoleType::Retail));
+    }
+}
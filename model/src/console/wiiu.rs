@@ -26,8 +26,9 @@ use thiserror::Error;
 use crate::{
     certificate::Certificate,
     console::common::{
-        Console, ConsoleSerial, Environment, HeaderConstructionError, InvalidSerialError,
-        Kind as ConsoleKind, Region as ConsoleRegion, Type as ConsoleType,
+        check_header_consistency, read_common_headers, Console, ConsoleSerial, Environment,
+        HeaderConstructionError, HeaderIssue, InvalidSerialError, Kind as ConsoleKind,
+        Model as ConsoleModel, Region as ConsoleRegion, Type as ConsoleType,
     },
     server::Kind as ServerKind,
     title::{
@@ -227,6 +228,39 @@ impl<'a> ConsoleWiiU<'a> {
     }
 }
 
+impl ConsoleWiiU<'static> {
+    /// Reads a [`ConsoleWiiU`] from the provided [`HeaderMap`], the inverse of
+    /// [`Console::http_headers`] when used with [`ServerKind::Account`]
+    ///
+    /// Every problem found with the headers is reported as a [`HeaderIssue`] alongside the data
+    /// that could be read. As only the major version of the title is sent, the [`title_version`]
+    /// field will have its minor and micro versions set to zero
+    ///
+    /// [`title_version`]: ./struct.ConsoleWiiU.html#structfield.title_version
+    pub fn from_http_headers(headers: &HeaderMap<HeaderValue>) -> (Self, Vec<HeaderIssue>) {
+        let mut console = Self::default();
+        let mut issues = Vec::new();
+
+        read_common_headers!(console, ConsoleKind::WiiU, headers, issues);
+
+        if let Some(serial) = &console.serial {
+            if serial
+                .device_model()
+                .map_or(true, |model| model != ConsoleModel::NintendoWiiU)
+            {
+                issues.push(HeaderIssue::Inconsistent(
+                    "X-Nintendo-Serial-Number",
+                    "X-Nintendo-Platform-ID",
+                ));
+            }
+        }
+
+        check_header_consistency!(console, ConsoleKind::WiiU, issues);
+
+        (console, issues)
+    }
+}
+
 impl<'a> Console<'a> for ConsoleWiiU<'_> {
     fn kind(&self) -> ConsoleKind {
         ConsoleKind::WiiU
@@ -243,7 +277,10 @@ impl<'a> Console<'a> for ConsoleWiiU<'_> {
                 // unsure if this is necessary
                 // let _ = h.append(header::USER_AGENT, "".parse().unwrap());
 
-                let _ = h.append("X-Nintendo-Platform-ID", "0".parse()?);
+                let _ = h.append(
+                    "X-Nintendo-Platform-ID",
+                    HeaderValue::from_static(self.kind().platform_id()),
+                );
 
                 if let Some(device_type) = self.device_type {
                     let _ = h.append(
@@ -298,8 +335,13 @@ impl<'a> Console<'a> for ConsoleWiiU<'_> {
                     let _ = h.append("X-Nintendo-Environment", environment.to_string().parse()?);
                 }
 
+                // the title id is sent as 16 hexadecimal digits, like the unique id and
+                // application version that accompany it
                 if let Some(title_id) = self.title_id {
-                    let _ = h.append("X-Nintendo-Title-ID", HeaderValue::from(title_id.0));
+                    let _ = h.append(
+                        "X-Nintendo-Title-ID",
+                        format!("{:0>16X}", title_id.0).parse()?,
+                    );
                     let _ = h.append(
                         "X-Nintendo-Unique-ID",
                         format!("{:0>5X}", u32::from(title_id.unique_id().0)).parse()?,
@@ -324,9 +366,98 @@ impl<'a> Console<'a> for ConsoleWiiU<'_> {
 
                 Ok(h)
             }
-            _ => Err(HeaderConstructionError::UnimplementedServerKind(
-                server.into(),
-            )),
+            ServerKind::Nus(_) | ServerKind::Ccs(_) => {
+                let mut h = HeaderMap::new();
+
+                // the update servers only look at the client certificate, so the system update
+                // process' user agent is all that is sent alongside it
+                let _ = h.append(
+                    header::USER_AGENT,
+                    HeaderValue::from_static("wii libnup/1.0"),
+                );
+
+                Ok(h)
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn console() -> ConsoleWiiU<'static> {
+        ConsoleWiiU::new(|b| {
+            Ok(b.device_id(1)
+                .serial(ConsoleSerial(Cow::Borrowed("FW404567772")))
+                .derive_region_from_serial()?
+                .derive_device_type_from_serial()?
+                .system_version(TitleVersion(0x0E00))
+                .country(CountryCode::USA)
+                .client_id(Cow::Borrowed("a2efa818a34fa16b8afbc8a74eba3eda"))
+                .client_secret(Cow::Borrowed("c91cdb5658bd4954ade78533a339cf9a"))
+                .fpd_version(0)
+                .environment(Environment::L(1))
+                .title_id(TitleId(0x0005001010040100))
+                .derive_unique_id_from_title_id()?
+                .title_version(TitleVersion(0x1000))
+                .language(Iso639_1::En))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn console_wiiu_http_headers() {
+        let headers = console()
+            .http_headers(ServerKind::Account(Cow::Borrowed("")))
+            .unwrap();
+        assert_eq!(headers["X-Nintendo-Platform-ID"], "1");
+        assert_eq!(headers["X-Nintendo-Title-ID"], "0005001010040100");
+        assert_eq!(headers["X-Nintendo-Unique-ID"], "100401");
+    }
+
+    #[test]
+    fn console_wiiu_from_http_headers() {
+        let console = console();
+        let (parsed, issues) = ConsoleWiiU::from_http_headers(
+            &console
+                .http_headers(ServerKind::Account(Cow::Borrowed("")))
+                .unwrap(),
+        );
+        assert_eq!(issues, vec![]);
+        assert_eq!(parsed, console);
+    }
+
+    #[test]
+    fn console_wiiu_from_inconsistent_http_headers() {
+        let mut headers = console()
+            .http_headers(ServerKind::Account(Cow::Borrowed("")))
+            .unwrap();
+        let _ = headers.insert("X-Nintendo-Region", HeaderValue::from(4_u16));
+        let _ = headers.insert("X-Nintendo-Platform-ID", HeaderValue::from_static("0"));
+        let _ = headers.remove("X-Nintendo-Client-Secret");
+        let _ = headers.insert("X-Nintendo-Unique-ID", HeaderValue::from_static("10041"));
+
+        let (_, issues) = ConsoleWiiU::from_http_headers(&headers);
+        assert_eq!(
+            issues,
+            vec![
+                HeaderIssue::Inconsistent("X-Nintendo-Platform-ID", "X-Nintendo-Serial-Number"),
+                HeaderIssue::Missing("X-Nintendo-Client-Secret"),
+                HeaderIssue::Inconsistent("X-Nintendo-Region", "X-Nintendo-Serial-Number"),
+                HeaderIssue::Inconsistent("X-Nintendo-Unique-ID", "X-Nintendo-Title-ID"),
+            ]
+        );
+
+        // a serial belonging to a 3ds is not that of a wii u
+        let _ = headers.insert(
+            "X-Nintendo-Serial-Number",
+            HeaderValue::from_static("CW404567772"),
+        );
+        let (_, issues) = ConsoleWiiU::from_http_headers(&headers);
+        assert!(issues.contains(&HeaderIssue::Inconsistent(
+            "X-Nintendo-Serial-Number",
+            "X-Nintendo-Platform-ID"
+        )));
+    }
+}
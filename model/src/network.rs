@@ -7,7 +7,8 @@
 // file, you can obtain one at http://mozilla.org/MPL/2.0/.
 //
 
-use std::{borrow::Cow, fmt};
+use std::{borrow::Cow, fmt, num::ParseIntError, str::FromStr};
+use thiserror::Error;
 
 /// An enumeration over possible identifiers used on Nintendo Network
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -17,6 +18,17 @@ pub enum Identifier<'a> {
 }
 
 impl<'a> Identifier<'a> {
+    /// Create an [`Identifier`] of the provided [`IdentifierKind`] from its string representation
+    ///
+    /// An error is returned if a [`Pid`] is requested and the provided value is not a valid
+    /// integer
+    pub fn from_kind(kind: IdentifierKind, value: Cow<'a, str>) -> Result<Self, ParseIntError> {
+        Ok(match kind {
+            IdentifierKind::Nnid => Self::Nnid(Nnid(value)),
+            IdentifierKind::Pid => Self::Pid(Pid(u32::from_str(&value)?)),
+        })
+    }
+
     /// Convert the [`Identifier`] into a [`Cow<'a, str>`](Cow), borrowing it if it is an [`Nnid`]
     /// or creating a new [`String`] if it is a [`Pid`]
     pub fn to_cow(&'a self) -> Cow<'a, str> {
@@ -45,7 +57,7 @@ impl<'a> fmt::Display for Identifier<'a> {
 }
 
 /// An enumeration over different kinds of identifiers on Nintendo Network
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum IdentifierKind {
     Nnid,
     Pid,
@@ -58,3 +70,262 @@ pub struct Nnid<'a>(pub Cow<'a, str>);
 /// A PID associated with a Nintendo Network Id
 #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
 pub struct Pid(pub u32);
+
+impl fmt::Display for Pid {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(formatter)
+    }
+}
+
+impl FromStr for Pid {
+    type Err = ParseIntError;
+
+    fn from_str(pid: &str) -> Result<Self, Self::Err> {
+        Ok(Self(u32::from_str(pid)?))
+    }
+}
+
+/// The address of a NEX server, along with the parameters needed to connect to it, in the form
+/// `scheme:/key=value;key=value`
+///
+/// Parameters are kept in the order they appear in, and parameters without a typed accessor are
+/// preserved as-is, so parsing a [`StationUrl`] and formatting it again produces the original
+/// string. Servers sometimes send an empty string in place of a url they have no use for, which
+/// is represented by the [`Default`] [`StationUrl`]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct StationUrl {
+    /// The url's scheme, such as `prudps`
+    pub scheme: String,
+
+    /// The url's parameters, in the order they appear in
+    pub parameters: Vec<(String, String)>,
+}
+
+/// Helper macro to generate a typed getter and setter for a parameter of a [`StationUrl`]
+macro generate_parameter_accessors($($(#[$meta:meta])* $getter:ident, $setter:ident => $key:literal: $kind:ty;)*) {
+    $(
+        $(#[$meta])*
+        ///
+        /// [`None`] is returned if the parameter is missing or malformed
+        pub fn $getter(&self) -> Option<$kind> {
+            self.get($key)?.parse().ok()
+        }
+
+        $(#[$meta])*
+        ///
+        /// This sets the parameter, adding it if it is not present
+        pub fn $setter(&mut self, value: $kind) {
+            self.set($key, &value.to_string());
+        }
+    )*
+}
+
+impl StationUrl {
+    /// Create a new [`StationUrl`] with the provided scheme and no parameters
+    pub fn new(scheme: &str) -> Self {
+        Self {
+            scheme: scheme.to_string(),
+            parameters: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the [`StationUrl`] points to a secure server
+    pub fn is_secure(&self) -> bool {
+        self.scheme == "prudps"
+    }
+
+    /// Returns the value of the provided parameter, if it is present
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the provided parameter to the provided value, adding it if it is not present
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.parameters.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.parameters.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    /// Removes the provided parameter, returning its value if it was present
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let index = self.parameters.iter().position(|(k, _)| k == key)?;
+        Some(self.parameters.remove(index).1)
+    }
+
+    /// The host the server is listening on (`address`), if it is present
+    pub fn address(&self) -> Option<&str> {
+        self.get("address")
+    }
+
+    /// Sets the host the server is listening on (`address`), adding it if it is not present
+    pub fn set_address(&mut self, address: &str) {
+        self.set("address", address);
+    }
+
+    generate_parameter_accessors! {
+        /// The port the server is listening on (`port`)
+        port, set_port => "port": u16;
+
+        /// The [`Pid`] of the server's user (`PID`)
+        pid, set_pid => "PID": Pid;
+
+        /// The connection id assigned by the server (`CID`)
+        connection_id, set_connection_id => "CID": u32;
+
+        /// The connection id of the relay used to reach the server (`RVCID`)
+        relay_connection_id, set_relay_connection_id => "RVCID": u32;
+
+        /// The stream id of the server, which is also called the port within PRUDP (`sid`)
+        stream_id, set_stream_id => "sid": u8;
+
+        /// The kind of PRUDP stream spoken by the server (`stream`)
+        stream_kind, set_stream_kind => "stream": u8;
+
+        /// A bitmask describing how the server may be reached (`type`)
+        url_type, set_url_type => "type": u8;
+
+        /// The NAT mapping behaviour of the server's network (`natm`)
+        nat_mapping, set_nat_mapping => "natm": u8;
+
+        /// The NAT filtering behaviour of the server's network (`natf`)
+        nat_filtering, set_nat_filtering => "natf": u8;
+
+        /// Whether or not the server's network supports universal plug and play (`upnp`)
+        upnp, set_upnp => "upnp": u8;
+
+        /// Whether or not the server's network supports NAT-PMP (`pmp`)
+        pmp, set_pmp => "pmp": u8;
+
+        /// The id of the server's platform (`PRID`)
+        platform_id, set_platform_id => "PRID": u8;
+    }
+}
+
+impl FromStr for StationUrl {
+    type Err = StationUrlError;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        if url.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let (scheme, parameters) = url
+            .split_once(":/")
+            .filter(|(scheme, _)| !scheme.is_empty())
+            .ok_or_else(|| StationUrlError::MissingScheme(url.to_string()))?;
+
+        Ok(Self {
+            scheme: scheme.to_string(),
+            parameters: if parameters.is_empty() {
+                Vec::new()
+            } else {
+                parameters
+                    .split(';')
+                    .map(|parameter| {
+                        parameter
+                            .split_once('=')
+                            .map(|(key, value)| (key.to_string(), value.to_string()))
+                            .ok_or_else(|| StationUrlError::InvalidParameter(parameter.to_string()))
+                    })
+                    .collect::<Result<_, _>>()?
+            },
+        })
+    }
+}
+
+impl fmt::Display for StationUrl {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.scheme.is_empty() && self.parameters.is_empty() {
+            return Ok(());
+        }
+
+        write!(formatter, "{}:/", self.scheme)?;
+        for (i, (key, value)) in self.parameters.iter().enumerate() {
+            if i != 0 {
+                formatter.write_str(";")?;
+            }
+            write!(formatter, "{}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// An enumeration over errors that can be encountered while parsing a [`StationUrl`]
+#[derive(Error, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum StationUrlError {
+    /// An error returned when the url has no scheme
+    #[error("`{0}` has no scheme")]
+    MissingScheme(String),
+
+    /// An error returned when one of the url's parameters is not of the form `key=value`
+    #[error("`{0}` is not a valid parameter")]
+    InvalidParameter(String),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn station_url_round_trip() {
+        for url in [
+            "prudps:/address=1.2.3.4;port=60000;CID=1;PID=2;sid=1;stream=10;type=2",
+            "prudp:/address=1.2.3.4;port=60000;natm=0;natf=0;upnp=0;pmp=0;probeinit=0;PRID=2",
+            "udp:/",
+            "",
+        ] {
+            assert_eq!(url.parse::<StationUrl>().unwrap().to_string(), url);
+        }
+        assert_eq!("".parse::<StationUrl>(), Ok(StationUrl::default()));
+        assert_eq!(
+            ":/address=1.2.3.4".parse::<StationUrl>(),
+            Err(StationUrlError::MissingScheme(
+                ":/address=1.2.3.4".to_string()
+            ))
+        );
+        assert_eq!(
+            "prudps:".parse::<StationUrl>(),
+            Err(StationUrlError::MissingScheme("prudps:".to_string()))
+        );
+        assert_eq!(
+            "prudps:/address=1.2.3.4;;port=60000".parse::<StationUrl>(),
+            Err(StationUrlError::InvalidParameter(String::new()))
+        );
+    }
+
+    #[test]
+    fn station_url_accessors() {
+        let mut url: StationUrl =
+            "prudps:/address=1.2.3.4;port=60000;CID=1;PID=2;sid=1;stream=10;type=2;RVCID=x"
+                .parse()
+                .unwrap();
+        assert!(url.is_secure());
+        assert_eq!(url.address(), Some("1.2.3.4"));
+        assert_eq!(url.port(), Some(60000));
+        assert_eq!(url.pid(), Some(Pid(2)));
+        assert_eq!(url.connection_id(), Some(1));
+        assert_eq!(url.stream_id(), Some(1));
+        assert_eq!(url.stream_kind(), Some(10));
+        assert_eq!(url.url_type(), Some(2));
+        assert_eq!(url.relay_connection_id(), None);
+        assert_eq!(url.nat_mapping(), None);
+
+        url.set_port(60001);
+        url.set_pid(Pid(1337));
+        assert_eq!(url.remove("RVCID"), Some("x".to_string()));
+        assert_eq!(
+            url.to_string(),
+            "prudps:/address=1.2.3.4;port=60001;CID=1;PID=1337;sid=1;stream=10;type=2"
+        );
+
+        let mut url = StationUrl::new("prudp");
+        url.set_address("10.0.0.1");
+        url.set_nat_mapping(1);
+        assert!(!url.is_secure());
+        assert_eq!(url.to_string(), "prudp:/address=10.0.0.1;natm=1");
+    }
+}
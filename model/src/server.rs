@@ -21,4 +21,10 @@ use strum_macros::{AsRefStr, Display, EnumString, IntoStaticStr};
 )]
 pub enum Kind<'a> {
     Account(Cow<'a, str>),
+
+    /// The network update SOAP server, which is queried for the titles of a system update
+    Nus(Cow<'a, str>),
+
+    /// The content server, which titles' metadata, tickets, and contents are downloaded from
+    Ccs(Cow<'a, str>),
 }
@@ -0,0 +1,690 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Type definitions related to the CTR importable archive (CIA) format used by the 3DS
+//!
+//! An archive bundles together everything needed to install a title: the [`Certificate`]s needed
+//! to verify it, its [`Ticket`], its [`TitleMetadata`], the contents described by the title
+//! metadata, and optionally a [`Meta`] section holding the title's dependencies and icon. Each of
+//! these sections begins at an offset aligned to 64 bytes. The format is documented on [3dbrew]
+//!
+//! A [`Cia`] holds an entire archive, borrowing its contents from the data it was parsed from.
+//! As archives can be several gigabytes large, a [`CiaHeader`] can also be used on its own to
+//! locate the sections of an archive without reading all of it, and a [`CiaWriter`] can be used
+//! to build an archive while streaming its contents into it
+//!
+//! [3dbrew]: https://www.3dbrew.org/wiki/CIA
+
+use std::{
+    borrow::Cow,
+    convert::{TryFrom, TryInto},
+    io::{self, Read, Write},
+};
+use thiserror::Error;
+
+use crate::{
+    certificate::{Certificate, CertificateError},
+    title::{
+        id::TitleId,
+        read_integer,
+        smdh::{Smdh, SmdhError},
+        ticket::{Ticket, TicketError},
+        tmd::{ContentChunk, TitleMetadata, TitleMetadataError},
+    },
+};
+
+/// The length of a [`CiaHeader`]
+pub const HEADER_LENGTH: usize = 0x2020;
+
+/// The length of the bitfield of the indices of the contents present in an archive
+const CONTENT_INDEX_LENGTH: usize = 0x2000;
+
+/// The alignment of each section of an archive
+const ALIGNMENT: u64 = 0x40;
+
+/// The number of dependencies that can be held by a [`Meta`] section
+pub const DEPENDENCY_COUNT: usize = 0x30;
+
+/// The length of the portion of a [`Meta`] section preceding the icon
+const META_HEADER_LENGTH: usize = 0x400;
+
+/// Rounds the provided offset up to the alignment of the sections of an archive
+///
+/// Offsets too large to be rounded up saturate, which leaves them out of the bounds of any archive
+const fn align(offset: u64) -> u64 {
+    offset.saturating_add(ALIGNMENT - 1) & !(ALIGNMENT - 1)
+}
+
+/// The header of an archive, describing the size of each of its sections
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct CiaHeader {
+    /// The type of the archive, which is always 0
+    pub kind: u16,
+
+    /// The version of the archive format, which is always 0
+    pub version: u16,
+    pub certificate_chain_size: u32,
+    pub ticket_size: u32,
+    pub title_metadata_size: u32,
+
+    /// The size of the [`Meta`] section, or zero if the archive has none
+    pub meta_size: u32,
+    pub content_size: u64,
+
+    /// A bitfield of the indices of the contents present in the archive, starting from the most
+    /// significant bit of the first byte
+    pub content_index: [u8; CONTENT_INDEX_LENGTH],
+}
+
+impl CiaHeader {
+    /// Returns whether the content with the provided index is present in the archive
+    pub fn contains_content(&self, index: u16) -> bool {
+        self.content_index[usize::from(index >> 3)] & (0x80 >> (index & 7)) != 0
+    }
+
+    /// Marks the content with the provided index as present in the archive
+    pub fn insert_content(&mut self, index: u16) {
+        self.content_index[usize::from(index >> 3)] |= 0x80 >> (index & 7);
+    }
+
+    /// Returns the offset of the certificate chain within the archive
+    pub fn certificate_chain_offset(&self) -> u64 {
+        align(HEADER_LENGTH as u64)
+    }
+
+    /// Returns the offset of the [`Ticket`] within the archive
+    pub fn ticket_offset(&self) -> u64 {
+        align(self.certificate_chain_offset() + u64::from(self.certificate_chain_size))
+    }
+
+    /// Returns the offset of the [`TitleMetadata`] within the archive
+    pub fn title_metadata_offset(&self) -> u64 {
+        align(self.ticket_offset() + u64::from(self.ticket_size))
+    }
+
+    /// Returns the offset of the first content within the archive, which the rest follow
+    /// back-to-back in the order they are described by the [`TitleMetadata`]
+    pub fn content_offset(&self) -> u64 {
+        align(self.title_metadata_offset() + u64::from(self.title_metadata_size))
+    }
+
+    /// Returns the offset of the [`Meta`] section within the archive
+    pub fn meta_offset(&self) -> u64 {
+        align(self.content_offset().saturating_add(self.content_size))
+    }
+
+    /// Converts a [`CiaHeader`] into a byte vector
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(HEADER_LENGTH);
+        header.extend_from_slice(&(HEADER_LENGTH as u32).to_le_bytes());
+        header.extend_from_slice(&self.kind.to_le_bytes());
+        header.extend_from_slice(&self.version.to_le_bytes());
+        header.extend_from_slice(&self.certificate_chain_size.to_le_bytes());
+        header.extend_from_slice(&self.ticket_size.to_le_bytes());
+        header.extend_from_slice(&self.title_metadata_size.to_le_bytes());
+        header.extend_from_slice(&self.meta_size.to_le_bytes());
+        header.extend_from_slice(&self.content_size.to_le_bytes());
+        header.extend_from_slice(&self.content_index);
+        header
+    }
+}
+
+impl TryFrom<&[u8]> for CiaHeader {
+    type Error = CiaError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let header_length =
+            read_integer(value, 0x0, u32::from_le_bytes).ok_or(CiaError::OutOfBounds)?;
+        if header_length as usize != HEADER_LENGTH {
+            return Err(CiaError::InvalidHeaderLength(header_length));
+        }
+
+        Ok(Self {
+            kind: read_integer(value, 0x4, u16::from_le_bytes).ok_or(CiaError::OutOfBounds)?,
+            version: read_integer(value, 0x6, u16::from_le_bytes).ok_or(CiaError::OutOfBounds)?,
+            certificate_chain_size: read_integer(value, 0x8, u32::from_le_bytes)
+                .ok_or(CiaError::OutOfBounds)?,
+            ticket_size: read_integer(value, 0xc, u32::from_le_bytes)
+                .ok_or(CiaError::OutOfBounds)?,
+            title_metadata_size: read_integer(value, 0x10, u32::from_le_bytes)
+                .ok_or(CiaError::OutOfBounds)?,
+            meta_size: read_integer(value, 0x14, u32::from_le_bytes)
+                .ok_or(CiaError::OutOfBounds)?,
+            content_size: read_integer(value, 0x18, u64::from_le_bytes)
+                .ok_or(CiaError::OutOfBounds)?,
+            content_index: value
+                .get(0x20..HEADER_LENGTH)
+                .ok_or(CiaError::OutOfBounds)?
+                .try_into()
+                .expect("unable to convert a slice into an array (this should be impossible)"),
+        })
+    }
+}
+
+/// A CTR importable archive, holding everything needed to install a title
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Cia<'a> {
+    /// The type of the archive, which is always 0
+    pub kind: u16,
+
+    /// The version of the archive format, which is always 0
+    pub version: u16,
+
+    /// The [`Certificate`]s needed to verify the [`Ticket`] and [`TitleMetadata`]
+    pub certificate_chain: Vec<Certificate<'a>>,
+    pub ticket: Ticket<'a>,
+    pub title_metadata: TitleMetadata<'a>,
+
+    /// The contents present in the archive along with their indices, which must be in the order
+    /// they are described by the [`TitleMetadata`]
+    pub contents: Vec<(u16, Cow<'a, [u8]>)>,
+    pub meta: Option<Meta<'a>>,
+}
+
+impl<'a> Cia<'a> {
+    /// Returns the content with the provided index, if it is present in the archive
+    pub fn content(&self, index: u16) -> Option<&[u8]> {
+        self.contents
+            .iter()
+            .find(|(content_index, _)| *content_index == index)
+            .map(|(_, content)| content.as_ref())
+    }
+
+    /// Converts a [`Cia`] into a byte vector
+    ///
+    /// The size of each content must match the size recorded in its [`ContentChunk`] record
+    pub fn to_bytes(&self) -> Result<Vec<u8>, CiaError> {
+        let indices = self
+            .contents
+            .iter()
+            .map(|(index, _)| *index)
+            .collect::<Vec<_>>();
+        let mut writer = CiaWriter::new(
+            Vec::new(),
+            &self.certificate_chain,
+            &self.ticket,
+            &self.title_metadata,
+            &indices,
+            self.meta.as_ref(),
+        )?;
+        for (_, content) in &self.contents {
+            writer.write_content(content.as_ref())?;
+        }
+        writer.finish()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Cia<'a> {
+    type Error = CiaError;
+
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        let header = CiaHeader::try_from(value)?;
+
+        let certificate_chain = Certificate::parse_all(section(
+            value,
+            header.certificate_chain_offset(),
+            u64::from(header.certificate_chain_size),
+        )?)?
+        .into_iter()
+        .map(|(certificate, _)| certificate)
+        .collect();
+        let ticket = Ticket::try_from(section(
+            value,
+            header.ticket_offset(),
+            u64::from(header.ticket_size),
+        )?)?;
+        let title_metadata = TitleMetadata::try_from(section(
+            value,
+            header.title_metadata_offset(),
+            u64::from(header.title_metadata_size),
+        )?)?;
+
+        let mut contents = Vec::new();
+        let mut offset = header.content_offset();
+        for content_chunk in &title_metadata.content_chunks {
+            if header.contains_content(content_chunk.index) {
+                contents.push((
+                    content_chunk.index,
+                    Cow::Borrowed(section(value, offset, content_chunk.size)?),
+                ));
+                offset = offset
+                    .checked_add(content_chunk.size)
+                    .ok_or(CiaError::OutOfBounds)?;
+            }
+        }
+        if offset - header.content_offset() != header.content_size {
+            return Err(CiaError::InvalidContentSize);
+        }
+
+        let meta = if header.meta_size == 0 {
+            None
+        } else {
+            Some(Meta::try_from(section(
+                value,
+                header.meta_offset(),
+                u64::from(header.meta_size),
+            )?)?)
+        };
+
+        Ok(Self {
+            kind: header.kind,
+            version: header.version,
+            certificate_chain,
+            ticket,
+            title_metadata,
+            contents,
+            meta,
+        })
+    }
+}
+
+/// The optional final section of an archive, holding the title's dependencies and icon
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Meta<'a> {
+    /// The titles the title depends on, of which there may be at most [`DEPENDENCY_COUNT`]
+    pub dependencies: Vec<TitleId>,
+
+    /// The version of the firmware the title's core uses
+    pub core_version: u32,
+
+    /// The title's icon, in the SMDH format
+    pub icon: Cow<'a, [u8]>,
+}
+
+impl Meta<'_> {
+    /// Returns the length of the [`Meta`] section once encoded
+    pub fn encoded_length(&self) -> usize {
+        META_HEADER_LENGTH + self.icon.len()
+    }
+
+    /// Parses the title's icon
+    pub fn smdh(&self) -> Result<Smdh, SmdhError> {
+        Smdh::try_from(self.icon.as_ref())
+    }
+
+    /// Converts a [`Meta`] section into a byte vector
+    pub fn to_bytes(&self) -> Result<Vec<u8>, CiaError> {
+        if self.dependencies.len() > DEPENDENCY_COUNT {
+            return Err(CiaError::TooManyDependencies(self.dependencies.len()));
+        }
+
+        let mut meta = vec![0; self.encoded_length()];
+        for (dependency, title_id) in meta[..DEPENDENCY_COUNT * 0x8]
+            .chunks_exact_mut(0x8)
+            .zip(&self.dependencies)
+        {
+            dependency.copy_from_slice(&title_id.0.to_le_bytes());
+        }
+        meta[0x300..0x304].copy_from_slice(&self.core_version.to_le_bytes());
+        meta[META_HEADER_LENGTH..].copy_from_slice(&self.icon);
+        Ok(meta)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Meta<'a> {
+    type Error = CiaError;
+
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        if value.len() < META_HEADER_LENGTH {
+            return Err(CiaError::OutOfBounds);
+        }
+
+        Ok(Self {
+            // the list of dependencies ends at the first empty entry
+            dependencies: value[..DEPENDENCY_COUNT * 0x8]
+                .chunks_exact(0x8)
+                .map(|dependency| {
+                    TitleId(u64::from_le_bytes(dependency.try_into().expect(
+                        "unable to convert a slice into an array (this should be impossible)",
+                    )))
+                })
+                .take_while(|title_id| title_id.0 != 0)
+                .collect(),
+            core_version: read_integer(value, 0x300, u32::from_le_bytes)
+                .ok_or(CiaError::OutOfBounds)?,
+            icon: Cow::Borrowed(&value[META_HEADER_LENGTH..]),
+        })
+    }
+}
+
+/// Builds an archive, writing it to the wrapped writer as its contents are streamed into it
+///
+/// Every section preceding the contents is written upon creation, after which each content must
+/// be written using [`write_content`](Self::write_content) in the order they are described by the
+/// [`TitleMetadata`], before the archive is completed using [`finish`](Self::finish)
+pub struct CiaWriter<W: Write> {
+    writer: W,
+
+    /// The number of bytes written so far
+    offset: u64,
+
+    /// The records of the contents yet to be written, in reverse
+    remaining_contents: Vec<ContentChunk>,
+
+    /// The encoded [`Meta`] section, if there is one
+    meta: Option<Vec<u8>>,
+}
+
+impl<W: Write> CiaWriter<W> {
+    /// Creates a new [`CiaWriter`], writing every section preceding the contents to the provided
+    /// writer
+    ///
+    /// The provided indices are those of the contents that will be present in the archive, each of
+    /// which must be described by the [`TitleMetadata`]
+    pub fn new(
+        writer: W,
+        certificate_chain: &[Certificate<'_>],
+        ticket: &Ticket<'_>,
+        title_metadata: &TitleMetadata<'_>,
+        contents: &[u16],
+        meta: Option<&Meta<'_>>,
+    ) -> Result<Self, CiaError> {
+        if let Some(&index) = contents.iter().find(|&&index| {
+            !title_metadata
+                .content_chunks
+                .iter()
+                .any(|content_chunk| content_chunk.index == index)
+        }) {
+            return Err(CiaError::UnknownContent(index));
+        }
+
+        let mut certificate_chain_bytes = Vec::new();
+        for certificate in certificate_chain {
+            certificate_chain_bytes.extend_from_slice(&certificate.to_bytes()?);
+        }
+        let ticket = ticket.to_bytes();
+        let title_metadata_bytes = title_metadata.to_bytes();
+        let meta = meta.map(Meta::to_bytes).transpose()?;
+        let mut remaining_contents = title_metadata
+            .content_chunks
+            .iter()
+            .filter(|content_chunk| contents.contains(&content_chunk.index))
+            .copied()
+            .collect::<Vec<_>>();
+        remaining_contents.reverse();
+
+        let mut header = CiaHeader {
+            kind: 0,
+            version: 0,
+            certificate_chain_size: certificate_chain_bytes.len() as u32,
+            ticket_size: ticket.len() as u32,
+            title_metadata_size: title_metadata_bytes.len() as u32,
+            meta_size: meta.as_ref().map_or(0, |meta| meta.len() as u32),
+            content_size: remaining_contents
+                .iter()
+                .map(|content_chunk| content_chunk.size)
+                .sum(),
+            content_index: [0; CONTENT_INDEX_LENGTH],
+        };
+        for content_chunk in &remaining_contents {
+            header.insert_content(content_chunk.index);
+        }
+
+        let mut cia_writer = Self {
+            writer,
+            offset: 0,
+            remaining_contents,
+            meta,
+        };
+        cia_writer.write_section(&header.to_bytes())?;
+        cia_writer.write_section(&certificate_chain_bytes)?;
+        cia_writer.write_section(&ticket)?;
+        cia_writer.write_section(&title_metadata_bytes)?;
+        Ok(cia_writer)
+    }
+
+    /// Writes the provided data followed by the padding needed to align the next section
+    fn write_section(&mut self, data: &[u8]) -> io::Result<()> {
+        self.writer.write_all(data)?;
+        self.offset += data.len() as u64;
+        self.pad()
+    }
+
+    /// Writes the padding needed to align the next section
+    fn pad(&mut self) -> io::Result<()> {
+        let padding = align(self.offset) - self.offset;
+        self.writer
+            .write_all(&[0; ALIGNMENT as usize][..padding as usize])?;
+        self.offset += padding;
+        Ok(())
+    }
+
+    /// Writes the next content, reading exactly as many bytes as are recorded in its
+    /// [`ContentChunk`] record from the provided reader
+    pub fn write_content<R: Read>(&mut self, reader: R) -> Result<(), CiaError> {
+        let content_chunk = self
+            .remaining_contents
+            .pop()
+            .ok_or(CiaError::UnexpectedContent)?;
+        let length = io::copy(&mut reader.take(content_chunk.size), &mut self.writer)?;
+        self.offset += length;
+        if length != content_chunk.size {
+            return Err(CiaError::UnexpectedEnd(content_chunk.index));
+        }
+        Ok(())
+    }
+
+    /// Completes the archive once every content has been written, returning the wrapped writer
+    pub fn finish(mut self) -> Result<W, CiaError> {
+        if let Some(content_chunk) = self.remaining_contents.last() {
+            return Err(CiaError::MissingContent(content_chunk.index));
+        }
+
+        self.pad()?;
+        if let Some(meta) = self.meta.take() {
+            self.write_section(&meta)?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// An enumeration over errors that can be encountered while handling [`Cia`]s
+#[derive(Error, Debug)]
+pub enum CiaError {
+    #[error("An error was encountered while handling the certificate chain")]
+    CertificateError(#[from] CertificateError),
+
+    #[error("An error was encountered while handling the Ticket")]
+    TicketError(#[from] TicketError),
+
+    #[error("An error was encountered while handling the TitleMetadata")]
+    TitleMetadataError(#[from] TitleMetadataError),
+
+    #[error("An error was encountered while reading or writing the archive")]
+    IoError(#[from] io::Error),
+
+    #[error("The provided archive is not large enough")]
+    OutOfBounds,
+
+    #[error("`{0}` is not the length of a CiaHeader")]
+    InvalidHeaderLength(u32),
+
+    #[error("The size of the contents does not match the contents described by the TitleMetadata")]
+    InvalidContentSize,
+
+    #[error("The TitleMetadata does not describe a content with the index `{0}`")]
+    UnknownContent(u16),
+
+    #[error("`{0}` dependencies cannot be held by a Meta section")]
+    TooManyDependencies(usize),
+
+    #[error("A content was written after every content had been written")]
+    UnexpectedContent,
+
+    #[error("The content with the index `{0}` ended before its recorded size")]
+    UnexpectedEnd(u16),
+
+    #[error("The content with the index `{0}` has not been written")]
+    MissingContent(u16),
+}
+
+/// Returns the section of the provided data with the provided offset and size
+fn section(data: &[u8], offset: u64, size: u64) -> Result<&[u8], CiaError> {
+    let start = usize::try_from(offset).map_err(|_| CiaError::OutOfBounds)?;
+    let end = offset.checked_add(size).ok_or(CiaError::OutOfBounds)?;
+    let end = usize::try_from(end).map_err(|_| CiaError::OutOfBounds)?;
+    data.get(start..end).ok_or(CiaError::OutOfBounds)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{certificate::Signature, title::smdh::TitleLanguage};
+
+    const CIA: &[u8] = include_bytes!("test/title.cia");
+    const TICKET: &[u8] = include_bytes!("test/ticket.tik");
+    const TITLE_METADATA: &[u8] = include_bytes!("test/title.tmd");
+    const ROOT_CA: &[u8] = include_bytes!("../certificate/test/root-ca.bin");
+    const CA: &[u8] = include_bytes!("../certificate/test/ca.bin");
+
+    #[test]
+    fn parse_cia() {
+        let header = CiaHeader::try_from(CIA).unwrap();
+        assert_eq!(header.certificate_chain_offset(), 0x2040);
+        assert_eq!(header.ticket_offset(), 0x2580);
+        assert_eq!(header.title_metadata_offset(), 0x2840);
+        assert_eq!(header.content_offset(), 0x3340);
+        assert_eq!(header.meta_offset(), 0xf340);
+        assert!(header.contains_content(0) && header.contains_content(1));
+        assert!(!header.contains_content(2));
+
+        let cia = Cia::try_from(CIA).unwrap();
+        assert_eq!(
+            cia.certificate_chain,
+            vec![
+                Certificate::try_from(ROOT_CA).unwrap(),
+                Certificate::try_from(CA).unwrap()
+            ]
+        );
+        assert_eq!(cia.ticket, Ticket::try_from(TICKET).unwrap());
+        assert_eq!(
+            cia.title_metadata,
+            TitleMetadata::try_from(TITLE_METADATA).unwrap()
+        );
+
+        // the optional content is left out
+        assert_eq!(cia.contents.len(), 2);
+        assert!(matches!(
+            cia.content(0),
+            Some(content) if content.len() == 0x8000 && content.iter().all(|&byte| byte == 1)
+        ));
+        assert!(matches!(
+            cia.content(1),
+            Some(content) if content.len() == 0x4000 && content.iter().all(|&byte| byte == 2)
+        ));
+        assert_eq!(cia.content(2), None);
+
+        let meta = cia.meta.as_ref().unwrap();
+        assert_eq!(
+            meta.dependencies,
+            vec![TitleId(0x0004013000001502), TitleId(0x0004013000003202)]
+        );
+        assert_eq!(meta.core_version, 2);
+        assert_eq!(&meta.icon[..4], b"SMDH");
+        assert!(matches!(meta.icon, Cow::Borrowed(_)));
+        assert_eq!(
+            meta.smdh().unwrap().title(TitleLanguage::English).publisher,
+            "Publisher 1"
+        );
+
+        assert_eq!(cia.to_bytes().unwrap(), CIA);
+    }
+
+    #[test]
+    fn build_cia() {
+        let cia = Cia::try_from(CIA).unwrap();
+
+        // a content may not be written short of its recorded size
+        let mut writer = CiaWriter::new(
+            Vec::new(),
+            &cia.certificate_chain,
+            &cia.ticket,
+            &cia.title_metadata,
+            &[0, 1],
+            cia.meta.as_ref(),
+        )
+        .unwrap();
+        writer.write_content(cia.content(0).unwrap()).unwrap();
+        assert!(matches!(
+            writer.write_content(&[2; 0x100][..]),
+            Err(CiaError::UnexpectedEnd(1))
+        ));
+
+        let writer = CiaWriter::new(
+            Vec::new(),
+            &cia.certificate_chain,
+            &cia.ticket,
+            &cia.title_metadata,
+            &[0],
+            None,
+        )
+        .unwrap();
+        assert!(matches!(writer.finish(), Err(CiaError::MissingContent(0))));
+
+        assert!(matches!(
+            CiaWriter::new(
+                Vec::new(),
+                &cia.certificate_chain,
+                &cia.ticket,
+                &cia.title_metadata,
+                &[3],
+                None,
+            ),
+            Err(CiaError::UnknownContent(3))
+        ));
+
+        // an archive without a meta section ends with its contents
+        let data = Cia {
+            meta: None,
+            contents: vec![(0, Cow::Borrowed(cia.content(0).unwrap()))],
+            ..cia.clone()
+        }
+        .to_bytes()
+        .unwrap();
+        assert_eq!(data.len(), 0x3340 + 0x8000);
+        let rebuilt = Cia::try_from(data.as_ref()).unwrap();
+        assert_eq!(rebuilt.meta, None);
+        assert_eq!(rebuilt.contents.len(), 1);
+    }
+
+    #[test]
+    fn oversized_sections() {
+        // the size of the first content is placed after the signature, header and content info
+        // records of the title metadata, and the id, index and type of its content chunk record
+        let title_metadata_offset = CiaHeader::try_from(CIA).unwrap().title_metadata_offset();
+        let (_, signature_length) = Signature::parse(TITLE_METADATA).unwrap();
+        let size_offset = title_metadata_offset as usize + signature_length + 0xc4 + 0x900 + 0x8;
+        let mut data = CIA.to_vec();
+        data[size_offset..size_offset + 0x8].copy_from_slice(&u64::MAX.to_be_bytes());
+        assert_eq!(
+            TitleMetadata::try_from(&data[title_metadata_offset as usize..])
+                .unwrap()
+                .content_chunks[0]
+                .size,
+            u64::MAX
+        );
+        assert!(matches!(
+            Cia::try_from(data.as_ref()),
+            Err(CiaError::OutOfBounds)
+        ));
+
+        let mut header = CiaHeader::try_from(CIA).unwrap();
+        header.content_size = u64::MAX;
+        assert_eq!(header.meta_offset(), !(ALIGNMENT - 1));
+        assert!(matches!(
+            Cia::try_from(
+                [header.to_bytes().as_ref(), &CIA[HEADER_LENGTH..]]
+                    .concat()
+                    .as_ref()
+            ),
+            Err(CiaError::InvalidContentSize)
+        ));
+    }
+}
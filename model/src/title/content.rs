@@ -0,0 +1,454 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Decryption of the contents of titles used by the 3DS and Wii U
+//!
+//! A title's contents are encrypted using AES-128-CBC with its title key, using the index of the
+//! content followed by zeroes as the initialization vector. The title key is held by the title's
+//! [`Ticket`], itself encrypted using one of the console's common keys, which ralsei does not
+//! provide and must be supplied by the caller.
+//!
+//! A [`ContentDecryptor`] decrypts a single content incrementally, so that the content never needs
+//! to be held in memory in its entirety, verifying it against the hash held by its
+//! [`ContentChunk`] record once it has been decrypted. A [`TitleDecryptor`] gathers what is
+//! needed to create them from a title's [`Ticket`] and [`TitleMetadata`]
+//!
+//! Hashed Wii U contents, which interleave their data with a tree of hashes and are verified
+//! against a separate `.h3` file, are not supported
+
+use aes::{Aes128, BlockDecrypt, NewBlockCipher};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::{
+    convert::TryFrom,
+    io::{self, ErrorKind, Read, Write},
+};
+use thiserror::Error;
+
+use crate::title::{
+    id::{Platform, TitleId},
+    ticket::{Ticket, TicketError},
+    tmd::{ContentChunk, ContentType, TitleMetadata, TitleMetadataError},
+};
+
+/// The size of the blocks of AES
+const BLOCK_SIZE: usize = 0x10;
+
+/// The size of the buffer used by [`ContentDecryptor::decrypt`]
+const BUFFER_SIZE: usize = 0x10000;
+
+/// An AES-128-CBC decryptor which keeps its chaining value between calls, such that data can be
+/// decrypted in pieces
+#[derive(Clone)]
+pub(crate) struct CbcDecryptor {
+    cipher: Aes128,
+    iv: [u8; BLOCK_SIZE],
+}
+
+impl CbcDecryptor {
+    /// Creates a new [`CbcDecryptor`] using the provided key and initialization vector
+    pub(crate) fn new(key: &[u8; BLOCK_SIZE], iv: [u8; BLOCK_SIZE]) -> Self {
+        Self {
+            cipher: Aes128::new(key.into()),
+            iv,
+        }
+    }
+
+    /// Decrypts the provided data in place, which must be made up of whole blocks
+    pub(crate) fn decrypt(&mut self, data: &mut [u8]) {
+        for block in data.chunks_exact_mut(BLOCK_SIZE) {
+            let mut ciphertext = [0; BLOCK_SIZE];
+            ciphertext.copy_from_slice(block);
+            self.cipher.decrypt_block(block.into());
+            for (byte, iv) in block.iter_mut().zip(&self.iv) {
+                *byte ^= iv;
+            }
+            self.iv = ciphertext;
+        }
+    }
+}
+
+/// The hash a content is verified against, which depends on the platform of its title
+#[derive(Clone)]
+enum ContentHasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+/// Gathers what is needed to decrypt the contents of a title from its [`Ticket`] and
+/// [`TitleMetadata`]
+///
+/// Hashed Wii U contents cannot be decrypted, and [`content_decryptor`](Self::content_decryptor)
+/// fails with [`ContentError::UnsupportedHashedContent`] for them
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TitleDecryptor {
+    /// The title's id
+    pub title_id: TitleId,
+
+    /// The decrypted title key
+    pub title_key: [u8; 0x10],
+
+    /// The records describing each of the title's contents
+    pub content_chunks: Vec<ContentChunk>,
+}
+
+impl TitleDecryptor {
+    /// Creates a new [`TitleDecryptor`] from the provided [`Ticket`] and [`TitleMetadata`],
+    /// decrypting the title key using the provided common key
+    ///
+    /// The common key must be the one at the ticket's
+    /// [`common_key_index`](Ticket::common_key_index) for the console the title is meant for
+    pub fn new(
+        ticket: &Ticket<'_>,
+        title_metadata: &TitleMetadata<'_>,
+        common_key: &[u8; 0x10],
+    ) -> Result<Self, ContentError> {
+        if ticket.title_id != title_metadata.title_id {
+            return Err(ContentError::MismatchedTitleId(
+                ticket.title_id,
+                title_metadata.title_id,
+            ));
+        }
+
+        Ok(Self {
+            title_id: ticket.title_id,
+            title_key: ticket.decrypt_title_key(common_key),
+            content_chunks: title_metadata.content_chunks.clone(),
+        })
+    }
+
+    /// Creates a new [`TitleDecryptor`] from the provided ticket and title metadata, as they are
+    /// downloaded from Nintendo's content servers, decrypting the title key using the provided
+    /// common key
+    ///
+    /// See [`new`](Self::new) for more details
+    pub fn from_raw(
+        ticket: &[u8],
+        title_metadata: &[u8],
+        common_key: &[u8; 0x10],
+    ) -> Result<Self, ContentError> {
+        Self::new(
+            &Ticket::try_from(ticket)?,
+            &TitleMetadata::try_from(title_metadata)?,
+            common_key,
+        )
+    }
+
+    /// Returns the [`ContentChunk`] record of the content with the provided index
+    pub fn content_chunk(&self, index: u16) -> Option<&ContentChunk> {
+        self.content_chunks
+            .iter()
+            .find(|content_chunk| content_chunk.index == index)
+    }
+
+    /// Creates a [`ContentDecryptor`] for the content with the provided index
+    pub fn content_decryptor(&self, index: u16) -> Result<ContentDecryptor, ContentError> {
+        ContentDecryptor::new(
+            self.title_id,
+            &self.title_key,
+            self.content_chunk(index)
+                .ok_or(ContentError::UnknownContent(index))?,
+        )
+    }
+}
+
+/// Decrypts a single content incrementally, verifying it against the hash held by its
+/// [`ContentChunk`] record once it has been decrypted in full
+///
+/// Contents are stored padded to a multiple of the AES block size, and the padding is removed
+/// from the decrypted data. Contents that are not encrypted are passed through as they are, but
+/// are still verified
+///
+/// Hashed Wii U contents, whose [`ContentChunk`] records carry the [`ContentType::DISC`] flag, are
+/// not supported
+#[derive(Clone)]
+pub struct ContentDecryptor {
+    /// The content's decryptor, or `None` if the content is not encrypted
+    cbc: Option<CbcDecryptor>,
+    hasher: ContentHasher,
+    index: u16,
+    hash: [u8; 0x20],
+
+    /// The number of bytes of the content that have not been decrypted yet
+    remaining: u64,
+
+    /// The data of a block that has not been received in full yet
+    partial_block: [u8; BLOCK_SIZE],
+    partial_block_length: usize,
+}
+
+impl ContentDecryptor {
+    /// Creates a new [`ContentDecryptor`] for the content described by the provided
+    /// [`ContentChunk`] record, belonging to the title with the provided [`TitleId`] and
+    /// (decrypted) title key
+    ///
+    /// If the title is a Wii U title and the content is hashed, this fails with
+    /// [`ContentError::UnsupportedHashedContent`]
+    pub fn new(
+        title_id: TitleId,
+        title_key: &[u8; 0x10],
+        content_chunk: &ContentChunk,
+    ) -> Result<Self, ContentError> {
+        let flags = content_chunk.flags();
+        let hasher = match title_id.platform() {
+            // on the wii u, this flag marks hashed contents rather than disc contents
+            Some(Platform::NintendoWiiU) if flags.contains(ContentType::DISC) => {
+                return Err(ContentError::UnsupportedHashedContent(content_chunk.index))
+            }
+            Some(Platform::NintendoWiiU) => ContentHasher::Sha1(Sha1::new()),
+            _ => ContentHasher::Sha256(Sha256::new()),
+        };
+
+        let mut iv = [0; BLOCK_SIZE];
+        iv[..0x2].copy_from_slice(&content_chunk.index.to_be_bytes());
+
+        Ok(Self {
+            cbc: flags
+                .contains(ContentType::ENCRYPTED)
+                .then(|| CbcDecryptor::new(title_key, iv)),
+            hasher,
+            index: content_chunk.index,
+            hash: content_chunk.hash,
+            remaining: content_chunk.size,
+            partial_block: [0; BLOCK_SIZE],
+            partial_block_length: 0,
+        })
+    }
+
+    /// Decrypts the provided piece of the content, which follows any pieces provided before it,
+    /// appending the decrypted data to the provided vector
+    ///
+    /// The pieces may be of any size, as any incomplete block is held onto until the rest of it
+    /// is provided
+    pub fn update(&mut self, data: &[u8], decrypted: &mut Vec<u8>) {
+        let start = decrypted.len();
+        let mut data = data;
+
+        if self.partial_block_length != 0 {
+            let length = data.len().min(BLOCK_SIZE - self.partial_block_length);
+            self.partial_block[self.partial_block_length..self.partial_block_length + length]
+                .copy_from_slice(&data[..length]);
+            self.partial_block_length += length;
+            data = &data[length..];
+
+            if self.partial_block_length != BLOCK_SIZE {
+                return;
+            }
+            decrypted.extend_from_slice(&self.partial_block);
+            self.partial_block_length = 0;
+        }
+
+        let whole_blocks_length = data.len() - data.len() % BLOCK_SIZE;
+        decrypted.extend_from_slice(&data[..whole_blocks_length]);
+        let partial_block = &data[whole_blocks_length..];
+        self.partial_block[..partial_block.len()].copy_from_slice(partial_block);
+        self.partial_block_length = partial_block.len();
+
+        if let Some(cbc) = &mut self.cbc {
+            cbc.decrypt(&mut decrypted[start..]);
+        }
+
+        // anything past the end of the content is padding
+        let length = u64::min((decrypted.len() - start) as u64, self.remaining);
+        decrypted.truncate(start + length as usize);
+        self.remaining -= length;
+        match &mut self.hasher {
+            ContentHasher::Sha1(hasher) => hasher.update(&decrypted[start..]),
+            ContentHasher::Sha256(hasher) => hasher.update(&decrypted[start..]),
+        }
+    }
+
+    /// Verifies the decrypted content against the hash held by its [`ContentChunk`] record
+    ///
+    /// This must only be called once the content has been provided in full
+    pub fn finish(self) -> Result<(), ContentError> {
+        if self.remaining != 0 {
+            return Err(ContentError::UnexpectedEnd(self.index, self.remaining));
+        }
+
+        let matches = match self.hasher {
+            ContentHasher::Sha1(hasher) => hasher.finalize()[..] == self.hash[..0x14],
+            ContentHasher::Sha256(hasher) => hasher.finalize()[..] == self.hash[..],
+        };
+        if matches {
+            Ok(())
+        } else {
+            Err(ContentError::HashMismatch(self.index))
+        }
+    }
+
+    /// Decrypts the content read from the provided reader in full, writing the decrypted data to
+    /// the provided writer before verifying it
+    ///
+    /// Should the content fail verification, the decrypted data will have already been written
+    pub fn decrypt<R, W>(mut self, mut reader: R, mut writer: W) -> Result<(), ContentError>
+    where
+        R: Read,
+        W: Write,
+    {
+        let mut buffer = vec![0; BUFFER_SIZE];
+        let mut decrypted = Vec::with_capacity(BUFFER_SIZE + BLOCK_SIZE);
+        loop {
+            let length = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(length) => length,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            };
+            self.update(&buffer[..length], &mut decrypted);
+            writer.write_all(&decrypted)?;
+            decrypted.clear();
+        }
+        writer.flush()?;
+        self.finish()
+    }
+}
+
+/// An enumeration over errors that can be encountered while decrypting contents
+#[derive(Error, Debug)]
+pub enum ContentError {
+    #[error("An error was encountered while parsing the Ticket")]
+    TicketError(#[from] TicketError),
+
+    #[error("An error was encountered while parsing the TitleMetadata")]
+    TitleMetadataError(#[from] TitleMetadataError),
+
+    #[error("An error was encountered while reading or writing a content")]
+    IoError(#[from] io::Error),
+
+    #[error("The Ticket is for the title `{0:?}`, but the TitleMetadata is for the title `{1:?}`")]
+    MismatchedTitleId(TitleId, TitleId),
+
+    #[error("The TitleMetadata does not describe a content with the index `{0}`")]
+    UnknownContent(u16),
+
+    #[error("The content with the index `{0}` is a hashed Wii U content, which is unsupported")]
+    UnsupportedHashedContent(u16),
+
+    #[error("The content with the index `{0}` ended `{1}` bytes early")]
+    UnexpectedEnd(u16, u64),
+
+    #[error("The content with the index `{0}` does not match its hash")]
+    HashMismatch(u16),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TICKET: &[u8] = include_bytes!("test/ticket.tik");
+    const TITLE_METADATA: &[u8] = include_bytes!("test/title.tmd");
+
+    // the content with the index 2 of the title, encrypted using the title key of the ticket
+    const CONTENT: &[u8] = include_bytes!("test/content.bin");
+    const COMMON_KEY: [u8; 0x10] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ];
+
+    /// Returns the decrypted content
+    fn content() -> Vec<u8> {
+        (0..0x1f0_usize).map(|i| (i * 7 + 3) as u8).collect()
+    }
+
+    #[test]
+    fn decrypt_content() {
+        let title = TitleDecryptor::from_raw(TICKET, TITLE_METADATA, &COMMON_KEY).unwrap();
+        assert_eq!(
+            title.title_key,
+            [
+                0x76, 0x2e, 0x5a, 0xb5, 0x09, 0x2a, 0x10, 0x9c, 0xef, 0xdb, 0x99, 0x43, 0x47, 0x90,
+                0xaa, 0xd8
+            ]
+        );
+
+        let mut content_chunk = ContentChunk {
+            id: 2,
+            index: 2,
+            content_type: ContentType::ENCRYPTED.bits(),
+            size: 0x1f0,
+            hash: [0; 0x20],
+        };
+        content_chunk
+            .hash
+            .copy_from_slice(&Sha256::digest(&content()));
+
+        let mut decrypted = Vec::new();
+        ContentDecryptor::new(title.title_id, &title.title_key, &content_chunk)
+            .unwrap()
+            .decrypt(CONTENT, &mut decrypted)
+            .unwrap();
+        assert_eq!(decrypted, content());
+
+        // pieces that do not line up with blocks
+        let mut decryptor =
+            ContentDecryptor::new(title.title_id, &title.title_key, &content_chunk).unwrap();
+        let mut decrypted = Vec::new();
+        for piece in CONTENT.chunks(7) {
+            decryptor.update(piece, &mut decrypted);
+        }
+        decryptor.finish().unwrap();
+        assert_eq!(decrypted, content());
+
+        // the wii u verifies contents using sha-1 instead
+        content_chunk.hash = [0; 0x20];
+        content_chunk.hash[..0x14].copy_from_slice(&Sha1::digest(&content()));
+        ContentDecryptor::new(
+            TitleId(0x0005000010101a00),
+            &title.title_key,
+            &content_chunk,
+        )
+        .unwrap()
+        .decrypt(CONTENT, io::sink())
+        .unwrap();
+    }
+
+    #[test]
+    fn reject_invalid_contents() {
+        let title = TitleDecryptor::from_raw(TICKET, TITLE_METADATA, &COMMON_KEY).unwrap();
+
+        // the title metadata's records hold hashes of other data
+        assert!(matches!(
+            title
+                .content_decryptor(2)
+                .unwrap()
+                .decrypt(CONTENT, io::sink()),
+            Err(ContentError::HashMismatch(2))
+        ));
+        assert!(matches!(
+            title
+                .content_decryptor(2)
+                .unwrap()
+                .decrypt(&CONTENT[..0x100], io::sink()),
+            Err(ContentError::UnexpectedEnd(2, 0x100))
+        ));
+        assert!(matches!(
+            title.content_decryptor(3),
+            Err(ContentError::UnknownContent(3))
+        ));
+
+        // hashed wii u contents are marked using the flag that marks disc contents on the 3ds
+        let content_chunk = ContentChunk {
+            id: 2,
+            index: 2,
+            content_type: (ContentType::ENCRYPTED | ContentType::DISC).bits(),
+            size: 0x1f0,
+            hash: [0; 0x20],
+        };
+        assert!(matches!(
+            ContentDecryptor::new(
+                TitleId(0x0005000010101a00),
+                &title.title_key,
+                &content_chunk
+            ),
+            Err(ContentError::UnsupportedHashedContent(2))
+        ));
+        assert!(ContentDecryptor::new(title.title_id, &title.title_key, &content_chunk).is_ok());
+    }
+}
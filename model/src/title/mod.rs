@@ -7,5 +7,25 @@
 // file, you can obtain one at http://mozilla.org/MPL/2.0/.
 //
 
+pub mod cia;
+pub mod content;
 pub mod id;
+pub mod smdh;
+pub mod ticket;
+pub mod tmd;
 pub mod version;
+
+use std::convert::TryInto;
+
+/// Reads an integer from the provided data at the provided offset using the provided conversion
+/// from bytes, returning [`None`] if it is out of bounds
+pub(crate) fn read_integer<I, const N: usize>(
+    data: &[u8],
+    offset: usize,
+    from_bytes: fn([u8; N]) -> I,
+) -> Option<I> {
+    data.get(offset..offset.checked_add(N)?)?
+        .try_into()
+        .ok()
+        .map(from_bytes)
+}
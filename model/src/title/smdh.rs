@@ -0,0 +1,512 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Type definitions related to the SMDH format used by the 3DS to hold a title's icon and metadata
+//!
+//! An [`Smdh`] holds the title's name and publisher in each language, its age ratings, the regions
+//! it may be used in, a set of [`Flags`], and two icons. The icons are stored as RGB565 pixels in
+//! 8x8 tiles, and can be decoded into linear RGBA buffers using
+//! [`small_icon_rgba`](Smdh::small_icon_rgba) and [`large_icon_rgba`](Smdh::large_icon_rgba).
+//! The format is documented on [3dbrew]
+//!
+//! [3dbrew]: https://www.3dbrew.org/wiki/SMDH
+
+use bitflags::bitflags;
+use std::{
+    convert::{TryFrom, TryInto},
+    string::FromUtf16Error,
+};
+use thiserror::Error;
+
+use crate::{console::common::Region, title::read_integer};
+
+/// The length of an [`Smdh`]
+pub const SMDH_LENGTH: usize = 0x36c0;
+
+/// The number of [`ApplicationTitle`]s held by an [`Smdh`], one for each language
+pub const TITLE_COUNT: usize = 16;
+
+/// The number of age ratings held by an [`Smdh`], one for each rating organization
+pub const AGE_RATING_COUNT: usize = 16;
+
+/// The width and height of the small icon, in pixels
+pub const SMALL_ICON_SIZE: usize = 24;
+
+/// The width and height of the large icon, in pixels
+pub const LARGE_ICON_SIZE: usize = 48;
+
+/// The offset of the [`ApplicationTitle`]s
+const TITLES_OFFSET: usize = 0x8;
+
+/// The length of an [`ApplicationTitle`]
+const TITLE_LENGTH: usize = 0x200;
+
+/// The offset of the application settings, which follow the [`ApplicationTitle`]s
+const SETTINGS_OFFSET: usize = 0x2008;
+
+/// The offset of the small icon
+const SMALL_ICON_OFFSET: usize = 0x2040;
+
+/// The offset of the large icon
+const LARGE_ICON_OFFSET: usize = 0x24c0;
+
+/// Every [`Region`] that a title may be locked to, in the order of their bits
+const REGIONS: [Region; 7] = [
+    Region::Japan,
+    Region::UnitedStates,
+    Region::Europe,
+    Region::Australia,
+    Region::China,
+    Region::Korea,
+    Region::Taiwan,
+];
+
+/// The region lockout of a title that may be used in any region
+pub const REGION_FREE: u32 = 0x7fffffff;
+
+/// A title's icon and metadata
+#[derive(Clone, Debug, PartialEq)]
+pub struct Smdh {
+    /// The version of the SMDH format, which is always 0
+    pub version: u16,
+
+    /// The title's name and publisher in each language, indexed by [`TitleLanguage`]
+    pub titles: [ApplicationTitle; TITLE_COUNT],
+
+    /// The title's age ratings, indexed by [`RatingOrganization`]
+    ///
+    /// See [`age_rating`](Self::age_rating) for a decoded representation
+    pub age_ratings: [u8; AGE_RATING_COUNT],
+
+    /// A bitfield of the regions the title may be used in, or [`REGION_FREE`] if it may be used in
+    /// any of them
+    ///
+    /// See [`regions`](Self::regions) for a decoded representation
+    pub region_lockout: u32,
+    pub match_maker_id: u32,
+    pub match_maker_bit_id: u64,
+
+    /// The title's flags, which are made up of [`Flags`] along with any undocumented flags
+    pub flags: u32,
+
+    /// The version of the EULA the title requires to be accepted, with the major version in the
+    /// upper byte and the minor version in the lower byte
+    pub eula_version: u16,
+
+    /// The frame of the title's banner animation that is displayed while it is not being played
+    pub optimal_animation_default_frame: f32,
+
+    /// The id used by the title for streetpass communication
+    pub cec_id: u32,
+
+    /// The 24x24 icon, as tiled RGB565 pixels
+    pub small_icon: Vec<u16>,
+
+    /// The 48x48 icon, as tiled RGB565 pixels
+    pub large_icon: Vec<u16>,
+}
+
+impl Smdh {
+    /// Returns the [`ApplicationTitle`] in the provided language
+    pub fn title(&self, language: TitleLanguage) -> &ApplicationTitle {
+        &self.titles[language as usize]
+    }
+
+    /// Returns the [`AgeRating`] given by the provided organization, or `None` if it has not rated
+    /// the title
+    pub fn age_rating(&self, organization: RatingOrganization) -> Option<AgeRating> {
+        let rating = self.age_ratings[organization as usize];
+        if rating & 0x80 == 0 {
+            None
+        } else if rating & 0x40 != 0 {
+            Some(AgeRating::Pending)
+        } else if rating & 0x20 != 0 {
+            Some(AgeRating::Unrestricted)
+        } else {
+            Some(AgeRating::Age(rating & 0x1f))
+        }
+    }
+
+    /// Returns whether the title may be used in any region
+    pub fn is_region_free(&self) -> bool {
+        self.region_lockout == REGION_FREE
+    }
+
+    /// Returns the [`Region`]s the title may be used in
+    pub fn regions(&self) -> Vec<Region> {
+        REGIONS
+            .iter()
+            .copied()
+            .filter(|&region| self.region_lockout & region as u32 != 0)
+            .collect()
+    }
+
+    /// Returns the documented [`Flags`] of the title's flags
+    pub fn flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.flags)
+    }
+
+    /// Decodes the 24x24 icon into a linear buffer of RGBA pixels
+    pub fn small_icon_rgba(&self) -> Vec<u8> {
+        decode_icon(&self.small_icon, SMALL_ICON_SIZE)
+    }
+
+    /// Decodes the 48x48 icon into a linear buffer of RGBA pixels
+    pub fn large_icon_rgba(&self) -> Vec<u8> {
+        decode_icon(&self.large_icon, LARGE_ICON_SIZE)
+    }
+
+    /// Converts an [`Smdh`] into a byte vector
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SmdhError> {
+        let mut smdh = vec![0; SMDH_LENGTH];
+        smdh[..0x4].copy_from_slice(b"SMDH");
+        smdh[0x4..0x6].copy_from_slice(&self.version.to_le_bytes());
+
+        for (title, data) in self
+            .titles
+            .iter()
+            .zip(smdh[TITLES_OFFSET..SETTINGS_OFFSET].chunks_exact_mut(TITLE_LENGTH))
+        {
+            write_utf16(&title.short_description, &mut data[..0x80])?;
+            write_utf16(&title.long_description, &mut data[0x80..0x180])?;
+            write_utf16(&title.publisher, &mut data[0x180..])?;
+        }
+
+        let settings = &mut smdh[SETTINGS_OFFSET..SMALL_ICON_OFFSET];
+        settings[..0x10].copy_from_slice(&self.age_ratings);
+        settings[0x10..0x14].copy_from_slice(&self.region_lockout.to_le_bytes());
+        settings[0x14..0x18].copy_from_slice(&self.match_maker_id.to_le_bytes());
+        settings[0x18..0x20].copy_from_slice(&self.match_maker_bit_id.to_le_bytes());
+        settings[0x20..0x24].copy_from_slice(&self.flags.to_le_bytes());
+        settings[0x24..0x26].copy_from_slice(&self.eula_version.to_le_bytes());
+        settings[0x28..0x2c].copy_from_slice(&self.optimal_animation_default_frame.to_le_bytes());
+        settings[0x2c..0x30].copy_from_slice(&self.cec_id.to_le_bytes());
+
+        for (icon, size, offset) in [
+            (&self.small_icon, SMALL_ICON_SIZE, SMALL_ICON_OFFSET),
+            (&self.large_icon, LARGE_ICON_SIZE, LARGE_ICON_OFFSET),
+        ] {
+            if icon.len() != size * size {
+                return Err(SmdhError::InvalidIconSize(icon.len()));
+            }
+            for (pixel, data) in icon
+                .iter()
+                .zip(smdh[offset..offset + size * size * 2].chunks_exact_mut(0x2))
+            {
+                data.copy_from_slice(&pixel.to_le_bytes());
+            }
+        }
+
+        Ok(smdh)
+    }
+}
+
+impl TryFrom<&[u8]> for Smdh {
+    type Error = SmdhError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() < SMDH_LENGTH {
+            return Err(SmdhError::OutOfBounds);
+        }
+        if &value[..0x4] != b"SMDH" {
+            return Err(SmdhError::InvalidMagic);
+        }
+
+        let mut titles = <[ApplicationTitle; TITLE_COUNT]>::default();
+        for (title, data) in titles
+            .iter_mut()
+            .zip(value[TITLES_OFFSET..SETTINGS_OFFSET].chunks_exact(TITLE_LENGTH))
+        {
+            *title = ApplicationTitle {
+                short_description: read_utf16(&data[..0x80])?,
+                long_description: read_utf16(&data[0x80..0x180])?,
+                publisher: read_utf16(&data[0x180..])?,
+            };
+        }
+
+        let settings = &value[SETTINGS_OFFSET..SMALL_ICON_OFFSET];
+        Ok(Self {
+            version: read_integer(value, 0x4, u16::from_le_bytes).ok_or(SmdhError::OutOfBounds)?,
+            titles,
+            age_ratings: settings[..0x10]
+                .try_into()
+                .expect("unable to convert a slice into an array (this should be impossible)"),
+            region_lockout: read_integer(settings, 0x10, u32::from_le_bytes)
+                .ok_or(SmdhError::OutOfBounds)?,
+            match_maker_id: read_integer(settings, 0x14, u32::from_le_bytes)
+                .ok_or(SmdhError::OutOfBounds)?,
+            match_maker_bit_id: read_integer(settings, 0x18, u64::from_le_bytes)
+                .ok_or(SmdhError::OutOfBounds)?,
+            flags: read_integer(settings, 0x20, u32::from_le_bytes)
+                .ok_or(SmdhError::OutOfBounds)?,
+            eula_version: read_integer(settings, 0x24, u16::from_le_bytes)
+                .ok_or(SmdhError::OutOfBounds)?,
+            optimal_animation_default_frame: f32::from_bits(
+                read_integer(settings, 0x28, u32::from_le_bytes).ok_or(SmdhError::OutOfBounds)?,
+            ),
+            cec_id: read_integer(settings, 0x2c, u32::from_le_bytes)
+                .ok_or(SmdhError::OutOfBounds)?,
+            small_icon: read_icon(&value[SMALL_ICON_OFFSET..LARGE_ICON_OFFSET]),
+            large_icon: read_icon(&value[LARGE_ICON_OFFSET..SMDH_LENGTH]),
+        })
+    }
+}
+
+/// A title's name and publisher in a single language
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct ApplicationTitle {
+    /// The title's name, which may be at most 64 UTF-16 code units long
+    pub short_description: String,
+
+    /// The title's full name, which may be at most 128 UTF-16 code units long
+    pub long_description: String,
+
+    /// The title's publisher, which may be at most 64 UTF-16 code units long
+    pub publisher: String,
+}
+
+/// An enumeration over the languages an [`Smdh`] holds an [`ApplicationTitle`] in
+///
+/// The remaining four [`ApplicationTitle`]s are unused
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum TitleLanguage {
+    Japanese = 0,
+    English = 1,
+    French = 2,
+    German = 3,
+    Italian = 4,
+    Spanish = 5,
+    SimplifiedChinese = 6,
+    Korean = 7,
+    Dutch = 8,
+    Portuguese = 9,
+    Russian = 10,
+    TraditionalChinese = 11,
+}
+
+/// An enumeration over the organizations an [`Smdh`] holds an age rating from
+///
+/// The remaining seven age ratings are unused
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum RatingOrganization {
+    Cero = 0,
+    Esrb = 1,
+    Usk = 3,
+    PegiGen = 4,
+    PegiPrt = 6,
+    PegiBbfc = 7,
+    Cob = 8,
+    Grb = 9,
+    Cgsrr = 10,
+}
+
+/// An age rating given to a title by a [`RatingOrganization`]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum AgeRating {
+    /// The title may only be used by those of at least the provided age
+    Age(u8),
+
+    /// The title has not been rated yet
+    Pending,
+
+    /// The title may be used by anyone
+    Unrestricted,
+}
+
+bitflags! {
+    /// The flags held by an [`Smdh`]
+    pub struct Flags: u32 {
+        const VISIBLE = 0x0001;
+        const AUTO_BOOT = 0x0002;
+        const ALLOW_3D = 0x0004;
+        const REQUIRE_EULA = 0x0008;
+        const AUTO_SAVE_ON_EXIT = 0x0010;
+        const EXTENDED_BANNER = 0x0020;
+        const REGION_RATING_REQUIRED = 0x0040;
+        const SAVE_DATA = 0x0080;
+        const RECORD_USAGE = 0x0100;
+        const DISABLE_SAVE_DATA_BACKUP = 0x0400;
+        const NEW_3DS_EXCLUSIVE = 0x1000;
+    }
+}
+
+/// An enumeration over errors that can be encountered while handling an [`Smdh`]
+#[derive(Error, Debug)]
+pub enum SmdhError {
+    #[error("The UTF-16 data inside of the Smdh is invalid")]
+    FromUtf16Error(#[from] FromUtf16Error),
+
+    #[error("The provided smdh is not large enough")]
+    OutOfBounds,
+
+    #[error("The provided smdh does not begin with the `SMDH` magic")]
+    InvalidMagic,
+
+    #[error("A string is too long to be held by an Smdh")]
+    StringTooLong,
+
+    #[error("An icon of `{0}` pixels cannot be held by an Smdh")]
+    InvalidIconSize(usize),
+}
+
+/// Decodes an icon of the provided width and height, made up of RGB565 pixels in 8x8 tiles, into
+/// a linear buffer of RGBA pixels
+fn decode_icon(icon: &[u16], size: usize) -> Vec<u8> {
+    let mut rgba = vec![0; size * size * 4];
+    for (index, &pixel) in icon.iter().enumerate().take(size * size) {
+        // the pixels of each tile are in z-order, so the bits of their index within the tile
+        // alternate between those of their x and y coordinates
+        let tile = index / 64;
+        let x = (tile % (size / 8)) * 8 + (index & 0x1 | (index >> 1) & 0x2 | (index >> 2) & 0x4);
+        let y = (tile / (size / 8)) * 8
+            + ((index >> 1) & 0x1 | (index >> 2) & 0x2 | (index >> 3) & 0x4);
+
+        let (red, green, blue) = (pixel >> 11, (pixel >> 5) & 0x3f, pixel & 0x1f);
+        let offset = (y * size + x) * 4;
+        rgba[offset] = (red << 3 | red >> 2) as u8;
+        rgba[offset + 1] = (green << 2 | green >> 4) as u8;
+        rgba[offset + 2] = (blue << 3 | blue >> 2) as u8;
+        rgba[offset + 3] = 0xff;
+    }
+    rgba
+}
+
+/// Reads an icon's little-endian pixels from the provided data
+fn read_icon(data: &[u8]) -> Vec<u16> {
+    data.chunks_exact(0x2)
+        .map(|pixel| u16::from_le_bytes([pixel[0], pixel[1]]))
+        .collect()
+}
+
+/// Reads a null-terminated little-endian UTF-16 string from the provided data
+fn read_utf16(data: &[u8]) -> Result<String, SmdhError> {
+    let string = data
+        .chunks_exact(0x2)
+        .map(|code_unit| u16::from_le_bytes([code_unit[0], code_unit[1]]))
+        .take_while(|&code_unit| code_unit != 0)
+        .collect::<Vec<_>>();
+    Ok(String::from_utf16(&string)?)
+}
+
+/// Writes the provided string into the provided data as little-endian UTF-16
+fn write_utf16(string: &str, data: &mut [u8]) -> Result<(), SmdhError> {
+    let mut code_units = string.encode_utf16();
+    for code_unit in data.chunks_exact_mut(0x2) {
+        match code_units.next() {
+            Some(code_unit_value) => code_unit.copy_from_slice(&code_unit_value.to_le_bytes()),
+            None => return Ok(()),
+        }
+    }
+    match code_units.next() {
+        Some(_) => Err(SmdhError::StringTooLong),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SMDH: &[u8] = include_bytes!("test/icon.smdh");
+
+    #[test]
+    fn parse_smdh() {
+        let smdh = Smdh::try_from(SMDH).unwrap();
+        assert_eq!(smdh.version, 0);
+        for (language, title) in smdh.titles.iter().enumerate() {
+            assert_eq!(title.short_description, format!("Title {}", language));
+            assert_eq!(
+                title.long_description,
+                format!("A title for language {}", language)
+            );
+            assert_eq!(title.publisher, format!("Publisher {}", language));
+        }
+        assert_eq!(
+            smdh.title(TitleLanguage::English).short_description,
+            "Title 1"
+        );
+
+        assert_eq!(
+            smdh.age_rating(RatingOrganization::Cero),
+            Some(AgeRating::Age(12))
+        );
+        assert_eq!(
+            smdh.age_rating(RatingOrganization::Esrb),
+            Some(AgeRating::Age(13))
+        );
+        assert_eq!(
+            smdh.age_rating(RatingOrganization::Usk),
+            Some(AgeRating::Unrestricted)
+        );
+        assert_eq!(
+            smdh.age_rating(RatingOrganization::PegiGen),
+            Some(AgeRating::Pending)
+        );
+        assert_eq!(smdh.age_rating(RatingOrganization::Grb), None);
+
+        assert!(!smdh.is_region_free());
+        assert_eq!(smdh.regions(), vec![Region::UnitedStates, Region::Europe]);
+        assert_eq!(smdh.match_maker_id, 0x12345);
+        assert_eq!(smdh.match_maker_bit_id, 0x6789a);
+        assert_eq!(
+            smdh.flags(),
+            Flags::VISIBLE | Flags::ALLOW_3D | Flags::SAVE_DATA | Flags::RECORD_USAGE
+        );
+        assert_eq!(smdh.eula_version, 0x0102);
+        assert_eq!(smdh.optimal_animation_default_frame, 1.5);
+        assert_eq!(smdh.cec_id, 0x00030800);
+
+        assert_eq!(smdh.to_bytes().unwrap(), SMDH);
+    }
+
+    #[test]
+    fn decode_icons() {
+        let smdh = Smdh::try_from(SMDH).unwrap();
+        for (rgba, size) in [
+            (smdh.small_icon_rgba(), SMALL_ICON_SIZE),
+            (smdh.large_icon_rgba(), LARGE_ICON_SIZE),
+        ] {
+            assert_eq!(rgba.len(), size * size * 4);
+
+            // each pixel of the icons was derived from its coordinates
+            for (index, pixel) in rgba.chunks_exact(4).enumerate() {
+                let (x, y) = (index % size, index / size);
+                let (red, green, blue) = (x & 0x1f, y & 0x3f, (x + y) & 0x1f);
+                assert_eq!(
+                    pixel,
+                    [
+                        (red << 3 | red >> 2) as u8,
+                        (green << 2 | green >> 4) as u8,
+                        (blue << 3 | blue >> 2) as u8,
+                        0xff
+                    ]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reject_malformed_smdh() {
+        assert!(matches!(
+            Smdh::try_from(&SMDH[..0x100]),
+            Err(SmdhError::OutOfBounds)
+        ));
+
+        let mut data = SMDH.to_vec();
+        data[0] = b'X';
+        assert!(matches!(
+            Smdh::try_from(data.as_ref()),
+            Err(SmdhError::InvalidMagic)
+        ));
+
+        let mut smdh = Smdh::try_from(SMDH).unwrap();
+        smdh.titles[0].short_description = "a".repeat(0x41);
+        assert!(matches!(smdh.to_bytes(), Err(SmdhError::StringTooLong)));
+    }
+}
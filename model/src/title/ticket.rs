@@ -0,0 +1,562 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Type definitions related to the ticket format used by the 3DS and Wii U
+//!
+//! A ticket grants a console the right to use a title, and carries the title key its contents are
+//! encrypted with, itself encrypted using one of the console's common keys. Tickets are signed in
+//! the same manner as [`Certificate`]s, and begin with a [`Signature`] of the same format. The
+//! format is documented on [3dbrew] and [wiiubrew]
+//!
+//! Version 1 tickets, which are the only ones used by the 3DS and Wii U, end with a
+//! [`ContentIndex`] describing the contents of the title the ticket permits the use of. The bytes
+//! a ticket was read from are kept alongside its fields, so that reserved portions of it survive
+//! being converted back into bytes and its signature can be checked against what was signed
+//!
+//! [3dbrew]: https://www.3dbrew.org/wiki/Ticket
+//! [wiiubrew]: https://wiiubrew.org/wiki/Ticket
+
+use std::{
+    borrow::Cow,
+    convert::{TryFrom, TryInto},
+    string::FromUtf8Error,
+};
+use thiserror::Error;
+
+use crate::{
+    certificate::{Certificate, CertificateError, Issuer, Signature},
+    title::{content::CbcDecryptor, id::TitleId, read_integer, version::TitleVersion},
+};
+
+/// The length of the portion of a [`Ticket`] between its [`Signature`] and its [`ContentIndex`]
+const TICKET_DATA_LENGTH: usize = 0x164;
+
+/// The length of a [`ContentIndex`]'s header
+const CONTENT_INDEX_HEADER_LENGTH: usize = 0x14;
+
+/// The length of each of a [`ContentIndex`]'s section headers
+const CONTENT_INDEX_SECTION_HEADER_LENGTH: usize = 0x14;
+
+/// A ticket, granting the right to use a title
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Ticket<'a> {
+    pub signature: Signature<'a>,
+    pub issuer: Issuer<'a>,
+
+    /// The public key used to exchange the title key of personalized tickets
+    pub ecdh_data: Cow<'a, [u8]>,
+
+    /// The version of the ticket format, which is 1 on the 3DS and Wii U
+    pub version: u8,
+    pub ca_crl_version: u8,
+    pub signer_crl_version: u8,
+
+    /// The title key, encrypted using the common key at [`common_key_index`]
+    ///
+    /// [`common_key_index`]: ./struct.Ticket.html#structfield.common_key_index
+    pub title_key: [u8; 0x10],
+    pub ticket_id: u64,
+
+    /// The id of the console the ticket is personalized to, or zero if it is not
+    pub console_id: u32,
+    pub title_id: TitleId,
+    pub title_version: TitleVersion,
+    pub license_type: u8,
+
+    /// The index of the common key the title key is encrypted with
+    pub common_key_index: u8,
+
+    /// The id of the eShop account the ticket was purchased by, or zero if it was not
+    pub account_id: u32,
+    pub audit: u8,
+
+    /// The limits placed on the title's use, as pairs of a limit's kind and its value
+    pub limits: [(u32, u32); 8],
+
+    /// The [`ContentIndex`], which is only present in version 1 tickets
+    pub content_index: Option<ContentIndex<'a>>,
+
+    /// The portion of the ticket covered by its [`Signature`], exactly as it was read
+    ///
+    /// Tickets hold data in regions that are reserved, and a [`ContentIndex`] may be laid out
+    /// differently than it would be encoded, so these bytes are used as the base that the fields
+    /// above are written over when converting the [`Ticket`] into bytes. This keeps the signature
+    /// of a parsed ticket valid and its bytes unchanged. A newly constructed [`Ticket`] leaves this
+    /// empty, in which case the reserved regions are zeroed
+    pub raw_signed_data: Cow<'a, [u8]>,
+}
+
+impl<'a> Ticket<'a> {
+    /// Converts a [`Ticket`] into a byte vector
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut ticket = self.signature.to_bytes();
+        ticket.extend(self.signed_data());
+        ticket
+    }
+
+    /// Returns the length of the [`Ticket`] once converted into bytes
+    pub fn encoded_length(&self) -> usize {
+        self.signature.magic().padded_length()
+            + TICKET_DATA_LENGTH
+            + match (&self.content_index, self.raw_content_index()) {
+                (Some(_), Some(raw_content_index)) => raw_content_index.len(),
+                (Some(content_index), None) => content_index.encoded_length(),
+                (None, _) => 0,
+            }
+    }
+
+    /// Returns the portion of the [`Ticket`] covered by its signature, which is everything
+    /// following the signature and its padding
+    ///
+    /// The fields of the [`Ticket`] are written over its [`raw_signed_data`], so reserved regions
+    /// keep the values they were read with
+    ///
+    /// [`raw_signed_data`]: ./struct.Ticket.html#structfield.raw_signed_data
+    pub fn signed_data(&self) -> Vec<u8> {
+        let mut ticket = match self.raw_signed_data.get(..TICKET_DATA_LENGTH) {
+            Some(raw_signed_data) => raw_signed_data.to_vec(),
+            None => vec![0; TICKET_DATA_LENGTH],
+        };
+        ticket[..0x7c].fill(0);
+        let issuer = &self.issuer.0.as_bytes()[..self.issuer.0.len().min(0x40)];
+        ticket[..issuer.len()].copy_from_slice(issuer);
+        let ecdh_data = &self.ecdh_data[..self.ecdh_data.len().min(0x3c)];
+        ticket[0x40..0x40 + ecdh_data.len()].copy_from_slice(ecdh_data);
+        ticket[0x7c] = self.version;
+        ticket[0x7d] = self.ca_crl_version;
+        ticket[0x7e] = self.signer_crl_version;
+        ticket[0x7f..0x8f].copy_from_slice(&self.title_key);
+        ticket[0x90..0x98].copy_from_slice(&self.ticket_id.to_be_bytes());
+        ticket[0x98..0x9c].copy_from_slice(&self.console_id.to_be_bytes());
+        ticket[0x9c..0xa4].copy_from_slice(&self.title_id.0.to_be_bytes());
+        ticket[0xa6..0xa8].copy_from_slice(&self.title_version.0.to_be_bytes());
+        ticket[0xb0] = self.license_type;
+        ticket[0xb1] = self.common_key_index;
+        ticket[0xdc..0xe0].copy_from_slice(&self.account_id.to_be_bytes());
+        ticket[0xe1] = self.audit;
+        for (limit, (kind, value)) in ticket[0x124..0x164].chunks_mut(8).zip(&self.limits) {
+            limit[..4].copy_from_slice(&kind.to_be_bytes());
+            limit[4..].copy_from_slice(&value.to_be_bytes());
+        }
+
+        if let Some(content_index) = &self.content_index {
+            match self.raw_content_index() {
+                Some(raw_content_index) => ticket.extend(raw_content_index),
+                None => ticket.extend(content_index.to_bytes()),
+            }
+        }
+        ticket
+    }
+
+    /// Returns the [`ContentIndex`] held by the [`raw_signed_data`] if it is the same as the
+    /// [`content_index`], meaning that it has not been changed since the [`Ticket`] was read
+    ///
+    /// [`raw_signed_data`]: ./struct.Ticket.html#structfield.raw_signed_data
+    /// [`content_index`]: ./struct.Ticket.html#structfield.content_index
+    fn raw_content_index(&self) -> Option<&[u8]> {
+        let raw_content_index = self.raw_signed_data.get(TICKET_DATA_LENGTH..)?;
+        match (
+            &self.content_index,
+            ContentIndex::try_from(raw_content_index),
+        ) {
+            (Some(content_index), Ok(raw)) if raw == *content_index => Some(raw_content_index),
+            _ => None,
+        }
+    }
+
+    /// Verifies that the [`Ticket`] was signed by the provided issuing [`Certificate`]
+    ///
+    /// As with [`Certificate::verify`], the issuer itself is not checked
+    pub fn verify(&self, issuer: &Certificate<'_>) -> Result<(), CertificateError> {
+        if !self.issuer.is_issued_by(&issuer.name) {
+            return Err(CertificateError::IssuerMismatch);
+        }
+        issuer.key.verify(&self.signature, &self.signed_data())
+    }
+
+    /// Decrypts the [`title_key`] using the provided common key, which must be the one at
+    /// [`common_key_index`] for the console the ticket is meant for
+    ///
+    /// The title key is encrypted using AES-128-CBC, with the [`TitleId`] followed by zeroes as the
+    /// initialization vector. ralsei does not provide any common keys
+    ///
+    /// [`title_key`]: ./struct.Ticket.html#structfield.title_key
+    /// [`common_key_index`]: ./struct.Ticket.html#structfield.common_key_index
+    pub fn decrypt_title_key(&self, common_key: &[u8; 0x10]) -> [u8; 0x10] {
+        let mut iv = [0; 0x10];
+        iv[..0x8].copy_from_slice(&self.title_id.0.to_be_bytes());
+        let mut title_key = self.title_key;
+        CbcDecryptor::new(common_key, iv).decrypt(&mut title_key);
+        title_key
+    }
+}
+
+impl TryFrom<&[u8]> for Ticket<'_> {
+    type Error = TicketError;
+
+    /// Creates a new [`Ticket`] from a byte slice
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let (signature, offset) = Signature::parse(value)?;
+        let ticket = value
+            .get(offset..offset + TICKET_DATA_LENGTH)
+            .ok_or(TicketError::OutOfBounds)?;
+
+        let mut issuer = ticket[..0x40].to_owned();
+        while let Some(&value) = issuer.last() {
+            if value == 0 {
+                issuer.pop();
+            } else {
+                break;
+            }
+        }
+
+        let mut limits = [(0, 0); 8];
+        for (limit, data) in limits.iter_mut().zip(ticket[0x124..0x164].chunks(8)) {
+            *limit = (
+                read_integer(data, 0, u32::from_be_bytes).ok_or(TicketError::OutOfBounds)?,
+                read_integer(data, 4, u32::from_be_bytes).ok_or(TicketError::OutOfBounds)?,
+            );
+        }
+
+        let version = ticket[0x7c];
+        let (content_index, content_index_length) = if version == 0 {
+            (None, 0)
+        } else {
+            let content_index = &value[offset + TICKET_DATA_LENGTH..];
+            (
+                Some(ContentIndex::try_from(content_index)?),
+                read_integer(content_index, 0x4, u32::from_be_bytes)
+                    .ok_or(TicketError::OutOfBounds)? as usize,
+            )
+        };
+
+        Ok(Self {
+            signature,
+            issuer: Issuer(Cow::Owned(String::from_utf8(issuer)?)),
+            ecdh_data: Cow::Owned(ticket[0x40..0x7c].to_owned()),
+            version,
+            ca_crl_version: ticket[0x7d],
+            signer_crl_version: ticket[0x7e],
+            title_key: ticket[0x7f..0x8f]
+                .try_into()
+                .expect("unable to convert a slice into an array (this should be impossible)"),
+            ticket_id: read_integer(ticket, 0x90, u64::from_be_bytes)
+                .ok_or(TicketError::OutOfBounds)?,
+            console_id: read_integer(ticket, 0x98, u32::from_be_bytes)
+                .ok_or(TicketError::OutOfBounds)?,
+            title_id: TitleId(
+                read_integer(ticket, 0x9c, u64::from_be_bytes).ok_or(TicketError::OutOfBounds)?,
+            ),
+            title_version: TitleVersion(
+                read_integer(ticket, 0xa6, u16::from_be_bytes).ok_or(TicketError::OutOfBounds)?,
+            ),
+            license_type: ticket[0xb0],
+            common_key_index: ticket[0xb1],
+            account_id: read_integer(ticket, 0xdc, u32::from_be_bytes)
+                .ok_or(TicketError::OutOfBounds)?,
+            audit: ticket[0xe1],
+            limits,
+            content_index,
+            raw_signed_data: Cow::Owned(
+                value[offset..offset + TICKET_DATA_LENGTH + content_index_length].to_owned(),
+            ),
+        })
+    }
+}
+
+/// The index of the contents of a title that a [`Ticket`] permits the use of, which is made up of
+/// sections of fixed-size records
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct ContentIndex<'a> {
+    /// The version of the content index format, which is always 1
+    pub version: u16,
+    pub flags: u32,
+    pub sections: Vec<ContentIndexSection<'a>>,
+}
+
+impl ContentIndex<'_> {
+    /// The kind of [`ContentIndexSection`] whose records mark the contents that may be used
+    ///
+    /// Each of its records is a 32-bit offset followed by a 0x80-byte bitfield, where each bit,
+    /// starting from the least significant bit of the first byte, marks whether the content with
+    /// the index of the offset plus the bit's position may be used
+    pub const CONTENT_SECTION_KIND: u16 = 3;
+
+    /// Returns the length of the [`ContentIndex`] once converted into bytes
+    pub fn encoded_length(&self) -> usize {
+        CONTENT_INDEX_HEADER_LENGTH
+            + self
+                .sections
+                .iter()
+                .map(|section| CONTENT_INDEX_SECTION_HEADER_LENGTH + section.records.len())
+                .sum::<usize>()
+    }
+
+    /// Converts a [`ContentIndex`] into a byte vector, placing the section headers directly after
+    /// its header and the records of each section after them, in order
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut content_index = Vec::with_capacity(self.encoded_length());
+        content_index.extend(&self.version.to_be_bytes());
+        content_index.extend(&(CONTENT_INDEX_HEADER_LENGTH as u16).to_be_bytes());
+        content_index.extend(&(self.encoded_length() as u32).to_be_bytes());
+        content_index.extend(&(CONTENT_INDEX_HEADER_LENGTH as u32).to_be_bytes());
+        content_index.extend(&(self.sections.len() as u16).to_be_bytes());
+        content_index.extend(&(CONTENT_INDEX_SECTION_HEADER_LENGTH as u16).to_be_bytes());
+        content_index.extend(&self.flags.to_be_bytes());
+
+        let mut records_offset =
+            CONTENT_INDEX_HEADER_LENGTH + self.sections.len() * CONTENT_INDEX_SECTION_HEADER_LENGTH;
+        for section in &self.sections {
+            let record_count = match section.record_size {
+                0 => 0,
+                record_size => section.records.len() / record_size as usize,
+            };
+            content_index.extend(&(records_offset as u32).to_be_bytes());
+            content_index.extend(&(record_count as u32).to_be_bytes());
+            content_index.extend(&section.record_size.to_be_bytes());
+            content_index.extend(&(section.records.len() as u32).to_be_bytes());
+            content_index.extend(&section.kind.to_be_bytes());
+            content_index.extend(&section.flags.to_be_bytes());
+            records_offset += section.records.len();
+        }
+        for section in &self.sections {
+            content_index.extend(section.records.as_ref());
+        }
+        content_index
+    }
+
+    /// Returns whether or not the content with the provided index may be used, according to the
+    /// sections of the [`CONTENT_SECTION_KIND`](Self::CONTENT_SECTION_KIND)
+    pub fn permits_content(&self, index: u16) -> bool {
+        self.sections
+            .iter()
+            .filter(|section| section.kind == Self::CONTENT_SECTION_KIND)
+            .flat_map(ContentIndexSection::records)
+            .filter_map(|record| {
+                Some((
+                    read_integer(record, 0, u32::from_be_bytes)?,
+                    record.get(4..)?,
+                ))
+            })
+            .any(
+                |(offset, bitfield)| match u32::from(index).checked_sub(offset) {
+                    Some(bit) => matches!(
+                        bitfield.get(bit as usize / 8),
+                        Some(byte) if (byte >> (bit % 8)) & 1 == 1
+                    ),
+                    None => false,
+                },
+            )
+    }
+}
+
+impl TryFrom<&[u8]> for ContentIndex<'_> {
+    type Error = TicketError;
+
+    /// Creates a new [`ContentIndex`] from a byte slice
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let length =
+            read_integer(value, 0x4, u32::from_be_bytes).ok_or(TicketError::OutOfBounds)? as usize;
+        if length < CONTENT_INDEX_HEADER_LENGTH {
+            return Err(TicketError::InvalidContentIndex);
+        }
+        let content_index = value.get(..length).ok_or(TicketError::OutOfBounds)?;
+        let section_headers_offset = read_integer(content_index, 0x8, u32::from_be_bytes)
+            .ok_or(TicketError::OutOfBounds)? as usize;
+        let section_count = usize::from(
+            read_integer(content_index, 0xc, u16::from_be_bytes).ok_or(TicketError::OutOfBounds)?,
+        );
+        let section_header_length = usize::from(
+            read_integer(content_index, 0xe, u16::from_be_bytes).ok_or(TicketError::OutOfBounds)?,
+        );
+        if section_header_length < CONTENT_INDEX_SECTION_HEADER_LENGTH {
+            return Err(TicketError::InvalidContentIndex);
+        }
+
+        let sections = (0..section_count)
+            .map(|section| {
+                let section_header_offset =
+                    section_headers_offset + section * section_header_length;
+                let section_header = content_index
+                    .get(
+                        section_header_offset
+                            ..section_header_offset + CONTENT_INDEX_SECTION_HEADER_LENGTH,
+                    )
+                    .ok_or(TicketError::InvalidContentIndex)?;
+                let records_offset = read_integer(section_header, 0x0, u32::from_be_bytes)
+                    .ok_or(TicketError::OutOfBounds)? as usize;
+                let record_size = read_integer(section_header, 0x8, u32::from_be_bytes)
+                    .ok_or(TicketError::OutOfBounds)?;
+                let section_length = read_integer(section_header, 0xc, u32::from_be_bytes)
+                    .ok_or(TicketError::OutOfBounds)? as usize;
+                let records = content_index
+                    .get(records_offset..records_offset + section_length)
+                    .ok_or(TicketError::InvalidContentIndex)?;
+
+                Ok(ContentIndexSection {
+                    kind: read_integer(section_header, 0x10, u16::from_be_bytes)
+                        .ok_or(TicketError::OutOfBounds)?,
+                    flags: read_integer(section_header, 0x12, u16::from_be_bytes)
+                        .ok_or(TicketError::OutOfBounds)?,
+                    record_size,
+                    records: Cow::Owned(records.to_owned()),
+                })
+            })
+            .collect::<Result<_, TicketError>>()?;
+
+        Ok(Self {
+            version: read_integer(content_index, 0x0, u16::from_be_bytes)
+                .ok_or(TicketError::OutOfBounds)?,
+            flags: read_integer(content_index, 0x10, u32::from_be_bytes)
+                .ok_or(TicketError::OutOfBounds)?,
+            sections,
+        })
+    }
+}
+
+/// A section of a [`ContentIndex`], holding records of a single kind
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct ContentIndexSection<'a> {
+    pub kind: u16,
+    pub flags: u16,
+    pub record_size: u32,
+
+    /// The section's records, placed back-to-back
+    pub records: Cow<'a, [u8]>,
+}
+
+impl ContentIndexSection<'_> {
+    /// Returns an iterator over the section's records
+    pub fn records(&self) -> impl Iterator<Item = &[u8]> {
+        self.records
+            .chunks(self.record_size.max(1) as usize)
+            .filter(move |record| record.len() == self.record_size as usize)
+    }
+}
+
+/// An enumeration over errors that can be encountered while parsing a [`Ticket`]
+#[derive(Error, Debug)]
+pub enum TicketError {
+    #[error("The Ticket's Signature is invalid")]
+    CertificateError(#[from] CertificateError),
+
+    #[error("The UTF-8 data inside of the Ticket is invalid")]
+    FromUtf8Error(#[from] FromUtf8Error),
+
+    #[error("The provided byte ticket is not large enough")]
+    OutOfBounds,
+
+    #[error("The Ticket's ContentIndex is malformed")]
+    InvalidContentIndex,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::certificate::{Key, KeyId, Name};
+
+    const TICKET: &[u8] = include_bytes!("test/ticket.tik");
+    const CA: &[u8] = include_bytes!("../certificate/test/ca.bin");
+
+    // a ticket signed by an rsa key generated separately from the test certificate authority,
+    // with every reserved region filled in
+    const XS_TICKET: &[u8] = include_bytes!("test/ticket-xs.tik");
+    const XS_KEY: &[u8] = include_bytes!("test/xs-key.bin");
+
+    #[test]
+    fn parse_ticket() {
+        let ticket = Ticket::try_from(TICKET).unwrap();
+        assert_eq!(ticket.issuer.0, "Root-CA00000000-RalseiTestCA");
+        assert_eq!(ticket.version, 1);
+        assert_eq!(
+            ticket.title_key,
+            [
+                0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+                0xee, 0xff
+            ]
+        );
+        assert_eq!(ticket.ticket_id, 0x0004000012345678);
+        assert_eq!(ticket.console_id, 0x0badcafe);
+        assert_eq!(ticket.title_id, TitleId(0x0004000000030800));
+        assert_eq!(ticket.title_version, TitleVersion(0x0c10));
+        assert_eq!(ticket.common_key_index, 1);
+        assert_eq!(ticket.account_id, 0x01020304);
+        assert_eq!(ticket.limits[0], (4, 10));
+        assert_eq!(ticket.encoded_length(), TICKET.len());
+        assert_eq!(ticket.to_bytes(), TICKET);
+
+        let content_index = ticket.content_index.as_ref().unwrap();
+        assert_eq!(content_index.sections.len(), 1);
+        assert!((0..3).all(|index| content_index.permits_content(index)));
+        assert!(!content_index.permits_content(3));
+
+        ticket.verify(&Certificate::try_from(CA).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn reject_malformed_tickets() {
+        assert!(matches!(
+            Ticket::try_from(&TICKET[..0x100]),
+            Err(TicketError::OutOfBounds)
+        ));
+
+        // a content index claiming a section header beyond its end
+        let mut ticket = TICKET.to_vec();
+        ticket[0x80 + 0x164 + 0xd] = 0xff;
+        assert!(matches!(
+            Ticket::try_from(ticket.as_ref()),
+            Err(TicketError::InvalidContentIndex)
+        ));
+
+        let mut ticket = Ticket::try_from(TICKET).unwrap();
+        ticket.ticket_id += 1;
+        assert!(matches!(
+            ticket.verify(&Certificate::try_from(CA).unwrap()),
+            Err(CertificateError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn keep_reserved_regions() {
+        let xs = Certificate::new(
+            Signature::Rsa2048WithSha256(Cow::Owned(vec![0; 0x100])),
+            Issuer(Cow::Borrowed("Root-CA00000003")),
+            Key::Rsa2048(Cow::Borrowed(XS_KEY)),
+            Name(Cow::Borrowed("XS0000000c")),
+            KeyId(0),
+        );
+
+        let mut ticket = Ticket::try_from(XS_TICKET).unwrap();
+        assert_eq!(ticket.title_id, TitleId(0x0005000e10101010));
+        assert_eq!(ticket.audit, 3);
+        assert_eq!(ticket.encoded_length(), XS_TICKET.len());
+        assert_eq!(ticket.to_bytes(), XS_TICKET);
+        ticket.verify(&xs).unwrap();
+
+        // changing a field leaves the reserved regions around it alone
+        ticket.title_version = TitleVersion(0x0030);
+        let bytes = ticket.to_bytes();
+        assert_eq!(bytes[..0x140 + 0xa6], XS_TICKET[..0x140 + 0xa6]);
+        assert_eq!(bytes[0x140 + 0xa6..0x140 + 0xa8], [0x00, 0x30]);
+        assert_eq!(bytes[0x140 + 0xa8..], XS_TICKET[0x140 + 0xa8..]);
+        assert!(matches!(
+            ticket.verify(&xs),
+            Err(CertificateError::InvalidSignature)
+        ));
+
+        // as does replacing the content index, which is then encoded from its fields
+        ticket.content_index.as_mut().unwrap().flags = 1;
+        assert_eq!(
+            Ticket::try_from(ticket.to_bytes().as_ref())
+                .unwrap()
+                .content_index,
+            ticket.content_index
+        );
+    }
+}
@@ -0,0 +1,538 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Type definitions related to the title metadata format used by the 3DS and Wii U
+//!
+//! Title metadata describes a version of a title and the contents making it up. It is signed in
+//! the same manner as [`Certificate`]s, although the signature only covers its header. The header
+//! holds a hash of the [`ContentInfo`] records, each of which holds a hash of a range of the
+//! [`ContentChunk`] records, so [`TitleMetadata::verify`] and
+//! [`TitleMetadata::verify_hashes`] must both be used to check the title metadata in full. The
+//! format is documented on [3dbrew] and [wiiubrew]
+//!
+//! Title metadata downloaded from Nintendo's content servers is followed by the
+//! [`Certificate`]s needed to verify it, which can be parsed from the data following
+//! [`encoded_length`](TitleMetadata::encoded_length) bytes. The header title metadata was read
+//! from is kept alongside its fields, so that reserved portions of it survive being converted back
+//! into bytes and its signature can be checked against what was signed
+//!
+//! [3dbrew]: https://www.3dbrew.org/wiki/Title_metadata
+//! [wiiubrew]: https://wiiubrew.org/wiki/Title_metadata
+
+use bitflags::bitflags;
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    convert::{TryFrom, TryInto},
+    string::FromUtf8Error,
+};
+use thiserror::Error;
+
+use crate::{
+    certificate::{Certificate, CertificateError, Issuer, Signature},
+    title::{id::TitleId, read_integer, version::TitleVersion},
+};
+
+/// The length of the header of [`TitleMetadata`], which follows its [`Signature`]
+const HEADER_LENGTH: usize = 0xc4;
+
+/// The number of [`ContentInfo`] records held by [`TitleMetadata`]
+pub const CONTENT_INFO_COUNT: usize = 64;
+
+/// The length of a [`ContentInfo`] record
+const CONTENT_INFO_LENGTH: usize = 0x24;
+
+/// The length of a [`ContentChunk`] record
+const CONTENT_CHUNK_LENGTH: usize = 0x30;
+
+/// A title's metadata, describing a version of the title and its contents
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct TitleMetadata<'a> {
+    pub signature: Signature<'a>,
+    pub issuer: Issuer<'a>,
+
+    /// The version of the title metadata format, which is 1 on the 3DS and Wii U
+    pub version: u8,
+    pub ca_crl_version: u8,
+    pub signer_crl_version: u8,
+
+    /// The [`TitleId`] of the system title required by the title, if any
+    pub system_version: TitleId,
+    pub title_id: TitleId,
+    pub title_type: u32,
+    pub group_id: u16,
+
+    /// The size of the title's save data, in bytes
+    pub save_data_size: u32,
+
+    /// The size of the private save data of titles for the dsi, in bytes
+    pub private_save_data_size: u32,
+
+    /// A flag specific to titles for the dsi
+    pub srl_flag: u8,
+    pub access_rights: u32,
+    pub title_version: TitleVersion,
+
+    /// The index of the content that is booted when the title is launched
+    pub boot_content: u16,
+
+    /// The SHA-256 hash of the [`ContentInfo`] records
+    pub content_info_hash: [u8; 0x20],
+
+    /// The [`ContentInfo`] records, of which there are always [`CONTENT_INFO_COUNT`]
+    pub content_info: Vec<ContentInfo>,
+
+    /// The [`ContentChunk`] records, one for each of the title's contents
+    pub content_chunks: Vec<ContentChunk>,
+
+    /// The header of the title metadata, which is the portion covered by its [`Signature`],
+    /// exactly as it was read
+    ///
+    /// The header holds data in regions that are reserved, which differ between the 3DS and Wii
+    /// U, so these bytes are used as the base that the fields above are written over when
+    /// converting the [`TitleMetadata`] into bytes. This keeps the signature of parsed title
+    /// metadata valid and its bytes unchanged. Newly constructed [`TitleMetadata`] leaves this
+    /// empty, in which case the reserved regions are zeroed
+    pub raw_signed_data: Cow<'a, [u8]>,
+}
+
+impl<'a> TitleMetadata<'a> {
+    /// Converts a [`TitleMetadata`] into a byte vector
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut title_metadata = self.signature.to_bytes();
+        title_metadata.extend(self.signed_data());
+        title_metadata.extend(self.content_info_bytes());
+        for content_chunk in &self.content_chunks {
+            title_metadata.extend(&content_chunk.to_bytes());
+        }
+        title_metadata
+    }
+
+    /// Returns the length of the [`TitleMetadata`] once converted into bytes
+    pub fn encoded_length(&self) -> usize {
+        self.signature.magic().padded_length()
+            + HEADER_LENGTH
+            + CONTENT_INFO_COUNT * CONTENT_INFO_LENGTH
+            + self.content_chunks.len() * CONTENT_CHUNK_LENGTH
+    }
+
+    /// Returns the portion of the [`TitleMetadata`] covered by its signature, which is its header
+    ///
+    /// The fields of the [`TitleMetadata`] are written over its [`raw_signed_data`], so reserved
+    /// regions keep the values they were read with
+    ///
+    /// [`raw_signed_data`]: ./struct.TitleMetadata.html#structfield.raw_signed_data
+    pub fn signed_data(&self) -> Vec<u8> {
+        let mut header = match self.raw_signed_data.get(..HEADER_LENGTH) {
+            Some(raw_signed_data) => raw_signed_data.to_vec(),
+            None => vec![0; HEADER_LENGTH],
+        };
+        header[..0x40].fill(0);
+        let issuer = &self.issuer.0.as_bytes()[..self.issuer.0.len().min(0x40)];
+        header[..issuer.len()].copy_from_slice(issuer);
+        header[0x40] = self.version;
+        header[0x41] = self.ca_crl_version;
+        header[0x42] = self.signer_crl_version;
+        header[0x44..0x4c].copy_from_slice(&self.system_version.0.to_be_bytes());
+        header[0x4c..0x54].copy_from_slice(&self.title_id.0.to_be_bytes());
+        header[0x54..0x58].copy_from_slice(&self.title_type.to_be_bytes());
+        header[0x58..0x5a].copy_from_slice(&self.group_id.to_be_bytes());
+        header[0x5a..0x5e].copy_from_slice(&self.save_data_size.to_le_bytes());
+        header[0x5e..0x62].copy_from_slice(&self.private_save_data_size.to_le_bytes());
+        header[0x66] = self.srl_flag;
+        header[0x98..0x9c].copy_from_slice(&self.access_rights.to_be_bytes());
+        header[0x9c..0x9e].copy_from_slice(&self.title_version.0.to_be_bytes());
+        header[0x9e..0xa0].copy_from_slice(&(self.content_chunks.len() as u16).to_be_bytes());
+        header[0xa0..0xa2].copy_from_slice(&self.boot_content.to_be_bytes());
+        header[0xa4..0xc4].copy_from_slice(&self.content_info_hash);
+        header
+    }
+
+    /// Verifies that the [`TitleMetadata`] was signed by the provided issuing [`Certificate`]
+    ///
+    /// As only the header is signed, this does not check the [`ContentInfo`] or [`ContentChunk`]
+    /// records, which are checked by [`verify_hashes`](Self::verify_hashes)
+    pub fn verify(&self, issuer: &Certificate<'_>) -> Result<(), CertificateError> {
+        if !self.issuer.is_issued_by(&issuer.name) {
+            return Err(CertificateError::IssuerMismatch);
+        }
+        issuer.key.verify(&self.signature, &self.signed_data())
+    }
+
+    /// Verifies that the hash held by the header matches the [`ContentInfo`] records, and that
+    /// the hash held by each [`ContentInfo`] record matches the [`ContentChunk`] records it covers
+    pub fn verify_hashes(&self) -> Result<(), TitleMetadataError> {
+        if Sha256::digest(&self.content_info_bytes())[..] != self.content_info_hash[..] {
+            return Err(TitleMetadataError::ContentInfoHashMismatch);
+        }
+
+        for (index, content_info) in self.content_info.iter().enumerate() {
+            if content_info.content_count == 0 {
+                continue;
+            }
+            if self.content_chunks_hash(content_info)? != content_info.hash {
+                return Err(TitleMetadataError::ContentChunkHashMismatch(index));
+            }
+        }
+        Ok(())
+    }
+
+    /// Recomputes the hashes held by the header and by each [`ContentInfo`] record, such that
+    /// [`verify_hashes`](Self::verify_hashes) succeeds
+    pub fn update_hashes(&mut self) -> Result<(), TitleMetadataError> {
+        for index in 0..self.content_info.len() {
+            if self.content_info[index].content_count != 0 {
+                self.content_info[index].hash =
+                    self.content_chunks_hash(&self.content_info[index])?;
+            }
+        }
+        self.content_info_hash = Sha256::digest(&self.content_info_bytes()).into();
+        Ok(())
+    }
+
+    /// Returns the hash of the [`ContentChunk`] records covered by the provided [`ContentInfo`]
+    fn content_chunks_hash(
+        &self,
+        content_info: &ContentInfo,
+    ) -> Result<[u8; 0x20], TitleMetadataError> {
+        let start = usize::from(content_info.content_index_offset);
+        let content_chunks = self
+            .content_chunks
+            .get(start..start + usize::from(content_info.content_count))
+            .ok_or(TitleMetadataError::InvalidContentInfo)?;
+
+        let mut hasher = Sha256::new();
+        for content_chunk in content_chunks {
+            hasher.update(content_chunk.to_bytes());
+        }
+        Ok(hasher.finalize().into())
+    }
+
+    /// Returns the [`ContentInfo`] records as they are encoded, padded or truncated to
+    /// [`CONTENT_INFO_COUNT`] records
+    fn content_info_bytes(&self) -> Vec<u8> {
+        let mut content_info = Vec::with_capacity(CONTENT_INFO_COUNT * CONTENT_INFO_LENGTH);
+        for record in self.content_info.iter().take(CONTENT_INFO_COUNT) {
+            content_info.extend(&record.to_bytes());
+        }
+        content_info.resize(CONTENT_INFO_COUNT * CONTENT_INFO_LENGTH, 0);
+        content_info
+    }
+}
+
+impl TryFrom<&[u8]> for TitleMetadata<'_> {
+    type Error = TitleMetadataError;
+
+    /// Creates a new [`TitleMetadata`] from a byte slice
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let (signature, offset) = Signature::parse(value)?;
+        let header = value
+            .get(offset..offset + HEADER_LENGTH)
+            .ok_or(TitleMetadataError::OutOfBounds)?;
+
+        let mut issuer = header[..0x40].to_owned();
+        while let Some(&value) = issuer.last() {
+            if value == 0 {
+                issuer.pop();
+            } else {
+                break;
+            }
+        }
+
+        let content_info_offset = offset + HEADER_LENGTH;
+        let content_info = value
+            .get(
+                content_info_offset..content_info_offset + CONTENT_INFO_COUNT * CONTENT_INFO_LENGTH,
+            )
+            .ok_or(TitleMetadataError::OutOfBounds)?
+            .chunks(CONTENT_INFO_LENGTH)
+            .map(ContentInfo::from_bytes)
+            .collect::<Result<_, _>>()?;
+
+        let content_count = usize::from(
+            read_integer(header, 0x9e, u16::from_be_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+        );
+        let content_chunks_offset = content_info_offset + CONTENT_INFO_COUNT * CONTENT_INFO_LENGTH;
+        let content_chunks = value
+            .get(
+                content_chunks_offset..content_chunks_offset + content_count * CONTENT_CHUNK_LENGTH,
+            )
+            .ok_or(TitleMetadataError::OutOfBounds)?
+            .chunks(CONTENT_CHUNK_LENGTH)
+            .map(ContentChunk::from_bytes)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            signature,
+            issuer: Issuer(Cow::Owned(String::from_utf8(issuer)?)),
+            version: header[0x40],
+            ca_crl_version: header[0x41],
+            signer_crl_version: header[0x42],
+            system_version: TitleId(
+                read_integer(header, 0x44, u64::from_be_bytes)
+                    .ok_or(TitleMetadataError::OutOfBounds)?,
+            ),
+            title_id: TitleId(
+                read_integer(header, 0x4c, u64::from_be_bytes)
+                    .ok_or(TitleMetadataError::OutOfBounds)?,
+            ),
+            title_type: read_integer(header, 0x54, u32::from_be_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            group_id: read_integer(header, 0x58, u16::from_be_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            save_data_size: read_integer(header, 0x5a, u32::from_le_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            private_save_data_size: read_integer(header, 0x5e, u32::from_le_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            srl_flag: header[0x66],
+            access_rights: read_integer(header, 0x98, u32::from_be_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            title_version: TitleVersion(
+                read_integer(header, 0x9c, u16::from_be_bytes)
+                    .ok_or(TitleMetadataError::OutOfBounds)?,
+            ),
+            boot_content: read_integer(header, 0xa0, u16::from_be_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            content_info_hash: header[0xa4..0xc4]
+                .try_into()
+                .expect("unable to convert a slice into an array (this should be impossible)"),
+            content_info,
+            content_chunks,
+            raw_signed_data: Cow::Owned(header.to_owned()),
+        })
+    }
+}
+
+/// A record describing a range of [`ContentChunk`] records, which it holds the hash of
+#[derive(Copy, Clone, Default, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct ContentInfo {
+    /// The index of the first [`ContentChunk`] record in the range
+    pub content_index_offset: u16,
+
+    /// The number of [`ContentChunk`] records in the range
+    pub content_count: u16,
+
+    /// The SHA-256 hash of the [`ContentChunk`] records in the range
+    pub hash: [u8; 0x20],
+}
+
+impl ContentInfo {
+    /// Converts a [`ContentInfo`] record into a byte array
+    pub fn to_bytes(&self) -> [u8; CONTENT_INFO_LENGTH] {
+        let mut content_info = [0; CONTENT_INFO_LENGTH];
+        content_info[..0x2].copy_from_slice(&self.content_index_offset.to_be_bytes());
+        content_info[0x2..0x4].copy_from_slice(&self.content_count.to_be_bytes());
+        content_info[0x4..].copy_from_slice(&self.hash);
+        content_info
+    }
+
+    /// Creates a new [`ContentInfo`] record from a byte slice
+    fn from_bytes(value: &[u8]) -> Result<Self, TitleMetadataError> {
+        Ok(Self {
+            content_index_offset: read_integer(value, 0x0, u16::from_be_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            content_count: read_integer(value, 0x2, u16::from_be_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            hash: value
+                .get(0x4..0x24)
+                .ok_or(TitleMetadataError::OutOfBounds)?
+                .try_into()
+                .expect("unable to convert a slice into an array (this should be impossible)"),
+        })
+    }
+}
+
+bitflags! {
+    /// The flags held by a [`ContentChunk`] record
+    pub struct ContentType: u16 {
+        const ENCRYPTED = 0x0001;
+        const DISC = 0x0002;
+        const CFM = 0x0004;
+        const OPTIONAL = 0x4000;
+        const SHARED = 0x8000;
+    }
+}
+
+/// A record describing one of the title's contents
+#[derive(Copy, Clone, Default, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct ContentChunk {
+    /// The content's id, which is also the name of its file
+    pub id: u32,
+
+    /// The content's index within the title
+    pub index: u16,
+
+    /// The content's type, which is made up of [`ContentType`] flags along with any undocumented
+    /// flags
+    pub content_type: u16,
+
+    /// The size of the content, in bytes
+    pub size: u64,
+
+    /// The hash of the decrypted content, which is a SHA-256 hash on the 3DS and a SHA-1 hash
+    /// followed by zeroes on the Wii U
+    pub hash: [u8; 0x20],
+}
+
+impl ContentChunk {
+    /// Returns the documented [`ContentType`] flags of the content's type
+    pub fn flags(&self) -> ContentType {
+        ContentType::from_bits_truncate(self.content_type)
+    }
+
+    /// Converts a [`ContentChunk`] record into a byte array
+    pub fn to_bytes(&self) -> [u8; CONTENT_CHUNK_LENGTH] {
+        let mut content_chunk = [0; CONTENT_CHUNK_LENGTH];
+        content_chunk[..0x4].copy_from_slice(&self.id.to_be_bytes());
+        content_chunk[0x4..0x6].copy_from_slice(&self.index.to_be_bytes());
+        content_chunk[0x6..0x8].copy_from_slice(&self.content_type.to_be_bytes());
+        content_chunk[0x8..0x10].copy_from_slice(&self.size.to_be_bytes());
+        content_chunk[0x10..].copy_from_slice(&self.hash);
+        content_chunk
+    }
+
+    /// Creates a new [`ContentChunk`] record from a byte slice
+    fn from_bytes(value: &[u8]) -> Result<Self, TitleMetadataError> {
+        Ok(Self {
+            id: read_integer(value, 0x0, u32::from_be_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            index: read_integer(value, 0x4, u16::from_be_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            content_type: read_integer(value, 0x6, u16::from_be_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            size: read_integer(value, 0x8, u64::from_be_bytes)
+                .ok_or(TitleMetadataError::OutOfBounds)?,
+            hash: value
+                .get(0x10..0x30)
+                .ok_or(TitleMetadataError::OutOfBounds)?
+                .try_into()
+                .expect("unable to convert a slice into an array (this should be impossible)"),
+        })
+    }
+}
+
+/// An enumeration over errors that can be encountered while handling [`TitleMetadata`]
+#[derive(Error, Debug)]
+pub enum TitleMetadataError {
+    #[error("The TitleMetadata's Signature is invalid")]
+    CertificateError(#[from] CertificateError),
+
+    #[error("The UTF-8 data inside of the TitleMetadata is invalid")]
+    FromUtf8Error(#[from] FromUtf8Error),
+
+    #[error("The provided byte title metadata is not large enough")]
+    OutOfBounds,
+
+    #[error("A ContentInfo record covers ContentChunk records that do not exist")]
+    InvalidContentInfo,
+
+    #[error("The hash of the ContentInfo records does not match them")]
+    ContentInfoHashMismatch,
+
+    #[error("The hash held by ContentInfo record `{0}` does not match the records it covers")]
+    ContentChunkHashMismatch(usize),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::certificate::{Issuer, Key, KeyId, Name, Signature};
+
+    const TITLE_METADATA: &[u8] = include_bytes!("test/title.tmd");
+    const CA: &[u8] = include_bytes!("../certificate/test/ca.bin");
+
+    // title metadata signed by an rsa key generated separately from the test certificate
+    // authority, with every reserved region of its header filled in
+    const CP_TITLE_METADATA: &[u8] = include_bytes!("test/title-cp.tmd");
+    const CP_KEY: &[u8] = include_bytes!("test/cp-key.bin");
+
+    #[test]
+    fn parse_title_metadata() {
+        let title_metadata = TitleMetadata::try_from(TITLE_METADATA).unwrap();
+        assert_eq!(title_metadata.issuer.0, "Root-CA00000000-RalseiTestCA");
+        assert_eq!(title_metadata.system_version, TitleId(0x000400db00017302));
+        assert_eq!(title_metadata.title_id, TitleId(0x0004000000030800));
+        assert_eq!(title_metadata.group_id, 0x1234);
+        assert_eq!(title_metadata.save_data_size, 0x80000);
+        assert_eq!(title_metadata.title_version, TitleVersion(0x0c10));
+        assert_eq!(title_metadata.content_info.len(), CONTENT_INFO_COUNT);
+        assert_eq!(title_metadata.content_chunks.len(), 3);
+        assert_eq!(title_metadata.content_chunks[1].size, 0x4000);
+        assert_eq!(
+            title_metadata.content_chunks[2].flags(),
+            ContentType::ENCRYPTED | ContentType::OPTIONAL
+        );
+        assert_eq!(title_metadata.encoded_length(), TITLE_METADATA.len());
+        assert_eq!(title_metadata.to_bytes(), TITLE_METADATA);
+
+        title_metadata
+            .verify(&Certificate::try_from(CA).unwrap())
+            .unwrap();
+        title_metadata.verify_hashes().unwrap();
+    }
+
+    #[test]
+    fn verify_title_metadata_hashes() {
+        let mut title_metadata = TitleMetadata::try_from(TITLE_METADATA).unwrap();
+        title_metadata.content_chunks[2].size += 1;
+        assert!(matches!(
+            title_metadata.verify_hashes(),
+            Err(TitleMetadataError::ContentChunkHashMismatch(0))
+        ));
+
+        title_metadata.update_hashes().unwrap();
+        title_metadata.verify_hashes().unwrap();
+        assert!(matches!(
+            title_metadata.verify(&Certificate::try_from(CA).unwrap()),
+            Err(CertificateError::InvalidSignature)
+        ));
+
+        title_metadata.content_info[0].hash = [0; 0x20];
+        assert!(matches!(
+            title_metadata.verify_hashes(),
+            Err(TitleMetadataError::ContentInfoHashMismatch)
+        ));
+
+        title_metadata.content_info[0].content_count = 4;
+        assert!(matches!(
+            title_metadata.update_hashes(),
+            Err(TitleMetadataError::InvalidContentInfo)
+        ));
+    }
+
+    #[test]
+    fn keep_reserved_regions() {
+        let cp = Certificate::new(
+            Signature::Rsa2048WithSha256(Cow::Owned(vec![0; 0x100])),
+            Issuer(Cow::Borrowed("Root-CA00000003")),
+            Key::Rsa2048(Cow::Borrowed(CP_KEY)),
+            Name(Cow::Borrowed("CP0000000b")),
+            KeyId(0),
+        );
+
+        let mut title_metadata = TitleMetadata::try_from(CP_TITLE_METADATA).unwrap();
+        assert_eq!(title_metadata.title_id, TitleId(0x0005000e10101010));
+        assert_eq!(title_metadata.encoded_length(), CP_TITLE_METADATA.len());
+        assert_eq!(title_metadata.to_bytes(), CP_TITLE_METADATA);
+        title_metadata.verify(&cp).unwrap();
+        title_metadata.verify_hashes().unwrap();
+
+        // changing a field leaves the reserved regions around it alone
+        title_metadata.title_version = TitleVersion(0x0030);
+        let bytes = title_metadata.to_bytes();
+        assert_eq!(bytes[..0x140 + 0x9c], CP_TITLE_METADATA[..0x140 + 0x9c]);
+        assert_eq!(bytes[0x140 + 0x9c..0x140 + 0x9e], [0x00, 0x30]);
+        assert_eq!(bytes[0x140 + 0x9e..], CP_TITLE_METADATA[0x140 + 0x9e..]);
+        assert!(matches!(
+            title_metadata.verify(&cp),
+            Err(CertificateError::InvalidSignature)
+        ));
+    }
+}
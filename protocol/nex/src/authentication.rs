@@ -0,0 +1,210 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The `TicketGranting` protocol, spoken by NEX authentication servers
+//!
+//! Logging in to an authentication server results in the address of the game server's secure
+//! server and a [`Ticket`](crate::kerberos::Ticket) for it, which is used to connect to it. For
+//! more information, see [the NintendoClients wiki]
+//!
+//! [the NintendoClients wiki]: https://github.com/kinnay/NintendoClients/wiki/Authentication-Protocol
+
+use ralsei_protocol_rmc::{
+    codec::{Context, Decode, DecodeError, Encode, EncodeError},
+    protocol::{protocol, structure},
+    types::{AnyDataHolder, Buffer, DateTime, QResult, StationUrl},
+};
+
+protocol! {
+    /// The protocol used to log in to a NEX authentication server and request tickets for other
+    /// servers
+    pub protocol TicketGranting = 10 {
+        client TicketGrantingClient;
+        server TicketGrantingServer => TicketGrantingDispatcher;
+
+        /// Logs in as the user with the provided name, which is usually their
+        /// [`Pid`](ralsei_model::network::Pid)
+        1 => fn login(user_name: String) -> LoginResult;
+
+        /// Logs in as the user with the provided name, passing along the provided data, which is
+        /// usually an [`AuthenticationInfo`] containing a NEX token
+        2 => fn login_ex(user_name: String, extra_data: AnyDataHolder) -> LoginResult;
+
+        /// Requests a [`Ticket`](crate::kerberos::Ticket) granting the user with the provided
+        /// [`Pid`](ralsei_model::network::Pid) access to the server with the other one
+        3 => fn request_ticket(source: u32, target: u32) -> (QResult, Buffer);
+
+        /// Returns the [`Pid`](ralsei_model::network::Pid) of the user with the provided name
+        4 => fn get_pid(user_name: String) -> u32;
+
+        /// Returns the name of the user with the provided [`Pid`](ralsei_model::network::Pid)
+        5 => fn get_name(pid: u32) -> String;
+    }
+}
+
+structure! {
+    /// The data passed to [`login_ex`](TicketGrantingClient::login_ex) to log in using a NEX token
+    /// obtained from the account server
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct AuthenticationInfo {
+        /// The token itself
+        pub token: String,
+
+        /// The version of the NEX game server library in use
+        pub ngs_version: u32,
+
+        /// The kind of token, which is always 1
+        pub token_type: u8,
+
+        /// The version of NEX the game server is running
+        pub server_version: u32,
+    }
+}
+
+impl AuthenticationInfo {
+    /// The name the structure is given when placed within an [`AnyDataHolder`]
+    pub const TYPE_NAME: &'static str = "AuthenticationInfo";
+}
+
+/// The first version of NEX whose authentication servers send their time along with the
+/// [`ConnectionData`]
+const TIME_NEX_VERSION: u32 = 30_500;
+
+/// The addresses of the secure server a user is directed to after logging in
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct ConnectionData {
+    /// The [`StationUrl`] of the secure server
+    pub regular_protocols: StationUrl,
+
+    /// The ids of the protocols served at [`special_protocols_url`](#structfield.special_protocols_url)
+    pub special_protocols: Vec<u8>,
+
+    /// The [`StationUrl`] of the server handling the special protocols, which is usually empty
+    pub special_protocols_url: StationUrl,
+
+    /// The time on the server, which is only sent by servers running NEX 3.5 or later
+    pub time: Option<DateTime>,
+}
+
+impl Encode for ConnectionData {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        self.regular_protocols.encode(buffer)?;
+        self.special_protocols.encode(buffer)?;
+        self.special_protocols_url.encode(buffer)?;
+        if let Some(time) = self.time {
+            time.encode(buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// The results of logging in to an authentication server
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct LoginResult {
+    /// Whether or not logging in was successful
+    pub result: QResult,
+
+    /// The [`Pid`](ralsei_model::network::Pid) of the user that was logged in as
+    pub pid: u32,
+
+    /// The encrypted [`Ticket`](crate::kerberos::Ticket) for the secure server
+    pub ticket: Buffer,
+
+    /// The addresses of the secure server
+    pub connection_data: ConnectionData,
+
+    /// A message describing the server, usually containing its build name
+    pub return_message: String,
+}
+
+impl Encode for LoginResult {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        self.result.encode(buffer)?;
+        self.pid.encode(buffer)?;
+        self.ticket.encode(buffer)?;
+        self.connection_data.encode(buffer)?;
+        self.return_message.encode(buffer)
+    }
+}
+
+/// Whether or not the time is present depends upon the version of NEX the server is running, so
+/// the [`Context`] the [`LoginResult`] is decoded under must name it
+impl Decode for LoginResult {
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        Self::decode_in(data, &Context::default())
+    }
+
+    fn decode_in(data: &mut &[u8], context: &Context) -> Result<Self, DecodeError> {
+        let result = QResult::decode(data)?;
+        let pid = u32::decode(data)?;
+        let ticket = Buffer::decode(data)?;
+        let regular_protocols = StationUrl::decode(data)?;
+        let special_protocols = Vec::decode(data)?;
+        let special_protocols_url = StationUrl::decode(data)?;
+        let time = if context.nex_version >= TIME_NEX_VERSION {
+            Some(DateTime::decode(data)?)
+        } else {
+            None
+        };
+        let return_message = String::decode(data)?;
+
+        Ok(Self {
+            result,
+            pid,
+            ticket,
+            connection_data: ConnectionData {
+                regular_protocols,
+                special_protocols,
+                special_protocols_url,
+                time,
+            },
+            return_message,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn login_result_round_trip() {
+        let mut result = LoginResult {
+            result: QResult::SUCCESS,
+            pid: 1337,
+            ticket: Buffer(vec![1, 2, 3]),
+            connection_data: ConnectionData {
+                regular_protocols: "prudps:/address=127.0.0.1;port=60001;PID=2;CID=1"
+                    .parse()
+                    .unwrap(),
+                ..ConnectionData::default()
+            },
+            return_message: "branch:origin/project/wup-agmj build:3_8_15_2004_0".to_string(),
+        };
+        assert_eq!(
+            LoginResult::from_bytes(&result.to_bytes().unwrap()).unwrap(),
+            result
+        );
+
+        let data = result.to_bytes().unwrap();
+        assert_eq!(
+            LoginResult::from_bytes_in(&data, &Context::new(30_000)).unwrap(),
+            result
+        );
+        assert!(LoginResult::from_bytes_in(&data, &Context::new(TIME_NEX_VERSION)).is_err());
+
+        result.connection_data.time = Some(DateTime::new(2021, 1, 2, 3, 4, 5));
+        let data = result.to_bytes().unwrap();
+        assert_eq!(
+            LoginResult::from_bytes_in(&data, &Context::new(TIME_NEX_VERSION)).unwrap(),
+            result
+        );
+        assert!(LoginResult::from_bytes_in(&data, &Context::new(30_000)).is_err());
+    }
+}
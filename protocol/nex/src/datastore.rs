@@ -0,0 +1,504 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The `DataStore` protocol, used by games to store user-created content
+//!
+//! The metadata of objects is managed over RMC, while their contents are transferred over HTTP.
+//! Uploading an object is done by preparing to post it, which results in a pre-signed url that
+//! its contents are [`upload`]ed to, and then completing the post. Downloading an object is done
+//! by preparing to get it, which results in a pre-signed url that its contents are
+//! [`download`]ed from. For more information, see [the NintendoClients wiki]
+//!
+//! The structures are modeled as they are in NEX 3 releases before 3.5, which appended fields to
+//! several of them
+//!
+//! [the NintendoClients wiki]: https://github.com/kinnay/NintendoClients/wiki/DataStore-Protocol
+
+use futures::stream::TryStreamExt;
+use http::{header, Error as HttpError, Request};
+use hyper::{
+    client::{Client as HttpClient, HttpConnector},
+    Body, Error as HyperError,
+};
+use hyper_tls::HttpsConnector;
+use native_tls::{Certificate, Error as NativeTlsError, TlsConnector as NativeTlsConnector};
+use std::fmt::Write;
+use thiserror::Error;
+use tokio_native_tls::TlsConnector;
+
+use ralsei_protocol_rmc::{
+    protocol::{protocol, structure},
+    types::{Buffer, DateTime, QBuffer},
+};
+
+protocol! {
+    /// The protocol used to store objects and their metadata
+    pub protocol DataStore = 115 {
+        client DataStoreClient;
+        server DataStoreServer => DataStoreDispatcher;
+
+        /// Deletes the object described by the provided parameters
+        4 => fn delete_object(param: DeleteParam) -> ();
+
+        /// Returns the metadata of the object described by the provided parameters
+        8 => fn get_meta(param: GetMetaParam) -> MetaInfo;
+
+        /// Refreshes the expiration time of the object described by the provided parameters
+        22 => fn touch_object(param: TouchObjectParam) -> ();
+
+        /// Prepares to upload the object described by the provided parameters, returning where to
+        /// upload it to
+        24 => fn prepare_post_object(param: PreparePostParam) -> ReqPostInfo;
+
+        /// Prepares to download the object described by the provided parameters, returning where
+        /// to download it from
+        25 => fn prepare_get_object(param: PrepareGetParam) -> ReqGetInfo;
+
+        /// Reports the outcome of an upload prepared using
+        /// [`prepare_post_object`](DataStoreClient::prepare_post_object)
+        26 => fn complete_post_object(param: CompletePostParam) -> ();
+
+        /// Returns the id of the object in the provided persistence slot of the user with the
+        /// provided [`Pid`](ralsei_model::network::Pid)
+        29 => fn get_persistence_info(owner_id: u32, persistence_slot_id: u16) -> PersistenceInfo;
+    }
+}
+
+structure! {
+    /// A key-value pair, used for HTTP headers and form fields
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct KeyValue {
+        /// The key
+        pub key: String,
+
+        /// The value
+        pub value: String,
+    }
+
+    /// Who is allowed to do something with an object
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct Permission {
+        /// The kind of permission, which is 0 for everyone, 1 for friends, 2 for the provided
+        /// recipients and 3 for only the owner
+        pub permission: u8,
+
+        /// The [`Pid`](ralsei_model::network::Pid)s of the users allowed when the permission is
+        /// limited to specific recipients
+        pub recipient_ids: Vec<u32>,
+    }
+
+    /// A persistence slot of a user, which refers to a single object
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct PersistenceTarget {
+        /// The [`Pid`](ralsei_model::network::Pid) of the slot's owner
+        pub owner_id: u32,
+
+        /// The slot's id
+        pub persistence_slot_id: u16,
+    }
+
+    /// The object stored in a persistence slot
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct PersistenceInfo {
+        /// The [`Pid`](ralsei_model::network::Pid) of the slot's owner
+        pub owner_id: u32,
+
+        /// The slot's id
+        pub persistence_slot_id: u16,
+
+        /// The id of the object stored in the slot
+        pub data_id: u64,
+    }
+
+    /// The options of a new persistence slot
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct PersistenceInitParam {
+        /// The slot's id
+        pub persistence_slot_id: u16,
+
+        /// Whether or not the object previously stored in the slot is deleted
+        pub delete_last_object: bool,
+    }
+
+    /// The ratings of an object within a single slot
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct RatingInfo {
+        /// The sum of the ratings
+        pub total_value: i64,
+
+        /// The number of ratings
+        pub count: u32,
+
+        /// The value the ratings started at
+        pub initial_value: i64,
+    }
+
+    /// The ratings of an object within the slot with the contained id
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct RatingInfoWithSlot {
+        /// The slot's id
+        pub slot: i8,
+
+        /// The ratings
+        pub rating: RatingInfo,
+    }
+
+    /// The options of a new rating slot
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct RatingInitParam {
+        /// The slot's flags
+        pub flag: u8,
+
+        /// The slot's internal flags
+        pub internal_flag: u8,
+
+        /// How users are prevented from rating an object more than once
+        pub lock_type: u8,
+
+        /// The value the ratings start at
+        pub initial_value: i64,
+
+        /// The lowest value a rating may have
+        pub range_min: i32,
+
+        /// The highest value a rating may have
+        pub range_max: i32,
+
+        /// The hour of the day the lock period starts at
+        pub period_hour: i8,
+
+        /// The length of the lock period
+        pub period_duration: i16,
+    }
+
+    /// The options of a new rating slot with the contained id
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct RatingInitParamWithSlot {
+        /// The slot's id
+        pub slot: i8,
+
+        /// The slot's options
+        pub param: RatingInitParam,
+    }
+
+    /// The parameters of [`get_meta`](DataStoreClient::get_meta)
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct GetMetaParam {
+        /// The object's id, which is 0 if the object is referred to by its persistence slot
+        pub data_id: u64,
+
+        /// The persistence slot the object is stored in
+        pub persistence_target: PersistenceTarget,
+
+        /// A bitmask of the optional parts of the metadata to return
+        pub result_option: u8,
+
+        /// The password needed to access the object, if it has one
+        pub access_password: u64,
+    }
+
+    /// The metadata of an object
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct MetaInfo {
+        /// The object's id
+        pub data_id: u64,
+
+        /// The [`Pid`](ralsei_model::network::Pid) of the object's owner
+        pub owner_id: u32,
+
+        /// The size of the object
+        pub size: u32,
+
+        /// The object's name
+        pub name: String,
+
+        /// The game-specific kind of object
+        pub data_type: u16,
+
+        /// Game-specific metadata
+        pub meta_binary: QBuffer,
+
+        /// Who is allowed to access the object
+        pub permission: Permission,
+
+        /// Who is allowed to delete the object
+        pub delete_permission: Permission,
+
+        /// When the object was created
+        pub created_at: DateTime,
+
+        /// When the object was last updated
+        pub updated_at: DateTime,
+
+        /// The number of days the object is kept for without being accessed
+        pub period: u16,
+
+        /// The object's status
+        pub status: u8,
+
+        /// The number of times the object has been accessed
+        pub referred_count: u32,
+
+        /// The id of the object the object refers to
+        pub refer_data_id: u32,
+
+        /// The object's flags
+        pub flag: u32,
+
+        /// When the object was last accessed
+        pub referred_at: DateTime,
+
+        /// When the object expires
+        pub expires_at: DateTime,
+
+        /// The object's tags
+        pub tags: Vec<String>,
+
+        /// The object's ratings
+        pub ratings: Vec<RatingInfoWithSlot>,
+    }
+
+    /// The parameters of [`prepare_post_object`](DataStoreClient::prepare_post_object)
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct PreparePostParam {
+        /// The size of the object
+        pub size: u32,
+
+        /// The object's name
+        pub name: String,
+
+        /// The game-specific kind of object
+        pub data_type: u16,
+
+        /// Game-specific metadata
+        pub meta_binary: QBuffer,
+
+        /// Who is allowed to access the object
+        pub permission: Permission,
+
+        /// Who is allowed to delete the object
+        pub delete_permission: Permission,
+
+        /// The object's flags
+        pub flag: u32,
+
+        /// The number of days the object is kept for without being accessed
+        pub period: u16,
+
+        /// The id of the object the object refers to
+        pub refer_data_id: u32,
+
+        /// The object's tags
+        pub tags: Vec<String>,
+
+        /// The options of the object's rating slots
+        pub rating_init_params: Vec<RatingInitParamWithSlot>,
+
+        /// The options of the persistence slot the object is stored in
+        pub persistence_init_param: PersistenceInitParam,
+    }
+
+    /// Where to upload an object to
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct ReqPostInfo {
+        /// The object's id
+        pub data_id: u64,
+
+        /// The pre-signed url to upload the object to
+        pub url: String,
+
+        /// The headers sent along with the upload
+        pub request_headers: Vec<KeyValue>,
+
+        /// The form fields sent along with the object
+        pub form_fields: Vec<KeyValue>,
+
+        /// The DER-encoded root certificate the server at the url is verified with, if it is not
+        /// one that is trusted by default
+        pub root_ca_cert: Buffer,
+    }
+
+    /// The parameters of [`complete_post_object`](DataStoreClient::complete_post_object)
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct CompletePostParam {
+        /// The object's id
+        pub data_id: u64,
+
+        /// Whether or not the object was uploaded
+        pub success: bool,
+    }
+
+    /// The parameters of [`prepare_get_object`](DataStoreClient::prepare_get_object)
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct PrepareGetParam {
+        /// The object's id, which is 0 if the object is referred to by its persistence slot
+        pub data_id: u64,
+
+        /// The id of the lock held on the object
+        pub lock_id: u32,
+
+        /// The persistence slot the object is stored in
+        pub persistence_target: PersistenceTarget,
+
+        /// The password needed to access the object, if it has one
+        pub access_password: u64,
+    }
+
+    /// Where to download an object from
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct ReqGetInfo {
+        /// The pre-signed url to download the object from
+        pub url: String,
+
+        /// The headers sent along with the download
+        pub request_headers: Vec<KeyValue>,
+
+        /// The size of the object
+        pub size: u32,
+
+        /// The DER-encoded root certificate the server at the url is verified with, if it is not
+        /// one that is trusted by default
+        pub root_ca_cert: Buffer,
+    }
+
+    /// The parameters of [`delete_object`](DataStoreClient::delete_object)
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct DeleteParam {
+        /// The object's id
+        pub data_id: u64,
+
+        /// The password needed to modify the object, if it has one
+        pub update_password: u64,
+    }
+
+    /// The parameters of [`touch_object`](DataStoreClient::touch_object)
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct TouchObjectParam {
+        /// The object's id
+        pub data_id: u64,
+
+        /// The id of the lock held on the object
+        pub lock_id: u32,
+
+        /// The password needed to access the object, if it has one
+        pub access_password: u64,
+    }
+}
+
+/// The name of the form field the object is placed in when uploading it
+const FILE_FIELD: &str = "file";
+
+/// Downloads an object from where [`prepare_get_object`](DataStoreClient::prepare_get_object)
+/// directed to
+pub async fn download(info: &ReqGetInfo) -> Result<Vec<u8>, TransferError> {
+    let mut request = Request::builder().method("GET").uri(info.url.as_str());
+    for KeyValue { key, value } in &info.request_headers {
+        request = request.header(key.as_str(), value.as_str());
+    }
+
+    let response = http_client(&info.root_ca_cert)?
+        .request(request.body(Body::empty())?)
+        .await?;
+    match response.status().as_u16() {
+        200 => Ok(response
+            .into_body()
+            .try_fold(Vec::new(), |mut accumulator, chunk| async move {
+                accumulator.extend_from_slice(&chunk);
+                Ok(accumulator)
+            })
+            .await?),
+        status => Err(TransferError::UnexpectedStatusCode(status)),
+    }
+}
+
+/// Uploads an object to where [`prepare_post_object`](DataStoreClient::prepare_post_object)
+/// directed to
+///
+/// The object is sent as a `multipart/form-data` form made up of the provided form fields,
+/// followed by the object itself
+pub async fn upload(info: &ReqPostInfo, data: &[u8]) -> Result<(), TransferError> {
+    let boundary = format!("{:032x}", rand::random::<u128>());
+
+    let mut body = String::new();
+    for KeyValue { key, value } in &info.form_fields {
+        let _ = write!(
+            body,
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+            boundary, key, value
+        );
+    }
+    let _ = write!(
+        body,
+        "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        boundary, FILE_FIELD, FILE_FIELD
+    );
+    let mut body = body.into_bytes();
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(info.url.as_str())
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", boundary),
+        );
+    for KeyValue { key, value } in &info.request_headers {
+        request = request.header(key.as_str(), value.as_str());
+    }
+
+    let response = http_client(&info.root_ca_cert)?
+        .request(request.body(Body::from(body))?)
+        .await?;
+    match response.status().as_u16() {
+        200..=299 => Ok(()),
+        status => Err(TransferError::UnexpectedStatusCode(status)),
+    }
+}
+
+/// Creates an HTTP client that trusts the provided DER-encoded root certificate in addition to
+/// those trusted by default
+fn http_client(
+    root_ca_cert: &Buffer,
+) -> Result<HttpClient<HttpsConnector<HttpConnector>, Body>, TransferError> {
+    let mut http = HttpConnector::new();
+    http.enforce_http(false);
+
+    let mut tls = NativeTlsConnector::builder();
+    if !root_ca_cert.0.is_empty() {
+        tls.add_root_certificate(Certificate::from_der(&root_ca_cert.0)?);
+    }
+
+    Ok(HttpClient::builder().build(HttpsConnector::from((
+        http,
+        TlsConnector::from(tls.build()?),
+    ))))
+}
+
+/// An enumeration over the errors that may occur while transferring an object
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum TransferError {
+    /// An error encountered while using the native tls implementation
+    #[error("An error was encountered while using the native tls implementation")]
+    NativeTlsError(#[from] NativeTlsError),
+
+    /// An error was encountered while using hyper
+    #[error("An error was encountered while using the `hyper` library")]
+    HyperError(#[from] HyperError),
+
+    /// An error was encountered while using the http library
+    #[error("An error was encountered while using the `http` library")]
+    HttpError(#[from] HttpError),
+
+    /// The server returned an unexpected status code
+    #[error("The server returned an unexpected status code, `{0}`")]
+    UnexpectedStatusCode(u16),
+}
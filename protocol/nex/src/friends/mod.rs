@@ -0,0 +1,32 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The friends protocols, spoken by the friends server's secure server
+//!
+//! The 3DS and the Wii U each speak their own variant, which are declared in the [`n3ds`] and
+//! [`wiiu`] modules respectively. For more information, see [the NintendoClients wiki]
+//!
+//! [the NintendoClients wiki]: https://github.com/kinnay/NintendoClients/wiki/Friends-Protocol
+
+use ralsei_protocol_rmc::protocol::structure;
+
+pub mod n3ds;
+pub mod wiiu;
+
+structure! {
+    /// A title and the version of it in use
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct GameKey {
+        /// The title's id
+        pub title_id: u64,
+
+        /// The title's version
+        pub title_version: u16,
+    }
+}
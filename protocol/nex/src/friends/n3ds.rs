@@ -0,0 +1,190 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The variant of the friends protocol spoken by the 3DS
+//!
+//! Friends are identified by both their [`Pid`](ralsei_model::network::Pid) and their local
+//! friend code, which is the friend code shown to the user without its checksum
+
+use super::GameKey;
+use ralsei_protocol_rmc::{
+    protocol::{protocol, structure},
+    types::{Buffer, DateTime},
+};
+
+protocol! {
+    /// The friends protocol spoken by the 3DS
+    pub protocol Friends3ds = 101 {
+        client Friends3dsClient;
+        server Friends3dsServer => Friends3dsDispatcher;
+
+        /// Sets whether or not the user's presence, the game they are playing and the games they
+        /// have played are shown to their friends
+        5 => fn update_preference(
+            public_mode: bool,
+            show_game: bool,
+            show_played_game: bool,
+        ) -> ();
+
+        /// Returns the user's relationships with the users with the provided
+        /// [`Pid`](ralsei_model::network::Pid)s
+        10 => fn get_friend_relationships(pids: Vec<u32>) -> Vec<FriendRelationship>;
+
+        /// Adds the user with the provided local friend code and
+        /// [`Pid`](ralsei_model::network::Pid) as a friend
+        11 => fn add_friend_by_principal_id(friend_code: u64, pid: u32) -> FriendRelationship;
+
+        /// Adds the users with the provided [`Pid`](ralsei_model::network::Pid)s as friends,
+        /// passing along the user's own local friend code
+        12 => fn add_friend_by_lst_principal_id(
+            friend_code: u64,
+            pids: Vec<u32>,
+        ) -> Vec<FriendRelationship>;
+
+        /// Removes the friend with the provided local friend code
+        13 => fn remove_friend_by_local_friend_code(friend_code: u64) -> ();
+
+        /// Removes the friend with the provided [`Pid`](ralsei_model::network::Pid)
+        14 => fn remove_friend_by_principal_id(pid: u32) -> ();
+
+        /// Returns the user's relationships with all of their friends
+        15 => fn get_all_friends() -> Vec<FriendRelationship>;
+
+        /// Replaces the user's block list with the provided
+        /// [`Pid`](ralsei_model::network::Pid)s
+        16 => fn update_black_list(pids: Vec<u32>) -> ();
+
+        /// Synchronizes the user's friend list with the one stored on the console, returning the
+        /// resulting relationships
+        17 => fn sync_friend(
+            friend_code: u64,
+            pids: Vec<u32>,
+            friend_codes: Vec<u64>,
+        ) -> Vec<FriendRelationship>;
+
+        /// Updates the user's presence
+        18 => fn update_presence(presence: NintendoPresence, show_game: bool) -> ();
+
+        /// Updates the user's favorite game
+        19 => fn update_favorite_game_key(game_key: GameKey) -> ();
+
+        /// Updates the user's status message
+        20 => fn update_comment(comment: String) -> ();
+
+        /// Returns the presences of the users with the provided
+        /// [`Pid`](ralsei_model::network::Pid)s
+        22 => fn get_friend_presence(pids: Vec<u32>) -> Vec<FriendPresence>;
+
+        /// Returns the persistent information of the users with the provided
+        /// [`Pid`](ralsei_model::network::Pid)s
+        25 => fn get_friend_persistent_info(pids: Vec<u32>) -> Vec<FriendPersistentInfo>;
+
+        /// Invites the users with the provided [`Pid`](ralsei_model::network::Pid)s to join the
+        /// user's game
+        26 => fn send_invitation(pids: Vec<u32>) -> ();
+    }
+}
+
+structure! {
+    /// The relationship between the user and another user
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct FriendRelationship {
+        /// The [`Pid`](ralsei_model::network::Pid) of the other user
+        pub pid: u32,
+
+        /// The local friend code of the other user
+        pub friend_code: u64,
+
+        /// The kind of relationship, which is 0 if it is incomplete, 1 if it is complete and 2
+        /// if it is unknown
+        pub relationship: u8,
+    }
+
+    /// What a user is currently doing
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct NintendoPresence {
+        /// A bitmask of the fields that have changed since the presence was last updated
+        pub changed_flags: u32,
+
+        /// The game being played
+        pub game_key: GameKey,
+
+        /// The game-specific status message
+        pub message: String,
+
+        /// Whether or not others are able to join the user's game
+        pub join_availability_flag: u32,
+
+        /// The kind of matchmaking in use
+        pub matchmake_type: u8,
+
+        /// The id of the game others are able to join
+        pub join_game_id: u32,
+
+        /// The mode of the game others are able to join
+        pub join_game_mode: u32,
+
+        /// The [`Pid`](ralsei_model::network::Pid) of the owner of the game others are able to
+        /// join
+        pub owner_pid: u32,
+
+        /// The id of the group others are able to join
+        pub join_group_id: u32,
+
+        /// Game-specific data passed to those joining
+        pub application_arg: Buffer,
+    }
+
+    /// The presence of one of the user's friends
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct FriendPresence {
+        /// The [`Pid`](ralsei_model::network::Pid) of the friend
+        pub pid: u32,
+
+        /// The friend's presence
+        pub presence: NintendoPresence,
+    }
+
+    /// The information about one of the user's friends that persists while they are offline
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct FriendPersistentInfo {
+        /// The [`Pid`](ralsei_model::network::Pid) of the friend
+        pub pid: u32,
+
+        /// The friend's region
+        pub region: u8,
+
+        /// The friend's country
+        pub country: u8,
+
+        /// The friend's area within their country
+        pub area: u8,
+
+        /// The friend's language
+        pub language: u8,
+
+        /// The friend's platform
+        pub platform: u8,
+
+        /// The friend's favorite game
+        pub game_key: GameKey,
+
+        /// The friend's status message
+        pub message: String,
+
+        /// When the friend's status message was last updated
+        pub message_updated_at: DateTime,
+
+        /// When the friend became the user's friend
+        pub friended_at: DateTime,
+
+        /// When the friend was last online
+        pub last_online: DateTime,
+    }
+}
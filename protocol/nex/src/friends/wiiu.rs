@@ -0,0 +1,371 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The variant of the friends protocol spoken by the Wii U
+//!
+//! Unlike on the 3DS, friendships are formed by sending friend requests, and friends are
+//! identified by their [`Pid`](ralsei_model::network::Pid) and Nintendo Network ID. Several of
+//! the structures contain fields whose purpose is unknown, which are named as such
+
+use super::GameKey;
+use ralsei_protocol_rmc::{
+    protocol::{protocol, structure},
+    types::{Buffer, DateTime},
+};
+
+protocol! {
+    /// The friends protocol spoken by the Wii U
+    pub protocol FriendsWiiU = 102 {
+        client FriendsWiiUClient;
+        server FriendsWiiUServer => FriendsWiiUDispatcher;
+
+        /// Updates the user's information and presence, returning everything the user needs to
+        /// know about their friends
+        1 => fn update_and_get_all_information(
+            nna_info: NnaInfo,
+            presence: NintendoPresenceV2,
+            birthday: DateTime,
+        ) -> AllInformation;
+
+        /// Sends a friend request to the user with the provided
+        /// [`Pid`](ralsei_model::network::Pid)
+        2 => fn add_friend(pid: u32) -> (FriendRequest, FriendInfo);
+
+        /// Sends a friend request to the user with the provided Nintendo Network ID
+        3 => fn add_friend_by_name(name: String) -> (FriendRequest, FriendInfo);
+
+        /// Removes the friend with the provided [`Pid`](ralsei_model::network::Pid)
+        4 => fn remove_friend(pid: u32) -> ();
+
+        /// Sends a friend request with the provided message to the user with the provided
+        /// [`Pid`](ralsei_model::network::Pid)
+        5 => fn add_friend_request(
+            pid: u32,
+            unknown2: u8,
+            message: String,
+            unknown4: u8,
+            unknown5: String,
+            game_key: GameKey,
+            unknown6: DateTime,
+        ) -> (FriendRequest, FriendInfo);
+
+        /// Cancels the sent friend request with the provided id
+        6 => fn cancel_friend_request(id: u64) -> ();
+
+        /// Accepts the received friend request with the provided id
+        7 => fn accept_friend_request(id: u64) -> FriendInfo;
+
+        /// Deletes the received friend request with the provided id
+        8 => fn delete_friend_request(id: u64) -> ();
+
+        /// Denies the received friend request with the provided id, blocking its sender
+        9 => fn deny_friend_request(id: u64) -> BlacklistedPrincipal;
+
+        /// Marks the received friend requests with the provided ids as having been seen
+        10 => fn mark_friend_requests_as_received(ids: Vec<u64>) -> ();
+
+        /// Blocks the provided user
+        11 => fn add_black_list(principal: BlacklistedPrincipal) -> BlacklistedPrincipal;
+
+        /// Unblocks the user with the provided [`Pid`](ralsei_model::network::Pid)
+        12 => fn remove_black_list(pid: u32) -> ();
+
+        /// Updates the user's presence
+        13 => fn update_presence(presence: NintendoPresenceV2) -> ();
+
+        /// Updates the user's Mii, returning when it was updated
+        14 => fn update_mii(mii: MiiV2) -> DateTime;
+
+        /// Updates the user's status message, returning when it was updated
+        15 => fn update_comment(comment: Comment) -> DateTime;
+
+        /// Updates the user's preferences
+        16 => fn update_preference(preference: PrincipalPreference) -> ();
+
+        /// Returns the basic information of the users with the provided
+        /// [`Pid`](ralsei_model::network::Pid)s
+        17 => fn get_basic_info(pids: Vec<u32>) -> Vec<PrincipalBasicInfo>;
+
+        /// Deletes the provided notifications
+        18 => fn delete_persistent_notification(
+            notifications: Vec<PersistentNotification>,
+        ) -> ();
+
+        /// Returns the status of the user's friends settings
+        19 => fn check_setting_status() -> u8;
+
+        /// Returns whether or not the users with the provided
+        /// [`Pid`](ralsei_model::network::Pid)s block friend requests
+        20 => fn get_request_block_settings(pids: Vec<u32>) -> Vec<PrincipalRequestBlockSetting>;
+    }
+}
+
+structure! {
+    /// A Mii, along with when it was last updated
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct MiiV2 {
+        /// The Mii's name
+        pub name: String,
+
+        /// Unknown
+        pub unknown1: u8,
+
+        /// Unknown
+        pub unknown2: u8,
+
+        /// The Mii's data
+        pub data: Buffer,
+
+        /// When the Mii was last updated
+        pub updated_at: DateTime,
+    }
+
+    /// The basic information of a user
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct PrincipalBasicInfo {
+        /// The user's [`Pid`](ralsei_model::network::Pid)
+        pub pid: u32,
+
+        /// The user's Nintendo Network ID
+        pub nnid: String,
+
+        /// The user's Mii
+        pub mii: MiiV2,
+
+        /// Unknown
+        pub unknown: u8,
+    }
+
+    /// The information of a user, as known to the account server
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct NnaInfo {
+        /// The user's basic information
+        pub principal_info: PrincipalBasicInfo,
+
+        /// Unknown
+        pub unknown1: u8,
+
+        /// Unknown
+        pub unknown2: u8,
+    }
+
+    /// What a user is currently doing
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct NintendoPresenceV2 {
+        /// A bitmask of the fields that have changed since the presence was last updated
+        pub changed_flags: u32,
+
+        /// Whether or not the user is online
+        pub online: bool,
+
+        /// The game being played
+        pub game_key: GameKey,
+
+        /// Unknown
+        pub unknown1: u8,
+
+        /// The game-specific status message
+        pub message: String,
+
+        /// Unknown
+        pub unknown2: u32,
+
+        /// Unknown
+        pub unknown3: u8,
+
+        /// The id of the game server of the game being played
+        pub game_server_id: u32,
+
+        /// Unknown
+        pub unknown4: u32,
+
+        /// The [`Pid`](ralsei_model::network::Pid) of the user
+        pub pid: u32,
+
+        /// The id of the gathering the user is participating in
+        pub gathering_id: u32,
+
+        /// Game-specific data
+        pub application_data: Buffer,
+
+        /// Unknown
+        pub unknown5: u8,
+
+        /// Unknown
+        pub unknown6: u8,
+
+        /// Unknown
+        pub unknown7: u8,
+    }
+
+    /// A user's status message
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct Comment {
+        /// Unknown
+        pub unknown: u8,
+
+        /// The message itself
+        pub contents: String,
+
+        /// When the message was last changed
+        pub changed_at: DateTime,
+    }
+
+    /// One of the user's friends
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct FriendInfo {
+        /// The friend's information
+        pub nna_info: NnaInfo,
+
+        /// The friend's presence
+        pub presence: NintendoPresenceV2,
+
+        /// The friend's status message
+        pub comment: Comment,
+
+        /// When the friend became the user's friend
+        pub friended_at: DateTime,
+
+        /// When the friend was last online
+        pub last_online: DateTime,
+
+        /// Unknown
+        pub unknown: u64,
+    }
+
+    /// The contents of a friend request
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct FriendRequestMessage {
+        /// The friend request's id
+        pub friend_request_id: u64,
+
+        /// Whether or not the friend request has been seen by its recipient
+        pub received: bool,
+
+        /// Unknown
+        pub unknown2: u8,
+
+        /// The message sent along with the friend request
+        pub message: String,
+
+        /// Unknown
+        pub unknown4: u8,
+
+        /// Unknown
+        pub unknown5: String,
+
+        /// The game the friend request was sent from
+        pub game_key: GameKey,
+
+        /// Unknown
+        pub unknown6: DateTime,
+
+        /// When the friend request expires
+        pub expires_at: DateTime,
+    }
+
+    /// A friend request sent to or by the user
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct FriendRequest {
+        /// The basic information of the other user
+        pub principal_info: PrincipalBasicInfo,
+
+        /// The friend request's contents
+        pub message: FriendRequestMessage,
+
+        /// When the friend request was sent
+        pub sent_at: DateTime,
+    }
+
+    /// A user blocked by the user
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct BlacklistedPrincipal {
+        /// The basic information of the blocked user
+        pub principal_info: PrincipalBasicInfo,
+
+        /// The game the user was blocked from
+        pub game_key: GameKey,
+
+        /// When the user was blocked
+        pub blocked_at: DateTime,
+    }
+
+    /// The user's preferences
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct PrincipalPreference {
+        /// Whether or not the user's online status is shown to their friends
+        pub show_online: bool,
+
+        /// Whether or not the game the user is playing is shown to their friends
+        pub show_current_game: bool,
+
+        /// Whether or not friend requests sent to the user are blocked
+        pub block_friend_requests: bool,
+    }
+
+    /// A notification that persists until it is deleted
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct PersistentNotification {
+        /// Unknown
+        pub unknown1: u64,
+
+        /// Unknown
+        pub unknown2: u32,
+
+        /// Unknown
+        pub unknown3: u32,
+
+        /// Unknown
+        pub unknown4: u32,
+
+        /// Unknown
+        pub unknown5: String,
+    }
+
+    /// Whether or not a user blocks friend requests
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct PrincipalRequestBlockSetting {
+        /// The user's [`Pid`](ralsei_model::network::Pid)
+        pub pid: u32,
+
+        /// Whether or not friend requests sent to the user are blocked
+        pub blocked: bool,
+    }
+
+    /// The results of [`update_and_get_all_information`](FriendsWiiUClient::update_and_get_all_information)
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct AllInformation {
+        /// The user's preferences
+        pub preference: PrincipalPreference,
+
+        /// The user's status message
+        pub comment: Comment,
+
+        /// The user's friends
+        pub friends: Vec<FriendInfo>,
+
+        /// The friend requests sent by the user
+        pub sent_requests: Vec<FriendRequest>,
+
+        /// The friend requests sent to the user
+        pub received_requests: Vec<FriendRequest>,
+
+        /// The users blocked by the user
+        pub black_list: Vec<BlacklistedPrincipal>,
+
+        /// Unknown
+        pub unknown1: bool,
+
+        /// The user's notifications
+        pub notifications: Vec<PersistentNotification>,
+
+        /// Unknown
+        pub unknown2: bool,
+    }
+}
@@ -0,0 +1,252 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The Kerberos-style encryption used to hand out tickets to NEX servers
+//!
+//! Data is encrypted using RC4 and followed by an HMAC-MD5 of the ciphertext, both keyed with the
+//! same key. Tickets handed to a user by the authentication server are encrypted using a key
+//! derived from their password, and contain a session key along with an opaque ticket that is
+//! presented to the server they were requested for
+
+use hmac::{Hmac, Mac, NewMac};
+use md5::{Digest, Md5};
+use thiserror::Error;
+
+use ralsei_protocol_rmc::{
+    codec::{Decode, DecodeError, Encode, EncodeError},
+    types::Buffer,
+};
+use ralsei_util::rc4::Rc4;
+
+/// The length of the HMAC placed after encrypted data
+pub const MAC_LENGTH: usize = 16;
+
+/// The length of the session keys handed out by most NEX servers
+pub const DEFAULT_SESSION_KEY_LENGTH: usize = 32;
+
+/// Derives the key used to decrypt the tickets handed to the user with the provided [`Pid`] from
+/// their password
+///
+/// This is the password, hashed using MD5 `65000 + pid % 1024` times
+///
+/// [`Pid`]: ralsei_model::network::Pid
+pub fn derive_key(pid: u32, password: &str) -> [u8; 16] {
+    let mut key: [u8; 16] = Md5::digest(password.as_bytes()).into();
+    for _ in 1..65000 + pid % 1024 {
+        key = Md5::digest(&key).into();
+    }
+    key
+}
+
+/// Encrypts the provided data using the provided key, appending its HMAC
+pub fn encrypt(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut encrypted = data.to_vec();
+    Rc4::new(key).apply(&mut encrypted);
+    let mac = mac(key, &encrypted);
+    encrypted.extend_from_slice(&mac);
+    encrypted
+}
+
+/// Verifies the HMAC of the provided data using the provided key, returning the decrypted data
+pub fn decrypt(key: &[u8], data: &[u8]) -> Result<Vec<u8>, KerberosError> {
+    if data.len() < MAC_LENGTH {
+        return Err(KerberosError::OutOfBounds);
+    }
+    let (encrypted, expected) = data.split_at(data.len() - MAC_LENGTH);
+
+    // the hmac crate compares the two in constant time
+    let mut mac = Hmac::<Md5>::new_from_slice(key).expect("hmac accepts keys of any length");
+    mac.update(encrypted);
+    mac.verify(expected)
+        .map_err(|_| KerberosError::InvalidMac)?;
+
+    let mut decrypted = encrypted.to_vec();
+    Rc4::new(key).apply(&mut decrypted);
+    Ok(decrypted)
+}
+
+/// Calculates the HMAC-MD5 of the provided data
+fn mac(key: &[u8], data: &[u8]) -> [u8; MAC_LENGTH] {
+    let mut mac = Hmac::<Md5>::new_from_slice(key).expect("hmac accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// A ticket handed to a user by the authentication server, granting them access to another
+/// server
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct Ticket {
+    /// The key shared with the server the ticket grants access to
+    pub session_key: Vec<u8>,
+
+    /// The [`Pid`](ralsei_model::network::Pid) of the server the ticket grants access to
+    pub target: u32,
+
+    /// The ticket presented to the server, which is encrypted using a key only known to it
+    pub internal: Buffer,
+}
+
+impl Ticket {
+    /// Decrypts a [`Ticket`] from the provided data using the provided key, which is usually
+    /// obtained using [`derive_key`]
+    pub fn decrypt(
+        key: &[u8],
+        data: &[u8],
+        session_key_length: usize,
+    ) -> Result<Self, KerberosError> {
+        let decrypted = decrypt(key, data)?;
+        if decrypted.len() < session_key_length {
+            return Err(KerberosError::OutOfBounds);
+        }
+        let (session_key, rest) = decrypted.split_at(session_key_length);
+        let (target, internal) = <(u32, Buffer)>::from_bytes(rest)?;
+        Ok(Self {
+            session_key: session_key.to_vec(),
+            target,
+            internal,
+        })
+    }
+
+    /// Encrypts the [`Ticket`] using the provided key
+    pub fn encrypt(&self, key: &[u8]) -> Result<Vec<u8>, KerberosError> {
+        let mut data = self.session_key.clone();
+        self.target.encode(&mut data)?;
+        self.internal.encode(&mut data)?;
+        Ok(encrypt(key, &data))
+    }
+
+    /// Creates the data passed in the payload of the connect packet sent to the server the
+    /// [`Ticket`] grants access to
+    ///
+    /// This is the internal ticket followed by the encryption of the user's
+    /// [`Pid`](ralsei_model::network::Pid), the connection id from the server's
+    /// [`StationUrl`](ralsei_protocol_rmc::types::StationUrl) and the provided value, which the
+    /// server is expected to respond with after incrementing it
+    pub fn connection_data(
+        &self,
+        pid: u32,
+        connection_id: u32,
+        check: u32,
+    ) -> Result<Vec<u8>, KerberosError> {
+        let mut data = Vec::new();
+        self.internal.encode(&mut data)?;
+        Buffer(encrypt(
+            &self.session_key,
+            &(pid, connection_id, check).to_bytes()?,
+        ))
+        .encode(&mut data)?;
+        Ok(data)
+    }
+
+    /// Verifies the data that the server the [`Ticket`] grants access to passed in the payload of
+    /// its acknowledgement of the connect packet, which must be the encryption of the provided
+    /// value after incrementing it
+    pub fn verify_connection_response(&self, data: &[u8], check: u32) -> Result<(), KerberosError> {
+        let encrypted = Buffer::from_bytes(data)?;
+        let found = u32::from_bytes(&decrypt(&self.session_key, &encrypted.0)?)?;
+        let expected = check.wrapping_add(1);
+        if found != expected {
+            return Err(KerberosError::CheckMismatch(found, expected));
+        }
+        Ok(())
+    }
+}
+
+/// Creates the data that a server passes in the payload of its acknowledgement of a connect
+/// packet made using a [`Ticket`] with the provided session key, which is the encryption of the
+/// provided value from the connect packet after incrementing it
+pub fn connection_response(session_key: &[u8], check: u32) -> Result<Vec<u8>, KerberosError> {
+    Ok(Buffer(encrypt(session_key, &check.wrapping_add(1).to_bytes()?)).to_bytes()?)
+}
+
+/// An enumeration over the errors that may occur while encrypting or decrypting data
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum KerberosError {
+    /// An error returned when the data is too short to contain what it is supposed to
+    #[error("The provided data is not long enough")]
+    OutOfBounds,
+
+    /// An error returned when the HMAC of the data does not match, meaning that either the key
+    /// is incorrect or the data has been tampered with
+    #[error("The HMAC of the provided data does not match")]
+    InvalidMac,
+
+    /// An error returned when a server responds to a connect packet with a value other than the
+    /// one expected of it
+    #[error("The server responded with `{0}` instead of `{1}`")]
+    CheckMismatch(u32, u32),
+
+    /// An error was encountered while decoding the decrypted data
+    #[error("An error was encountered while decoding the decrypted data")]
+    DecodeError(#[from] DecodeError),
+
+    /// An error was encountered while encoding the data to be encrypted
+    #[error("An error was encountered while encoding the data to be encrypted")]
+    EncodeError(#[from] EncodeError),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn key_derivation() {
+        assert_eq!(
+            derive_key(1337, "password"),
+            [
+                0x78, 0x84, 0x99, 0x3E, 0xA4, 0xFF, 0x2E, 0x70, 0x84, 0x4B, 0xBA, 0x47, 0xB6, 0x9E,
+                0x25, 0x99
+            ]
+        );
+    }
+
+    #[test]
+    fn ticket_round_trip() {
+        let key = derive_key(1337, "password");
+        let ticket = Ticket {
+            session_key: vec![0x2A; DEFAULT_SESSION_KEY_LENGTH],
+            target: 2,
+            internal: Buffer(vec![1, 2, 3]),
+        };
+        let encrypted = ticket.encrypt(&key).unwrap();
+        assert_eq!(
+            Ticket::decrypt(&key, &encrypted, DEFAULT_SESSION_KEY_LENGTH).unwrap(),
+            ticket
+        );
+        assert!(matches!(
+            Ticket::decrypt(&[0; 16], &encrypted, DEFAULT_SESSION_KEY_LENGTH),
+            Err(KerberosError::InvalidMac)
+        ));
+    }
+
+    #[test]
+    fn connection_response() {
+        let ticket = Ticket {
+            session_key: vec![0x2A; DEFAULT_SESSION_KEY_LENGTH],
+            target: 2,
+            internal: Buffer(vec![1, 2, 3]),
+        };
+        let response = super::connection_response(&ticket.session_key, u32::MAX).unwrap();
+        ticket
+            .verify_connection_response(&response, u32::MAX)
+            .unwrap();
+        assert!(matches!(
+            ticket.verify_connection_response(&response, 1),
+            Err(KerberosError::CheckMismatch(0, 2))
+        ));
+        assert!(matches!(
+            ticket.verify_connection_response(
+                &super::connection_response(&[0; DEFAULT_SESSION_KEY_LENGTH], u32::MAX).unwrap(),
+                u32::MAX
+            ),
+            Err(KerberosError::InvalidMac)
+        ));
+    }
+}
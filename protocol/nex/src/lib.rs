@@ -0,0 +1,58 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+#![allow(clippy::cognitive_complexity)]
+#![warn(clippy::cargo_common_metadata)]
+#![warn(clippy::dbg_macro)]
+#![warn(clippy::explicit_deref_methods)]
+#![warn(clippy::filetype_is_file)]
+#![warn(clippy::imprecise_flops)]
+#![warn(clippy::large_stack_arrays)]
+#![warn(clippy::todo)]
+#![warn(clippy::unimplemented)]
+#![deny(clippy::await_holding_lock)]
+#![deny(clippy::cast_lossless)]
+#![deny(clippy::clone_on_ref_ptr)]
+#![deny(clippy::doc_markdown)]
+#![deny(clippy::empty_enum)]
+#![deny(clippy::enum_glob_use)]
+#![deny(clippy::exit)]
+#![deny(clippy::explicit_into_iter_loop)]
+#![deny(clippy::explicit_iter_loop)]
+#![deny(clippy::fallible_impl_from)]
+#![deny(clippy::inefficient_to_string)]
+#![deny(clippy::large_digit_groups)]
+#![deny(clippy::wildcard_dependencies)]
+#![deny(clippy::wildcard_imports)]
+#![deny(clippy::unused_self)]
+#![deny(clippy::single_match_else)]
+#![deny(clippy::option_option)]
+#![deny(clippy::mut_mut)]
+
+//! An implementation of the NEX protocols spoken by Nintendo's game servers
+//!
+//! NEX servers are split into an authentication server and a secure server. The
+//! [`authentication`] module declares the `TicketGranting` protocol spoken by the former, the
+//! [`kerberos`] module the encryption of the tickets it hands out, and the [`login`] module ties
+//! them together with the account server, going from an account
+//! [`Client`](ralsei_service_account::client::Client) to an authenticated connection to the
+//! latter. The [`friends`], [`matchmaking`] and [`datastore`] modules declare the most commonly
+//! used of the protocols spoken by secure servers. Everything is spoken using
+//! [RMC](ralsei_protocol_rmc) over either version of PRUDP, depending on the console in use.
+//!
+//! For more information, see [the NintendoClients wiki]
+//!
+//! [the NintendoClients wiki]: https://github.com/kinnay/NintendoClients/wiki/NEX-Overview
+
+pub mod authentication;
+pub mod datastore;
+pub mod friends;
+pub mod kerberos;
+pub mod login;
+pub mod matchmaking;
@@ -0,0 +1,294 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Logging in to a NEX game server
+//!
+//! [`login`] takes care of the whole process, from requesting a NEX token from the account
+//! server to connecting to the game server's secure server with the ticket handed out by its
+//! authentication server. The version of PRUDP spoken is chosen based upon the [`Kind`] of
+//! console in use
+
+use thiserror::Error;
+
+use crate::{
+    authentication::{AuthenticationInfo, TicketGrantingClient},
+    kerberos::{self, KerberosError, Ticket},
+};
+use ralsei_model::console::common::{Console, Kind};
+use ralsei_protocol_prudp_v0 as prudp_v0;
+use ralsei_protocol_prudp_v1 as prudp_v1;
+use ralsei_protocol_rmc::{
+    client::{CallError, Client},
+    codec::{Context, EncodeError},
+    types::{AnyDataHolder, QResult, StationUrl},
+};
+use ralsei_service_account::{
+    client::{Client as AccountClient, ClientError},
+    xml::token::NexToken,
+};
+use ralsei_util::transport::{Transport, TransportError};
+
+/// The virtual port NEX servers accept connections on
+const SERVER_PORT: u8 = 1;
+
+/// The game-specific settings used to log in to a NEX game server
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Settings {
+    /// The id of the game server, used to request a NEX token
+    pub game_server_id: u32,
+
+    /// The game-specific access key, used by PRUDP
+    pub access_key: Vec<u8>,
+
+    /// The version of NEX the game server is running, which is reported to the authentication
+    /// server and decides the layout of the results of its methods
+    pub nex_version: u32,
+
+    /// The version of the NEX game server library in use, reported to the authentication server
+    pub ngs_version: u32,
+
+    /// The length of the session key contained within tickets
+    pub session_key_length: usize,
+}
+
+impl Settings {
+    /// Creates a new [`Settings`] using the provided game server id, access key and NEX version,
+    /// and the default values for everything else
+    pub fn new(game_server_id: u32, access_key: &[u8], nex_version: u32) -> Self {
+        Self {
+            game_server_id,
+            access_key: access_key.to_vec(),
+            nex_version,
+            ngs_version: 3,
+            session_key_length: kerberos::DEFAULT_SESSION_KEY_LENGTH,
+        }
+    }
+}
+
+/// An authenticated connection to a game server's secure server
+pub struct Session {
+    /// The [`Pid`](ralsei_model::network::Pid) of the user that was logged in as
+    pub pid: u32,
+
+    /// The [`StationUrl`] of the secure server
+    pub station_url: StationUrl,
+
+    /// The RMC [`Client`] connected to the secure server
+    pub client: Client,
+}
+
+/// Logs in to the game server described by the provided [`Settings`] on behalf of the user the
+/// provided account [`Client`](AccountClient) is logged in as
+///
+/// This requests a NEX token from the account server and passes it along to
+/// [`login_with_token`]
+pub async fn login<'a, C>(
+    account: &AccountClient<'a, C>,
+    settings: &Settings,
+) -> Result<Session, LoginError>
+where
+    C: Console<'a> + Send + Clone,
+{
+    let kind = account.console.read().kind();
+    let token = account.nex_token(settings.game_server_id).await?;
+    login_with_token(kind, &token, settings).await
+}
+
+/// Logs in to the game server described by the provided [`Settings`] using the provided
+/// [`NexToken`], connecting as the provided [`Kind`] of console would
+///
+/// This logs in to the authentication server named by the token, requests a [`Ticket`] for the
+/// secure server it directs the user to and connects to it using that ticket, checking that the
+/// secure server's response proves that it holds the ticket's session key
+pub async fn login_with_token(
+    kind: Kind,
+    token: &NexToken<'_>,
+    settings: &Settings,
+) -> Result<Session, LoginError> {
+    let host = token
+        .host
+        .as_deref()
+        .ok_or(LoginError::MissingTokenField("host"))?;
+    let port = token.port.ok_or(LoginError::MissingTokenField("port"))?;
+    let pid = token
+        .pid
+        .as_ref()
+        .ok_or(LoginError::MissingTokenField("pid"))?
+        .0;
+    let password = token
+        .nex_password
+        .as_deref()
+        .ok_or(LoginError::MissingTokenField("nex_password"))?;
+    let token = token
+        .token
+        .as_deref()
+        .ok_or(LoginError::MissingTokenField("token"))?;
+
+    let (authentication, _) = connect(kind, host, port, &[], None, settings).await?;
+    let mut authentication =
+        Client::with_context(authentication, Context::new(settings.nex_version));
+    let login = authentication
+        .login_ex(
+            pid.to_string(),
+            AnyDataHolder::new(
+                AuthenticationInfo::TYPE_NAME,
+                &AuthenticationInfo {
+                    token: token.to_string(),
+                    ngs_version: settings.ngs_version,
+                    token_type: 1,
+                    server_version: settings.nex_version,
+                },
+            )?,
+        )
+        .await?;
+    if login.result.is_error() {
+        return Err(LoginError::Failed(login.result));
+    }
+
+    let station_url = login.connection_data.regular_protocols;
+    let invalid = || LoginError::InvalidStationUrl(station_url.clone());
+    let target = station_url.pid().ok_or_else(invalid)?.0;
+    let (result, ticket) = authentication.request_ticket(pid, target).await?;
+    if result.is_error() {
+        return Err(LoginError::Failed(result));
+    }
+
+    // the connection to the authentication server is of no further use, and failing to close it
+    // cleanly has no bearing on the connection to the secure server
+    let _ = authentication.into_inner().disconnect().await;
+
+    let ticket = Ticket::decrypt(
+        &kerberos::derive_key(pid, password),
+        &ticket.0,
+        settings.session_key_length,
+    )?;
+    let check = rand::random();
+    let connection_data =
+        ticket.connection_data(pid, station_url.connection_id().unwrap_or(0), check)?;
+    let (transport, response_data) = connect(
+        kind,
+        station_url.address().ok_or_else(invalid)?,
+        station_url.port().ok_or_else(invalid)?,
+        &connection_data,
+        Some(&ticket.session_key),
+        settings,
+    )
+    .await?;
+    ticket.verify_connection_response(&response_data, check)?;
+
+    Ok(Session {
+        pid,
+        station_url,
+        client: Client::with_context(transport, Context::new(settings.nex_version)),
+    })
+}
+
+/// Connects to the NEX server at the provided address using the version of PRUDP spoken by the
+/// provided [`Kind`] of console, encrypting the connection with the provided session key if one
+/// is provided
+///
+/// The connection is returned along with the data the server responded to the connect packet with
+async fn connect(
+    kind: Kind,
+    host: &str,
+    port: u16,
+    connection_data: &[u8],
+    session_key: Option<&[u8]>,
+    settings: &Settings,
+) -> Result<(Box<dyn Transport>, Vec<u8>), LoginError> {
+    Ok(match kind {
+        Kind::N3ds => {
+            let mut prudp = prudp_v0::connection::Settings::new(&settings.access_key);
+            if let Some(session_key) = session_key {
+                prudp.encryption_key = session_key.to_vec();
+            }
+            let connection = prudp_v0::connection::Connection::connect(
+                (host, port),
+                prudp_v0::packet::VirtualPort::new(
+                    prudp_v0::packet::StreamKind::RvSecure,
+                    SERVER_PORT,
+                ),
+                connection_data,
+                prudp,
+            )
+            .await
+            .map_err(TransportError::from)?;
+            let response_data = connection.response_data().to_vec();
+            (Box::new(connection), response_data)
+        }
+        Kind::WiiU => {
+            let mut prudp = prudp_v1::connection::Settings::new(&settings.access_key);
+            if let Some(session_key) = session_key {
+                prudp.encryption_key = session_key.to_vec();
+                prudp.session_key = session_key.to_vec();
+            }
+            let connection = prudp_v1::connection::Connection::connect(
+                (host, port),
+                prudp_v1::packet::VirtualPort::new(
+                    prudp_v1::packet::StreamKind::RvSecure,
+                    SERVER_PORT,
+                ),
+                connection_data,
+                prudp,
+            )
+            .await
+            .map_err(TransportError::from)?;
+            let response_data = connection.response_data().to_vec();
+            (Box::new(connection), response_data)
+        }
+        kind => return Err(LoginError::UnsupportedConsoleKind(kind)),
+    })
+}
+
+/// An enumeration over the errors that may occur while logging in to a NEX game server
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum LoginError {
+    /// An error encountered when the provided [`Kind`] of console is not supported
+    #[error("`{0}` is an unsupported console Kind")]
+    UnsupportedConsoleKind(Kind),
+
+    /// An error was encountered while requesting a NEX token from the account server
+    #[error("An error was encountered while requesting a NEX token")]
+    AccountError(#[from] ClientError),
+
+    /// The NEX token lacks a field needed to log in
+    #[error("The NEX token lacks an expected field, `{0}`")]
+    MissingTokenField(&'static str),
+
+    /// An error was encountered by the underlying transport
+    #[error("An error was encountered by the underlying transport")]
+    TransportError(#[from] TransportError),
+
+    /// An error was encountered while encoding the data passed to the authentication server
+    #[error(
+        "An error was encountered while encoding the data passed to the authentication server"
+    )]
+    EncodeError(#[from] EncodeError),
+
+    /// An error was encountered while calling a method of the authentication server
+    #[error("An error was encountered while calling a method of the authentication server")]
+    CallError(#[from] CallError),
+
+    /// An error returned when the authentication server refuses to log in or hand out a ticket
+    #[error("The authentication server failed with the result `{0}`")]
+    Failed(QResult),
+
+    /// An error returned when the [`StationUrl`] of the secure server lacks a parameter needed
+    /// to connect to it
+    #[error("`{0}` is not a valid secure server StationUrl")]
+    InvalidStationUrl(StationUrl),
+
+    /// An error was encountered while decrypting the ticket for the secure server or verifying
+    /// the secure server's response to it
+    #[error(
+        "An error was encountered while decrypting the ticket for the secure server or verifying its response"
+    )]
+    KerberosError(#[from] KerberosError),
+}
@@ -0,0 +1,278 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The matchmaking protocols, spoken by the secure servers of games with online play
+//!
+//! Players are grouped into gatherings, the most common kind of which is the
+//! [`MatchmakeSession`]. The `MatchMaking` protocol manages gatherings of any kind, which are
+//! passed around within [`AnyDataHolder`]s, while the `MatchmakeExtension` protocol adds methods
+//! specific to matchmake sessions. For more information, see [the NintendoClients wiki]
+//!
+//! The layout of [`MatchmakeSession`] changed between versions of NEX, and it is modeled here as
+//! it is in early NEX 3 releases, without the fields appended to it by later ones
+//!
+//! [the NintendoClients wiki]: https://github.com/kinnay/NintendoClients/wiki/Match-Making-Protocol
+
+use ralsei_protocol_rmc::{
+    protocol::{protocol, structure},
+    types::{AnyDataHolder, Buffer, StationUrl},
+};
+
+protocol! {
+    /// The protocol used to manage gatherings of any kind
+    pub protocol MatchMaking = 21 {
+        client MatchMakingClient;
+        server MatchMakingServer => MatchMakingDispatcher;
+
+        /// Registers the provided gathering, returning its id
+        1 => fn register_gathering(gathering: AnyDataHolder) -> u32;
+
+        /// Unregisters the gathering with the provided id
+        2 => fn unregister_gathering(gathering_id: u32) -> bool;
+
+        /// Updates the provided gathering
+        4 => fn update_gathering(gathering: AnyDataHolder) -> bool;
+
+        /// Participates in the gathering with the provided id, passing along the provided message
+        11 => fn participate(gathering_id: u32, message: String) -> bool;
+
+        /// Stops participating in the gathering with the provided id, passing along the provided
+        /// message
+        12 => fn cancel_participation(gathering_id: u32, message: String) -> bool;
+
+        /// Returns the [`Pid`](ralsei_model::network::Pid)s of the participants of the gathering
+        /// with the provided id
+        13 => fn get_participants(gathering_id: u32) -> Vec<u32>;
+
+        /// Returns the [`StationUrl`]s of the participants of the gathering with the provided id
+        16 => fn get_participants_urls(gathering_id: u32) -> Vec<StationUrl>;
+
+        /// Returns the gatherings with the provided ids
+        20 => fn find_by_id(gathering_ids: Vec<u32>) -> Vec<AnyDataHolder>;
+
+        /// Returns the gathering with the provided id, if it exists
+        21 => fn find_by_single_id(gathering_id: u32) -> (bool, AnyDataHolder);
+
+        /// Returns the gatherings owned by the user with the provided
+        /// [`Pid`](ralsei_model::network::Pid) within the provided range
+        22 => fn find_by_owner(owner_pid: u32, range: ResultRange) -> Vec<AnyDataHolder>;
+
+        /// Returns the url of the session the gathering with the provided id has launched, if it
+        /// has launched one
+        28 => fn get_session_url(gathering_id: u32) -> (bool, String);
+
+        /// Returns the game-specific state of the gathering with the provided id, if it exists
+        29 => fn get_state(gathering_id: u32) -> (bool, u32);
+
+        /// Sets the game-specific state of the gathering with the provided id
+        30 => fn set_state(gathering_id: u32, state: u32) -> bool;
+
+        /// Deletes the gathering with the provided id
+        33 => fn delete_gathering(gathering_id: u32) -> bool;
+
+        /// Returns the [`StationUrl`]s of the host of the gathering with the provided id
+        41 => fn get_session_urls(gathering_id: u32) -> Vec<StationUrl>;
+    }
+}
+
+protocol! {
+    /// The protocol used to create, find and join matchmake sessions
+    pub protocol MatchmakeExtension = 109 {
+        client MatchmakeExtensionClient;
+        server MatchmakeExtensionServer => MatchmakeExtensionDispatcher;
+
+        /// Stops others from joining the gathering with the provided id
+        1 => fn close_participation(gathering_id: u32) -> ();
+
+        /// Allows others to join the gathering with the provided id
+        2 => fn open_participation(gathering_id: u32) -> ();
+
+        /// Joins a gathering matching the provided one, creating it if there is no such
+        /// gathering, and returns it
+        3 => fn auto_matchmake_postpone(
+            gathering: AnyDataHolder,
+            message: String,
+        ) -> AnyDataHolder;
+
+        /// Creates the provided gathering, returning its id and session key
+        6 => fn create_matchmake_session(
+            gathering: AnyDataHolder,
+            message: String,
+        ) -> (u32, Buffer);
+
+        /// Joins the gathering with the provided id, returning its session key
+        7 => fn join_matchmake_session(gathering_id: u32, message: String) -> Buffer;
+
+        /// Sets the value of the attribute at the provided index of the gathering with the
+        /// provided id
+        8 => fn modify_current_game_attribute(
+            gathering_id: u32,
+            attribute: u32,
+            value: u32,
+        ) -> ();
+
+        /// Replaces the game-specific data of the gathering with the provided id
+        11 => fn update_application_buffer(gathering_id: u32, application_data: Buffer) -> ();
+
+        /// Replaces the attributes of the gathering with the provided id
+        12 => fn update_matchmake_session_attribute(
+            gathering_id: u32,
+            attributes: Vec<u32>,
+        ) -> ();
+
+        /// Updates the provided gathering
+        14 => fn update_matchmake_session(gathering: AnyDataHolder) -> ();
+
+        /// Returns the [`Pid`](ralsei_model::network::Pid)s of the users blocked by the user
+        23 => fn get_my_block_list() -> Vec<u32>;
+
+        /// Blocks the users with the provided [`Pid`](ralsei_model::network::Pid)s
+        24 => fn add_to_block_list(pids: Vec<u32>) -> ();
+
+        /// Unblocks the users with the provided [`Pid`](ralsei_model::network::Pid)s
+        25 => fn remove_from_block_list(pids: Vec<u32>) -> ();
+
+        /// Unblocks every user blocked by the user
+        26 => fn clear_my_block_list() -> ();
+
+        /// Joins the gathering with the provided id on behalf of the provided number of
+        /// participants, optionally ignoring the block lists of its participants, and returns its
+        /// session key
+        29 => fn join_matchmake_session_ex(
+            gathering_id: u32,
+            message: String,
+            ignore_block_list: bool,
+            participation_count: u16,
+        ) -> Buffer;
+
+        /// Updates the progress score of the gathering with the provided id
+        33 => fn update_progress_score(gathering_id: u32, progress_score: u8) -> ();
+    }
+}
+
+structure! {
+    /// The range of results returned by methods that find things
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    pub struct ResultRange {
+        /// The offset of the first result
+        pub offset: u32,
+
+        /// The maximum number of results
+        pub size: u32,
+    }
+
+    /// A group of players, which is the base of every kind of gathering
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct Gathering {
+        /// The gathering's id
+        pub id: u32,
+
+        /// The [`Pid`](ralsei_model::network::Pid) of the gathering's owner
+        pub owner_pid: u32,
+
+        /// The [`Pid`](ralsei_model::network::Pid) of the gathering's host
+        pub host_pid: u32,
+
+        /// The minimum number of participants
+        pub min_participants: u16,
+
+        /// The maximum number of participants
+        pub max_participants: u16,
+
+        /// The policy deciding who may participate
+        pub participation_policy: u32,
+
+        /// The argument to the participation policy
+        pub policy_argument: u32,
+
+        /// The gathering's flags
+        pub flags: u32,
+
+        /// The game-specific state of the gathering
+        pub state: u32,
+
+        /// The gathering's description
+        pub description: String,
+    }
+
+    /// A gathering used to play a game
+    #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+    pub struct MatchmakeSession(
+        /// The underlying gathering
+        pub gathering: Gathering
+    ) {
+        /// The game mode being played
+        pub game_mode: u32,
+
+        /// The game-specific attributes of the session, used when searching for sessions
+        pub attributes: Vec<u32>,
+
+        /// Whether or not others are able to join the session
+        pub open_participation: bool,
+
+        /// The kind of matchmaking used to create the session
+        pub matchmake_system_type: u32,
+
+        /// Game-specific data
+        pub application_data: Buffer,
+
+        /// The number of participants
+        pub participation_count: u32,
+
+        /// The key shared by the session's participants
+        pub session_key: Buffer,
+    }
+}
+
+impl Default for ResultRange {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            size: 10,
+        }
+    }
+}
+
+impl Gathering {
+    /// The name the structure is given when placed within an [`AnyDataHolder`]
+    pub const TYPE_NAME: &'static str = "Gathering";
+}
+
+impl MatchmakeSession {
+    /// The name the structure is given when placed within an [`AnyDataHolder`]
+    pub const TYPE_NAME: &'static str = "MatchmakeSession";
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ralsei_protocol_rmc::codec::Encode;
+
+    #[test]
+    fn matchmake_session_holder() {
+        let session = MatchmakeSession {
+            gathering: Gathering {
+                id: 1,
+                owner_pid: 1337,
+                max_participants: 8,
+                ..Gathering::default()
+            },
+            attributes: vec![0; 6],
+            open_participation: true,
+            ..MatchmakeSession::default()
+        };
+        let holder = AnyDataHolder::new(MatchmakeSession::TYPE_NAME, &session).unwrap();
+
+        // a session is encoded as its gathering followed by its own fields
+        assert!(holder
+            .data
+            .starts_with(&session.gathering.to_bytes().unwrap()));
+        assert_eq!(holder.get::<MatchmakeSession>().unwrap(), session);
+    }
+}
@@ -0,0 +1,127 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use hyper::{
+    body,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use std::{convert::Infallible, net::SocketAddr};
+use tokio::sync::mpsc;
+
+use ralsei_protocol_nex::datastore::{
+    download, upload, KeyValue, ReqGetInfo, ReqPostInfo, TransferError,
+};
+
+const OBJECT: &[u8] = b"\x00an object\xFF";
+
+/// Starts a server standing in for the one objects are stored on, returning its address along
+/// with the channel the bodies of uploads are passed into
+async fn start_server() -> (SocketAddr, mpsc::UnboundedReceiver<(String, Vec<u8>)>) {
+    let (uploads_sender, uploads) = mpsc::unbounded_channel();
+    let service = make_service_fn(move |_| {
+        let uploads = uploads_sender.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                let uploads = uploads.clone();
+                async move {
+                    let authorized = matches!(
+                        request.headers().get("x-signature"),
+                        Some(signature) if signature == "signed"
+                    );
+                    let status = match (request.method().as_str(), request.uri().path()) {
+                        _ if !authorized => StatusCode::FORBIDDEN,
+                        ("GET", "/object") => return Ok(Response::new(Body::from(OBJECT))),
+                        ("POST", "/object") => {
+                            let content_type = request.headers()[hyper::header::CONTENT_TYPE]
+                                .to_str()
+                                .unwrap()
+                                .to_string();
+                            let body = body::to_bytes(request.into_body()).await?;
+                            let _ = uploads.send((content_type, body.to_vec()));
+                            StatusCode::NO_CONTENT
+                        }
+                        _ => StatusCode::NOT_FOUND,
+                    };
+                    let mut response = Response::new(Body::empty());
+                    *response.status_mut() = status;
+                    Ok::<_, hyper::Error>(response)
+                }
+            }))
+        }
+    });
+
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(service);
+    let address = server.local_addr();
+    tokio::spawn(server);
+    (address, uploads)
+}
+
+fn signature() -> Vec<KeyValue> {
+    vec![KeyValue {
+        key: "x-signature".to_string(),
+        value: "signed".to_string(),
+    }]
+}
+
+#[tokio::test]
+async fn transfers() {
+    let (address, mut uploads) = start_server().await;
+    let url = format!("http://{}/object", address);
+
+    let get_info = ReqGetInfo {
+        url: url.clone(),
+        request_headers: signature(),
+        size: OBJECT.len() as u32,
+        ..ReqGetInfo::default()
+    };
+    assert_eq!(download(&get_info).await.unwrap(), OBJECT);
+
+    let post_info = ReqPostInfo {
+        data_id: 1,
+        url,
+        request_headers: signature(),
+        form_fields: vec![KeyValue {
+            key: "key".to_string(),
+            value: "1.bin".to_string(),
+        }],
+        ..ReqPostInfo::default()
+    };
+    upload(&post_info, OBJECT).await.unwrap();
+
+    // the form fields come before the object itself
+    let (content_type, body) = uploads.recv().await.unwrap();
+    let boundary = content_type
+        .strip_prefix("multipart/form-data; boundary=")
+        .unwrap();
+    let mut expected = format!(
+        "--{0}\r\nContent-Disposition: form-data; name=\"key\"\r\n\r\n1.bin\r\n\
+         --{0}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"file\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        boundary
+    )
+    .into_bytes();
+    expected.extend_from_slice(OBJECT);
+    expected.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    assert_eq!(body, expected);
+}
+
+#[tokio::test]
+async fn rejected_transfer() {
+    let (address, _uploads) = start_server().await;
+
+    let get_info = ReqGetInfo {
+        url: format!("http://{}/object", address),
+        ..ReqGetInfo::default()
+    };
+    assert!(matches!(
+        download(&get_info).await,
+        Err(TransferError::UnexpectedStatusCode(403))
+    ));
+}
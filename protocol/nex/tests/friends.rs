@@ -0,0 +1,304 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// the expected bytes were produced by an independent implementation of the encoding described on
+// the NintendoClients wiki, rather than by the code being tested
+
+use ralsei_protocol_nex::friends::{
+    n3ds::{
+        FriendPersistentInfo, FriendPresence, FriendRelationship, Friends3dsClient,
+        Friends3dsDispatcher, Friends3dsServer, NintendoPresence,
+    },
+    wiiu::{Comment, FriendInfo, MiiV2, NintendoPresenceV2, NnaInfo, PrincipalBasicInfo},
+    GameKey,
+};
+use ralsei_protocol_rmc::{
+    client::{CallError, Caller},
+    codec::{Decode, Encode},
+    protocol::async_trait,
+    server::Dispatch,
+    types::{Buffer, DateTime, QResult},
+};
+
+const NINTENDO_PRESENCE: &[u8] = &[
+    0xFF, 0x01, 0x00, 0x00, 0x00, 0x08, 0x03, 0x00, 0x00, 0x00, 0x04, 0x00, 0x10, 0x00, 0x0A, 0x00,
+    0x49, 0x6E, 0x20, 0x61, 0x20, 0x72, 0x61, 0x63, 0x65, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x34,
+    0x12, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x39, 0x05, 0x00, 0x00, 0x78, 0x56, 0x00, 0x00, 0x03,
+    0x00, 0x00, 0x00, 0x01, 0x02, 0x03,
+];
+
+const FRIEND_PERSISTENT_INFO: &[u8] = &[
+    0x39, 0x05, 0x00, 0x00, 0x01, 0x31, 0x00, 0x01, 0x02, 0x00, 0x08, 0x03, 0x00, 0x00, 0x00, 0x04,
+    0x00, 0x10, 0x00, 0x06, 0x00, 0x68, 0x65, 0x6C, 0x6C, 0x6F, 0x00, 0xDE, 0x7E, 0xDD, 0x95, 0x1F,
+    0x00, 0x00, 0x00, 0x05, 0x31, 0x44, 0x90, 0x1F, 0x00, 0x00, 0x00, 0x00, 0x00, 0xDE, 0x95, 0x1F,
+    0x00, 0x00, 0x00,
+];
+
+const PERSISTENT_INFO_PARAMETERS: &[u8] = &[0x01, 0x00, 0x00, 0x00, 0x39, 0x05, 0x00, 0x00];
+
+const PERSISTENT_INFO_RESULTS: &[u8] = &[
+    0x01, 0x00, 0x00, 0x00, 0x39, 0x05, 0x00, 0x00, 0x01, 0x31, 0x00, 0x01, 0x02, 0x00, 0x08, 0x03,
+    0x00, 0x00, 0x00, 0x04, 0x00, 0x10, 0x00, 0x06, 0x00, 0x68, 0x65, 0x6C, 0x6C, 0x6F, 0x00, 0xDE,
+    0x7E, 0xDD, 0x95, 0x1F, 0x00, 0x00, 0x00, 0x05, 0x31, 0x44, 0x90, 0x1F, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0xDE, 0x95, 0x1F, 0x00, 0x00, 0x00,
+];
+
+const FRIEND_INFO: &[u8] = &[
+    0x39, 0x05, 0x00, 0x00, 0x07, 0x00, 0x72, 0x61, 0x6C, 0x73, 0x65, 0x69, 0x00, 0x07, 0x00, 0x72,
+    0x61, 0x6C, 0x73, 0x65, 0x69, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0xAA, 0xBB, 0x00, 0xC0,
+    0xDC, 0x95, 0x1F, 0x00, 0x00, 0x00, 0x00, 0x5E, 0x00, 0xFF, 0xE7, 0x1E, 0x00, 0x01, 0x00, 0xEC,
+    0x10, 0x10, 0x00, 0x00, 0x05, 0x00, 0xD0, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x39, 0x05, 0x00, 0x00, 0x2A, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x03, 0x03, 0x00, 0x03, 0x00, 0x68, 0x69, 0x00, 0x80, 0xC7,
+    0xDC, 0x95, 0x1F, 0x00, 0x00, 0x00, 0x05, 0x31, 0x44, 0x90, 0x1F, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xDE, 0x95, 0x1F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+fn friend_persistent_info() -> FriendPersistentInfo {
+    FriendPersistentInfo {
+        pid: 1337,
+        region: 1,
+        country: 49,
+        area: 0,
+        language: 1,
+        platform: 2,
+        game_key: GameKey {
+            title_id: 0x0004_0000_0003_0800,
+            title_version: 16,
+        },
+        message: "hello".to_string(),
+        message_updated_at: DateTime::new(2021, 7, 14, 23, 59, 30),
+        friended_at: DateTime::new(2020, 1, 2, 3, 4, 5),
+        last_online: DateTime::new(2021, 7, 15, 0, 0, 0),
+    }
+}
+
+#[test]
+fn n3ds_structures() {
+    let presence = NintendoPresence {
+        changed_flags: 0x1FF,
+        game_key: GameKey {
+            title_id: 0x0004_0000_0003_0800,
+            title_version: 16,
+        },
+        message: "In a race".to_string(),
+        join_availability_flag: 1,
+        matchmake_type: 2,
+        join_game_id: 0x1234,
+        join_game_mode: 3,
+        owner_pid: 1337,
+        join_group_id: 0x5678,
+        application_arg: Buffer(vec![1, 2, 3]),
+    };
+    assert_eq!(presence.to_bytes().unwrap(), NINTENDO_PRESENCE);
+    assert_eq!(
+        NintendoPresence::from_bytes(NINTENDO_PRESENCE).unwrap(),
+        presence
+    );
+
+    let info = friend_persistent_info();
+    assert_eq!(info.to_bytes().unwrap(), FRIEND_PERSISTENT_INFO);
+    assert_eq!(
+        FriendPersistentInfo::from_bytes(FRIEND_PERSISTENT_INFO).unwrap(),
+        info
+    );
+}
+
+#[test]
+fn wiiu_structures() {
+    let friend = FriendInfo {
+        nna_info: NnaInfo {
+            principal_info: PrincipalBasicInfo {
+                pid: 1337,
+                nnid: "ralsei".to_string(),
+                mii: MiiV2 {
+                    name: "ralsei".to_string(),
+                    unknown1: 0,
+                    unknown2: 0,
+                    data: Buffer(vec![0xAA, 0xBB]),
+                    updated_at: DateTime::new(2021, 7, 14, 12, 0, 0),
+                },
+                unknown: 0,
+            },
+            unknown1: 94,
+            unknown2: 0,
+        },
+        presence: NintendoPresenceV2 {
+            changed_flags: 0x1E_E7FF,
+            online: true,
+            game_key: GameKey {
+                title_id: 0x0005_0000_1010_EC00,
+                title_version: 208,
+            },
+            game_server_id: 0x0010_1000,
+            pid: 1337,
+            gathering_id: 0x2A,
+            unknown5: 3,
+            unknown6: 3,
+            unknown7: 3,
+            ..NintendoPresenceV2::default()
+        },
+        comment: Comment {
+            unknown: 0,
+            contents: "hi".to_string(),
+            changed_at: DateTime::new(2021, 7, 14, 12, 30, 0),
+        },
+        friended_at: DateTime::new(2020, 1, 2, 3, 4, 5),
+        last_online: DateTime::new(2021, 7, 15, 0, 0, 0),
+        unknown: 0,
+    };
+    assert_eq!(friend.to_bytes().unwrap(), FRIEND_INFO);
+    assert_eq!(FriendInfo::from_bytes(FRIEND_INFO).unwrap(), friend);
+}
+
+/// A friends server that only knows of the friend returned by [`friend_persistent_info`]
+struct Friends;
+
+#[async_trait]
+impl Friends3dsServer for Friends {
+    async fn update_preference(
+        &self,
+        _public_mode: bool,
+        _show_game: bool,
+        _show_played_game: bool,
+    ) -> Result<(), QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn get_friend_relationships(
+        &self,
+        _pids: Vec<u32>,
+    ) -> Result<Vec<FriendRelationship>, QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn add_friend_by_principal_id(
+        &self,
+        _friend_code: u64,
+        _pid: u32,
+    ) -> Result<FriendRelationship, QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn add_friend_by_lst_principal_id(
+        &self,
+        _friend_code: u64,
+        _pids: Vec<u32>,
+    ) -> Result<Vec<FriendRelationship>, QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn remove_friend_by_local_friend_code(&self, _friend_code: u64) -> Result<(), QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn remove_friend_by_principal_id(&self, _pid: u32) -> Result<(), QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn get_all_friends(&self) -> Result<Vec<FriendRelationship>, QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn update_black_list(&self, _pids: Vec<u32>) -> Result<(), QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn sync_friend(
+        &self,
+        _friend_code: u64,
+        _pids: Vec<u32>,
+        _friend_codes: Vec<u64>,
+    ) -> Result<Vec<FriendRelationship>, QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn update_presence(
+        &self,
+        _presence: NintendoPresence,
+        _show_game: bool,
+    ) -> Result<(), QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn update_favorite_game_key(&self, _game_key: GameKey) -> Result<(), QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn update_comment(&self, _comment: String) -> Result<(), QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn get_friend_presence(&self, _pids: Vec<u32>) -> Result<Vec<FriendPresence>, QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn get_friend_persistent_info(
+        &self,
+        pids: Vec<u32>,
+    ) -> Result<Vec<FriendPersistentInfo>, QResult> {
+        let friend = friend_persistent_info();
+        Ok(pids
+            .into_iter()
+            .filter(|pid| *pid == friend.pid)
+            .map(|_| friend.clone())
+            .collect())
+    }
+
+    async fn send_invitation(&self, _pids: Vec<u32>) -> Result<(), QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+}
+
+/// A [`Caller`] that passes calls straight to a dispatcher, without a connection in between
+struct Loopback<D>(D);
+
+#[async_trait]
+impl<D> Caller for Loopback<D>
+where
+    D: Dispatch,
+{
+    async fn call(
+        &mut self,
+        protocol_id: u16,
+        method_id: u32,
+        parameters: Vec<u8>,
+    ) -> Result<Vec<u8>, CallError> {
+        assert_eq!(protocol_id, self.0.protocol_id());
+        self.0
+            .dispatch(method_id, &parameters)
+            .await
+            .map_err(CallError::Failed)
+    }
+}
+
+#[tokio::test]
+async fn dispatcher() {
+    let dispatcher = Friends3dsDispatcher(Friends);
+    assert_eq!(
+        dispatcher
+            .dispatch(25, PERSISTENT_INFO_PARAMETERS)
+            .await
+            .unwrap(),
+        PERSISTENT_INFO_RESULTS
+    );
+
+    let mut client = Loopback(dispatcher);
+    assert_eq!(
+        client
+            .get_friend_persistent_info(vec![1337, 1])
+            .await
+            .unwrap(),
+        [friend_persistent_info()]
+    );
+    assert!(matches!(
+        client.get_all_friends().await,
+        Err(CallError::Failed(QResult::NOT_IMPLEMENTED))
+    ));
+}
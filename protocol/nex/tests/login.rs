@@ -0,0 +1,287 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::borrow::Cow;
+
+use ralsei_model::{console::common::Kind, network::Pid};
+use ralsei_protocol_nex::{
+    authentication::{
+        AuthenticationInfo, ConnectionData, LoginResult, TicketGrantingDispatcher,
+        TicketGrantingServer,
+    },
+    kerberos::{self, KerberosError, Ticket},
+    login::{login_with_token, LoginError, Settings},
+};
+use ralsei_protocol_prudp_v0 as prudp_v0;
+use ralsei_protocol_prudp_v1 as prudp_v1;
+use ralsei_protocol_rmc::{
+    codec::Decode,
+    protocol::async_trait,
+    server::serve,
+    types::{AnyDataHolder, Buffer, QResult, StationUrl},
+};
+use ralsei_service_account::xml::token::NexToken;
+use ralsei_util::transport::{Transport, TransportError};
+
+const ACCESS_KEY: &[u8] = b"6f599f81";
+const PID: u32 = 1337;
+const PASSWORD: &str = "password";
+const TOKEN: &str = "token";
+const SESSION_KEY: [u8; kerberos::DEFAULT_SESSION_KEY_LENGTH] =
+    [0x2A; kerberos::DEFAULT_SESSION_KEY_LENGTH];
+
+/// The result returned when the provided token is not valid
+const INVALID_TOKEN: QResult = QResult(0x8068_0003);
+
+/// A function responding to the data passed in the payload of a connect packet
+type Respond = fn(&[u8]) -> Option<Vec<u8>>;
+
+/// Responds to the connect packet of a client of the authentication server
+fn respond_empty(_: &[u8]) -> Option<Vec<u8>> {
+    Some(Vec::new())
+}
+
+/// Responds to the connect packet of a client of the secure server with the check value it
+/// passed after decrypting it using the session key, offset by the provided amount
+fn respond_checked(connection_data: &[u8], offset: u32) -> Option<Vec<u8>> {
+    let (_, credentials) = <(Buffer, Buffer)>::from_bytes(connection_data).ok()?;
+    let (_, _, check) =
+        <(u32, u32, u32)>::from_bytes(&kerberos::decrypt(&SESSION_KEY, &credentials.0).ok()?)
+            .ok()?;
+    kerberos::connection_response(&SESSION_KEY, check.wrapping_add(offset)).ok()
+}
+
+struct Authentication {
+    secure: StationUrl,
+}
+
+#[async_trait]
+impl TicketGrantingServer for Authentication {
+    async fn login(&self, _user_name: String) -> Result<LoginResult, QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn login_ex(
+        &self,
+        user_name: String,
+        extra_data: AnyDataHolder,
+    ) -> Result<LoginResult, QResult> {
+        let info = extra_data
+            .get::<AuthenticationInfo>()
+            .map_err(|_| QResult::INVALID_ARGUMENT)?;
+        if extra_data.type_name != AuthenticationInfo::TYPE_NAME || info.token != TOKEN {
+            return Ok(LoginResult {
+                result: INVALID_TOKEN,
+                ..LoginResult::default()
+            });
+        }
+
+        Ok(LoginResult {
+            result: QResult::SUCCESS,
+            pid: user_name.parse().map_err(|_| QResult::INVALID_ARGUMENT)?,
+            ticket: Buffer::default(),
+            connection_data: ConnectionData {
+                regular_protocols: self.secure.clone(),
+                ..ConnectionData::default()
+            },
+            return_message: "branch:origin/project/test build:0".to_string(),
+        })
+    }
+
+    async fn request_ticket(&self, source: u32, target: u32) -> Result<(QResult, Buffer), QResult> {
+        let ticket = Ticket {
+            session_key: SESSION_KEY.to_vec(),
+            target,
+            internal: Buffer(b"internal".to_vec()),
+        };
+        Ok((
+            QResult::SUCCESS,
+            Buffer(
+                ticket
+                    .encrypt(&kerberos::derive_key(source, PASSWORD))
+                    .unwrap(),
+            ),
+        ))
+    }
+
+    async fn get_pid(&self, _user_name: String) -> Result<u32, QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+
+    async fn get_name(&self, _pid: u32) -> Result<String, QResult> {
+        Err(QResult::NOT_IMPLEMENTED)
+    }
+}
+
+/// A NEX server speaking the version of PRUDP used by a [`Kind`] of console
+enum Listener {
+    V0(prudp_v0::listener::Listener),
+    V1(prudp_v1::listener::Listener),
+}
+
+impl Listener {
+    /// Binds a server for the provided [`Kind`] of console to an unused port on the loopback
+    /// interface, encrypting its connections with the provided session key if one is provided and
+    /// responding to their connect packets using the provided function
+    async fn bind(kind: Kind, session_key: Option<&[u8]>, respond: Respond) -> Self {
+        match kind {
+            Kind::N3ds => {
+                let mut settings = prudp_v0::connection::Settings::new(ACCESS_KEY);
+                if let Some(session_key) = session_key {
+                    settings.encryption_key = session_key.to_vec();
+                }
+                let port =
+                    prudp_v0::packet::VirtualPort::new(prudp_v0::packet::StreamKind::RvSecure, 1);
+                Self::V0(
+                    prudp_v0::listener::Listener::bind_with("127.0.0.1:0", port, settings, respond)
+                        .await
+                        .unwrap(),
+                )
+            }
+            _ => {
+                let mut settings = prudp_v1::connection::Settings::new(ACCESS_KEY);
+                if let Some(session_key) = session_key {
+                    settings.encryption_key = session_key.to_vec();
+                    settings.session_key = session_key.to_vec();
+                }
+                let port =
+                    prudp_v1::packet::VirtualPort::new(prudp_v1::packet::StreamKind::RvSecure, 1);
+                Self::V1(
+                    prudp_v1::listener::Listener::bind_with("127.0.0.1:0", port, settings, respond)
+                        .await
+                        .unwrap(),
+                )
+            }
+        }
+    }
+
+    fn port(&self) -> u16 {
+        match self {
+            Self::V0(listener) => listener.local_addr().port(),
+            Self::V1(listener) => listener.local_addr().port(),
+        }
+    }
+
+    /// Accepts a connection, returning it along with the data passed in its connect packet
+    async fn accept(&mut self) -> Result<(Box<dyn Transport>, Vec<u8>), TransportError> {
+        Ok(match self {
+            Self::V0(listener) => {
+                let connection = listener.accept().await?;
+                let connection_data = connection.connection_data().to_vec();
+                (Box::new(connection), connection_data)
+            }
+            Self::V1(listener) => {
+                let connection = listener.accept().await?;
+                let connection_data = connection.connection_data().to_vec();
+                (Box::new(connection), connection_data)
+            }
+        })
+    }
+}
+
+/// Starts an authentication server for the provided [`Kind`] of console directing users to a
+/// secure server that responds to connect packets using the provided function, returning the
+/// [`NexToken`] used to log in to it along with the secure server's [`Listener`]
+async fn start_servers(kind: Kind, respond: Respond) -> (NexToken<'static>, Listener) {
+    let secure = Listener::bind(kind, Some(&SESSION_KEY), respond).await;
+    let mut authentication = Listener::bind(kind, None, respond_empty).await;
+    let token = NexToken {
+        host: Some(Cow::Borrowed("127.0.0.1")),
+        nex_password: Some(Cow::Borrowed(PASSWORD)),
+        pid: Some(Pid(PID)),
+        port: Some(authentication.port()),
+        token: Some(Cow::Borrowed(TOKEN)),
+    };
+
+    let secure_url = format!(
+        "prudps:/address=127.0.0.1;port={};CID=1;PID=2;sid=1;stream=10;type=2",
+        secure.port()
+    )
+    .parse()
+    .unwrap();
+    tokio::spawn(async move {
+        let dispatcher = TicketGrantingDispatcher(Authentication { secure: secure_url });
+        while let Ok((mut connection, _)) = authentication.accept().await {
+            let _ = serve(connection.as_mut(), &[&dispatcher]).await;
+        }
+    });
+
+    (token, secure)
+}
+
+/// Logs in using the provided [`Kind`] of console, checking what the secure server is presented
+/// with and that the connection to it is usable
+async fn login(kind: Kind) {
+    let (token, mut secure) = start_servers(kind, |data| respond_checked(data, 0)).await;
+
+    let session = login_with_token(kind, &token, &Settings::new(0, ACCESS_KEY, 30_000))
+        .await
+        .unwrap();
+    assert_eq!(session.pid, PID);
+    assert_eq!(session.station_url.get("PID"), Some("2"));
+
+    // the secure server is presented with the internal ticket and the user's credentials,
+    // encrypted using the session key
+    let (mut connection, connection_data) = secure.accept().await.unwrap();
+    let (internal, credentials) = <(Buffer, Buffer)>::from_bytes(&connection_data).unwrap();
+    assert_eq!(internal.0, b"internal");
+    let (pid, connection_id, _) =
+        <(u32, u32, u32)>::from_bytes(&kerberos::decrypt(&SESSION_KEY, &credentials.0).unwrap())
+            .unwrap();
+    assert_eq!((pid, connection_id), (PID, 1));
+
+    let mut transport = session.client.into_inner();
+    transport.send(b"hello").await.unwrap();
+    assert_eq!(connection.recv().await.unwrap(), b"hello");
+    connection.send(b"world").await.unwrap();
+    assert_eq!(transport.recv().await.unwrap(), b"world");
+}
+
+#[tokio::test]
+async fn login_n3ds() {
+    login(Kind::N3ds).await;
+}
+
+#[tokio::test]
+async fn login_wiiu() {
+    login(Kind::WiiU).await;
+}
+
+#[tokio::test]
+async fn rejected_token() {
+    let (mut token, _secure) = start_servers(Kind::WiiU, |data| respond_checked(data, 0)).await;
+    let settings = Settings::new(0, ACCESS_KEY, 30_000);
+
+    token.token = Some(Cow::Borrowed("invalid"));
+    assert!(matches!(
+        login_with_token(Kind::WiiU, &token, &settings).await,
+        Err(LoginError::Failed(INVALID_TOKEN))
+    ));
+
+    token.nex_password = None;
+    assert!(matches!(
+        login_with_token(Kind::WiiU, &token, &settings).await,
+        Err(LoginError::MissingTokenField("nex_password"))
+    ));
+}
+
+#[tokio::test]
+async fn mismatched_check() {
+    let (token, _secure) = start_servers(Kind::N3ds, |data| respond_checked(data, 1)).await;
+    assert!(matches!(
+        login_with_token(Kind::N3ds, &token, &Settings::new(0, ACCESS_KEY, 30_000)).await,
+        Err(LoginError::KerberosError(KerberosError::CheckMismatch(..)))
+    ));
+
+    let (token, _secure) = start_servers(Kind::WiiU, respond_empty).await;
+    assert!(matches!(
+        login_with_token(Kind::WiiU, &token, &Settings::new(0, ACCESS_KEY, 30_000)).await,
+        Err(LoginError::KerberosError(KerberosError::DecodeError(_)))
+    ));
+}
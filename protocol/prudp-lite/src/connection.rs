@@ -0,0 +1,367 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Both ends of a PRUDP Lite connection
+//!
+//! A [`Connection`] is a handle to a task that drives the connection, which takes care of
+//! acknowledging and fragmenting the packets sent over it, leaving the handle to send and receive
+//! whole messages. As the underlying stream is reliable, nothing is ever retransmitted. The task
+//! itself is shared with the other versions of PRUDP, and lives in
+//! [`ralsei_util::prudp::connection`], leaving this module with the handshake and the framing of
+//! packets on the stream
+
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::{
+    io::{self, AsyncRead, AsyncReadExt, AsyncWrite, ReadHalf, WriteHalf},
+    net::{TcpStream, ToSocketAddrs},
+    sync::mpsc,
+    time,
+};
+
+use crate::packet::{Packet, PacketError, PacketFlags, PacketKind, VirtualPort, HEADER_LENGTH};
+use ralsei_util::{
+    prudp::{
+        self,
+        connection::{Handle, Packets, Reliability, State},
+    },
+    transport::{Transport, TransportError},
+};
+
+/// The settings used by both ends of a PRUDP Lite connection
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Settings {
+    /// The largest payload placed in a single data packet before a message is fragmented
+    pub fragment_size: usize,
+
+    /// The time to wait for the peer to respond during the handshake and when disconnecting
+    pub timeout: Duration,
+
+    /// The time to wait without sending anything before sending a ping to keep the connection
+    /// alive
+    pub ping_interval: Duration,
+
+    /// The functionality advertised to the peer during the handshake
+    ///
+    /// This is not otherwise interpreted by this crate
+    pub supported_functions: u32,
+}
+
+impl Settings {
+    /// Returns the parts of the [`Settings`] that govern how messages are delivered
+    ///
+    /// As the underlying stream is reliable, nothing is ever retransmitted, and a packet that goes
+    /// unacknowledged for the timeout closes the connection
+    fn reliability(&self) -> Reliability {
+        Reliability {
+            fragment_size: self.fragment_size,
+            resend_timeout: self.timeout,
+            max_resends: 0,
+            ping_interval: self.ping_interval,
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fragment_size: 1000,
+            timeout: Duration::from_secs(10),
+            ping_interval: Duration::from_secs(5),
+            supported_functions: 0,
+        }
+    }
+}
+
+/// A handle to an established PRUDP Lite connection
+///
+/// Dropping it disconnects from the peer
+#[derive(Debug)]
+pub struct Connection {
+    connection_data: Vec<u8>,
+    handle: Handle<PacketError>,
+}
+
+impl Connection {
+    /// Connects to the PRUDP Lite server at the provided address and [`VirtualPort`] over TCP,
+    /// passing it the provided data in the payload of the connect packet
+    ///
+    /// See [`Connection::connect_over`] for details
+    pub async fn connect<A>(
+        address: A,
+        destination: VirtualPort,
+        connection_data: &[u8],
+        settings: Settings,
+    ) -> Result<Self, ConnectionError>
+    where
+        A: ToSocketAddrs,
+    {
+        let stream = TcpStream::connect(address).await?;
+        stream.set_nodelay(true)?;
+        Self::connect_over(stream, destination, connection_data, settings).await
+    }
+
+    /// Connects to the PRUDP Lite server at the provided [`VirtualPort`] over the provided
+    /// stream, passing it the provided data in the payload of the connect packet
+    ///
+    /// The connection is made from the same kind of stream as the destination, on port 31
+    pub async fn connect_over<S>(
+        stream: S,
+        destination: VirtualPort,
+        connection_data: &[u8],
+        settings: Settings,
+    ) -> Result<Self, ConnectionError>
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (mut state, mut packets) = start(
+            stream,
+            &settings,
+            VirtualPort::new(destination.stream, 0x1F),
+            destination,
+            1,
+        )?;
+
+        let syn = state.packet(PacketKind::Syn, PacketFlags::NEED_ACK, 0, 0, Vec::new());
+        let _ = state
+            .exchange(&mut packets, syn, |packet| {
+                packet.kind == PacketKind::Syn && packet.flags.contains(PacketFlags::ACK)
+            })
+            .await?;
+
+        // the connect packet occupies the first sequence id of the reliable stream
+        let sequence_id = state.next_sequence_id(0);
+        let connect = state.packet(
+            PacketKind::Connect,
+            PacketFlags::RELIABLE | PacketFlags::NEED_ACK | PacketFlags::HAS_SIZE,
+            0,
+            sequence_id,
+            connection_data.to_vec(),
+        );
+        let _ = state
+            .exchange(&mut packets, connect, |packet| {
+                packet.kind == PacketKind::Connect && packet.flags.contains(PacketFlags::ACK)
+            })
+            .await?;
+
+        Ok(Self {
+            connection_data: connection_data.to_vec(),
+            handle: state.spawn(packets),
+        })
+    }
+
+    /// Waits for a client to connect to the provided [`VirtualPort`] over the provided stream,
+    /// answering its handshake
+    pub async fn accept_over<S>(
+        stream: S,
+        port: VirtualPort,
+        settings: Settings,
+    ) -> Result<Self, ConnectionError>
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        // the client's connect packet occupies the first sequence id of its reliable stream
+        let (mut state, mut packets) = start(stream, &settings, port, port, 2)?;
+
+        loop {
+            let packet = time::timeout(settings.timeout, packets.recv())
+                .await
+                .map_err(|_| ConnectionError::TimedOut)?
+                .ok_or(ConnectionError::Closed)??;
+            if packet.destination != port || packet.flags.contains(PacketFlags::ACK) {
+                continue;
+            }
+
+            state.codec_mut().remote = packet.source;
+            match packet.kind {
+                PacketKind::Syn => state.acknowledge(&packet).await?,
+                PacketKind::Connect => {
+                    state.acknowledge(&packet).await?;
+                    return Ok(Self {
+                        connection_data: packet.payload,
+                        handle: state.spawn(packets),
+                    });
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Returns the data the client passed in the payload of its connect packet
+    pub fn connection_data(&self) -> &[u8] {
+        &self.connection_data
+    }
+
+    /// Sends the provided message to the peer, fragmenting it if necessary
+    ///
+    /// The message is queued to be sent by the connection's task, so this only fails if the
+    /// connection has been closed
+    pub async fn send(&self, message: &[u8]) -> Result<(), ConnectionError> {
+        self.handle.send(0, message)
+    }
+
+    /// Receives the next message sent by the peer
+    ///
+    /// Once the connection has been closed, this returns the error that closed it (if any), and
+    /// [`ConnectionError::Closed`] after that
+    pub async fn recv(&mut self) -> Result<Vec<u8>, ConnectionError> {
+        Ok(self.handle.recv().await?.1)
+    }
+
+    /// Disconnects from the peer once it has acknowledged the disconnection
+    pub async fn disconnect(self) -> Result<(), ConnectionError> {
+        self.handle.disconnect().await
+    }
+}
+
+#[async_trait]
+impl Transport for Connection {
+    async fn send(&self, message: &[u8]) -> Result<(), TransportError> {
+        Ok(Connection::send(self, message).await?)
+    }
+
+    async fn recv(&mut self) -> Result<Vec<u8>, TransportError> {
+        Ok(Connection::recv(self).await?)
+    }
+
+    async fn disconnect(self: Box<Self>) -> Result<(), TransportError> {
+        Ok(Connection::disconnect(*self).await?)
+    }
+}
+
+/// Splits the provided stream, creating a [`State`] that writes to one half and starting a task
+/// that reads packets from the other, returning the state along with the channel those packets are
+/// passed into
+///
+/// The peer's reliable stream is expected to continue from the provided sequence id
+#[allow(clippy::type_complexity)]
+fn start<S>(
+    stream: S,
+    settings: &Settings,
+    local: VirtualPort,
+    remote: VirtualPort,
+    expected_sequence_id: u16,
+) -> Result<(State<Endpoint, WriteHalf<S>>, Packets<Endpoint>), ConnectionError>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (reader, writer) = io::split(stream);
+    let (packets_sender, packets) = mpsc::unbounded_channel();
+    tokio::spawn(read_packets(reader, packets_sender));
+
+    let endpoint = Endpoint {
+        local,
+        remote,
+        supported_functions: settings.supported_functions,
+    };
+    let state = State::new(
+        endpoint,
+        writer,
+        settings.reliability(),
+        None,
+        0,
+        1,
+        expected_sequence_id,
+    )?;
+    Ok((state, packets))
+}
+
+/// Reads packets from the provided stream, passing them into the provided channel until either
+/// is closed
+async fn read_packets<S>(
+    mut stream: ReadHalf<S>,
+    packets: mpsc::UnboundedSender<Result<Packet, ConnectionError>>,
+) where
+    S: AsyncRead,
+{
+    let mut buffer = vec![0; HEADER_LENGTH];
+    loop {
+        let result = tokio::select! {
+            result = read_packet(&mut stream, &mut buffer) => result,
+            _ = packets.closed() => return,
+        };
+
+        // as packets are not delimited, the stream cannot be recovered from a malformed one
+        let error = result.is_err();
+        if packets.send(result).is_err() || error {
+            return;
+        }
+    }
+}
+
+/// Reads a single packet from the provided stream, using the provided buffer as scratch space
+async fn read_packet<S>(
+    stream: &mut ReadHalf<S>,
+    buffer: &mut Vec<u8>,
+) -> Result<Packet, ConnectionError>
+where
+    S: AsyncRead,
+{
+    buffer.resize(HEADER_LENGTH, 0);
+    match stream.read_exact(buffer).await {
+        Ok(_) => (),
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+            return Err(ConnectionError::Closed)
+        }
+        Err(error) => return Err(error.into()),
+    }
+
+    buffer.resize(Packet::encoded_length(buffer)?, 0);
+    let _ = stream.read_exact(&mut buffer[HEADER_LENGTH..]).await?;
+    Ok(Packet::from_bytes(buffer)?)
+}
+
+/// The addressing information of one end of a PRUDP Lite connection
+#[derive(Debug)]
+struct Endpoint {
+    local: VirtualPort,
+    remote: VirtualPort,
+    supported_functions: u32,
+}
+
+impl prudp::packet::Codec for Endpoint {
+    type Packet = Packet;
+    type Error = PacketError;
+
+    fn packet(
+        &self,
+        kind: PacketKind,
+        flags: PacketFlags,
+        _substream_id: u8,
+        sequence_id: u16,
+        fragment_id: u8,
+        payload: Vec<u8>,
+    ) -> Packet {
+        let mut packet = Packet::new(self.local, self.remote, kind);
+        packet.flags = flags;
+        packet.sequence_id = sequence_id;
+        packet.fragment_id = fragment_id;
+        packet.supported_functions = self.supported_functions;
+        packet.payload = payload;
+        packet
+    }
+
+    fn encode(&self, packet: Packet) -> Result<Vec<u8>, PacketError> {
+        packet.to_bytes()
+    }
+
+    // packets are not signed, as the underlying stream is expected to take care of that
+    fn is_signed(&self, _packet: &Packet) -> bool {
+        true
+    }
+}
+
+/// An enumeration over the errors that may occur while using a PRUDP Lite connection
+pub type ConnectionError = prudp::connection::ConnectionError<PacketError>;
+
+impl From<PacketError> for ConnectionError {
+    fn from(error: PacketError) -> Self {
+        Self::PacketError(error)
+    }
+}
@@ -0,0 +1,97 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The server end of a PRUDP Lite connection over TCP
+//!
+//! A [`Listener`] owns a TCP listener, answering the handshakes of clients as they connect. To
+//! accept connections over other kinds of streams, see [`Connection::accept_over`]
+
+use std::net::SocketAddr;
+use tokio::{
+    net::{TcpListener, ToSocketAddrs},
+    sync::mpsc,
+};
+
+use crate::{
+    connection::{Connection, ConnectionError, Settings},
+    packet::VirtualPort,
+};
+
+/// A PRUDP Lite server, accepting connections made to a single [`VirtualPort`] over TCP
+///
+/// Dropping it stops new connections from being accepted, but does not close the connections
+/// that have already been accepted
+#[derive(Debug)]
+pub struct Listener {
+    local_addr: SocketAddr,
+    connections: mpsc::UnboundedReceiver<Connection>,
+}
+
+impl Listener {
+    /// Binds a new [`Listener`] to the provided address, accepting connections made to the
+    /// provided [`VirtualPort`]
+    pub async fn bind<A>(
+        address: A,
+        port: VirtualPort,
+        settings: Settings,
+    ) -> Result<Self, ConnectionError>
+    where
+        A: ToSocketAddrs,
+    {
+        let listener = TcpListener::bind(address).await?;
+        let local_addr = listener.local_addr()?;
+
+        let (connections_sender, connections) = mpsc::unbounded_channel();
+        tokio::spawn(dispatch(listener, port, settings, connections_sender));
+
+        Ok(Self {
+            local_addr,
+            connections,
+        })
+    }
+
+    /// Returns the address that the [`Listener`] is bound to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Waits for a client to complete its handshake, returning the resulting [`Connection`]
+    pub async fn accept(&mut self) -> Result<Connection, ConnectionError> {
+        self.connections.recv().await.ok_or(ConnectionError::Closed)
+    }
+}
+
+/// Accepts streams from the provided listener, answering the handshakes of the clients on them
+async fn dispatch(
+    listener: TcpListener,
+    port: VirtualPort,
+    settings: Settings,
+    connections: mpsc::UnboundedSender<Connection>,
+) {
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(_) => return,
+            },
+            _ = connections.closed() => return,
+        };
+        if stream.set_nodelay(true).is_err() {
+            continue;
+        }
+
+        // clients that fail to complete their handshake are dropped
+        let (settings, connections) = (settings.clone(), connections.clone());
+        tokio::spawn(async move {
+            if let Ok(connection) = Connection::accept_over(stream, port, settings).await {
+                let _ = connections.send(connection);
+            }
+        });
+    }
+}
@@ -0,0 +1,345 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Type definitions for the PRUDP Lite wire format
+//!
+//! The main focal point of this module is the [`Packet`] structure, which can be encoded to and
+//! decoded from bytes using [`Packet::to_bytes`] and [`Packet::from_bytes`]. As packets are sent
+//! over a stream, [`Packet::encoded_length`] can be used to find where one ends given its header
+
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::cast::FromPrimitive;
+use std::convert::{TryFrom, TryInto};
+use thiserror::Error;
+
+use ralsei_util::prudp::packet as common;
+
+pub use ralsei_util::prudp::packet::{PacketFlags, PacketKind};
+
+/// The byte every packet begins with
+pub const MAGIC: u8 = 0x80;
+
+/// The length of a packet's header, including the magic
+pub const HEADER_LENGTH: usize = 12;
+
+/// Enumeration of the kinds of streams a [`VirtualPort`] may refer to
+#[non_exhaustive]
+#[derive(FromPrimitive, ToPrimitive, Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum StreamKind {
+    Do = 1,
+    Rv = 2,
+    OldRvSec = 3,
+    SbMgmt = 4,
+    Nat = 5,
+    SessionDiscovery = 6,
+    NatEcho = 7,
+    Routing = 8,
+    Game = 9,
+    RvSecure = 10,
+    Relay = 11,
+}
+
+/// An endpoint within a PRUDP host, made up of a [`StreamKind`] and a port number
+///
+/// Unlike in the other versions of PRUDP, the port takes up a whole byte, with the stream kinds of
+/// both ends of a packet packed into a separate byte
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct VirtualPort {
+    /// The kind of stream the port is a part of
+    pub stream: StreamKind,
+
+    /// The port number
+    pub port: u8,
+}
+
+impl VirtualPort {
+    /// Creates a new [`VirtualPort`] from its components
+    pub fn new(stream: StreamKind, port: u8) -> Self {
+        Self { stream, port }
+    }
+}
+
+/// Enumeration of the options that may follow a [`Packet`]'s header
+#[derive(FromPrimitive, ToPrimitive, Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum OptionId {
+    SupportedFunctions = 0,
+    MaxSubstreamId = 4,
+}
+
+/// A PRUDP Lite packet
+///
+/// The option fields are only encoded for [`PacketKind::Syn`] and [`PacketKind::Connect`]
+/// packets. For all other kinds of packets, they are ignored when encoding and zero when decoded
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Packet {
+    /// The [`VirtualPort`] the packet was sent from
+    pub source: VirtualPort,
+
+    /// The [`VirtualPort`] the packet is being sent to
+    pub destination: VirtualPort,
+
+    /// The kind of packet
+    pub kind: PacketKind,
+
+    /// The packet's flags
+    pub flags: PacketFlags,
+
+    /// The packet's position within a fragmented message, where zero marks the last fragment
+    pub fragment_id: u8,
+
+    /// The packet's position in the sender's stream of packets
+    pub sequence_id: u16,
+
+    /// The functionality supported by the sender
+    pub supported_functions: u32,
+
+    /// The highest substream id the sender is willing to use
+    ///
+    /// The PRUDP Lite header has no room for a substream id, so this is always zero in practice
+    pub max_substream_id: u8,
+
+    /// The packet's payload
+    pub payload: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new [`Packet`] with the provided routing information, leaving all other fields
+    /// zeroed
+    pub fn new(source: VirtualPort, destination: VirtualPort, kind: PacketKind) -> Self {
+        Self {
+            source,
+            destination,
+            kind,
+            flags: PacketFlags::empty(),
+            fragment_id: 0,
+            sequence_id: 0,
+            supported_functions: 0,
+            max_substream_id: 0,
+            payload: Vec::new(),
+        }
+    }
+
+    /// Returns the length of the encoded packet beginning with the provided header
+    pub fn encoded_length(header: &[u8]) -> Result<usize, PacketError> {
+        let header = header
+            .get(..HEADER_LENGTH)
+            .ok_or(PacketError::OutOfBounds)?;
+        if header[0] != MAGIC {
+            return Err(PacketError::InvalidMagic);
+        }
+
+        Ok(HEADER_LENGTH
+            + usize::from(header[1])
+            + usize::from(u16::from_le_bytes([header[2], header[3]])))
+    }
+
+    /// Encodes the [`Packet`] into bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PacketError> {
+        let mut options = Vec::new();
+        if let PacketKind::Syn | PacketKind::Connect = self.kind {
+            options.extend_from_slice(&[OptionId::SupportedFunctions as u8, 4]);
+            options.extend_from_slice(&self.supported_functions.to_le_bytes());
+            options.extend_from_slice(&[OptionId::MaxSubstreamId as u8, 1, self.max_substream_id]);
+        }
+
+        let mut data = Vec::with_capacity(HEADER_LENGTH + options.len() + self.payload.len());
+        data.push(MAGIC);
+        data.push(options.len() as u8);
+        data.extend_from_slice(
+            &u16::try_from(self.payload.len())
+                .map_err(|_| PacketError::PayloadTooLarge(self.payload.len()))?
+                .to_le_bytes(),
+        );
+        data.push(((self.source.stream as u8) << 4) | self.destination.stream as u8);
+        data.push(self.source.port);
+        data.push(self.destination.port);
+        data.push(self.fragment_id);
+        data.extend_from_slice(&(self.kind as u16 | (self.flags.bits() << 4)).to_le_bytes());
+        data.extend_from_slice(&self.sequence_id.to_le_bytes());
+        data.extend_from_slice(&options);
+        data.extend_from_slice(&self.payload);
+        Ok(data)
+    }
+
+    /// Decodes a [`Packet`] from bytes, ignoring anything following it
+    pub fn from_bytes(data: &[u8]) -> Result<Self, PacketError> {
+        let length = Self::encoded_length(data)?;
+        let data = data.get(..length).ok_or(PacketError::OutOfBounds)?;
+
+        let stream_kind = |b: u8| StreamKind::from_u8(b).ok_or(PacketError::InvalidStreamKind(b));
+        let kind_and_flags = u16::from_le_bytes([data[8], data[9]]);
+
+        let mut packet = Self::new(
+            VirtualPort::new(stream_kind(data[4] >> 4)?, data[5]),
+            VirtualPort::new(stream_kind(data[4] & 0xF)?, data[6]),
+            PacketKind::from_u16(kind_and_flags & 0xF)
+                .ok_or(PacketError::InvalidPacketKind(kind_and_flags & 0xF))?,
+        );
+        packet.flags = PacketFlags::from_bits_truncate(kind_and_flags >> 4);
+        packet.fragment_id = data[7];
+        packet.sequence_id = u16::from_le_bytes([data[10], data[11]]);
+
+        let payload_offset = HEADER_LENGTH + usize::from(data[1]);
+        let mut options = &data[HEADER_LENGTH..payload_offset];
+        while let [id, length, rest @ ..] = options {
+            let value = rest
+                .get(..usize::from(*length))
+                .ok_or(PacketError::OutOfBounds)?;
+            options = &rest[usize::from(*length)..];
+
+            // unknown options are skipped, as their length is known
+            match OptionId::from_u8(*id) {
+                Some(id @ OptionId::SupportedFunctions) => {
+                    packet.supported_functions = u32::from_le_bytes(
+                        value
+                            .try_into()
+                            .map_err(|_| PacketError::InvalidOption(id))?,
+                    )
+                }
+                Some(id @ OptionId::MaxSubstreamId) => {
+                    packet.max_substream_id = match value {
+                        [max_substream_id] => *max_substream_id,
+                        _ => return Err(PacketError::InvalidOption(id)),
+                    }
+                }
+                None => (),
+            }
+        }
+        if !options.is_empty() {
+            return Err(PacketError::OutOfBounds);
+        }
+
+        packet.payload = data[payload_offset..].to_vec();
+        Ok(packet)
+    }
+}
+
+impl common::Packet for Packet {
+    fn kind(&self) -> PacketKind {
+        self.kind
+    }
+
+    fn flags(&self) -> PacketFlags {
+        self.flags
+    }
+
+    fn sequence_id(&self) -> u16 {
+        self.sequence_id
+    }
+
+    fn fragment_id(&self) -> u8 {
+        self.fragment_id
+    }
+
+    fn payload_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.payload
+    }
+}
+
+/// An enumeration over the errors that may occur while encoding or decoding a [`Packet`]
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum PacketError {
+    /// An error returned when the data being decoded is too small
+    #[error("The provided data is not long enough to contain a packet")]
+    OutOfBounds,
+
+    /// An error returned when the data does not begin with the packet magic
+    #[error("The provided data does not begin with the packet magic")]
+    InvalidMagic,
+
+    /// An error returned when a [`VirtualPort`] has an unknown stream kind
+    #[error("`{0}` is not a known stream kind")]
+    InvalidStreamKind(u8),
+
+    /// An error returned when the packet has an unknown kind
+    #[error("`{0}` is not a known packet kind")]
+    InvalidPacketKind(u16),
+
+    /// An error returned when an option's value has the wrong length
+    #[error("The value of the `{0:?}` option has the wrong length")]
+    InvalidOption(OptionId),
+
+    /// An error returned when the payload is too large to have its size encoded
+    #[error("A payload of `{0}` bytes is too large to be encoded")]
+    PayloadTooLarge(usize),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn packet(kind: PacketKind, flags: PacketFlags, payload: &[u8]) -> Packet {
+        let mut packet = Packet::new(
+            VirtualPort::new(StreamKind::RvSecure, 0x1F),
+            VirtualPort::new(StreamKind::RvSecure, 1),
+            kind,
+        );
+        packet.flags = flags;
+        packet.fragment_id = 1;
+        packet.sequence_id = 3;
+        if let PacketKind::Syn | PacketKind::Connect = kind {
+            packet.supported_functions = 0x104;
+        }
+        packet.payload = payload.to_vec();
+        packet
+    }
+
+    #[test]
+    fn packet_round_trip() {
+        for packet in &[
+            packet(PacketKind::Syn, PacketFlags::NEED_ACK, &[]),
+            packet(
+                PacketKind::Connect,
+                PacketFlags::RELIABLE | PacketFlags::NEED_ACK | PacketFlags::HAS_SIZE,
+                b"ticket",
+            ),
+            packet(
+                PacketKind::Data,
+                PacketFlags::RELIABLE | PacketFlags::NEED_ACK | PacketFlags::HAS_SIZE,
+                b"payload",
+            ),
+            packet(PacketKind::Ping, PacketFlags::ACK, &[]),
+        ] {
+            let data = packet.to_bytes().unwrap();
+            assert_eq!(Packet::encoded_length(&data).unwrap(), data.len());
+            assert_eq!(&Packet::from_bytes(&data).unwrap(), packet);
+        }
+    }
+
+    #[test]
+    fn packet_encoding() {
+        assert_eq!(
+            packet(PacketKind::Data, PacketFlags::NEED_ACK, b"hi")
+                .to_bytes()
+                .unwrap(),
+            [0x80, 0x00, 0x02, 0x00, 0xAA, 0x1F, 0x01, 0x01, 0x42, 0x00, 0x03, 0x00, b'h', b'i']
+        );
+    }
+
+    #[test]
+    fn packet_framing() {
+        let mut data = packet(PacketKind::Data, PacketFlags::NEED_ACK, b"hi")
+            .to_bytes()
+            .unwrap();
+        let length = data.len();
+        data.extend_from_slice(&[MAGIC; 4]);
+
+        assert_eq!(Packet::encoded_length(&data).unwrap(), length);
+        assert_eq!(Packet::from_bytes(&data).unwrap().payload, b"hi");
+        assert!(matches!(
+            Packet::from_bytes(&data[..length - 1]),
+            Err(PacketError::OutOfBounds)
+        ));
+        assert!(matches!(
+            Packet::encoded_length(&data[1..]),
+            Err(PacketError::InvalidMagic)
+        ));
+    }
+}
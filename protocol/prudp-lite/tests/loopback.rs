@@ -0,0 +1,70 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// the delivery of messages is shared with the other versions of PRUDP and tested alongside
+// PRUDPv0, so these only cover what is specific to PRUDP Lite
+
+use tokio::io;
+
+use ralsei_protocol_prudp_lite::{
+    connection::{Connection, ConnectionError, Settings},
+    listener::Listener,
+    packet::{StreamKind, VirtualPort},
+};
+use ralsei_util::transport::Transport;
+
+const PORT: VirtualPort = VirtualPort {
+    stream: StreamKind::RvSecure,
+    port: 1,
+};
+
+#[tokio::test]
+async fn transport() {
+    let mut listener = Listener::bind("127.0.0.1:0", PORT, Settings::default())
+        .await
+        .expect("unable to bind a listener");
+    let (client, server) = tokio::join!(
+        Connection::connect(listener.local_addr(), PORT, b"ticket", Settings::default()),
+        listener.accept(),
+    );
+    let mut client: Box<dyn Transport> = Box::new(client.expect("unable to connect"));
+    let mut server = server.expect("unable to accept");
+
+    assert_eq!(server.connection_data(), b"ticket");
+    client.send(b"hello").await.unwrap();
+    assert_eq!(server.recv().await.unwrap(), b"hello");
+    server.send(b"hi").await.unwrap();
+    assert_eq!(client.recv().await.unwrap(), b"hi");
+
+    client.disconnect().await.expect("unable to disconnect");
+    assert!(matches!(server.recv().await, Err(ConnectionError::Closed)));
+}
+
+#[tokio::test]
+async fn stream() {
+    let (client, server) = io::duplex(0x1000);
+    let (client, server) = tokio::join!(
+        Connection::connect_over(client, PORT, &[], Settings::default()),
+        Connection::accept_over(server, PORT, Settings::default()),
+    );
+    let (client, mut server) = (
+        client.expect("unable to connect"),
+        server.expect("unable to accept"),
+    );
+
+    client.send(b"goodbye").await.unwrap();
+    drop(client);
+
+    assert_eq!(server.recv().await.unwrap(), b"goodbye");
+    assert!(matches!(server.recv().await, Err(ConnectionError::Closed)));
+    assert!(matches!(
+        server.send(b"hello?").await,
+        Err(ConnectionError::Closed)
+    ));
+}
@@ -0,0 +1,94 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! A collection of the cryptographic primitives used by PRUDPv0
+//!
+//! This includes the checksum appended to every packet and the signatures placed in their headers.
+//! The RC4 stream cipher used to encrypt the payloads of data packets is shared with the other
+//! PRUDP versions, and lives in [`ralsei_util::rc4`]
+
+use hmac::{Hmac, Mac, NewMac};
+use md5::{Digest, Md5};
+use std::{convert::TryInto, net::SocketAddr};
+
+/// The key used to encrypt the payloads of data packets when no other key is provided
+pub const DEFAULT_ENCRYPTION_KEY: &[u8] = b"CD&ML";
+
+/// The signature placed on data packets without a payload, such as acknowledgements
+pub const EMPTY_DATA_SIGNATURE: u32 = 0x12345678;
+
+/// Calculates the checksum of the provided packet data using the provided access key
+///
+/// The checksum is the low byte of the sum of the access key's bytes, the bytes of the wrapping sum
+/// of the data's little-endian words, and the bytes of the data that do not make up a full word
+pub fn checksum(access_key: &[u8], data: &[u8]) -> u8 {
+    let words = data.chunks_exact(4);
+    let remainder = words.remainder();
+    let sum = words.fold(0_u32, |sum, word| {
+        sum.wrapping_add(u32::from_le_bytes(
+            word.try_into().expect("a chunk was not four bytes long"),
+        ))
+    });
+
+    access_key
+        .iter()
+        .chain(remainder)
+        .chain(&sum.to_le_bytes())
+        .fold(0_u8, |checksum, &b| checksum.wrapping_add(b))
+}
+
+/// Calculates the signature of a data packet with the provided (encrypted) payload
+///
+/// This is the first four bytes of the HMAC-MD5 of the payload keyed with the MD5 of the access
+/// key, or [`EMPTY_DATA_SIGNATURE`] if the payload is empty
+pub fn data_signature(access_key: &[u8], payload: &[u8]) -> u32 {
+    if payload.is_empty() {
+        return EMPTY_DATA_SIGNATURE;
+    }
+
+    signature(access_key, payload)
+}
+
+/// Calculates the connection signature for the provided address
+///
+/// Connection signatures are exchanged during the handshake, and are placed in the headers of all
+/// packets sent to the peer that generated it (aside from data packets), allowing it to reject
+/// packets that do not belong to the connection
+pub fn connection_signature(access_key: &[u8], address: &SocketAddr) -> u32 {
+    let mut data = match address {
+        SocketAddr::V4(address) => address.ip().octets().to_vec(),
+        SocketAddr::V6(address) => address.ip().octets().to_vec(),
+    };
+    data.extend_from_slice(&address.port().to_be_bytes());
+    signature(access_key, &data)
+}
+
+/// Returns the first four bytes of the HMAC-MD5 of the provided data keyed with the MD5 of the
+/// access key as a little-endian integer
+fn signature(access_key: &[u8], data: &[u8]) -> u32 {
+    let mut mac = Hmac::<Md5>::new_from_slice(&Md5::digest(access_key))
+        .expect("hmac accepts keys of any length");
+    mac.update(data);
+    u32::from_le_bytes(
+        mac.finalize().into_bytes()[..4]
+            .try_into()
+            .expect("a slice was not four bytes long"),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checksum_sums_words_and_remainder() {
+        // the access key sums to 0x06, the word sums to 0x04030201, and the remainder is 0x05
+        assert_eq!(checksum(&[1, 2, 3], &[1, 2, 3, 4, 5]), 0x06 + 0x0A + 0x05);
+    }
+}
@@ -0,0 +1,53 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+#![allow(clippy::cognitive_complexity)]
+#![warn(clippy::cargo_common_metadata)]
+#![warn(clippy::dbg_macro)]
+#![warn(clippy::explicit_deref_methods)]
+#![warn(clippy::filetype_is_file)]
+#![warn(clippy::imprecise_flops)]
+#![warn(clippy::large_stack_arrays)]
+#![warn(clippy::todo)]
+#![warn(clippy::unimplemented)]
+#![deny(clippy::await_holding_lock)]
+#![deny(clippy::cast_lossless)]
+#![deny(clippy::clone_on_ref_ptr)]
+#![deny(clippy::doc_markdown)]
+#![deny(clippy::empty_enum)]
+#![deny(clippy::enum_glob_use)]
+#![deny(clippy::exit)]
+#![deny(clippy::explicit_into_iter_loop)]
+#![deny(clippy::explicit_iter_loop)]
+#![deny(clippy::fallible_impl_from)]
+#![deny(clippy::inefficient_to_string)]
+#![deny(clippy::large_digit_groups)]
+#![deny(clippy::wildcard_dependencies)]
+#![deny(clippy::wildcard_imports)]
+#![deny(clippy::unused_self)]
+#![deny(clippy::single_match_else)]
+#![deny(clippy::option_option)]
+#![deny(clippy::mut_mut)]
+
+//! An implementation of version 0 of the PRUDP transport protocol
+//!
+//! PRUDP is the reliable transport built on top of UDP that NEX game servers communicate over.
+//! Version 0 of it is the one used by the 3ds, and is implemented here on top of tokio. The
+//! [`packet`] module defines the wire format, the [`crypto`] module the checksums, signatures and
+//! stream encryption applied to it, and the [`connection`] and [`listener`] modules the client and
+//! server ends of a connection, respectively.
+//!
+//! For more information, see [the NintendoClients wiki]
+//!
+//! [the NintendoClients wiki]: https://github.com/kinnay/NintendoClients/wiki/PRUDP-Protocol
+
+pub mod connection;
+pub mod crypto;
+pub mod listener;
+pub mod packet;
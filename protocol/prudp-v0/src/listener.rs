@@ -0,0 +1,164 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The server end of a PRUDPv0 connection
+//!
+//! A [`Listener`] owns a UDP socket, answering the handshakes of clients and passing the packets
+//! of established connections on to their tasks
+
+use ralsei_util::prudp::listener::{self, Handshake, Reply, Respond};
+use std::{net::SocketAddr, sync::Arc};
+use tokio::{
+    net::{ToSocketAddrs, UdpSocket},
+    sync::mpsc,
+};
+
+use crate::{
+    connection::{Connection, ConnectionError, Settings},
+    crypto,
+    packet::{Packet, PacketError, PacketFlags, PacketKind, VirtualPort},
+};
+
+/// A PRUDPv0 server, accepting connections made to a single [`VirtualPort`]
+///
+/// Dropping it stops new connections from being accepted, but does not close the connections
+/// that have already been accepted
+#[derive(Debug)]
+pub struct Listener {
+    local_addr: SocketAddr,
+    connections: mpsc::UnboundedReceiver<Connection>,
+}
+
+impl Listener {
+    /// Binds a new [`Listener`] to the provided address, accepting connections made to the
+    /// provided [`VirtualPort`]
+    pub async fn bind<A>(
+        address: A,
+        port: VirtualPort,
+        settings: Settings,
+    ) -> Result<Self, ConnectionError>
+    where
+        A: ToSocketAddrs,
+    {
+        Self::bind_with(address, port, settings, |_| Some(Vec::new())).await
+    }
+
+    /// Binds a new [`Listener`] to the provided address, accepting connections made to the
+    /// provided [`VirtualPort`] and responding to the data passed in the payload of their connect
+    /// packets using the provided function
+    ///
+    /// The data the function returns is placed in the payload of the acknowledgement of the
+    /// connect packet, and clients it returns [`None`] for are not accepted
+    pub async fn bind_with<A, F>(
+        address: A,
+        port: VirtualPort,
+        settings: Settings,
+        respond: F,
+    ) -> Result<Self, ConnectionError>
+    where
+        A: ToSocketAddrs,
+        F: Fn(&[u8]) -> Option<Vec<u8>> + Send + Sync + 'static,
+    {
+        settings.validate()?;
+        let socket = Arc::new(UdpSocket::bind(address).await?);
+        let local_addr = socket.local_addr()?;
+
+        let (connections_sender, connections) = mpsc::unbounded_channel();
+        tokio::spawn(listener::dispatch(
+            socket,
+            Acceptor {
+                port,
+                settings: Arc::new(settings),
+                respond: Box::new(respond),
+            },
+            connections_sender,
+        ));
+
+        Ok(Self {
+            local_addr,
+            connections,
+        })
+    }
+
+    /// Returns the address that the [`Listener`] is bound to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Waits for a client to complete its handshake, returning the resulting [`Connection`]
+    pub async fn accept(&mut self) -> Result<Connection, ConnectionError> {
+        self.connections.recv().await.ok_or(ConnectionError::Closed)
+    }
+}
+
+/// The PRUDPv0 half of a [`Listener`], answering the handshakes of clients
+struct Acceptor {
+    port: VirtualPort,
+    settings: Arc<Settings>,
+    respond: Respond,
+}
+
+impl Handshake for Acceptor {
+    type Packet = Packet;
+    type Error = PacketError;
+    type Connection = Connection;
+
+    fn decode(&self, data: &[u8]) -> Option<Packet> {
+        Packet::from_bytes(data, &self.settings.access_key)
+            .ok()
+            .filter(|packet| packet.destination == self.port)
+    }
+
+    fn handshake(
+        &self,
+        socket: &Arc<UdpSocket>,
+        address: SocketAddr,
+        packet: &Packet,
+    ) -> Reply<Packet, PacketError, Connection> {
+        let connection_signature =
+            crypto::connection_signature(&self.settings.access_key, &address);
+        match packet.kind {
+            PacketKind::Syn => {
+                let syn_ack = Packet {
+                    source: self.port,
+                    destination: packet.source,
+                    kind: PacketKind::Syn,
+                    flags: PacketFlags::ACK,
+                    session_id: packet.session_id,
+                    signature: 0,
+                    sequence_id: packet.sequence_id,
+                    connection_signature,
+                    fragment_id: 0,
+                    payload: Vec::new(),
+                };
+                match syn_ack.to_bytes(&self.settings.access_key) {
+                    Ok(data) => Reply::Respond(data),
+                    Err(_) => Reply::Ignore,
+                }
+            }
+            PacketKind::Connect if packet.signature == connection_signature => {
+                let response_data = match (self.respond)(&packet.payload) {
+                    Some(response_data) => response_data,
+                    None => return Reply::Ignore,
+                };
+                match Connection::accept(
+                    Arc::clone(socket),
+                    address,
+                    &self.settings,
+                    packet,
+                    response_data,
+                ) {
+                    Ok((connection, peer)) => Reply::Accept(connection, peer),
+                    Err(_) => Reply::Ignore,
+                }
+            }
+            _ => Reply::Ignore,
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Type definitions for the PRUDPv0 wire format
+//!
+//! The main focal point of this module is the [`Packet`] structure, which can be encoded to and
+//! decoded from the bytes of a UDP datagram using [`Packet::to_bytes`] and [`Packet::from_bytes`]
+
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::cast::FromPrimitive;
+use std::convert::{TryFrom, TryInto};
+use thiserror::Error;
+
+use crate::crypto;
+use ralsei_util::prudp::packet as common;
+
+pub use ralsei_util::prudp::packet::{PacketFlags, PacketKind};
+
+/// The length of a packet's header, excluding the fields that are only present in some packets
+pub const HEADER_LENGTH: usize = 11;
+
+/// Enumeration of the kinds of streams a [`VirtualPort`] may refer to
+#[non_exhaustive]
+#[derive(FromPrimitive, ToPrimitive, Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum StreamKind {
+    Do = 1,
+    Rv = 2,
+    OldRvSec = 3,
+    SbMgmt = 4,
+    Nat = 5,
+    SessionDiscovery = 6,
+    NatEcho = 7,
+    Routing = 8,
+    Game = 9,
+    RvSecure = 10,
+    Relay = 11,
+}
+
+/// An endpoint within a PRUDP host, made up of a [`StreamKind`] and a port number
+///
+/// It is encoded as a single byte, with the stream kind in the upper nibble and the port in the
+/// lower one
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct VirtualPort {
+    /// The kind of stream the port is a part of
+    pub stream: StreamKind,
+
+    /// The port number, which must fit within four bits
+    pub port: u8,
+}
+
+impl VirtualPort {
+    /// Creates a new [`VirtualPort`] from its components
+    pub fn new(stream: StreamKind, port: u8) -> Self {
+        Self { stream, port }
+    }
+
+    /// Decodes a [`VirtualPort`] from its byte representation
+    pub fn from_byte(b: u8) -> Result<Self, PacketError> {
+        Ok(Self {
+            stream: StreamKind::from_u8(b >> 4).ok_or(PacketError::InvalidStreamKind(b >> 4))?,
+            port: b & 0xF,
+        })
+    }
+
+    /// Encodes the [`VirtualPort`] into its byte representation
+    pub fn to_byte(self) -> u8 {
+        ((self.stream as u8) << 4) | (self.port & 0xF)
+    }
+}
+
+/// A PRUDPv0 packet
+///
+/// The [`connection_signature`] field is only encoded for [`PacketKind::Syn`] and
+/// [`PacketKind::Connect`] packets, and the [`fragment_id`] field only for [`PacketKind::Data`]
+/// packets. For all other kinds of packets, they are ignored when encoding and zero when decoded
+///
+/// [`connection_signature`]: ./struct.Packet.html#structfield.connection_signature
+/// [`fragment_id`]: ./struct.Packet.html#structfield.fragment_id
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Packet {
+    /// The [`VirtualPort`] the packet was sent from
+    pub source: VirtualPort,
+
+    /// The [`VirtualPort`] the packet is being sent to
+    pub destination: VirtualPort,
+
+    /// The kind of packet
+    pub kind: PacketKind,
+
+    /// The packet's flags
+    pub flags: PacketFlags,
+
+    /// The id of the sender's session
+    pub session_id: u8,
+
+    /// The packet's signature, see the [`crypto`] module for how it is derived
+    pub signature: u32,
+
+    /// The packet's position in the sender's stream of packets
+    pub sequence_id: u16,
+
+    /// The sender's connection signature
+    pub connection_signature: u32,
+
+    /// The packet's position within a fragmented message, where zero marks the last fragment
+    pub fragment_id: u8,
+
+    /// The packet's payload
+    pub payload: Vec<u8>,
+}
+
+impl Packet {
+    /// Encodes the [`Packet`] into the bytes of a datagram, appending a checksum calculated using
+    /// the provided access key
+    pub fn to_bytes(&self, access_key: &[u8]) -> Result<Vec<u8>, PacketError> {
+        let mut data = Vec::with_capacity(HEADER_LENGTH + 8 + self.payload.len());
+        data.push(self.source.to_byte());
+        data.push(self.destination.to_byte());
+        data.extend_from_slice(&(self.kind as u16 | (self.flags.bits() << 4)).to_le_bytes());
+        data.push(self.session_id);
+        data.extend_from_slice(&self.signature.to_le_bytes());
+        data.extend_from_slice(&self.sequence_id.to_le_bytes());
+
+        match self.kind {
+            PacketKind::Syn | PacketKind::Connect => {
+                data.extend_from_slice(&self.connection_signature.to_le_bytes())
+            }
+            PacketKind::Data => data.push(self.fragment_id),
+            _ => (),
+        }
+
+        if self.flags.contains(PacketFlags::HAS_SIZE) {
+            data.extend_from_slice(
+                &u16::try_from(self.payload.len())
+                    .map_err(|_| PacketError::PayloadTooLarge(self.payload.len()))?
+                    .to_le_bytes(),
+            );
+        }
+
+        data.extend_from_slice(&self.payload);
+        data.push(crypto::checksum(access_key, &data));
+        Ok(data)
+    }
+
+    /// Decodes a [`Packet`] from the bytes of a datagram, verifying its checksum using the
+    /// provided access key
+    pub fn from_bytes(data: &[u8], access_key: &[u8]) -> Result<Self, PacketError> {
+        let (&checksum, data) = data.split_last().ok_or(PacketError::OutOfBounds)?;
+        if crypto::checksum(access_key, data) != checksum {
+            return Err(PacketError::InvalidChecksum);
+        }
+
+        let header = data.get(..HEADER_LENGTH).ok_or(PacketError::OutOfBounds)?;
+        let kind_and_flags = u16::from_le_bytes([header[2], header[3]]);
+        let kind = PacketKind::from_u16(kind_and_flags & 0xF)
+            .ok_or(PacketError::InvalidPacketKind(kind_and_flags & 0xF))?;
+        let flags = PacketFlags::from_bits_truncate(kind_and_flags >> 4);
+
+        let mut packet = Self {
+            source: VirtualPort::from_byte(header[0])?,
+            destination: VirtualPort::from_byte(header[1])?,
+            kind,
+            flags,
+            session_id: header[4],
+            signature: u32::from_le_bytes(
+                header[5..9]
+                    .try_into()
+                    .expect("a slice was not four bytes long"),
+            ),
+            sequence_id: u16::from_le_bytes([header[9], header[10]]),
+            connection_signature: 0,
+            fragment_id: 0,
+            payload: Vec::new(),
+        };
+
+        let mut offset = HEADER_LENGTH;
+        match kind {
+            PacketKind::Syn | PacketKind::Connect => {
+                packet.connection_signature = u32::from_le_bytes(
+                    data.get(offset..offset + 4)
+                        .ok_or(PacketError::OutOfBounds)?
+                        .try_into()
+                        .expect("a slice was not four bytes long"),
+                );
+                offset += 4;
+            }
+            PacketKind::Data => {
+                packet.fragment_id = *data.get(offset).ok_or(PacketError::OutOfBounds)?;
+                offset += 1;
+            }
+            _ => (),
+        }
+
+        let payload = if flags.contains(PacketFlags::HAS_SIZE) {
+            let size = data
+                .get(offset..offset + 2)
+                .ok_or(PacketError::OutOfBounds)?;
+            let size = usize::from(u16::from_le_bytes([size[0], size[1]]));
+            offset += 2;
+            data.get(offset..offset + size)
+                .ok_or(PacketError::OutOfBounds)?
+        } else {
+            data.get(offset..).ok_or(PacketError::OutOfBounds)?
+        };
+        packet.payload = payload.to_vec();
+
+        Ok(packet)
+    }
+}
+
+impl common::Packet for Packet {
+    fn kind(&self) -> PacketKind {
+        self.kind
+    }
+
+    fn flags(&self) -> PacketFlags {
+        self.flags
+    }
+
+    fn sequence_id(&self) -> u16 {
+        self.sequence_id
+    }
+
+    fn fragment_id(&self) -> u8 {
+        self.fragment_id
+    }
+
+    fn payload_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.payload
+    }
+}
+
+/// An enumeration over the errors that may occur while encoding or decoding a [`Packet`]
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum PacketError {
+    /// An error returned when the data being decoded is too small
+    #[error("The provided data is not long enough to contain a packet")]
+    OutOfBounds,
+
+    /// An error returned when the packet's checksum does not match its contents
+    #[error("The packet's checksum does not match its contents")]
+    InvalidChecksum,
+
+    /// An error returned when a [`VirtualPort`] has an unknown stream kind
+    #[error("`{0}` is not a known stream kind")]
+    InvalidStreamKind(u8),
+
+    /// An error returned when the packet has an unknown kind
+    #[error("`{0}` is not a known packet kind")]
+    InvalidPacketKind(u16),
+
+    /// An error returned when the payload is too large to have its size encoded
+    #[error("A payload of `{0}` bytes is too large to be encoded")]
+    PayloadTooLarge(usize),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ACCESS_KEY: &[u8] = b"ridfebb9";
+
+    fn packet(kind: PacketKind, flags: PacketFlags, payload: &[u8]) -> Packet {
+        Packet {
+            source: VirtualPort::new(StreamKind::RvSecure, 15),
+            destination: VirtualPort::new(StreamKind::RvSecure, 1),
+            kind,
+            flags,
+            session_id: 0x2A,
+            signature: 0xDEADBEEF,
+            sequence_id: 3,
+            connection_signature: if matches!(kind, PacketKind::Syn | PacketKind::Connect) {
+                0xCAFEBABE
+            } else {
+                0
+            },
+            fragment_id: if kind == PacketKind::Data { 1 } else { 0 },
+            payload: payload.to_vec(),
+        }
+    }
+
+    #[test]
+    fn packet_round_trip() {
+        for packet in &[
+            packet(PacketKind::Syn, PacketFlags::NEED_ACK, &[]),
+            packet(
+                PacketKind::Connect,
+                PacketFlags::RELIABLE | PacketFlags::NEED_ACK | PacketFlags::HAS_SIZE,
+                b"ticket",
+            ),
+            packet(
+                PacketKind::Data,
+                PacketFlags::RELIABLE | PacketFlags::NEED_ACK | PacketFlags::HAS_SIZE,
+                b"payload",
+            ),
+            packet(PacketKind::Data, PacketFlags::ACK, &[]),
+            packet(PacketKind::Ping, PacketFlags::NEED_ACK, b"unsized"),
+        ] {
+            let data = packet.to_bytes(ACCESS_KEY).unwrap();
+            assert_eq!(&Packet::from_bytes(&data, ACCESS_KEY).unwrap(), packet);
+        }
+    }
+
+    #[test]
+    fn packet_encoding() {
+        assert_eq!(
+            packet(PacketKind::Syn, PacketFlags::NEED_ACK, &[])
+                .to_bytes(ACCESS_KEY)
+                .unwrap()[..HEADER_LENGTH + 4],
+            [
+                0xAF, 0xA1, 0x40, 0x00, 0x2A, 0xEF, 0xBE, 0xAD, 0xDE, 0x03, 0x00, 0xBE, 0xBA, 0xFE,
+                0xCA
+            ]
+        );
+    }
+
+    #[test]
+    fn packet_invalid_checksum() {
+        let mut data = packet(PacketKind::Ping, PacketFlags::empty(), &[])
+            .to_bytes(ACCESS_KEY)
+            .unwrap();
+        *data.last_mut().unwrap() ^= 0xFF;
+        assert!(matches!(
+            Packet::from_bytes(&data, ACCESS_KEY),
+            Err(PacketError::InvalidChecksum)
+        ));
+    }
+}
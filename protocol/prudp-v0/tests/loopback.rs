@@ -0,0 +1,221 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::net::UdpSocket;
+
+use ralsei_protocol_prudp_v0::{
+    connection::{Connection, ConnectionError, Settings},
+    listener::Listener,
+    packet::{StreamKind, VirtualPort},
+};
+
+const ACCESS_KEY: &[u8] = b"ridfebb9";
+
+fn port() -> VirtualPort {
+    VirtualPort::new(StreamKind::RvSecure, 1)
+}
+
+fn settings() -> Settings {
+    Settings {
+        resend_timeout: Duration::from_millis(50),
+        max_resends: 20,
+        ..Settings::new(ACCESS_KEY)
+    }
+}
+
+/// Start a server on the loopback interface that echoes every message it receives back to its
+/// sender, returning its address
+async fn echo_server() -> SocketAddr {
+    let mut listener = Listener::bind("127.0.0.1:0", port(), settings())
+        .await
+        .expect("unable to bind a listener");
+    let address = listener.local_addr();
+
+    tokio::spawn(async move {
+        while let Ok(mut connection) = listener.accept().await {
+            tokio::spawn(async move {
+                // the connection data is echoed first, so that the client can check it arrived
+                let connection_data = connection.connection_data().to_vec();
+                connection
+                    .send(&connection_data)
+                    .await
+                    .expect("unable to echo the connection data");
+                while let Ok(message) = connection.recv().await {
+                    connection
+                        .send(&message)
+                        .await
+                        .expect("unable to echo a message");
+                }
+            });
+        }
+    });
+
+    address
+}
+
+/// Start a proxy on the loopback interface that forwards datagrams between a single client and the
+/// provided server, dropping every `n`th datagram in each direction, returning its address
+async fn lossy_proxy(server: SocketAddr, n: usize) -> SocketAddr {
+    let socket = Arc::new(
+        UdpSocket::bind("127.0.0.1:0")
+            .await
+            .expect("unable to bind the proxy"),
+    );
+    let address = socket
+        .local_addr()
+        .expect("unable to get the proxy's address");
+    let upstream = Arc::new(
+        UdpSocket::bind("127.0.0.1:0")
+            .await
+            .expect("unable to bind the proxy"),
+    );
+    upstream
+        .connect(server)
+        .await
+        .expect("unable to connect the proxy to the server");
+
+    // the server only ever responds to the client, so its address is known by then
+    let client = Arc::new(Mutex::new(None));
+    {
+        let (socket, upstream, client) = (
+            Arc::clone(&socket),
+            Arc::clone(&upstream),
+            Arc::clone(&client),
+        );
+        tokio::spawn(async move {
+            let mut buffer = vec![0; 0x10000];
+            for i in 1.. {
+                let (length, from) = socket.recv_from(&mut buffer).await.unwrap();
+                *client.lock().unwrap() = Some(from);
+                if i % n != 0 {
+                    let _ = upstream.send(&buffer[..length]).await;
+                }
+            }
+        });
+    }
+    tokio::spawn(async move {
+        let mut buffer = vec![0; 0x10000];
+        for i in 1.. {
+            let length = upstream.recv(&mut buffer).await.unwrap();
+            let to = client
+                .lock()
+                .unwrap()
+                .expect("the client's address is unknown");
+            if i % n != 0 {
+                let _ = socket.send_to(&buffer[..length], to).await;
+            }
+        }
+    });
+
+    address
+}
+
+#[tokio::test]
+async fn echo() {
+    let server = echo_server().await;
+    let mut connection = Connection::connect(server, port(), b"ticket", settings())
+        .await
+        .expect("unable to connect");
+
+    assert_eq!(connection.recv().await.unwrap(), b"ticket");
+    for message in &[&b"hello"[..], &[], &[0x2A; 0x1000]] {
+        connection.send(message).await.unwrap();
+        assert_eq!(&connection.recv().await.unwrap(), message);
+    }
+
+    connection.disconnect().await.expect("unable to disconnect");
+}
+
+#[tokio::test]
+async fn lossy() {
+    let server = echo_server().await;
+    let proxy = lossy_proxy(server, 4).await;
+    let mut connection = Connection::connect(proxy, port(), b"ticket", settings())
+        .await
+        .expect("unable to connect");
+
+    assert_eq!(connection.recv().await.unwrap(), b"ticket");
+    let messages: Vec<Vec<u8>> = (0..32_u8).map(|i| vec![i; usize::from(i) * 100]).collect();
+    for message in &messages {
+        connection.send(message).await.unwrap();
+    }
+    for message in &messages {
+        assert_eq!(&connection.recv().await.unwrap(), message);
+    }
+
+    connection.disconnect().await.expect("unable to disconnect");
+}
+
+#[tokio::test]
+async fn disconnect() {
+    let mut listener = Listener::bind("127.0.0.1:0", port(), settings())
+        .await
+        .expect("unable to bind a listener");
+    let client = Connection::connect(listener.local_addr(), port(), &[], settings())
+        .await
+        .expect("unable to connect");
+    let mut server = listener.accept().await.expect("unable to accept");
+
+    client.send(b"goodbye").await.unwrap();
+    drop(client);
+
+    assert_eq!(server.recv().await.unwrap(), b"goodbye");
+    assert!(matches!(server.recv().await, Err(ConnectionError::Closed)));
+    assert!(matches!(
+        server.send(b"hello?").await,
+        Err(ConnectionError::Closed)
+    ));
+}
+
+#[tokio::test]
+async fn listener_shutdown() {
+    let mut listener = Listener::bind("127.0.0.1:0", port(), settings())
+        .await
+        .expect("unable to bind a listener");
+    let address = listener.local_addr();
+    let client = Connection::connect(address, port(), &[], settings())
+        .await
+        .expect("unable to connect");
+    let mut server = listener.accept().await.expect("unable to accept");
+
+    drop(client);
+    assert!(matches!(server.recv().await, Err(ConnectionError::Closed)));
+    drop(server);
+    drop(listener);
+
+    // the socket is only released once the task dispatching its packets has exited
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while UdpSocket::bind(address).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the listener's socket was never released");
+}
+
+#[tokio::test]
+async fn empty_encryption_key() {
+    let settings = Settings {
+        encryption_key: Vec::new(),
+        ..settings()
+    };
+    assert!(matches!(
+        Listener::bind("127.0.0.1:0", port(), settings.clone()).await,
+        Err(ConnectionError::EmptyEncryptionKey)
+    ));
+    assert!(matches!(
+        Connection::connect("127.0.0.1:1", port(), &[], settings).await,
+        Err(ConnectionError::EmptyEncryptionKey)
+    ));
+}
@@ -0,0 +1,422 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The client end of a PRUDPv1 connection, along with the parts of it shared with the server end
+//!
+//! A [`Connection`] is a handle to a task that drives the connection, which takes care of
+//! acknowledging, retransmitting, fragmenting, encrypting and ordering the packets sent over each
+//! of its substreams, leaving the handle to send and receive whole messages. The task itself is
+//! shared with the other versions of PRUDP, and lives in [`ralsei_util::prudp::connection`],
+//! leaving this module with the handshake and the signing of the packets it sends
+
+use async_trait::async_trait;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    net::{lookup_host, ToSocketAddrs, UdpSocket},
+    sync::mpsc,
+};
+
+use crate::{
+    crypto::{self, SIGNATURE_LENGTH},
+    packet::{Packet, PacketError, PacketFlags, PacketKind, VirtualPort},
+};
+use ralsei_util::{
+    prudp::{
+        self,
+        connection::{Datagram, Handle, Reliability, State},
+    },
+    transport::{Transport, TransportError},
+};
+
+/// The settings used by both ends of a PRUDPv1 connection
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Settings {
+    /// The game-specific access key, used in calculating signatures
+    pub access_key: Vec<u8>,
+
+    /// The key used to encrypt the payloads of data packets
+    ///
+    /// This is [`DEFAULT_ENCRYPTION_KEY`](crypto::DEFAULT_ENCRYPTION_KEY) unless connecting to a
+    /// secure server, where the session key is used instead
+    pub encryption_key: Vec<u8>,
+
+    /// The session key established with a secure server, which is included in the signatures of
+    /// packets sent after the handshake
+    ///
+    /// This is empty unless connecting to a secure server
+    pub session_key: Vec<u8>,
+
+    /// The largest payload placed in a single data packet before a message is fragmented
+    pub fragment_size: usize,
+
+    /// The time to wait for an acknowledgement before retransmitting a packet
+    pub resend_timeout: Duration,
+
+    /// The number of times a packet is retransmitted before the connection is considered to have
+    /// timed out
+    pub max_resends: u8,
+
+    /// The time to wait without sending anything before sending a ping to keep the connection
+    /// alive
+    pub ping_interval: Duration,
+
+    /// The highest substream id that may be used, which is negotiated down to the lower of the
+    /// two peers' values during the handshake
+    pub max_substream_id: u8,
+
+    /// The functionality advertised to the peer during the handshake
+    ///
+    /// This is not otherwise interpreted by this crate
+    pub supported_functions: u32,
+}
+
+impl Settings {
+    /// Creates a new [`Settings`] using the provided access key and the default values for
+    /// everything else
+    pub fn new(access_key: &[u8]) -> Self {
+        Self {
+            access_key: access_key.to_vec(),
+            encryption_key: crypto::DEFAULT_ENCRYPTION_KEY.to_vec(),
+            session_key: Vec::new(),
+            fragment_size: 1000,
+            resend_timeout: Duration::from_secs(1),
+            max_resends: 5,
+            ping_interval: Duration::from_secs(5),
+            max_substream_id: 0,
+            supported_functions: 0,
+        }
+    }
+
+    /// Returns the parts of the [`Settings`] that govern how messages are delivered
+    pub(crate) fn reliability(&self) -> Reliability {
+        Reliability {
+            fragment_size: self.fragment_size,
+            resend_timeout: self.resend_timeout,
+            max_resends: self.max_resends,
+            ping_interval: self.ping_interval,
+        }
+    }
+
+    /// Checks that the [`Settings`] can be used to open a connection
+    ///
+    /// The encryption key seeds RC4, which cannot be keyed with nothing
+    pub(crate) fn validate(&self) -> Result<(), ConnectionError> {
+        if self.encryption_key.is_empty() {
+            return Err(ConnectionError::EmptyEncryptionKey);
+        }
+        Ok(())
+    }
+}
+
+/// A handle to an established PRUDPv1 connection
+///
+/// Dropping it disconnects from the peer once everything sent has been acknowledged
+#[derive(Debug)]
+pub struct Connection {
+    peer: SocketAddr,
+    connection_data: Vec<u8>,
+    response_data: Vec<u8>,
+    handle: Handle<PacketError>,
+}
+
+impl Connection {
+    /// Connects to the PRUDPv1 server at the provided address and [`VirtualPort`], passing it the
+    /// provided data in the payload of the connect packet
+    ///
+    /// The data the server responds with is available through
+    /// [`response_data`](Self::response_data)
+    ///
+    /// The connection is made from the same kind of stream as the destination, on port 15
+    pub async fn connect<A>(
+        address: A,
+        destination: VirtualPort,
+        connection_data: &[u8],
+        settings: Settings,
+    ) -> Result<Self, ConnectionError>
+    where
+        A: ToSocketAddrs,
+    {
+        settings.validate()?;
+        let peer = lookup_host(address)
+            .await?
+            .next()
+            .ok_or(ConnectionError::NoAddress)?;
+        let socket = Arc::new(
+            UdpSocket::bind(if peer.is_ipv4() {
+                "0.0.0.0:0"
+            } else {
+                "[::]:0"
+            })
+            .await?,
+        );
+
+        let (packets_sender, mut packets) = mpsc::unbounded_channel();
+        tokio::spawn(prudp::connection::read_datagrams(
+            Arc::clone(&socket),
+            peer,
+            Packet::from_bytes,
+            packets_sender,
+        ));
+
+        let settings = Arc::new(settings);
+        let initial_sequence_id = rand::random();
+        let endpoint = Endpoint {
+            settings: Arc::clone(&settings),
+            local: VirtualPort::new(destination.stream, 0xF),
+            remote: destination,
+            session_id: rand::random(),
+            local_signature: rand::random(),
+            remote_signature: [0; SIGNATURE_LENGTH],
+            max_substream_id: settings.max_substream_id,
+            initial_sequence_id,
+        };
+        let mut state = State::new(
+            endpoint,
+            Datagram::new(socket, peer),
+            settings.reliability(),
+            Some(&settings.encryption_key),
+            settings.max_substream_id,
+            initial_sequence_id,
+            0,
+        )?;
+
+        // the syn packet is answered with the server's connection signature and the highest
+        // substream id it is willing to use
+        let syn = state.packet(PacketKind::Syn, PacketFlags::NEED_ACK, 0, 0, Vec::new());
+        let syn_ack = state
+            .exchange(&mut packets, syn, |packet| {
+                packet.kind == PacketKind::Syn && packet.flags.contains(PacketFlags::ACK)
+            })
+            .await?;
+        state.codec_mut().remote_signature = syn_ack.connection_signature;
+        negotiate_max_substream_id(&mut state, syn_ack.max_substream_id);
+
+        // the connect packet occupies the first sequence id of the first substream, and is
+        // answered with the sequence id the server's substreams begin at
+        let sequence_id = state.next_sequence_id(0);
+        let connect = state.packet(
+            PacketKind::Connect,
+            PacketFlags::RELIABLE | PacketFlags::NEED_ACK | PacketFlags::HAS_SIZE,
+            0,
+            sequence_id,
+            connection_data.to_vec(),
+        );
+        let connect_ack = state
+            .exchange(&mut packets, connect, |packet| {
+                packet.kind == PacketKind::Connect && packet.flags.contains(PacketFlags::ACK)
+            })
+            .await?;
+        state.expect_sequence_id(connect_ack.initial_sequence_id);
+
+        Ok(Self {
+            peer,
+            connection_data: connection_data.to_vec(),
+            response_data: connect_ack.payload,
+            handle: state.spawn(packets),
+        })
+    }
+
+    /// Creates a [`Connection`] for a client that has connected to a server, returning it along
+    /// with the channel its packets are to be passed into
+    ///
+    /// The client's connect packet is acknowledged with the provided data in its payload
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn accept(
+        socket: Arc<UdpSocket>,
+        peer: SocketAddr,
+        settings: Arc<Settings>,
+        connect: &Packet,
+        response_data: Vec<u8>,
+    ) -> Result<(Self, mpsc::UnboundedSender<Result<Packet, ConnectionError>>), ConnectionError>
+    {
+        let (packets_sender, packets) = mpsc::unbounded_channel();
+        let initial_sequence_id = rand::random();
+        let endpoint = Endpoint {
+            settings: Arc::clone(&settings),
+            local: connect.destination,
+            remote: connect.source,
+            session_id: rand::random(),
+            local_signature: crypto::connection_signature(&settings.access_key, &peer),
+            remote_signature: connect.connection_signature,
+            max_substream_id: settings.max_substream_id,
+            initial_sequence_id,
+        };
+        let mut state = State::new(
+            endpoint,
+            Datagram::new(socket, peer),
+            settings.reliability(),
+            Some(&settings.encryption_key),
+            settings.max_substream_id,
+            initial_sequence_id,
+            connect.initial_sequence_id,
+        )?;
+        negotiate_max_substream_id(&mut state, connect.max_substream_id);
+        state.respond_to_connect(response_data.clone());
+
+        Ok((
+            Self {
+                peer,
+                connection_data: connect.payload.clone(),
+                response_data,
+                handle: state.spawn(packets),
+            },
+            packets_sender,
+        ))
+    }
+
+    /// Returns the address of the peer on the other end of the [`Connection`]
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Returns the data the client passed in the payload of its connect packet
+    pub fn connection_data(&self) -> &[u8] {
+        &self.connection_data
+    }
+
+    /// Returns the data the server passed in the payload of its acknowledgement of the connect
+    /// packet
+    pub fn response_data(&self) -> &[u8] {
+        &self.response_data
+    }
+
+    /// Returns the highest substream id that may be used on the [`Connection`], as negotiated
+    /// during the handshake
+    pub fn max_substream_id(&self) -> u8 {
+        self.handle.max_substream_id()
+    }
+
+    /// Sends the provided message to the peer on the first substream, fragmenting it if necessary
+    ///
+    /// The message is queued to be sent by the connection's task, so this only fails if the
+    /// connection has been closed
+    pub async fn send(&self, message: &[u8]) -> Result<(), ConnectionError> {
+        self.send_on(0, message).await
+    }
+
+    /// Sends the provided message to the peer on the provided substream, fragmenting it if
+    /// necessary
+    ///
+    /// Messages sent on different substreams are delivered independently of each other, so a lost
+    /// packet on one does not hold back the others
+    pub async fn send_on(&self, substream_id: u8, message: &[u8]) -> Result<(), ConnectionError> {
+        self.handle.send(substream_id, message)
+    }
+
+    /// Receives the next message sent by the peer on any substream
+    ///
+    /// Once the connection has been closed, this returns the error that closed it (if any), and
+    /// [`ConnectionError::Closed`] after that
+    pub async fn recv(&mut self) -> Result<Vec<u8>, ConnectionError> {
+        Ok(self.recv_with_substream().await?.1)
+    }
+
+    /// Receives the next message sent by the peer, along with the id of the substream it was sent
+    /// on
+    ///
+    /// See [`Connection::recv`] for details
+    pub async fn recv_with_substream(&mut self) -> Result<(u8, Vec<u8>), ConnectionError> {
+        self.handle.recv().await
+    }
+
+    /// Disconnects from the peer once everything sent has been acknowledged
+    pub async fn disconnect(self) -> Result<(), ConnectionError> {
+        self.handle.disconnect().await
+    }
+}
+
+#[async_trait]
+impl Transport for Connection {
+    async fn send(&self, message: &[u8]) -> Result<(), TransportError> {
+        Ok(Connection::send(self, message).await?)
+    }
+
+    async fn recv(&mut self) -> Result<Vec<u8>, TransportError> {
+        Ok(Connection::recv(self).await?)
+    }
+
+    async fn disconnect(self: Box<Self>) -> Result<(), TransportError> {
+        Ok(Connection::disconnect(*self).await?)
+    }
+}
+
+/// Lowers the highest substream id that may be used to the one requested by the peer, if it is
+/// lower
+fn negotiate_max_substream_id(state: &mut State<Endpoint, Datagram>, max_substream_id: u8) {
+    let max_substream_id = state.max_substream_id().min(max_substream_id);
+    state.truncate_substreams(max_substream_id);
+    state.codec_mut().max_substream_id = max_substream_id;
+}
+
+/// The addressing and signing information of one end of a PRUDPv1 connection
+#[derive(Debug)]
+struct Endpoint {
+    settings: Arc<Settings>,
+    local: VirtualPort,
+    remote: VirtualPort,
+    session_id: u8,
+    local_signature: [u8; SIGNATURE_LENGTH],
+    remote_signature: [u8; SIGNATURE_LENGTH],
+    max_substream_id: u8,
+    initial_sequence_id: u16,
+}
+
+impl prudp::packet::Codec for Endpoint {
+    type Packet = Packet;
+    type Error = PacketError;
+
+    fn packet(
+        &self,
+        kind: PacketKind,
+        flags: PacketFlags,
+        substream_id: u8,
+        sequence_id: u16,
+        fragment_id: u8,
+        payload: Vec<u8>,
+    ) -> Packet {
+        let mut packet = Packet::new(self.local, self.remote, kind);
+        packet.flags = flags;
+        packet.session_id = self.session_id;
+        packet.substream_id = substream_id;
+        packet.sequence_id = sequence_id;
+        packet.supported_functions = self.settings.supported_functions;
+        packet.connection_signature = self.local_signature;
+        packet.fragment_id = fragment_id;
+        packet.initial_sequence_id = self.initial_sequence_id;
+        packet.max_substream_id = self.max_substream_id;
+        packet.payload = payload;
+        packet
+    }
+
+    fn encode(&self, mut packet: Packet) -> Result<Vec<u8>, PacketError> {
+        packet.sign(
+            &self.settings.access_key,
+            &self.settings.session_key,
+            &self.remote_signature,
+        )?;
+        packet.to_bytes()
+    }
+
+    fn is_signed(&self, packet: &Packet) -> bool {
+        packet.verify(
+            &self.settings.access_key,
+            &self.settings.session_key,
+            &self.local_signature,
+        )
+    }
+}
+
+/// An enumeration over the errors that may occur while using a PRUDPv1 connection
+pub type ConnectionError = prudp::connection::ConnectionError<PacketError>;
+
+impl From<PacketError> for ConnectionError {
+    fn from(error: PacketError) -> Self {
+        Self::PacketError(error)
+    }
+}
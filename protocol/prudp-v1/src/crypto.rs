@@ -0,0 +1,111 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! A collection of the cryptographic primitives used by PRUDPv1
+//!
+//! This includes the signature placed on every packet and the connection signatures exchanged
+//! during the handshake. The RC4 stream cipher used to encrypt the payloads of data packets is
+//! shared with the other PRUDP versions, and lives in [`ralsei_util::rc4`]
+
+use hmac::{Hmac, Mac, NewMac};
+use md5::{Digest, Md5};
+use std::net::SocketAddr;
+
+/// The key used to encrypt the payloads of data packets when no other key is provided
+pub const DEFAULT_ENCRYPTION_KEY: &[u8] = b"CD&ML";
+
+/// The length of a packet or connection signature
+pub const SIGNATURE_LENGTH: usize = 16;
+
+/// Calculates the signature of a packet from its parts
+///
+/// This is the HMAC-MD5, keyed with the MD5 of the access key, of the part of the header following
+/// the payload size, the session key (empty on connections to anything but a secure server), the
+/// sum of the access key's bytes, the connection signature of the packet's recipient (empty for
+/// [`Syn`](crate::packet::PacketKind::Syn) packets), the encoded options, and the payload
+pub fn packet_signature(
+    access_key: &[u8],
+    session_key: &[u8],
+    connection_signature: &[u8],
+    header: &[u8],
+    options: &[u8],
+    payload: &[u8],
+) -> [u8; SIGNATURE_LENGTH] {
+    let access_key_sum = access_key
+        .iter()
+        .fold(0_u32, |sum, &b| sum.wrapping_add(u32::from(b)));
+
+    let mut mac = mac(access_key);
+    mac.update(header);
+    mac.update(session_key);
+    mac.update(&access_key_sum.to_le_bytes());
+    mac.update(connection_signature);
+    mac.update(options);
+    mac.update(payload);
+    mac.finalize().into_bytes().into()
+}
+
+/// Calculates the connection signature a server assigns to the provided address
+///
+/// The server's connection signature is sent to the client in its response to the client's
+/// [`Syn`](crate::packet::PacketKind::Syn) packet, and used in the signatures of all packets the
+/// client sends after that
+pub fn connection_signature(access_key: &[u8], address: &SocketAddr) -> [u8; SIGNATURE_LENGTH] {
+    let mut mac = mac(access_key);
+    match address {
+        SocketAddr::V4(address) => mac.update(&address.ip().octets()),
+        SocketAddr::V6(address) => mac.update(&address.ip().octets()),
+    }
+    mac.update(&address.port().to_be_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+/// Creates an HMAC-MD5 instance keyed with the MD5 of the access key
+fn mac(access_key: &[u8]) -> Hmac<Md5> {
+    Hmac::<Md5>::new_from_slice(&Md5::digest(access_key)).expect("hmac accepts keys of any length")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // the expected values were computed using an independent implementation of the signatures as
+    // described on the NintendoClients wiki
+
+    const ACCESS_KEY: &[u8] = b"6f599f81";
+
+    #[test]
+    fn connection_signature_vector() {
+        assert_eq!(
+            connection_signature(ACCESS_KEY, &"192.168.1.10:60000".parse().unwrap()),
+            [
+                0xFB, 0x8D, 0xC0, 0x6A, 0x16, 0x92, 0x6E, 0xC1, 0x9F, 0x19, 0x2F, 0x92, 0xD6, 0x6B,
+                0xED, 0x9E
+            ]
+        );
+    }
+
+    #[test]
+    fn packet_signature_vector() {
+        assert_eq!(
+            packet_signature(
+                ACCESS_KEY,
+                b"0123456789abcdef0123456789abcdef",
+                &connection_signature(ACCESS_KEY, &"192.168.1.10:60000".parse().unwrap()),
+                &[0xAF, 0xA1, 0xE2, 0x00, 0x2A, 0x01, 0x35, 0x12],
+                &[0x02, 0x01, 0x00],
+                b"hello",
+            ),
+            [
+                0x01, 0xC9, 0x2E, 0xD7, 0x3A, 0x61, 0xCC, 0x2B, 0x5F, 0x70, 0xB9, 0x6C, 0xD0, 0x16,
+                0xB2, 0x71
+            ]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+// the delivery of messages is shared with the other versions of PRUDP and tested alongside
+// PRUDPv0, so these only cover what is specific to PRUDPv1
+
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+use ralsei_protocol_prudp_v1::{
+    connection::{Connection, ConnectionError, Settings},
+    listener::Listener,
+    packet::{StreamKind, VirtualPort},
+};
+
+const ACCESS_KEY: &[u8] = b"6f599f81";
+
+const PORT: VirtualPort = VirtualPort {
+    stream: StreamKind::RvSecure,
+    port: 1,
+};
+
+#[tokio::test]
+async fn substreams() {
+    let settings = Settings {
+        resend_timeout: Duration::from_millis(50),
+        max_substream_id: 2,
+        ..Settings::new(ACCESS_KEY)
+    };
+    let mut listener = Listener::bind("127.0.0.1:0", PORT, settings.clone())
+        .await
+        .expect("unable to bind a listener");
+    let mut client = Connection::connect(
+        listener.local_addr(),
+        PORT,
+        b"ticket",
+        Settings {
+            max_substream_id: 1,
+            ..settings
+        },
+    )
+    .await
+    .expect("unable to connect");
+    let mut server = listener.accept().await.expect("unable to accept");
+    assert_eq!(server.connection_data(), b"ticket");
+
+    // the server allows more substreams than the client, so the client's limit is used by both
+    assert_eq!(client.max_substream_id(), 1);
+    assert_eq!(server.max_substream_id(), 1);
+    assert!(matches!(
+        client.send_on(2, b"hello").await,
+        Err(ConnectionError::InvalidSubstream(2))
+    ));
+
+    client.send_on(1, b"one").await.unwrap();
+    assert_eq!(
+        server.recv_with_substream().await.unwrap(),
+        (1, b"one".to_vec())
+    );
+    server.send_on(0, b"zero").await.unwrap();
+    assert_eq!(
+        client.recv_with_substream().await.unwrap(),
+        (0, b"zero".to_vec())
+    );
+
+    client.disconnect().await.expect("unable to disconnect");
+}
+
+#[tokio::test]
+async fn listener_shutdown() {
+    let settings = Settings {
+        resend_timeout: Duration::from_millis(50),
+        ..Settings::new(ACCESS_KEY)
+    };
+    let mut listener = Listener::bind("127.0.0.1:0", PORT, settings.clone())
+        .await
+        .expect("unable to bind a listener");
+    let address = listener.local_addr();
+    let client = Connection::connect(address, PORT, &[], settings)
+        .await
+        .expect("unable to connect");
+    let mut server = listener.accept().await.expect("unable to accept");
+
+    client.disconnect().await.expect("unable to disconnect");
+    assert!(matches!(server.recv().await, Err(ConnectionError::Closed)));
+    drop(server);
+    drop(listener);
+
+    // the socket is only released once the task dispatching its packets has exited
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while UdpSocket::bind(address).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the listener's socket was never released");
+}
+
+#[tokio::test]
+async fn empty_encryption_key() {
+    let settings = Settings {
+        encryption_key: Vec::new(),
+        ..Settings::new(ACCESS_KEY)
+    };
+    assert!(matches!(
+        Listener::bind("127.0.0.1:0", PORT, settings.clone()).await,
+        Err(ConnectionError::EmptyEncryptionKey)
+    ));
+    assert!(matches!(
+        Connection::connect("127.0.0.1:1", PORT, &[], settings).await,
+        Err(ConnectionError::EmptyEncryptionKey)
+    ));
+}
@@ -0,0 +1,141 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The calling end of an RMC connection
+//!
+//! The client stubs generated by [`protocol`](crate::protocol::protocol) are implemented for
+//! anything implementing [`Caller`], such as the [`Client`] provided here
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+use crate::{
+    codec::{Context, DecodeError, EncodeError},
+    message::{Message, MessageError, Request, Response, ResponseBody},
+    types::QResult,
+};
+use ralsei_util::transport::{Transport, TransportError};
+
+/// Something that can call RMC methods and wait for their results
+#[async_trait]
+pub trait Caller: Send {
+    /// Calls the provided method of the provided protocol with the provided serialized
+    /// parameters, returning its serialized results
+    async fn call(
+        &mut self,
+        protocol_id: u16,
+        method_id: u32,
+        parameters: Vec<u8>,
+    ) -> Result<Vec<u8>, CallError>;
+
+    /// Returns the [`Context`] that the results of methods are deserialized under
+    fn context(&self) -> Context {
+        Context::default()
+    }
+}
+
+/// An RMC client, calling methods over a [`Transport`] one at a time
+///
+/// Requests sent by the server and responses to calls that are no longer being waited on are
+/// discarded
+pub struct Client {
+    transport: Box<dyn Transport>,
+    context: Context,
+    next_call_id: u32,
+}
+
+impl Client {
+    /// Creates a new [`Client`] that calls methods over the provided [`Transport`]
+    pub fn new(transport: Box<dyn Transport>) -> Self {
+        Self::with_context(transport, Context::default())
+    }
+
+    /// Creates a new [`Client`] that calls methods over the provided [`Transport`],
+    /// deserializing their results under the provided [`Context`]
+    pub fn with_context(transport: Box<dyn Transport>, context: Context) -> Self {
+        Self {
+            transport,
+            context,
+            next_call_id: 1,
+        }
+    }
+
+    /// Returns the [`Transport`] the [`Client`] calls methods over
+    pub fn into_inner(self) -> Box<dyn Transport> {
+        self.transport
+    }
+}
+
+#[async_trait]
+impl Caller for Client {
+    async fn call(
+        &mut self,
+        protocol_id: u16,
+        method_id: u32,
+        parameters: Vec<u8>,
+    ) -> Result<Vec<u8>, CallError> {
+        let call_id = self.next_call_id;
+        self.next_call_id = self.next_call_id.wrapping_add(1);
+
+        let request = Message::Request(Request {
+            protocol_id,
+            call_id,
+            method_id,
+            parameters,
+        });
+        self.transport.send(&request.to_bytes()?).await?;
+
+        loop {
+            if let Message::Response(Response {
+                protocol_id: response_protocol_id,
+                call_id: response_call_id,
+                body,
+            }) = Message::from_bytes(&self.transport.recv().await?)?
+            {
+                if response_protocol_id != protocol_id || response_call_id != call_id {
+                    continue;
+                }
+
+                return match body {
+                    ResponseBody::Success { results, .. } => Ok(results),
+                    ResponseBody::Error(result) => Err(CallError::Failed(result)),
+                };
+            }
+        }
+    }
+
+    fn context(&self) -> Context {
+        self.context
+    }
+}
+
+/// An enumeration over the errors that may occur while calling an RMC method
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum CallError {
+    /// An error was encountered by the underlying transport
+    #[error("An error was encountered by the underlying transport")]
+    TransportError(#[from] TransportError),
+
+    /// An error was encountered while encoding the method's parameters or the request
+    #[error("An error was encountered while encoding the method's parameters or the request")]
+    EncodeError(#[from] EncodeError),
+
+    /// An error was encountered while decoding a message
+    #[error("An error was encountered while decoding a message")]
+    MessageError(#[from] MessageError),
+
+    /// An error was encountered while decoding the method's results
+    #[error("An error was encountered while decoding the method's results")]
+    DecodeError(#[from] DecodeError),
+
+    /// An error returned when the method returned an error code
+    #[error("The method failed with the result `{0}`")]
+    Failed(QResult),
+}
@@ -0,0 +1,482 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The serialization of the values passed to and returned from RMC methods
+//!
+//! Values are serialized using the [`Encode`] and [`Decode`] traits, which are implemented here
+//! for the primitive types, strings, lists (as [`Vec`]s), maps (as [`BTreeMap`]s and
+//! [`HashMap`]s) and tuples, and in the [`types`](crate::types) module for the types specific to
+//! NEX. Everything is encoded in little-endian byte order
+//!
+//! Structures declared using the [`structure`](crate::protocol::structure) macro may opt into
+//! the header used by NEX 3.5 and later, which is written and read using [`encode_structure`] and
+//! [`decode_structure`]
+//!
+//! The few values whose layout depends upon the version of NEX in use are deserialized under a
+//! [`Context`] naming it, which is passed along to the values they contain
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    hash::Hash,
+    mem,
+    str::Utf8Error,
+};
+use thiserror::Error;
+
+/// A value that can be serialized into the body of an RMC message
+pub trait Encode {
+    /// Appends the serialized value to the provided buffer
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError>;
+
+    /// Serializes the value into a new buffer
+    fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut buffer = Vec::new();
+        self.encode(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+/// The circumstances a value is deserialized under, which decide the layout of values that
+/// differ between versions of NEX
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Context {
+    /// The version of NEX the other end is running, with each part of the version taking up two
+    /// decimal digits (for example, 30500 for 3.5.0)
+    pub nex_version: u32,
+}
+
+impl Context {
+    /// Creates a new [`Context`] for the provided version of NEX
+    pub fn new(nex_version: u32) -> Self {
+        Self { nex_version }
+    }
+}
+
+/// A value that can be deserialized from the body of an RMC message
+pub trait Decode: Sized {
+    /// Deserializes a value from the start of the provided data, advancing it past the value
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError>;
+
+    /// Deserializes a value from the start of the provided data under the provided [`Context`],
+    /// advancing it past the value
+    ///
+    /// Values whose layout does not depend upon the [`Context`] need not implement this
+    fn decode_in(data: &mut &[u8], context: &Context) -> Result<Self, DecodeError> {
+        let _ = context;
+        Self::decode(data)
+    }
+
+    /// Deserializes a value from the provided data, which must not contain anything else
+    fn from_bytes(data: &[u8]) -> Result<Self, DecodeError> {
+        Self::from_bytes_in(data, &Context::default())
+    }
+
+    /// Deserializes a value from the provided data under the provided [`Context`], which must not
+    /// contain anything else
+    fn from_bytes_in(mut data: &[u8], context: &Context) -> Result<Self, DecodeError> {
+        let value = Self::decode_in(&mut data, context)?;
+        if !data.is_empty() {
+            return Err(DecodeError::TrailingData(data.len()));
+        }
+        Ok(value)
+    }
+}
+
+/// Splits the provided number of bytes off of the start of the provided data
+pub(crate) fn take<'a>(data: &mut &'a [u8], length: usize) -> Result<&'a [u8], DecodeError> {
+    if data.len() < length {
+        return Err(DecodeError::OutOfBounds);
+    }
+    let (taken, rest) = data.split_at(length);
+    *data = rest;
+    Ok(taken)
+}
+
+/// Converts a length into the provided integer type for use as a length prefix
+pub(crate) fn length_prefix<T>(length: usize) -> Result<T, EncodeError>
+where
+    T: TryFrom<usize>,
+{
+    T::try_from(length).map_err(|_| EncodeError::TooLong(length))
+}
+
+/// Appends the fields of a structure, as serialized by the provided function, to the provided
+/// buffer
+///
+/// If a version is provided, the fields are preceded by the header used by NEX 3.5 and later,
+/// made up of the version of the structure and the length of its fields. Structures that inherit
+/// from another are serialized as the inherited structure followed by their own fields, each
+/// with their own header
+pub fn encode_structure<F>(
+    buffer: &mut Vec<u8>,
+    version: Option<u8>,
+    fields: F,
+) -> Result<(), EncodeError>
+where
+    F: FnOnce(&mut Vec<u8>) -> Result<(), EncodeError>,
+{
+    match version {
+        Some(version) => {
+            let mut contents = Vec::new();
+            fields(&mut contents)?;
+            version.encode(buffer)?;
+            length_prefix::<u32>(contents.len())?.encode(buffer)?;
+            buffer.append(&mut contents);
+            Ok(())
+        }
+        None => fields(buffer),
+    }
+}
+
+/// Deserializes the fields of a structure from the start of the provided data using the provided
+/// function, advancing it past them
+///
+/// If a version is provided, the fields are expected to be preceded by the header used by NEX
+/// 3.5 and later, and must make up the length it specifies. Newer versions of the structure than
+/// the one provided are rejected, as they may carry fields that are not known about
+pub fn decode_structure<T, F>(
+    data: &mut &[u8],
+    name: &'static str,
+    version: Option<u8>,
+    fields: F,
+) -> Result<T, DecodeError>
+where
+    F: FnOnce(&mut &[u8]) -> Result<T, DecodeError>,
+{
+    match version {
+        Some(version) => {
+            let found = u8::decode(data)?;
+            if found > version {
+                return Err(DecodeError::UnsupportedVersion(name, found));
+            }
+            let length = u32::decode(data)?;
+            let mut contents = take(data, length as usize)?;
+            let value = fields(&mut contents)?;
+            if !contents.is_empty() {
+                return Err(DecodeError::TrailingData(contents.len()));
+            }
+            Ok(value)
+        }
+        None => fields(data),
+    }
+}
+
+macro generate_number_impls($($type:ty),*) {
+    $(
+        impl Encode for $type {
+            fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+                buffer.extend_from_slice(&self.to_le_bytes());
+                Ok(())
+            }
+        }
+
+        impl Decode for $type {
+            fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+                let mut bytes = [0; mem::size_of::<$type>()];
+                bytes.copy_from_slice(take(data, mem::size_of::<$type>())?);
+                Ok(<$type>::from_le_bytes(bytes))
+            }
+        }
+    )*
+}
+
+generate_number_impls!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl Encode for bool {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        buffer.push(u8::from(*self));
+        Ok(())
+    }
+}
+
+impl Decode for bool {
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(u8::decode(data)? != 0)
+    }
+}
+
+/// Strings are encoded as a 16-bit length followed by the string's UTF-8 bytes and a
+/// null terminator, which is included in the length
+impl Encode for str {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        length_prefix::<u16>(self.len() + 1)?.encode(buffer)?;
+        buffer.extend_from_slice(self.as_bytes());
+        buffer.push(0);
+        Ok(())
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        self.as_str().encode(buffer)
+    }
+}
+
+impl Decode for String {
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        let length = u16::decode(data)?;
+        let mut bytes = take(data, usize::from(length))?;
+
+        // the null terminator is optional, as some implementations omit it
+        if let [rest @ .., 0] = bytes {
+            bytes = rest;
+        }
+        Ok(std::str::from_utf8(bytes)?.to_string())
+    }
+}
+
+/// Lists are encoded as a 32-bit count followed by their elements
+impl<T> Encode for Vec<T>
+where
+    T: Encode,
+{
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        length_prefix::<u32>(self.len())?.encode(buffer)?;
+        for element in self {
+            element.encode(buffer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Decode for Vec<T>
+where
+    T: Decode,
+{
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        Self::decode_in(data, &Context::default())
+    }
+
+    fn decode_in(data: &mut &[u8], context: &Context) -> Result<Self, DecodeError> {
+        let count = u32::decode(data)?;
+
+        // the count is untrusted, so the capacity is bounded by the amount of data remaining
+        let mut list = Vec::with_capacity((count as usize).min(data.len()));
+        for _ in 0..count {
+            list.push(T::decode_in(data, context)?);
+        }
+        Ok(list)
+    }
+}
+
+/// Maps are encoded as a 32-bit count followed by their key-value pairs
+impl<K, V> Encode for BTreeMap<K, V>
+where
+    K: Encode,
+    V: Encode,
+{
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        length_prefix::<u32>(self.len())?.encode(buffer)?;
+        for (key, value) in self {
+            key.encode(buffer)?;
+            value.encode(buffer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<K, V> Decode for BTreeMap<K, V>
+where
+    K: Decode + Ord,
+    V: Decode,
+{
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        Self::decode_in(data, &Context::default())
+    }
+
+    fn decode_in(data: &mut &[u8], context: &Context) -> Result<Self, DecodeError> {
+        let count = u32::decode(data)?;
+        let mut map = BTreeMap::new();
+        for _ in 0..count {
+            let _ = map.insert(K::decode_in(data, context)?, V::decode_in(data, context)?);
+        }
+        Ok(map)
+    }
+}
+
+impl<K, V> Encode for HashMap<K, V>
+where
+    K: Encode,
+    V: Encode,
+{
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        length_prefix::<u32>(self.len())?.encode(buffer)?;
+        for (key, value) in self {
+            key.encode(buffer)?;
+            value.encode(buffer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<K, V> Decode for HashMap<K, V>
+where
+    K: Decode + Eq + Hash,
+    V: Decode,
+{
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        Self::decode_in(data, &Context::default())
+    }
+
+    fn decode_in(data: &mut &[u8], context: &Context) -> Result<Self, DecodeError> {
+        let count = u32::decode(data)?;
+        let mut map = HashMap::new();
+        for _ in 0..count {
+            let _ = map.insert(K::decode_in(data, context)?, V::decode_in(data, context)?);
+        }
+        Ok(map)
+    }
+}
+
+impl<T> Encode for &T
+where
+    T: Encode + ?Sized,
+{
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        (*self).encode(buffer)
+    }
+}
+
+/// Tuples are encoded as their elements in order, which is how methods with several parameters
+/// or results are represented
+macro generate_tuple_impls($(($($name:ident),*)),*) {
+    $(
+        impl<$($name),*> Encode for ($($name,)*)
+        where
+            $($name: Encode,)*
+        {
+            #[allow(non_snake_case, unused_variables)]
+            fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+                let ($($name,)*) = self;
+                $($name.encode(buffer)?;)*
+                Ok(())
+            }
+        }
+
+        impl<$($name),*> Decode for ($($name,)*)
+        where
+            $($name: Decode,)*
+        {
+            fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+                Self::decode_in(data, &Context::default())
+            }
+
+            #[allow(unused_variables)]
+            fn decode_in(data: &mut &[u8], context: &Context) -> Result<Self, DecodeError> {
+                Ok(($($name::decode_in(data, context)?,)*))
+            }
+        }
+    )*
+}
+
+generate_tuple_impls!(
+    (),
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H)
+);
+
+/// An enumeration over the errors that may occur while serializing a value
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum EncodeError {
+    /// An error returned when a value is too long to have its length encoded
+    #[error("A value of length `{0}` is too long to have its length encoded")]
+    TooLong(usize),
+}
+
+/// An enumeration over the errors that may occur while deserializing a value
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum DecodeError {
+    /// An error returned when the data ends before the value does
+    #[error("The provided data is not long enough to contain the value")]
+    OutOfBounds,
+
+    /// An error returned when there is data left over after the value
+    #[error("`{0}` bytes were left over after the value")]
+    TrailingData(usize),
+
+    /// An error returned when a string is not valid UTF-8
+    #[error("A string is not valid UTF-8")]
+    InvalidString(#[from] Utf8Error),
+
+    /// An error returned when a value is not one of those allowed
+    #[error("`{1}` is not a valid `{0}`")]
+    InvalidValue(&'static str, String),
+
+    /// An error returned when a structure is of a newer version than is supported
+    #[error("Version `{1}` of `{0}` is not supported")]
+    UnsupportedVersion(&'static str, u8),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn string_encoding() {
+        assert_eq!(
+            "nex".to_bytes().unwrap(),
+            [0x04, 0x00, b'n', b'e', b'x', 0x00]
+        );
+        assert_eq!(
+            String::from_bytes(&"nex".to_bytes().unwrap()).unwrap(),
+            "nex"
+        );
+        assert_eq!(
+            String::from_bytes(&[0x03, 0x00, b'n', b'e', b'x']).unwrap(),
+            "nex"
+        );
+        assert!(matches!(
+            String::from_bytes(&[0x05, 0x00, b'n', b'e', b'x', 0x00]),
+            Err(DecodeError::OutOfBounds)
+        ));
+        assert!(matches!(
+            "n".repeat(0xFFFF).to_bytes(),
+            Err(EncodeError::TooLong(0x10000))
+        ));
+    }
+
+    #[test]
+    fn collection_round_trip() {
+        let list = vec![1_u32, 2, 3];
+        assert_eq!(
+            list.to_bytes().unwrap(),
+            [3, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0]
+        );
+        assert_eq!(
+            Vec::<u32>::from_bytes(&list.to_bytes().unwrap()).unwrap(),
+            list
+        );
+
+        let mut map = BTreeMap::new();
+        let _ = map.insert("key".to_string(), true);
+        assert_eq!(
+            BTreeMap::<String, bool>::from_bytes(&map.to_bytes().unwrap()).unwrap(),
+            map
+        );
+
+        let tuple = (0x2A_u8, -1_i64, "nex".to_string());
+        assert_eq!(
+            <(u8, i64, String)>::from_bytes(&tuple.to_bytes().unwrap()).unwrap(),
+            tuple
+        );
+        assert!(matches!(
+            u8::from_bytes(&[0, 0]),
+            Err(DecodeError::TrailingData(1))
+        ));
+    }
+}
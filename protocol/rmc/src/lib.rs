@@ -0,0 +1,60 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+#![allow(clippy::cognitive_complexity)]
+#![warn(clippy::cargo_common_metadata)]
+#![warn(clippy::dbg_macro)]
+#![warn(clippy::explicit_deref_methods)]
+#![warn(clippy::filetype_is_file)]
+#![warn(clippy::imprecise_flops)]
+#![warn(clippy::large_stack_arrays)]
+#![warn(clippy::todo)]
+#![warn(clippy::unimplemented)]
+#![deny(clippy::await_holding_lock)]
+#![deny(clippy::cast_lossless)]
+#![deny(clippy::clone_on_ref_ptr)]
+#![deny(clippy::doc_markdown)]
+#![deny(clippy::empty_enum)]
+#![deny(clippy::enum_glob_use)]
+#![deny(clippy::exit)]
+#![deny(clippy::explicit_into_iter_loop)]
+#![deny(clippy::explicit_iter_loop)]
+#![deny(clippy::fallible_impl_from)]
+#![deny(clippy::inefficient_to_string)]
+#![deny(clippy::large_digit_groups)]
+#![deny(clippy::wildcard_dependencies)]
+#![deny(clippy::wildcard_imports)]
+#![deny(clippy::unused_self)]
+#![deny(clippy::single_match_else)]
+#![deny(clippy::option_option)]
+#![deny(clippy::mut_mut)]
+#![feature(decl_macro)]
+
+//! An implementation of RMC, the remote method call protocol spoken by NEX servers
+//!
+//! RMC sits on top of a PRUDP connection (any [`Transport`] will do), and is made up of requests
+//! to call a method of a protocol and responses carrying either the method's results or an error
+//! code. The [`message`] module defines the framing of these messages, the [`codec`] and [`types`]
+//! modules the serialization of the values passed to and returned from methods, and the
+//! [`protocol`] module a macro for declaring protocols, which generates typed client stubs for
+//! use with a [`Client`] and server traits whose implementations can be served using [`serve`].
+//!
+//! For more information, see [the NintendoClients wiki]
+//!
+//! [`Transport`]: ralsei_util::transport::Transport
+//! [`Client`]: client::Client
+//! [`serve`]: server::serve
+//! [the NintendoClients wiki]: https://github.com/kinnay/NintendoClients/wiki/RMC-Protocol
+
+pub mod client;
+pub mod codec;
+pub mod message;
+pub mod protocol;
+pub mod server;
+pub mod types;
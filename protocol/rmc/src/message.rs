@@ -0,0 +1,262 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Type definitions for the framing of RMC messages
+//!
+//! Every message begins with its length and the id of the protocol it concerns, where the most
+//! significant bit of the id is set on requests. Requests go on to carry the id of the call and
+//! the method being called, followed by the method's serialized parameters, while responses carry
+//! either the id of the call and method followed by the method's serialized results, or an error
+//! code and the id of the call
+
+use std::convert::TryFrom;
+use thiserror::Error;
+
+use crate::{
+    codec::{length_prefix, Decode, DecodeError, Encode, EncodeError},
+    types::QResult,
+};
+
+/// The bit set on the protocol id of requests
+const REQUEST_FLAG: u8 = 0x80;
+
+/// The protocol id signifying that the actual protocol id follows as a 16-bit integer
+const EXTENDED_PROTOCOL_ID: u8 = 0x7F;
+
+/// The bit set on the method id of successful responses
+const RESPONSE_METHOD_FLAG: u32 = 0x8000;
+
+/// A request to call a method of a protocol
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Request {
+    /// The id of the protocol the method is a part of
+    pub protocol_id: u16,
+
+    /// The id of the call, which is echoed in the response
+    pub call_id: u32,
+
+    /// The id of the method being called
+    pub method_id: u32,
+
+    /// The method's serialized parameters
+    pub parameters: Vec<u8>,
+}
+
+/// A response to a [`Request`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Response {
+    /// The id of the protocol the called method is a part of
+    pub protocol_id: u16,
+
+    /// The id of the call being responded to
+    pub call_id: u32,
+
+    /// The outcome of the call
+    pub body: ResponseBody,
+}
+
+/// The outcome of a call, as carried by a [`Response`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ResponseBody {
+    /// The call succeeded
+    Success {
+        /// The id of the method that was called
+        method_id: u32,
+
+        /// The method's serialized results
+        results: Vec<u8>,
+    },
+
+    /// The call failed with the contained error code
+    Error(QResult),
+}
+
+/// An RMC message
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Message {
+    Request(Request),
+    Response(Response),
+}
+
+impl Message {
+    /// Encodes the [`Message`] into bytes
+    ///
+    /// This fails if the message is larger than 4 GiB
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut body = Vec::new();
+        match self {
+            Self::Request(request) => {
+                encode_protocol_id(&mut body, request.protocol_id, REQUEST_FLAG)?;
+                request.call_id.encode(&mut body)?;
+                request.method_id.encode(&mut body)?;
+                body.extend_from_slice(&request.parameters);
+            }
+            Self::Response(response) => {
+                encode_protocol_id(&mut body, response.protocol_id, 0)?;
+                match &response.body {
+                    ResponseBody::Success { method_id, results } => {
+                        true.encode(&mut body)?;
+                        response.call_id.encode(&mut body)?;
+                        (method_id | RESPONSE_METHOD_FLAG).encode(&mut body)?;
+                        body.extend_from_slice(results);
+                    }
+                    ResponseBody::Error(result) => {
+                        false.encode(&mut body)?;
+                        result.encode(&mut body)?;
+                        response.call_id.encode(&mut body)?;
+                    }
+                }
+            }
+        }
+
+        let mut data = length_prefix::<u32>(body.len())?.to_bytes()?;
+        data.append(&mut body);
+        Ok(data)
+    }
+
+    /// Decodes a [`Message`] from bytes
+    pub fn from_bytes(mut data: &[u8]) -> Result<Self, MessageError> {
+        let length = u32::decode(&mut data)?;
+        if length as usize != data.len() {
+            return Err(MessageError::LengthMismatch(length, data.len()));
+        }
+
+        let (protocol_id, is_request) = match u8::decode(&mut data)? {
+            id if id & !REQUEST_FLAG == EXTENDED_PROTOCOL_ID => {
+                (u16::decode(&mut data)?, id & REQUEST_FLAG != 0)
+            }
+            id => (u16::from(id & !REQUEST_FLAG), id & REQUEST_FLAG != 0),
+        };
+
+        Ok(if is_request {
+            Self::Request(Request {
+                protocol_id,
+                call_id: u32::decode(&mut data)?,
+                method_id: u32::decode(&mut data)?,
+                parameters: data.to_vec(),
+            })
+        } else if bool::decode(&mut data)? {
+            Self::Response(Response {
+                protocol_id,
+                call_id: u32::decode(&mut data)?,
+                body: ResponseBody::Success {
+                    method_id: u32::decode(&mut data)? & !RESPONSE_METHOD_FLAG,
+                    results: data.to_vec(),
+                },
+            })
+        } else {
+            let result = QResult::decode(&mut data)?;
+            Self::Response(Response {
+                protocol_id,
+                call_id: u32::decode(&mut data)?,
+                body: ResponseBody::Error(result),
+            })
+        })
+    }
+}
+
+/// Encodes the provided protocol id, using the extended form if it does not fit in seven bits
+fn encode_protocol_id(
+    buffer: &mut Vec<u8>,
+    protocol_id: u16,
+    flags: u8,
+) -> Result<(), EncodeError> {
+    match u8::try_from(protocol_id) {
+        Ok(id) if id < EXTENDED_PROTOCOL_ID => {
+            buffer.push(id | flags);
+            Ok(())
+        }
+        _ => {
+            buffer.push(EXTENDED_PROTOCOL_ID | flags);
+            protocol_id.encode(buffer)
+        }
+    }
+}
+
+/// An enumeration over the errors that may occur while decoding a [`Message`]
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum MessageError {
+    /// An error was encountered while decoding one of the message's fields
+    #[error("An error was encountered while decoding one of the message's fields")]
+    DecodeError(#[from] DecodeError),
+
+    /// An error returned when the message's length does not match the amount of data provided
+    #[error("The message claims to be `{0}` bytes long, but `{1}` bytes were provided")]
+    LengthMismatch(u32, usize),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn request_encoding() {
+        let request = Message::Request(Request {
+            protocol_id: 10,
+            call_id: 1,
+            method_id: 2,
+            parameters: vec![0x2A],
+        });
+        let data = request.to_bytes().unwrap();
+        assert_eq!(
+            data,
+            [0x0A, 0x00, 0x00, 0x00, 0x8A, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x2A]
+        );
+        assert_eq!(Message::from_bytes(&data).unwrap(), request);
+
+        let request = Message::Request(Request {
+            protocol_id: 0x1234,
+            call_id: 1,
+            method_id: 2,
+            parameters: Vec::new(),
+        });
+        let data = request.to_bytes().unwrap();
+        assert_eq!(data[4..7], [0xFF, 0x34, 0x12]);
+        assert_eq!(Message::from_bytes(&data).unwrap(), request);
+    }
+
+    #[test]
+    fn response_encoding() {
+        let success = Message::Response(Response {
+            protocol_id: 10,
+            call_id: 1,
+            body: ResponseBody::Success {
+                method_id: 2,
+                results: vec![0x2A],
+            },
+        });
+        let data = success.to_bytes().unwrap();
+        assert_eq!(
+            data,
+            [
+                0x0B, 0x00, 0x00, 0x00, 0x0A, 0x01, 0x01, 0x00, 0x00, 0x00, 0x02, 0x80, 0x00, 0x00,
+                0x2A
+            ]
+        );
+        assert_eq!(Message::from_bytes(&data).unwrap(), success);
+
+        let error = Message::Response(Response {
+            protocol_id: 10,
+            call_id: 1,
+            body: ResponseBody::Error(QResult::NOT_IMPLEMENTED),
+        });
+        let data = error.to_bytes().unwrap();
+        assert_eq!(
+            data,
+            [0x0A, 0x00, 0x00, 0x00, 0x0A, 0x00, 0x02, 0x00, 0x01, 0x80, 0x01, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(Message::from_bytes(&data).unwrap(), error);
+
+        assert!(matches!(
+            Message::from_bytes(&data[..data.len() - 1]),
+            Err(MessageError::LengthMismatch(10, 9))
+        ));
+    }
+}
@@ -0,0 +1,327 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The declaration of RMC protocols
+//!
+//! Protocols are declared using the [`protocol`] macro, which generates typed client stubs and a
+//! server trait from a list of methods, and the structures passed to and returned from them using
+//! the [`structure`] macro
+
+#[doc(hidden)]
+pub use async_trait::async_trait;
+
+/// An RMC protocol, as declared using the [`protocol`] macro
+pub trait Protocol {
+    /// The protocol's id
+    const ID: u16;
+}
+
+/// A macro used to declare an RMC protocol and its methods
+///
+/// Given a protocol named `Example`, it generates:
+///
+/// - a unit struct named `Example` implementing [`Protocol`]
+/// - a trait (here, `ExampleClient`) with an asynchronous stub for each method that serializes
+///   its parameters, calls the method and deserializes its results, which is implemented for
+///   every [`Caller`](crate::client::Caller)
+/// - a trait (here, `ExampleServer`) with an asynchronous method for each method of the protocol,
+///   to be implemented using [`async_trait`](https://docs.rs/async-trait)
+/// - a struct (here, `ExampleDispatcher`) wrapping an implementation of the server trait, which
+///   implements [`Dispatch`](crate::server::Dispatch) so that it can be served
+///
+/// The parameters and results of each method are serialized in order, so a method may take at
+/// most eight parameters and return a tuple of at most eight results
+///
+/// # Example
+///
+/// ```
+/// use ralsei_protocol_rmc::{protocol::protocol, types::QResult};
+///
+/// protocol! {
+///     /// A protocol for keeping count
+///     pub protocol Counter = 100 {
+///         client CounterClient;
+///         server CounterServer => CounterDispatcher;
+///
+///         /// Adds the provided amount to the count, returning the new count
+///         1 => fn add(amount: u32) -> (QResult, u32);
+///
+///         /// Resets the count to zero
+///         2 => fn reset() -> ();
+///     }
+/// }
+/// ```
+pub macro protocol(
+    $(#[$meta:meta])*
+    $vis:vis protocol $protocol:ident = $protocol_id:literal {
+        client $client:ident;
+        server $server:ident => $dispatcher:ident;
+        $(
+            $(#[$method_meta:meta])*
+            $method_id:literal => fn $method:ident(
+                $($parameter:ident: $parameter_type:ty),* $(,)?
+            ) -> $results:ty;
+        )*
+    }
+) {
+    $(#[$meta])*
+    #[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+    $vis struct $protocol;
+
+    impl $crate::protocol::Protocol for $protocol {
+        const ID: u16 = $protocol_id;
+    }
+
+    /// The client stubs of the protocol's methods
+    #[allow(clippy::too_many_arguments)]
+    #[$crate::protocol::async_trait]
+    $vis trait $client: $crate::client::Caller {
+        $(
+            $(#[$method_meta])*
+            async fn $method(
+                &mut self,
+                $($parameter: $parameter_type),*
+            ) -> Result<$results, $crate::client::CallError> {
+                let mut parameters = Vec::new();
+                $($crate::codec::Encode::encode(&$parameter, &mut parameters)?;)*
+                let results =
+                    $crate::client::Caller::call(self, $protocol_id, $method_id, parameters).await?;
+                Ok(<$results as $crate::codec::Decode>::from_bytes_in(
+                    &results,
+                    &$crate::client::Caller::context(self),
+                )?)
+            }
+        )*
+    }
+
+    impl<C> $client for C where C: $crate::client::Caller + ?Sized {}
+
+    /// The server end of the protocol's methods
+    #[allow(clippy::too_many_arguments)]
+    #[$crate::protocol::async_trait]
+    $vis trait $server: Send + Sync {
+        $(
+            $(#[$method_meta])*
+            async fn $method(
+                &self,
+                $($parameter: $parameter_type),*
+            ) -> Result<$results, $crate::types::QResult>;
+        )*
+    }
+
+    /// A wrapper around an implementation of the protocol's server trait that dispatches calls to
+    /// it
+    $vis struct $dispatcher<T>(pub T);
+
+    #[$crate::protocol::async_trait]
+    impl<T> $crate::server::Dispatch for $dispatcher<T>
+    where
+        T: $server,
+    {
+        fn protocol_id(&self) -> u16 {
+            $protocol_id
+        }
+
+        #[allow(unused_variables)]
+        async fn dispatch(
+            &self,
+            method_id: u32,
+            parameters: &[u8],
+        ) -> Result<Vec<u8>, $crate::types::QResult> {
+            match method_id {
+                $(
+                    $method_id => {
+                        let ($($parameter,)*) =
+                            <($($parameter_type,)*) as $crate::codec::Decode>::from_bytes(
+                                parameters,
+                            )
+                            .map_err(|_| $crate::types::QResult::INVALID_ARGUMENT)?;
+                        let results = self.0.$method($($parameter),*).await?;
+                        $crate::codec::Encode::to_bytes(&results)
+                            .map_err(|_| $crate::types::QResult::BUFFER_OVERFLOW)
+                    }
+                )*
+                _ => Err($crate::types::QResult::NOT_IMPLEMENTED),
+            }
+        }
+    }
+}
+
+/// A macro used to declare the structures passed to and returned from RMC methods
+///
+/// Each structure is serialized as its fields, in the order they are declared in. Structures
+/// that inherit from another are declared with a field holding the inherited structure in
+/// parentheses after their name, which is serialized before the rest of their fields
+///
+/// Servers running NEX 3.5 or later precede every structure with a header, made up of the
+/// version of the structure and the length of its fields. Structures declared with a version
+/// after their name (and after the inherited structure, if any) are serialized with this header,
+/// see [`encode_structure`](crate::codec::encode_structure) for details
+///
+/// # Example
+///
+/// ```
+/// use ralsei_protocol_rmc::{codec::Decode, codec::Encode, protocol::structure};
+///
+/// structure! {
+///     /// A title and the version of it in use
+///     #[derive(Clone, Debug, Default, PartialEq)]
+///     pub struct GameKey {
+///         /// The title's id
+///         pub title_id: u64,
+///
+///         /// The title's version
+///         pub title_version: u16,
+///     }
+///
+///     /// A [`GameKey`] along with the title's region, serialized with the header of NEX 3.5
+///     #[derive(Clone, Debug, Default, PartialEq)]
+///     pub struct RegionalGameKey(pub game_key: GameKey) version 1 {
+///         /// The title's region
+///         pub region: u8,
+///     }
+/// }
+///
+/// let key = RegionalGameKey {
+///     game_key: GameKey {
+///         title_id: 0x0005_0000_1010_EC00,
+///         title_version: 16,
+///     },
+///     region: 2,
+/// };
+/// assert_eq!(
+///     RegionalGameKey::from_bytes(&key.to_bytes()?)?,
+///     key
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub macro structure($(
+    $(#[$meta:meta])*
+    $vis:vis struct $structure:ident
+        $(($(#[$parent_meta:meta])* $parent_vis:vis $parent:ident: $parent_type:ty))?
+        $(version $version:literal)?
+    {
+        $(
+            $(#[$field_meta:meta])*
+            $field_vis:vis $field:ident: $field_type:ty
+        ),* $(,)?
+    }
+)*) {
+    $(
+        $(#[$meta])*
+        $vis struct $structure {
+            $(
+                $(#[$parent_meta])*
+                $parent_vis $parent: $parent_type,
+            )?
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $field_type,
+            )*
+        }
+
+        impl $crate::codec::Encode for $structure {
+            #[allow(unused_variables)]
+            fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), $crate::codec::EncodeError> {
+                $($crate::codec::Encode::encode(&self.$parent, buffer)?;)?
+                $crate::codec::encode_structure(
+                    buffer,
+                    $crate::protocol::structure_version!($($version)?),
+                    |buffer| {
+                        $($crate::codec::Encode::encode(&self.$field, buffer)?;)*
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        impl $crate::codec::Decode for $structure {
+            fn decode(data: &mut &[u8]) -> Result<Self, $crate::codec::DecodeError> {
+                Self::decode_in(data, &$crate::codec::Context::default())
+            }
+
+            #[allow(unused_variables)]
+            fn decode_in(
+                data: &mut &[u8],
+                context: &$crate::codec::Context,
+            ) -> Result<Self, $crate::codec::DecodeError> {
+                $(let $parent = <$parent_type as $crate::codec::Decode>::decode_in(data, context)?;)?
+                $crate::codec::decode_structure(
+                    data,
+                    stringify!($structure),
+                    $crate::protocol::structure_version!($($version)?),
+                    |data| {
+                        Ok(Self {
+                            $($parent,)?
+                            $($field: <$field_type as $crate::codec::Decode>::decode_in(
+                                data,
+                                context,
+                            )?,)*
+                        })
+                    },
+                )
+            }
+        }
+    )*
+}
+
+/// Expands to the version of a structure declared using the [`structure`] macro, if it has one
+#[doc(hidden)]
+pub macro structure_version {
+    () => {
+        None
+    },
+    ($version:literal) => {
+        Some($version)
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::codec::{Decode, DecodeError, Encode};
+
+    structure! {
+        #[derive(Clone, Debug, Default, PartialEq)]
+        struct Parent version 1 {
+            id: u32,
+        }
+
+        #[derive(Clone, Debug, Default, PartialEq)]
+        struct Child(parent: Parent) version 0 {
+            name: String,
+        }
+    }
+
+    #[test]
+    fn versioned_structure() {
+        let child = Child {
+            parent: Parent { id: 0x2A },
+            name: "nex".to_string(),
+        };
+        let data = child.to_bytes().unwrap();
+        assert_eq!(
+            data,
+            [
+                0x01, 0x04, 0x00, 0x00, 0x00, 0x2A, 0x00, 0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00,
+                0x04, 0x00, b'n', b'e', b'x', 0x00
+            ]
+        );
+        assert_eq!(Child::from_bytes(&data).unwrap(), child);
+
+        assert!(matches!(
+            Parent::from_bytes(&[0x02, 0x04, 0x00, 0x00, 0x00, 0x2A, 0x00, 0x00, 0x00]),
+            Err(DecodeError::UnsupportedVersion("Parent", 2))
+        ));
+        assert!(matches!(
+            Parent::from_bytes(&[0x01, 0x05, 0x00, 0x00, 0x00, 0x2A, 0x00, 0x00, 0x00, 0x00]),
+            Err(DecodeError::TrailingData(1))
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The serving end of an RMC connection
+//!
+//! The dispatchers generated by [`protocol`](crate::protocol::protocol) implement [`Dispatch`],
+//! and any number of them can be served over a [`Transport`] using [`serve`]
+
+use async_trait::async_trait;
+
+use crate::{
+    codec::EncodeError,
+    message::{Message, Request, Response, ResponseBody},
+    types::QResult,
+};
+use ralsei_util::transport::{Transport, TransportError};
+
+/// Something that can handle calls to the methods of a single protocol
+#[async_trait]
+pub trait Dispatch: Send + Sync {
+    /// Returns the id of the protocol whose methods are handled
+    fn protocol_id(&self) -> u16;
+
+    /// Calls the provided method with the provided serialized parameters, returning its
+    /// serialized results
+    async fn dispatch(&self, method_id: u32, parameters: &[u8]) -> Result<Vec<u8>, QResult>;
+}
+
+/// Answers the requests received over the provided [`Transport`] using the provided protocols
+/// until the connection is closed
+///
+/// Calls to protocols that are not provided are answered with [`QResult::NOT_IMPLEMENTED`], calls
+/// whose results are too large to be sent are answered with [`QResult::BUFFER_OVERFLOW`], and
+/// messages that cannot be decoded or are not requests are discarded
+pub async fn serve(
+    transport: &mut dyn Transport,
+    protocols: &[&dyn Dispatch],
+) -> Result<(), TransportError> {
+    loop {
+        let request = match transport.recv().await {
+            Ok(message) => match Message::from_bytes(&message) {
+                Ok(Message::Request(request)) => request,
+                _ => continue,
+            },
+            Err(TransportError::Closed) => return Ok(()),
+            Err(error) => return Err(error),
+        };
+
+        let body = match protocols
+            .iter()
+            .find(|protocol| protocol.protocol_id() == request.protocol_id)
+        {
+            Some(protocol) => match protocol
+                .dispatch(request.method_id, &request.parameters)
+                .await
+            {
+                Ok(results) => ResponseBody::Success {
+                    method_id: request.method_id,
+                    results,
+                },
+                Err(result) => ResponseBody::Error(result),
+            },
+            None => ResponseBody::Error(QResult::NOT_IMPLEMENTED),
+        };
+
+        let response = respond(&request, body)
+            .or_else(|_| respond(&request, ResponseBody::Error(QResult::BUFFER_OVERFLOW)))
+            .map_err(|error| TransportError::Other(Box::new(error)))?;
+        transport.send(&response).await?;
+    }
+}
+
+/// Encodes a response to the provided [`Request`] with the provided body
+fn respond(request: &Request, body: ResponseBody) -> Result<Vec<u8>, EncodeError> {
+    Message::Response(Response {
+        protocol_id: request.protocol_id,
+        call_id: request.call_id,
+        body,
+    })
+    .to_bytes()
+}
@@ -0,0 +1,389 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! Type definitions for the values specific to NEX that may be passed to and returned from RMC
+//! methods
+//!
+//! Strings, lists and maps are represented using [`String`], [`Vec`] and
+//! [`BTreeMap`](std::collections::BTreeMap) respectively, see the [`codec`](crate::codec) module
+
+use std::fmt;
+
+use crate::codec::{length_prefix, take, Decode, DecodeError, Encode, EncodeError};
+
+pub use ralsei_model::network::StationUrl;
+
+/// A sequence of bytes with a 32-bit length
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Buffer(pub Vec<u8>);
+
+impl Encode for Buffer {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        length_prefix::<u32>(self.0.len())?.encode(buffer)?;
+        buffer.extend_from_slice(&self.0);
+        Ok(())
+    }
+}
+
+impl Decode for Buffer {
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        let length = u32::decode(data)?;
+        Ok(Self(take(data, length as usize)?.to_vec()))
+    }
+}
+
+/// A sequence of bytes with a 16-bit length
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct QBuffer(pub Vec<u8>);
+
+impl Encode for QBuffer {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        length_prefix::<u16>(self.0.len())?.encode(buffer)?;
+        buffer.extend_from_slice(&self.0);
+        Ok(())
+    }
+}
+
+impl Decode for QBuffer {
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        let length = u16::decode(data)?;
+        Ok(Self(take(data, usize::from(length))?.to_vec()))
+    }
+}
+
+/// A date and time, packed into a 64-bit integer
+///
+/// From the least significant bit up, it is made up of six bits for the second, six for the
+/// minute, five for the hour, five for the day, four for the month and the rest for the year
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct DateTime(pub u64);
+
+impl DateTime {
+    /// Creates a new [`DateTime`] from its components
+    pub fn new(year: u64, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+        Self(
+            u64::from(second & 0x3F)
+                | u64::from(minute & 0x3F) << 6
+                | u64::from(hour & 0x1F) << 12
+                | u64::from(day & 0x1F) << 17
+                | u64::from(month & 0xF) << 22
+                | year << 26,
+        )
+    }
+
+    /// Returns the year
+    pub fn year(self) -> u64 {
+        self.0 >> 26
+    }
+
+    /// Returns the month, starting from 1
+    pub fn month(self) -> u8 {
+        (self.0 >> 22 & 0xF) as u8
+    }
+
+    /// Returns the day of the month, starting from 1
+    pub fn day(self) -> u8 {
+        (self.0 >> 17 & 0x1F) as u8
+    }
+
+    /// Returns the hour
+    pub fn hour(self) -> u8 {
+        (self.0 >> 12 & 0x1F) as u8
+    }
+
+    /// Returns the minute
+    pub fn minute(self) -> u8 {
+        (self.0 >> 6 & 0x3F) as u8
+    }
+
+    /// Returns the second
+    pub fn second(self) -> u8 {
+        (self.0 & 0x3F) as u8
+    }
+}
+
+impl Encode for DateTime {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        self.0.encode(buffer)
+    }
+}
+
+impl Decode for DateTime {
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(Self(u64::decode(data)?))
+    }
+}
+
+/// A value of one of several types, prefixed by a byte identifying its type
+#[derive(Clone, Debug, PartialEq)]
+pub enum Variant {
+    None,
+    Int64(i64),
+    Double(f64),
+    Bool(bool),
+    String(String),
+    DateTime(DateTime),
+    UInt64(u64),
+}
+
+impl Encode for Variant {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        match self {
+            Self::None => {
+                buffer.push(0);
+                Ok(())
+            }
+            Self::Int64(value) => {
+                buffer.push(1);
+                value.encode(buffer)
+            }
+            Self::Double(value) => {
+                buffer.push(2);
+                value.encode(buffer)
+            }
+            Self::Bool(value) => {
+                buffer.push(3);
+                value.encode(buffer)
+            }
+            Self::String(value) => {
+                buffer.push(4);
+                value.encode(buffer)
+            }
+            Self::DateTime(value) => {
+                buffer.push(5);
+                value.encode(buffer)
+            }
+            Self::UInt64(value) => {
+                buffer.push(6);
+                value.encode(buffer)
+            }
+        }
+    }
+}
+
+impl Decode for Variant {
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(match u8::decode(data)? {
+            0 => Self::None,
+            1 => Self::Int64(Decode::decode(data)?),
+            2 => Self::Double(Decode::decode(data)?),
+            3 => Self::Bool(Decode::decode(data)?),
+            4 => Self::String(Decode::decode(data)?),
+            5 => Self::DateTime(Decode::decode(data)?),
+            6 => Self::UInt64(Decode::decode(data)?),
+            kind => return Err(DecodeError::InvalidValue("Variant", kind.to_string())),
+        })
+    }
+}
+
+/// The structure all structures held by an [`AnyDataHolder`] derive from, which has no fields of
+/// its own
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Data;
+
+impl Encode for Data {
+    fn encode(&self, _: &mut Vec<u8>) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+impl Decode for Data {
+    fn decode(_: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(Self)
+    }
+}
+
+/// A serialized structure, along with the name of its type
+///
+/// This is how NEX passes values of a type that is only known at runtime
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct AnyDataHolder {
+    /// The name of the held structure's type
+    pub type_name: String,
+
+    /// The serialized structure
+    pub data: Vec<u8>,
+}
+
+impl AnyDataHolder {
+    /// Creates a new [`AnyDataHolder`] holding the provided value
+    pub fn new<T>(type_name: &str, value: &T) -> Result<Self, EncodeError>
+    where
+        T: Encode,
+    {
+        Ok(Self {
+            type_name: type_name.to_string(),
+            data: value.to_bytes()?,
+        })
+    }
+
+    /// Deserializes the held structure as the provided type
+    pub fn get<T>(&self) -> Result<T, DecodeError>
+    where
+        T: Decode,
+    {
+        T::from_bytes(&self.data)
+    }
+}
+
+impl Encode for AnyDataHolder {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        self.type_name.encode(buffer)?;
+        length_prefix::<u32>(self.data.len() + 4)?.encode(buffer)?;
+        length_prefix::<u32>(self.data.len())?.encode(buffer)?;
+        buffer.extend_from_slice(&self.data);
+        Ok(())
+    }
+}
+
+impl Decode for AnyDataHolder {
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        let type_name = String::decode(data)?;
+        let _ = u32::decode(data)?;
+        Ok(Self {
+            type_name,
+            data: Buffer::decode(data)?.0,
+        })
+    }
+}
+
+/// [`StationUrl`]s are passed around in their string form
+impl Encode for StationUrl {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        self.to_string().encode(buffer)
+    }
+}
+
+impl Decode for StationUrl {
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        let url = String::decode(data)?;
+        url.parse()
+            .map_err(|_| DecodeError::InvalidValue("StationUrl", url))
+    }
+}
+
+/// The result of an operation, where the most significant bit is set on errors
+///
+/// This is what NEX calls a `Result`, and is the error code carried by failed RMC responses
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct QResult(pub u32);
+
+impl QResult {
+    /// The generic success result
+    pub const SUCCESS: Self = Self(0x0001_0001);
+
+    /// The result of calling a protocol or method that is not implemented
+    pub const NOT_IMPLEMENTED: Self = Self(0x8001_0002);
+
+    /// The result of calling a method with arguments that could not be deserialized
+    pub const INVALID_ARGUMENT: Self = Self(0x8001_000A);
+
+    /// The result of calling a method whose results are too large to be serialized
+    pub const BUFFER_OVERFLOW: Self = Self(0x8001_000F);
+
+    /// Returns `true` if the [`QResult`] represents an error
+    pub fn is_error(self) -> bool {
+        self.0 & 0x8000_0000 != 0
+    }
+
+    /// Returns `true` if the [`QResult`] represents a success
+    pub fn is_success(self) -> bool {
+        !self.is_error()
+    }
+}
+
+impl Default for QResult {
+    fn default() -> Self {
+        Self::SUCCESS
+    }
+}
+
+impl fmt::Display for QResult {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{:#010X}", self.0)
+    }
+}
+
+impl Encode for QResult {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        self.0.encode(buffer)
+    }
+}
+
+impl Decode for QResult {
+    fn decode(data: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(Self(u32::decode(data)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn date_time_components() {
+        let date_time = DateTime::new(2021, 7, 14, 23, 59, 30);
+        assert_eq!(date_time.0, 0x1F_95DD_7EDE);
+        assert_eq!(
+            (
+                date_time.year(),
+                date_time.month(),
+                date_time.day(),
+                date_time.hour(),
+                date_time.minute(),
+                date_time.second()
+            ),
+            (2021, 7, 14, 23, 59, 30)
+        );
+    }
+
+    #[test]
+    fn station_url_round_trip() {
+        let mut url: StationUrl =
+            "prudps:/address=10.0.0.1;port=60000;CID=1;PID=2;sid=1;stream=10;type=2"
+                .parse()
+                .unwrap();
+        assert_eq!(url.scheme, "prudps");
+        assert_eq!(url.get("port"), Some("60000"));
+        assert_eq!(url.get("missing"), None);
+
+        url.set("port", "60001");
+        assert_eq!(
+            StationUrl::from_bytes(&url.to_bytes().unwrap())
+                .unwrap()
+                .to_string(),
+            "prudps:/address=10.0.0.1;port=60001;CID=1;PID=2;sid=1;stream=10;type=2"
+        );
+        assert!("no scheme".parse::<StationUrl>().is_err());
+    }
+
+    #[test]
+    fn holder_round_trip() {
+        for variant in &[
+            Variant::None,
+            Variant::Int64(-1),
+            Variant::Double(0.5),
+            Variant::String("nex".to_string()),
+            Variant::DateTime(DateTime::new(2021, 1, 1, 0, 0, 0)),
+        ] {
+            assert_eq!(
+                &Variant::from_bytes(&variant.to_bytes().unwrap()).unwrap(),
+                variant
+            );
+        }
+
+        let holder = AnyDataHolder::new("Buffer", &Buffer(vec![1, 2, 3])).unwrap();
+        assert_eq!(
+            holder.to_bytes().unwrap()[holder.type_name.len() + 3..],
+            [11, 0, 0, 0, 7, 0, 0, 0, 3, 0, 0, 0, 1, 2, 3]
+        );
+        let holder = AnyDataHolder::from_bytes(&holder.to_bytes().unwrap()).unwrap();
+        assert_eq!(holder.get::<Buffer>().unwrap(), Buffer(vec![1, 2, 3]));
+    }
+}
@@ -0,0 +1,136 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::io;
+
+use ralsei_protocol_prudp_lite::{
+    connection::{Connection, Settings},
+    packet::{StreamKind, VirtualPort},
+};
+use ralsei_protocol_rmc::{
+    client::{CallError, Client},
+    protocol::{async_trait, protocol, Protocol},
+    server::{serve, Dispatch},
+    types::{QResult, StationUrl},
+};
+use ralsei_util::transport::Transport;
+
+protocol! {
+    /// A protocol for keeping count
+    pub protocol Counter = 100 {
+        client CounterClient;
+        server CounterServer => CounterDispatcher;
+
+        /// Adds the provided amount to the count, returning the new count
+        1 => fn add(amount: u32) -> (QResult, u32);
+
+        /// Returns the address the count is kept at
+        2 => fn locate(name: String, secure: bool) -> StationUrl;
+
+        /// Fails
+        3 => fn fail() -> ();
+    }
+}
+
+#[derive(Default)]
+struct Count(AtomicU32);
+
+#[async_trait]
+impl CounterServer for Count {
+    async fn add(&self, amount: u32) -> Result<(QResult, u32), QResult> {
+        Ok((
+            QResult::SUCCESS,
+            self.0.fetch_add(amount, Ordering::SeqCst) + amount,
+        ))
+    }
+
+    async fn locate(&self, name: String, secure: bool) -> Result<StationUrl, QResult> {
+        let mut url = StationUrl {
+            scheme: if secure { "prudps" } else { "prudp" }.to_string(),
+            parameters: Vec::new(),
+        };
+        url.set("address", &name);
+        Ok(url)
+    }
+
+    async fn fail(&self) -> Result<(), QResult> {
+        Err(QResult(0x8068_0001))
+    }
+}
+
+/// Connect a client to a server serving the counter protocol over an in-memory stream
+async fn connect() -> Client {
+    let port = VirtualPort::new(StreamKind::RvSecure, 1);
+    let (client, server) = io::duplex(0x1000);
+    let (client, server) = tokio::join!(
+        Connection::connect_over(client, port, &[], Settings::default()),
+        Connection::accept_over(server, port, Settings::default()),
+    );
+    let mut server: Box<dyn Transport> = Box::new(server.expect("unable to accept"));
+
+    tokio::spawn(async move {
+        let counter = CounterDispatcher(Count::default());
+        serve(server.as_mut(), &[&counter])
+            .await
+            .expect("unable to serve");
+    });
+
+    Client::new(Box::new(client.expect("unable to connect")))
+}
+
+#[tokio::test]
+async fn call() {
+    let mut client = connect().await;
+
+    assert_eq!(Counter::ID, 100);
+    assert_eq!(client.add(2).await.unwrap(), (QResult::SUCCESS, 2));
+    assert_eq!(client.add(3).await.unwrap(), (QResult::SUCCESS, 5));
+    assert_eq!(
+        client
+            .locate("10.0.0.1".to_string(), true)
+            .await
+            .unwrap()
+            .to_string(),
+        "prudps:/address=10.0.0.1"
+    );
+}
+
+#[tokio::test]
+async fn errors() {
+    let mut client = connect().await;
+
+    assert!(matches!(
+        client.fail().await,
+        Err(CallError::Failed(QResult(0x8068_0001)))
+    ));
+
+    // unknown methods and protocols, as well as malformed parameters, are reported as errors
+    use ralsei_protocol_rmc::client::Caller;
+    for (protocol_id, method_id, parameters) in [
+        (100, 4, Vec::new()),
+        (101, 1, Vec::new()),
+        (100, 1, vec![0]),
+    ] {
+        let result = client.call(protocol_id, method_id, parameters).await;
+        assert!(matches!(result, Err(CallError::Failed(_))));
+    }
+    assert!(matches!(
+        client.call(100, 1, vec![0]).await,
+        Err(CallError::Failed(QResult::INVALID_ARGUMENT))
+    ));
+    assert!(matches!(
+        client.call(101, 1, Vec::new()).await,
+        Err(CallError::Failed(QResult::NOT_IMPLEMENTED))
+    ));
+
+    // the dispatcher is usable as a trait object
+    let dispatcher: &dyn Dispatch = &CounterDispatcher(Count::default());
+    assert_eq!(dispatcher.protocol_id(), Counter::ID);
+}
@@ -0,0 +1,838 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use chrono::Duration;
+use http::{
+    header::{self, HeaderMap, HeaderValue},
+    Error as HttpError,
+};
+use hyper::{
+    server::conn::Http, service::service_fn, Body, Error as HyperError, Method, Request, Response,
+    StatusCode,
+};
+use iso::language::Iso639_1;
+use isocountry::CountryCode;
+use native_tls::Error as NativeTlsError;
+use parking_lot::RwLock;
+use quick_xml::Reader as XmlReader;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    convert::{Infallible, TryFrom},
+    io::Error as IoError,
+    str::FromStr,
+    sync::Arc,
+};
+use tokio::net::TcpListener;
+use tokio_native_tls::TlsAcceptor;
+
+use crate::store::{Session, Store};
+use ralsei_model::{
+    certificate::Certificate,
+    network::{Identifier, Nnid, Pid},
+};
+use ralsei_service_account::{
+    client::Password,
+    common::{account_api_endpoints, XML_DECLARATION},
+    xml::{
+        agreement::{AgreementKind, Agreements},
+        error::{Error as ErrorXml, ErrorCode, ErrorCodeValue, Errors},
+        errors::Error as XmlErrorExtension,
+        mapped_id::{MappedId, MappedIds},
+        new_person::NewPerson,
+        oauth::{AccessToken, OAuth20},
+        person::Person,
+        timezone::Timezones,
+        token::{NexToken, ServiceToken},
+    },
+};
+use ralsei_util::xml::{
+    errors::Error as XmlError,
+    framework::{self as xml_framework, FromXml, ToXml},
+    GLOBAL_BUFFER_POOL,
+};
+
+/// The lifetime of the access tokens issued by the [`Server`], in seconds
+pub const ACCESS_TOKEN_LIFETIME: u32 = 3600;
+
+/// An in-memory implementation of the Nintendo Network account server
+///
+/// It answers the same endpoints the account [`Client`] calls using the data held in its
+/// [`Store`], making it suitable for testing the client without network access
+///
+/// [`Client`]: ralsei_service_account::client::Client
+#[derive(Debug)]
+pub struct Server {
+    /// The data served by the server
+    pub store: RwLock<Store>,
+
+    /// The certificate that device certificates must be signed by, such as that of
+    /// [`KnownIssuer::NintendoCaG3NintendoCtr2Prod`]
+    ///
+    /// Requests carrying an `X-Nintendo-Device-Cert` header that was not signed by it are
+    /// rejected. If none is provided, no device certificate can be verified, so every request
+    /// carrying one is rejected
+    ///
+    /// ralsei-keypairs does not bundle this certificate, so it must be provided by whoever runs
+    /// the server
+    ///
+    /// [`KnownIssuer::NintendoCaG3NintendoCtr2Prod`]: ralsei_model::certificate::KnownIssuer::NintendoCaG3NintendoCtr2Prod
+    pub device_certificate_issuer: Option<Certificate<'static>>,
+
+    /// Whether or not requests without an `X-Nintendo-Device-Cert` header are rejected
+    ///
+    /// This is the case by default. Consoles do not send the header to every endpoint, so it may
+    /// need to be turned off to serve them
+    pub device_certificate_required: bool,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new(Store::default())
+    }
+}
+
+impl Server {
+    /// Create a new [`Server`] serving the data held in the provided [`Store`]
+    pub fn new(store: Store) -> Self {
+        Self {
+            store: RwLock::new(store),
+            device_certificate_issuer: None,
+            device_certificate_required: true,
+        }
+    }
+
+    /// Require that device certificates sent to the [`Server`] be signed by the provided
+    /// certificate, see [`device_certificate_issuer`](#structfield.device_certificate_issuer)
+    pub fn with_device_certificate_issuer(mut self, issuer: Certificate<'static>) -> Self {
+        self.device_certificate_issuer = Some(issuer);
+        self
+    }
+
+    /// Set whether or not requests without a device certificate are rejected, see
+    /// [`device_certificate_required`](#structfield.device_certificate_required)
+    pub fn with_device_certificate_required(mut self, required: bool) -> Self {
+        self.device_certificate_required = required;
+        self
+    }
+
+    /// Accept connections from the provided [`TcpListener`] until an error is encountered while
+    /// doing so, serving each of them on its own task
+    ///
+    /// If a [`TlsAcceptor`] is provided, connections are served over TLS. As the account
+    /// [`Client`] always connects using HTTPS, this is necessary for the two to communicate
+    ///
+    /// [`Client`]: ralsei_service_account::client::Client
+    pub async fn serve(
+        self: Arc<Self>,
+        listener: TcpListener,
+        acceptor: Option<TlsAcceptor>,
+    ) -> Result<!, ServerError> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = Arc::clone(&self);
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |request| {
+                    let server = Arc::clone(&server);
+                    async move {
+                        // errors encountered while handling a request are reported to the client
+                        // without any further detail
+                        Ok::<_, Infallible>(server.handle(request).await.unwrap_or_else(|_| {
+                            let mut response = Response::new(Body::empty());
+                            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                            response
+                        }))
+                    }
+                });
+
+                // errors are confined to the connection they were encountered on
+                let _ = match acceptor {
+                    Some(acceptor) => match acceptor.accept(stream).await {
+                        Ok(stream) => Http::new().serve_connection(stream, service).await,
+                        Err(_) => return,
+                    },
+                    None => Http::new().serve_connection(stream, service).await,
+                };
+            });
+        }
+    }
+
+    /// Produce a [`Response`] to the provided [`Request`]
+    pub async fn handle(&self, request: Request<Body>) -> Result<Response<Body>, ServerError> {
+        let authorized = match request.headers().get("X-Nintendo-Device-Cert") {
+            Some(device_certificate) => matches!(
+                &self.device_certificate_issuer,
+                Some(issuer) if is_signed_device_certificate(device_certificate, issuer)
+            ),
+            None => !self.device_certificate_required,
+        };
+        if !authorized {
+            return error_response(StatusCode::UNAUTHORIZED, ErrorCodeValue::UnauthorizedDevice)
+                .await;
+        }
+
+        let (parts, body) = request.into_parts();
+        let path = parts.uri.path();
+        let query = parts.uri.query().unwrap_or("");
+        match (&parts.method, path) {
+            (&Method::GET, account_api_endpoints::TIME) => self.time(),
+            (&Method::GET, account_api_endpoints::MAPPED_IDS) => self.mapped_ids(query).await,
+            (&Method::POST, account_api_endpoints::ACCESS_TOKEN) => {
+                let form = read_form(body).await?;
+                self.access_token(&form).await
+            }
+            (&Method::POST, account_api_endpoints::VALIDATE_EMAIL) => {
+                let form = read_form(body).await?;
+                self.validate_email(&form).await
+            }
+            (&Method::POST, account_api_endpoints::PEOPLE) => {
+                let body = hyper::body::to_bytes(body).await?;
+                self.create_account(&body).await
+            }
+            (&Method::GET, account_api_endpoints::NEX_TOKEN) => {
+                self.nex_token(&parts.headers, query).await
+            }
+            (&Method::GET, account_api_endpoints::SERVICE_TOKEN) => {
+                self.service_token(&parts.headers, query).await
+            }
+            (&Method::GET, path) if path.starts_with(account_api_endpoints::AGREEMENTS) => {
+                self.agreements(&path[account_api_endpoints::AGREEMENTS.len()..])
+                    .await
+            }
+            (&Method::GET, path) if path.starts_with(account_api_endpoints::TIMEZONES) => {
+                self.timezones(&path[account_api_endpoints::TIMEZONES.len()..])
+                    .await
+            }
+            (&Method::GET, path) if path.starts_with(account_api_endpoints::PEOPLE) => {
+                match &path[account_api_endpoints::PEOPLE.len()..] {
+                    "@me/profile" => self.profile(&parts.headers).await,
+
+                    // only a single path segment may name a user
+                    nnid if !nnid.is_empty() && !nnid.contains('/') && nnid != "@me" => {
+                        self.does_user_exist(nnid).await
+                    }
+                    _ => {
+                        error_response(StatusCode::NOT_FOUND, ErrorCodeValue::RequestNotFound).await
+                    }
+                }
+            }
+            (_, path) if is_endpoint(path) => {
+                error_response(
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    ErrorCodeValue::WrongHttpMethod,
+                )
+                .await
+            }
+            _ => error_response(StatusCode::NOT_FOUND, ErrorCodeValue::RequestNotFound).await,
+        }
+    }
+
+    /// Returns the [`Pid`] of the account the access token provided in the `Authorization` header
+    /// was issued to, or the [`ErrorCodeValue`] describing why it is unable to be used
+    fn authenticate(&self, headers: &HeaderMap) -> Result<Pid, ErrorCodeValue> {
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|authorization| authorization.to_str().ok())
+            .and_then(|authorization| authorization.strip_prefix("Bearer "))
+            .ok_or(ErrorCodeValue::UnauthorizedClient)?;
+        let store = self.store.read();
+        match store.access_tokens.get(token) {
+            Some(session) if session.expires_at <= store.now() => {
+                Err(ErrorCodeValue::ExpiredAccountToken)
+            }
+            Some(session) => Ok(session.pid.clone()),
+            None => Err(ErrorCodeValue::InvalidAccountToken),
+        }
+    }
+
+    /// Respond to a request for an access token
+    async fn access_token(
+        &self,
+        form: &HashMap<String, String>,
+    ) -> Result<Response<Body>, ServerError> {
+        let pid = match form.get("grant_type").map(String::as_str) {
+            Some("password") => self.check_password(form),
+            Some("refresh_token") => match form.get("refresh_token") {
+                Some(refresh_token) => self
+                    .store
+                    .write()
+                    .refresh_tokens
+                    .remove(refresh_token)
+                    .ok_or(ErrorCodeValue::InvalidAccountToken),
+                None => Err(ErrorCodeValue::MissingRequestParameter),
+            },
+            Some(_) => Err(ErrorCodeValue::BadParameterFormat),
+            None => Err(ErrorCodeValue::MissingRequestParameter),
+        };
+        match pid {
+            Ok(pid) => {
+                let oauth = self.issue_access_token(pid);
+                xml_response(StatusCode::OK, &oauth).await
+            }
+            Err(code) => error_response(StatusCode::BAD_REQUEST, code).await,
+        }
+    }
+
+    /// Returns the [`Pid`] of the account the credentials provided in an access token request
+    /// belong to, or the [`ErrorCodeValue`] describing why they are unable to be used
+    fn check_password(&self, form: &HashMap<String, String>) -> Result<Pid, ErrorCodeValue> {
+        let (nnid, password) = match (form.get("user_id"), form.get("password")) {
+            (Some(nnid), Some(password)) => (nnid, password),
+            _ => return Err(ErrorCodeValue::MissingRequestParameter),
+        };
+
+        let store = self.store.read();
+        let pid = store
+            .person_by_nnid(&Nnid(Cow::Borrowed(nnid)))
+            .and_then(|person| person.pid.clone())
+            .ok_or(ErrorCodeValue::WrongAccountPassword)?;
+        let expected = store
+            .passwords
+            .get(&pid)
+            .ok_or(ErrorCodeValue::WrongAccountPassword)?;
+        let matches = match form.get("password_type").map(String::as_str) {
+            None => expected == password,
+            Some("hash") => {
+                Password::hash(pid.clone(), expected) == Password::Hashed(Cow::Borrowed(password))
+            }
+            Some(_) => return Err(ErrorCodeValue::BadParameterFormat),
+        };
+        if matches {
+            Ok(pid)
+        } else {
+            Err(ErrorCodeValue::WrongAccountPassword)
+        }
+    }
+
+    /// Issue an access token and a refresh token to the account with the provided [`Pid`]
+    fn issue_access_token(&self, pid: Pid) -> OAuth20<'static> {
+        let (token, refresh_token) = (generate_token(32), generate_token(32));
+        let mut store = self.store.write();
+        let expires_at = store.now() + Duration::seconds(i64::from(ACCESS_TOKEN_LIFETIME));
+        store.access_tokens.insert(
+            token.clone(),
+            Session {
+                pid: pid.clone(),
+                expires_at,
+            },
+        );
+        store.refresh_tokens.insert(refresh_token.clone(), pid);
+        OAuth20 {
+            access_token: Some(AccessToken {
+                token: Some(Cow::Owned(token)),
+                refresh_token: Some(Cow::Owned(refresh_token)),
+                expires_in: Some(ACCESS_TOKEN_LIFETIME),
+            }),
+        }
+    }
+
+    /// Respond to a request checking if an email address is able to be used to register an
+    /// account
+    async fn validate_email(
+        &self,
+        form: &HashMap<String, String>,
+    ) -> Result<Response<Body>, ServerError> {
+        match form.get("email").map(|email| check_email(email)) {
+            Some(Ok(())) => Ok(Response::new(Body::empty())),
+            Some(Err(code)) => error_response(StatusCode::BAD_REQUEST, code).await,
+            None => {
+                error_response(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCodeValue::MissingRequestParameter,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Respond to a request registering a new account
+    async fn create_account(&self, body: &[u8]) -> Result<Response<Body>, ServerError> {
+        let mut new_person = NewPerson::default();
+        if new_person
+            .from_xml(
+                &mut XmlReader::from_reader(body),
+                GLOBAL_BUFFER_POOL.clone(),
+            )
+            .await
+            .is_err()
+        {
+            return error_response(StatusCode::BAD_REQUEST, ErrorCodeValue::BadRequestFormat).await;
+        }
+
+        match self.register(new_person) {
+            Ok(pid) => {
+                xml_response(
+                    StatusCode::OK,
+                    &Person {
+                        pid: Some(pid),
+                        ..Person::default()
+                    },
+                )
+                .await
+            }
+            Err(code) => error_response(StatusCode::BAD_REQUEST, code).await,
+        }
+    }
+
+    /// Register the account described by the provided [`NewPerson`], returning the [`Pid`] it was
+    /// assigned or the [`ErrorCodeValue`] describing why it is unable to be registered
+    fn register(&self, new_person: NewPerson<'static>) -> Result<Pid, ErrorCodeValue> {
+        let (nnid, password) = match (new_person.user_id, new_person.password) {
+            (Some(nnid), Some(password)) => (nnid, password),
+            _ => return Err(ErrorCodeValue::MissingRequestParameter),
+        };
+        if !(6..=16).contains(&nnid.0.len())
+            || !nnid
+                .0
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err(ErrorCodeValue::AccountIdFormatInvalid);
+        }
+        if nnid.0.eq_ignore_ascii_case(&password) {
+            return Err(ErrorCodeValue::AccountIdPasswordSame);
+        }
+        if let Some(address) = new_person
+            .email
+            .as_ref()
+            .and_then(|email| email.address.as_ref())
+        {
+            check_email(address)?;
+        }
+
+        let mut store = self.store.write();
+        if store.person_by_nnid(&nnid).is_some() {
+            return Err(ErrorCodeValue::AccountIdExists);
+        }
+        let pid = store.next_pid();
+        let now = store.now();
+        store.people.push(Person {
+            active: Some(true),
+            birth_date: new_person.birth_date,
+            country: new_person.country,
+            create_date: Some(now),
+            device_attributes: new_person.device_attributes,
+            gender: new_person.gender,
+            language: new_person.language,
+            updated: Some(now),
+            marketing: new_person.marketing,
+            off_device: new_person.off_device,
+            pid: Some(pid.clone()),
+            email: new_person.email,
+            mii: new_person.mii,
+            region: new_person.region,
+            timezone: new_person.timezone,
+            user_id: Some(nnid),
+            utc_offset: None,
+        });
+        store.passwords.insert(pid.clone(), password);
+        Ok(pid)
+    }
+
+    /// Respond to a request for the profile of the authenticated user
+    async fn profile(&self, headers: &HeaderMap) -> Result<Response<Body>, ServerError> {
+        let pid = match self.authenticate(headers) {
+            Ok(pid) => pid,
+            Err(code) => return error_response(StatusCode::UNAUTHORIZED, code).await,
+        };
+        let person = self.store.read().person_by_pid(&pid).cloned();
+        match person {
+            Some(person) => xml_response(StatusCode::OK, &person).await,
+            None => error_response(StatusCode::BAD_REQUEST, ErrorCodeValue::PidNotFound).await,
+        }
+    }
+
+    /// Respond to a request for a token used to log in to a game server on behalf of the
+    /// authenticated user
+    async fn nex_token(
+        &self,
+        headers: &HeaderMap,
+        query: &str,
+    ) -> Result<Response<Body>, ServerError> {
+        let pid = match self.authenticate(headers) {
+            Ok(pid) => pid,
+            Err(code) => return error_response(StatusCode::UNAUTHORIZED, code).await,
+        };
+        let game_server_id = match query_parameter(query, "game_server_id")
+            .map(|game_server_id| u32::from_str_radix(&game_server_id, 16))
+        {
+            Some(Ok(game_server_id)) => game_server_id,
+            Some(Err(_)) => {
+                return error_response(StatusCode::BAD_REQUEST, ErrorCodeValue::BadParameterFormat)
+                    .await
+            }
+            None => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCodeValue::MissingRequestParameter,
+                )
+                .await
+            }
+        };
+
+        let nex_token = {
+            let mut store = self.store.write();
+            store
+                .game_servers
+                .get(&game_server_id)
+                .cloned()
+                .map(|game_server| {
+                    let nex_password = store
+                        .nex_passwords
+                        .entry(pid.clone())
+                        .or_insert_with(|| Cow::Owned(generate_token(16)))
+                        .clone();
+                    NexToken {
+                        host: Some(game_server.host),
+                        nex_password: Some(nex_password),
+                        pid: Some(pid),
+                        port: Some(game_server.port),
+                        token: Some(Cow::Owned(generate_token(64))),
+                    }
+                })
+        };
+        match nex_token {
+            Some(nex_token) => xml_response(StatusCode::OK, &nex_token).await,
+            None => {
+                error_response(StatusCode::BAD_REQUEST, ErrorCodeValue::InvalidGameServerId).await
+            }
+        }
+    }
+
+    /// Respond to a request for a token used to access an independent service on behalf of the
+    /// authenticated user
+    async fn service_token(
+        &self,
+        headers: &HeaderMap,
+        query: &str,
+    ) -> Result<Response<Body>, ServerError> {
+        if let Err(code) = self.authenticate(headers) {
+            return error_response(StatusCode::UNAUTHORIZED, code).await;
+        }
+        let known = match query_parameter(query, "client_id") {
+            Some(client_id) => self.store.read().services.contains(client_id.as_str()),
+            None => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCodeValue::MissingRequestParameter,
+                )
+                .await
+            }
+        };
+        if known {
+            xml_response(
+                StatusCode::OK,
+                &ServiceToken {
+                    token: Some(Cow::Owned(generate_token(64))),
+                },
+            )
+            .await
+        } else {
+            error_response(StatusCode::BAD_REQUEST, ErrorCodeValue::InvalidClientId).await
+        }
+    }
+
+    /// Respond to a request checking if a user exists
+    async fn does_user_exist(&self, nnid: &str) -> Result<Response<Body>, ServerError> {
+        let exists = self
+            .store
+            .read()
+            .person_by_nnid(&Nnid(Cow::Borrowed(nnid)))
+            .is_some();
+        if exists {
+            error_response(StatusCode::BAD_REQUEST, ErrorCodeValue::AccountIdExists).await
+        } else {
+            Ok(Response::new(Body::empty()))
+        }
+    }
+
+    /// Respond to a request for an agreement
+    async fn agreements(&self, parameters: &str) -> Result<Response<Body>, ServerError> {
+        let mut parameters = parameters.split('/');
+        let (kind, country, version) =
+            match (parameters.next(), parameters.next(), parameters.next()) {
+                (Some(kind), Some(country), Some(version)) => (kind, country, version),
+                _ => {
+                    return error_response(
+                        StatusCode::BAD_REQUEST,
+                        ErrorCodeValue::MissingRequestParameter,
+                    )
+                    .await
+                }
+            };
+        let country = match CountryCode::for_alpha2(country) {
+            Ok(country) => country,
+            Err(_) => {
+                return error_response(StatusCode::BAD_REQUEST, ErrorCodeValue::InvalidEulaCountry)
+                    .await
+            }
+        };
+        let version = match version {
+            "@latest" => None,
+            version => match u16::from_str(version) {
+                Ok(version) => Some(version),
+                Err(_) => {
+                    return error_response(
+                        StatusCode::BAD_REQUEST,
+                        ErrorCodeValue::BadParameterFormat,
+                    )
+                    .await
+                }
+            },
+        };
+
+        let agreement = self
+            .store
+            .read()
+            .agreement(
+                &AgreementKind::from_cow(Cow::Borrowed(kind)),
+                country,
+                version,
+            )
+            .cloned();
+        match agreement {
+            Some(agreement) => {
+                xml_response(
+                    StatusCode::OK,
+                    &Agreements {
+                        agreements: vec![agreement],
+                    },
+                )
+                .await
+            }
+            None => {
+                error_response(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCodeValue::InvalidEulaCountryAndVersion,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Respond to a request for the timezones of a country
+    async fn timezones(&self, parameters: &str) -> Result<Response<Body>, ServerError> {
+        let mut parameters = parameters.split('/');
+        let (country, language) = match (
+            parameters.next().map(CountryCode::for_alpha2),
+            parameters.next().map(Iso639_1::from_str),
+        ) {
+            (Some(Ok(country)), Some(Ok(language))) => (country, language),
+            _ => {
+                return error_response(StatusCode::BAD_REQUEST, ErrorCodeValue::BadParameterFormat)
+                    .await
+            }
+        };
+
+        let timezones = self.store.read().timezones(country, language).to_vec();
+        xml_response(StatusCode::OK, &Timezones { timezones }).await
+    }
+
+    /// Respond to a request for the current time
+    fn time(&self) -> Result<Response<Body>, ServerError> {
+        let time = self.store.read().now();
+        Ok(Response::builder()
+            .header(
+                "X-Nintendo-Date",
+                HeaderValue::from(time.timestamp_millis()),
+            )
+            .body(Body::empty())?)
+    }
+
+    /// Respond to a request mapping identifiers of one kind to another
+    async fn mapped_ids(&self, query: &str) -> Result<Response<Body>, ServerError> {
+        let (mut input_type, mut output_type, mut input) = (None, None, None);
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "input_type" => input_type = Some(value),
+                "output_type" => output_type = Some(value),
+                "input" => input = Some(value),
+                _ => (),
+            }
+        }
+        let (input_type, output_type, input) = match (input_type, output_type, input) {
+            (Some(input_type), Some(output_type), Some(input)) => (input_type, output_type, input),
+            _ => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCodeValue::MissingRequestParameter,
+                )
+                .await
+            }
+        };
+        if !matches!(input_type.as_ref(), "user" | "pid")
+            || !matches!(output_type.as_ref(), "user" | "pid")
+        {
+            return error_response(StatusCode::BAD_REQUEST, ErrorCodeValue::BadParameterFormat)
+                .await;
+        }
+
+        let mapped_ids = {
+            let store = self.store.read();
+            input
+                .split(',')
+                .map(|id| {
+                    let identifier = match input_type.as_ref() {
+                        "user" => Some(Identifier::Nnid(Nnid(Cow::Borrowed(id)))),
+                        _ => u32::from_str(id).ok().map(|pid| Identifier::Pid(Pid(pid))),
+                    };
+                    let output = identifier
+                        .and_then(|identifier| store.person(&identifier))
+                        .and_then(|person| match output_type.as_ref() {
+                            "user" => person
+                                .user_id
+                                .as_ref()
+                                .map(|user_id| Cow::Owned(user_id.0.to_string())),
+                            _ => person.pid.as_ref().map(|pid| Cow::Owned(pid.0.to_string())),
+                        });
+                    MappedId {
+                        input: Some(Cow::Owned(id.to_string())),
+                        output,
+                    }
+                })
+                .collect()
+        };
+        xml_response(StatusCode::OK, &MappedIds { mapped_ids }).await
+    }
+}
+
+/// Returns `true` if the provided path is that of an endpoint served by the [`Server`]
+fn is_endpoint(path: &str) -> bool {
+    [
+        account_api_endpoints::TIME,
+        account_api_endpoints::MAPPED_IDS,
+        account_api_endpoints::ACCESS_TOKEN,
+        account_api_endpoints::VALIDATE_EMAIL,
+        account_api_endpoints::NEX_TOKEN,
+        account_api_endpoints::SERVICE_TOKEN,
+    ]
+    .contains(&path)
+        || path.starts_with(account_api_endpoints::PEOPLE)
+        || path.starts_with(account_api_endpoints::AGREEMENTS)
+        || path.starts_with(account_api_endpoints::TIMEZONES)
+}
+
+/// Read the url-encoded form contained within the provided request [`Body`]
+async fn read_form(body: Body) -> Result<HashMap<String, String>, ServerError> {
+    Ok(form_urlencoded::parse(&hyper::body::to_bytes(body).await?)
+        .into_owned()
+        .collect())
+}
+
+/// Returns the value of the parameter with the provided name in the provided query string, or
+/// `None` if there is none
+fn query_parameter(query: &str, name: &str) -> Option<String> {
+    form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Returns the [`ErrorCodeValue`] describing why the provided email address is unable to be used,
+/// if it is
+fn check_email(address: &str) -> Result<(), ErrorCodeValue> {
+    match address.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && !domain.contains('@') => {
+            let mut labels = domain.split('.');
+            if domain.contains('.')
+                && labels.all(|label| {
+                    !label.is_empty()
+                        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                })
+            {
+                Ok(())
+            } else {
+                Err(ErrorCodeValue::InvalidMailAddressDomainName)
+            }
+        }
+        _ => Err(ErrorCodeValue::InvalidMailAddress),
+    }
+}
+
+/// Generate a random alphanumeric token of the provided length
+fn generate_token(length: usize) -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+/// Returns `true` if the provided `X-Nintendo-Device-Cert` header value is a well-formed device
+/// certificate signed by the provided issuer
+fn is_signed_device_certificate(
+    device_certificate: &HeaderValue,
+    issuer: &Certificate<'_>,
+) -> bool {
+    matches!(
+        base64::decode(device_certificate.as_bytes())
+            .ok()
+            .and_then(|device_certificate| Certificate::try_from(device_certificate.as_slice()).ok()),
+        Some(device_certificate) if device_certificate.verify(issuer).is_ok()
+    )
+}
+
+/// Produce a [`Response`] with the provided status code containing the XML representation of the
+/// provided value
+async fn xml_response<T>(status: StatusCode, value: &T) -> Result<Response<Body>, ServerError>
+where
+    T: ToXml<XmlErrorExtension> + Sync,
+{
+    let mut body = String::from(XML_DECLARATION);
+    body.push_str(&xml_framework::to_string(value).await?);
+    Ok(Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/xml;charset=UTF-8")
+        .body(Body::from(body))?)
+}
+
+/// Produce a [`Response`] with the provided status code containing an error XML document
+/// describing the provided [`ErrorCodeValue`]
+async fn error_response(
+    status: StatusCode,
+    code: ErrorCodeValue,
+) -> Result<Response<Body>, ServerError> {
+    xml_response(
+        status,
+        &Errors {
+            errors: vec![ErrorXml {
+                cause: None,
+                message: Some(Cow::Owned(code.to_string())),
+                code: ErrorCode::Known(code),
+            }],
+        },
+    )
+    .await
+}
+
+/// An error type enumerating over errors that may occur while serving requests
+#[non_exhaustive]
+#[derive(thiserror::Error, Debug)]
+pub enum ServerError {
+    /// An error was encountered while performing io
+    #[error("An error was encountered while performing io")]
+    IoError(#[from] IoError),
+
+    /// An error was encountered while using hyper
+    #[error("An error was encountered while using the `hyper` library")]
+    HyperError(#[from] HyperError),
+
+    /// An error was encountered while using the http library
+    #[error("An error was encountered while using the `http` library")]
+    HttpError(#[from] HttpError),
+
+    /// An error encountered while using the native tls implementation
+    #[error("An error was encountered while using the native tls implementation")]
+    NativeTlsError(#[from] NativeTlsError),
+
+    /// An error was encountered while serializing XML
+    #[error("An error was encountered while serializing XML")]
+    XmlError(#[from] XmlError<XmlErrorExtension>),
+}
@@ -0,0 +1,167 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use chrono::{offset::Utc, DateTime};
+use iso::language::Iso639_1;
+use isocountry::CountryCode;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use ralsei_model::network::{Identifier, Nnid, Pid};
+use ralsei_service_account::xml::{
+    agreement::{Agreement, AgreementKind},
+    person::Person,
+    timezone::Timezone,
+};
+
+/// An in-memory store of the data served by the account [`Server`]
+///
+/// [`Server`]: crate::server::Server
+#[derive(Clone, Default, Debug)]
+pub struct Store {
+    /// The accounts registered on the server
+    pub people: Vec<Person<'static>>,
+
+    /// The agreements served by the server
+    pub agreements: Vec<Agreement<'static>>,
+
+    /// The timezones served by the server, keyed by the country they are in and the language
+    /// their names are in
+    pub timezones: HashMap<(CountryCode, Iso639_1), Vec<Timezone<'static>>>,
+
+    /// The time reported by the server
+    ///
+    /// If no value is provided, the current time is reported
+    pub time: Option<DateTime<Utc>>,
+
+    /// The plaintext passwords of the accounts registered on the server, keyed by their [`Pid`]
+    pub passwords: HashMap<Pid, Cow<'static, str>>,
+
+    /// The passwords used by the accounts registered on the server to log in to game servers,
+    /// keyed by their [`Pid`]
+    ///
+    /// If an account does not have one when a [`NexToken`] is requested for it, one is generated
+    ///
+    /// [`NexToken`]: ralsei_service_account::xml::token::NexToken
+    pub nex_passwords: HashMap<Pid, Cow<'static, str>>,
+
+    /// The game servers [`NexToken`]s may be issued for, keyed by their game server id
+    ///
+    /// [`NexToken`]: ralsei_service_account::xml::token::NexToken
+    pub game_servers: HashMap<u32, GameServer>,
+
+    /// The client ids of the services [`ServiceToken`]s may be issued for
+    ///
+    /// [`ServiceToken`]: ralsei_service_account::xml::token::ServiceToken
+    pub services: HashSet<Cow<'static, str>>,
+
+    /// The access tokens issued by the server, keyed by the token itself
+    pub access_tokens: HashMap<String, Session>,
+
+    /// The refresh tokens issued by the server alongside access tokens, mapped to the [`Pid`] of
+    /// the account they were issued to
+    pub refresh_tokens: HashMap<String, Pid>,
+}
+
+impl Store {
+    /// Returns the [`Person`] with the provided [`Nnid`], or `None` if there is none
+    ///
+    /// As with the official account server, [`Nnid`]s are compared case-insensitively
+    pub fn person_by_nnid(&self, nnid: &Nnid<'_>) -> Option<&Person<'static>> {
+        self.people.iter().find(|person| {
+            matches!(&person.user_id, Some(user_id) if user_id.0.eq_ignore_ascii_case(&nnid.0))
+        })
+    }
+
+    /// Returns the [`Person`] with the provided [`Pid`], or `None` if there is none
+    pub fn person_by_pid(&self, pid: &Pid) -> Option<&Person<'static>> {
+        self.people
+            .iter()
+            .find(|person| person.pid.as_ref() == Some(pid))
+    }
+
+    /// Returns the [`Person`] referred to by the provided [`Identifier`], or `None` if there is
+    /// none
+    pub fn person(&self, identifier: &Identifier<'_>) -> Option<&Person<'static>> {
+        match identifier {
+            Identifier::Nnid(nnid) => self.person_by_nnid(nnid),
+            Identifier::Pid(pid) => self.person_by_pid(pid),
+        }
+    }
+
+    /// Returns the current time according to the store, which is [`time`](#structfield.time) if
+    /// it is provided
+    pub fn now(&self) -> DateTime<Utc> {
+        self.time.unwrap_or_else(Utc::now)
+    }
+
+    /// Returns the [`Pid`] the next registered account is assigned
+    ///
+    /// As with the official account server, [`Pid`]s are assigned sequentially starting from
+    /// 1000000000, so accounts added to the store with lower [`Pid`]s do not affect the
+    /// assignment of new ones
+    pub fn next_pid(&self) -> Pid {
+        Pid(self
+            .people
+            .iter()
+            .filter_map(|person| person.pid.as_ref())
+            .map(|pid| pid.0.saturating_add(1))
+            .fold(1_000_000_000, u32::max))
+    }
+
+    /// Returns the [`Agreement`] of the provided kind in the provided country with the provided
+    /// version, or the latest one if no version is provided
+    pub fn agreement(
+        &self,
+        kind: &AgreementKind<'_>,
+        country: CountryCode,
+        version: Option<u16>,
+    ) -> Option<&Agreement<'static>> {
+        let mut agreements = self.agreements.iter().filter(|agreement| {
+            agreement.kind.as_str() == kind.as_str() && agreement.country == Some(country)
+        });
+        match version {
+            Some(version) => agreements.find(|agreement| agreement.version == Some(version)),
+            None => agreements.max_by_key(|agreement| agreement.version),
+        }
+    }
+
+    /// Returns the [`Timezone`]s in the provided country with names in the provided language
+    pub fn timezones(&self, country: CountryCode, language: Iso639_1) -> &[Timezone<'static>] {
+        self.timezones
+            .get(&(country, language))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// A game server [`NexToken`]s may be issued for
+///
+/// [`NexToken`]: ralsei_service_account::xml::token::NexToken
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct GameServer {
+    /// The host of the game server's authentication server
+    pub host: Cow<'static, str>,
+
+    /// The port of the game server's authentication server
+    pub port: u16,
+}
+
+/// An access token issued by the account [`Server`]
+///
+/// [`Server`]: crate::server::Server
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Session {
+    /// The [`Pid`] of the account the access token was issued to
+    pub pid: Pid,
+
+    /// The point in time at which the access token expires
+    pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,644 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use chrono::{offset::TimeZone, Utc};
+use iso::language::Iso639_1;
+use isocountry::CountryCode;
+use native_tls::{Certificate, Identity, TlsAcceptor};
+use parking_lot::RwLock;
+use std::{borrow::Cow, convert::TryFrom, sync::Arc};
+use tokio::net::TcpListener;
+
+use ralsei_model::{
+    certificate::{Certificate as DeviceCertificate, KeyId},
+    console::{
+        common::{ConsoleSerial, Environment as DeviceEnvironment},
+        n3ds::Console3ds,
+    },
+    network::{Identifier, IdentifierKind, Nnid, Pid},
+    title::{id::TitleId, version::TitleVersion},
+};
+use ralsei_service_account::{
+    client::{AgreementVersionParameter, Client, ClientError, Password, RegistrationError},
+    xml::{
+        agreement::{Agreement, AgreementKind, AgreementKindValue},
+        error::{ErrorCode, ErrorCodeValue},
+        new_person::NewPerson,
+        person::{Email, Person},
+        timezone::Timezone,
+    },
+};
+use ralsei_service_account_server::{
+    server::Server,
+    store::{GameServer, Store},
+};
+
+// a certificate authority and a device certificate signed by it, generated for testing
+const CA_CERTIFICATE: &[u8] = include_bytes!("data/ca.bin");
+const DEVICE_CERTIFICATE: &[u8] = include_bytes!("data/device.bin");
+
+/// Start a server serving the provided [`Store`] over TLS using a freshly generated certificate,
+/// returning a client connected to it that sends a device certificate the server accepts
+async fn start(store: Store) -> Client<'static, Console3ds<'static>> {
+    start_server(
+        Server::new(store).with_device_certificate_issuer(
+            DeviceCertificate::try_from(CA_CERTIFICATE).expect("unable to parse the issuer"),
+        ),
+        Some(
+            DeviceCertificate::try_from(DEVICE_CERTIFICATE)
+                .expect("unable to parse the certificate"),
+        ),
+    )
+    .await
+}
+
+/// Start the provided [`Server`] over TLS using a freshly generated certificate, returning a
+/// client connected to it that sends the provided device certificate
+async fn start_server(
+    server: Server,
+    device_certificate: Option<DeviceCertificate<'static>>,
+) -> Client<'static, Console3ds<'static>> {
+    // the certificate is serialized only once, as each serialization produces a new signature
+    let certificate = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+        .expect("unable to generate a certificate");
+    let certificate_pem = certificate
+        .serialize_pem()
+        .expect("unable to serialize the certificate");
+    let private_key_pem = certificate.serialize_private_key_pem();
+    let identity = || {
+        Identity::from_pkcs8(certificate_pem.as_bytes(), private_key_pem.as_bytes())
+            .expect("unable to construct an identity")
+    };
+    let acceptor = TlsAcceptor::new(identity()).expect("unable to construct a tls acceptor");
+
+    let listener = TcpListener::bind("localhost:0")
+        .await
+        .expect("unable to bind a listener");
+    let port = listener
+        .local_addr()
+        .expect("unable to get the listener's address")
+        .port();
+    let server = Arc::new(server);
+    tokio::spawn(async move {
+        let _ = server.serve(listener, Some(acceptor.into())).await;
+    });
+
+    let console = Console3ds::new(|b| {
+        let b = match device_certificate {
+            Some(device_certificate) => b
+                .device_certificate(device_certificate)
+                .derive_device_id_from_device_certificate()?,
+            None => b.device_id(1),
+        };
+        Ok(b.serial(ConsoleSerial(Cow::Borrowed("CW404567772")))
+            .derive_region_from_serial()?
+            .derive_device_model_from_serial()?
+            .derive_device_type_from_serial()?
+            .system_version(TitleVersion(0x02E0))
+            .country(CountryCode::USA)
+            .client_id(Cow::Borrowed("ea25c66c26b403376b4c5ed94ab9cdea"))
+            .client_secret(Cow::Borrowed("d137be62cb6a2b831cad8c013b92fb55"))
+            .fpd_version(0)
+            .environment(DeviceEnvironment::L(1))
+            .title_id(TitleId(0x000400100002C000))
+            .derive_unique_id_from_title_id()?
+            .title_version(TitleVersion(3))
+            .language(Iso639_1::En))
+    })
+    .expect("unable to construct a console");
+
+    Client::new(
+        Some(Cow::Owned(format!("localhost:{}", port))),
+        Arc::new(RwLock::new(console)),
+        // the server does not verify client certificates, so the one it uses is reused here
+        Some(identity()),
+        Some(Cow::Owned(vec![Certificate::from_pem(
+            certificate_pem.as_bytes(),
+        )
+        .expect("unable to parse the certificate")])),
+        None,
+    )
+    .expect("unable to construct a client")
+}
+
+fn person(nnid: &'static str, pid: u32) -> Person<'static> {
+    Person {
+        user_id: Some(Nnid(Cow::Borrowed(nnid))),
+        pid: Some(Pid(pid)),
+        ..Person::default()
+    }
+}
+
+/// Returns a [`Store`] holding a single account, `superwhiskers`, with the password `hunter22`
+fn store_with_account() -> Store {
+    Store {
+        people: vec![person("superwhiskers", 1)],
+        passwords: vec![(Pid(1), Cow::Borrowed("hunter22"))]
+            .into_iter()
+            .collect(),
+        ..Store::default()
+    }
+}
+
+/// Asserts that the provided result is an error xml document with the provided code
+fn assert_error_code<T: std::fmt::Debug>(result: Result<T, ClientError>, code: ErrorCodeValue) {
+    match result {
+        Err(ClientError::ErrorXml(errors)) => {
+            assert_eq!(errors.first_code(), Some(&ErrorCode::Known(code)))
+        }
+        result => panic!("expected an error of {:?}, got {:?}", code, result),
+    }
+}
+
+#[tokio::test]
+async fn does_user_exist() {
+    let client = start(Store {
+        people: vec![person("superwhiskers", 1)],
+        ..Store::default()
+    })
+    .await;
+
+    assert!(client
+        .does_user_exist(Nnid(Cow::Borrowed("SuperWhiskers")))
+        .await
+        .expect("unable to check if the user exists"));
+    assert!(!client
+        .does_user_exist(Nnid(Cow::Borrowed("ralsei")))
+        .await
+        .expect("unable to check if the user exists"));
+
+    // paths nested under that of a user are not lookups of a user
+    assert!(matches!(
+        client
+            .does_user_exist(Nnid(Cow::Borrowed("superwhiskers/profile")))
+            .await,
+        Err(ClientError::UnexpectedStatusCode(404))
+    ));
+    assert_error_code(
+        client
+            .does_user_exist(Nnid(Cow::Borrowed("@me/profile")))
+            .await,
+        ErrorCodeValue::UnauthorizedClient,
+    );
+}
+
+#[tokio::test]
+async fn agreements() {
+    let agreement = |version| Agreement {
+        country: Some(CountryCode::USA),
+        language: Some(Iso639_1::En),
+        title_text: Some(Cow::Borrowed("Nintendo Network Services Agreement")),
+        kind: AgreementKind::Known(AgreementKindValue::Eula),
+        version: Some(version),
+        ..Agreement::default()
+    };
+    let client = start(Store {
+        agreements: vec![agreement(0x0200), agreement(0x0300)],
+        ..Store::default()
+    })
+    .await;
+
+    assert_eq!(
+        client
+            .agreements(
+                AgreementKindValue::Eula,
+                CountryCode::USA,
+                AgreementVersionParameter::Latest
+            )
+            .await
+            .expect("unable to retrieve the latest agreement")
+            .agreements,
+        vec![agreement(0x0300)]
+    );
+    assert_eq!(
+        client
+            .agreements(
+                AgreementKindValue::Eula,
+                CountryCode::USA,
+                AgreementVersionParameter::Version(0x0200)
+            )
+            .await
+            .expect("unable to retrieve an older agreement")
+            .agreements,
+        vec![agreement(0x0200)]
+    );
+    assert!(client
+        .agreements(
+            AgreementKindValue::Eula,
+            CountryCode::JPN,
+            AgreementVersionParameter::Latest
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn timezones() {
+    let timezone = Timezone {
+        area: Some(Cow::Borrowed("America/New_York")),
+        language: Some(Iso639_1::En),
+        name: Some(Cow::Borrowed("Eastern Time")),
+        order: Some(1),
+        ..Timezone::default()
+    };
+    let client = start(Store {
+        timezones: vec![((CountryCode::USA, Iso639_1::En), vec![timezone.clone()])]
+            .into_iter()
+            .collect(),
+        ..Store::default()
+    })
+    .await;
+
+    assert_eq!(
+        client
+            .timezones(CountryCode::USA, Iso639_1::En)
+            .await
+            .expect("unable to retrieve timezones")
+            .timezones,
+        vec![timezone]
+    );
+}
+
+#[tokio::test]
+async fn time() {
+    let time = Utc
+        .timestamp_millis_opt(1_600_000_000_000)
+        .single()
+        .expect("unable to construct a timestamp");
+    let client = start(Store {
+        time: Some(time),
+        ..Store::default()
+    })
+    .await;
+
+    assert_eq!(
+        client.time().await.expect("unable to retrieve the time"),
+        time
+    );
+}
+
+#[tokio::test]
+async fn convert_id() {
+    let client = start(Store {
+        people: vec![person("superwhiskers", 1), person("ralsei", 2)],
+        ..Store::default()
+    })
+    .await;
+
+    assert_eq!(
+        client
+            .convert_id(
+                &[
+                    Identifier::Nnid(Nnid(Cow::Borrowed("ralsei"))),
+                    Identifier::Nnid(Nnid(Cow::Borrowed("kris"))),
+                ],
+                IdentifierKind::Nnid,
+                IdentifierKind::Pid
+            )
+            .await
+            .expect("unable to convert identifiers"),
+        vec![
+            (
+                Identifier::Nnid(Nnid(Cow::Borrowed("ralsei"))),
+                Some(Identifier::Pid(Pid(2)))
+            ),
+            (Identifier::Nnid(Nnid(Cow::Borrowed("kris"))), None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn device_certificate() {
+    let issuer = DeviceCertificate::try_from(CA_CERTIFICATE).expect("unable to parse the issuer");
+    let device_certificate =
+        DeviceCertificate::try_from(DEVICE_CERTIFICATE).expect("unable to parse the certificate");
+
+    let client = start_server(
+        Server::default().with_device_certificate_issuer(issuer.clone()),
+        Some(device_certificate.clone()),
+    )
+    .await;
+    assert!(!client
+        .does_user_exist(Nnid(Cow::Borrowed("ralsei")))
+        .await
+        .expect("unable to check if the user exists"));
+
+    // a certificate altered after it was signed is rejected
+    let forged_certificate = DeviceCertificate {
+        key_id: KeyId(device_certificate.key_id.0 + 1),
+        ..device_certificate
+    };
+    let client = start_server(
+        Server::default().with_device_certificate_issuer(issuer),
+        Some(forged_certificate),
+    )
+    .await;
+    match client.does_user_exist(Nnid(Cow::Borrowed("ralsei"))).await {
+        Err(ClientError::ErrorXml(errors)) => assert_eq!(
+            errors.first_code(),
+            Some(&ErrorCode::Known(ErrorCodeValue::UnauthorizedDevice))
+        ),
+        result => panic!("the forged certificate was accepted: {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn device_certificate_by_default() {
+    let device_certificate =
+        DeviceCertificate::try_from(DEVICE_CERTIFICATE).expect("unable to parse the certificate");
+    let forged_certificate = DeviceCertificate {
+        key_id: KeyId(device_certificate.key_id.0 + 1),
+        ..device_certificate
+    };
+
+    // without an issuer to check it against, no certificate is accepted, and one is required
+    for device_certificate in [Some(forged_certificate), None] {
+        let client = start_server(Server::default(), device_certificate).await;
+        assert_error_code(
+            client.does_user_exist(Nnid(Cow::Borrowed("ralsei"))).await,
+            ErrorCodeValue::UnauthorizedDevice,
+        );
+    }
+
+    let client = start_server(
+        Server::default().with_device_certificate_required(false),
+        None,
+    )
+    .await;
+    assert!(!client
+        .does_user_exist(Nnid(Cow::Borrowed("ralsei")))
+        .await
+        .expect("unable to check if the user exists"));
+}
+
+#[tokio::test]
+async fn login() {
+    let client = start(store_with_account()).await;
+
+    assert_error_code(
+        client
+            .login(
+                Nnid(Cow::Borrowed("superwhiskers")),
+                Password::Plain(Cow::Borrowed("hunter2")),
+            )
+            .await,
+        ErrorCodeValue::WrongAccountPassword,
+    );
+    assert_error_code(
+        client
+            .login(
+                Nnid(Cow::Borrowed("ralsei")),
+                Password::Plain(Cow::Borrowed("hunter22")),
+            )
+            .await,
+        ErrorCodeValue::WrongAccountPassword,
+    );
+
+    let token = client
+        .login(
+            Nnid(Cow::Borrowed("SuperWhiskers")),
+            Password::Plain(Cow::Borrowed("hunter22")),
+        )
+        .await
+        .expect("unable to log in using a plaintext password");
+    assert!(!token.is_expired());
+    assert_eq!(client.access_token.read().as_ref(), Some(&token));
+
+    let hashed_token = client
+        .login(
+            Nnid(Cow::Borrowed("superwhiskers")),
+            Password::hash(Pid(1), "hunter22"),
+        )
+        .await
+        .expect("unable to log in using a hashed password");
+    assert_ne!(hashed_token.access_token, token.access_token);
+
+    // a refresh token may only be used once
+    let refreshed_token = client
+        .refresh_login()
+        .await
+        .expect("unable to refresh the access token");
+    assert_ne!(refreshed_token.access_token, hashed_token.access_token);
+    *client.access_token.write() = Some(hashed_token);
+    assert_error_code(
+        client.refresh_login().await,
+        ErrorCodeValue::InvalidAccountToken,
+    );
+}
+
+#[tokio::test]
+async fn profile() {
+    let client = start(store_with_account()).await;
+
+    assert!(matches!(
+        client.profile().await,
+        Err(ClientError::MissingAccessToken)
+    ));
+
+    client
+        .login(
+            Nnid(Cow::Borrowed("superwhiskers")),
+            Password::Plain(Cow::Borrowed("hunter22")),
+        )
+        .await
+        .expect("unable to log in");
+    assert_eq!(
+        client
+            .profile()
+            .await
+            .expect("unable to retrieve the profile"),
+        person("superwhiskers", 1)
+    );
+
+    // access tokens that were not issued by the server are rejected
+    if let Some(token) = client.access_token.write().as_mut() {
+        token.access_token = Cow::Borrowed("forged");
+    }
+    assert_error_code(client.profile().await, ErrorCodeValue::InvalidAccountToken);
+}
+
+#[tokio::test]
+async fn tokens() {
+    let client = start(Store {
+        game_servers: vec![(
+            0x1010EB00,
+            GameServer {
+                host: Cow::Borrowed("127.0.0.1"),
+                port: 60000,
+            },
+        )]
+        .into_iter()
+        .collect(),
+        services: vec![
+            Cow::Borrowed("87cd32617f1985439ea608c2746e4610"),
+            Cow::Borrowed("client id&with=reserved characters"),
+        ]
+        .into_iter()
+        .collect(),
+        ..store_with_account()
+    })
+    .await;
+    client
+        .login(
+            Nnid(Cow::Borrowed("superwhiskers")),
+            Password::Plain(Cow::Borrowed("hunter22")),
+        )
+        .await
+        .expect("unable to log in");
+
+    let nex_token = client
+        .nex_token(0x1010EB00)
+        .await
+        .expect("unable to retrieve a nex token");
+    assert_eq!(nex_token.host.as_deref(), Some("127.0.0.1"));
+    assert_eq!(nex_token.port, Some(60000));
+    assert_eq!(nex_token.pid, Some(Pid(1)));
+    assert!(nex_token.token.is_some());
+
+    // the nex password of an account does not change between tokens
+    let nex_password = nex_token.nex_password.clone();
+    assert!(nex_password.is_some());
+    assert_eq!(
+        client
+            .nex_token(0x1010EB00)
+            .await
+            .expect("unable to retrieve a nex token")
+            .nex_password,
+        nex_password
+    );
+    assert_error_code(
+        client.nex_token(0x10101000).await,
+        ErrorCodeValue::InvalidGameServerId,
+    );
+
+    assert!(client
+        .service_token("87cd32617f1985439ea608c2746e4610")
+        .await
+        .expect("unable to retrieve a service token")
+        .token
+        .is_some());
+    assert_error_code(
+        client
+            .service_token("ea25c66c26b403376b4c5ed94ab9cdea")
+            .await,
+        ErrorCodeValue::InvalidClientId,
+    );
+
+    // client ids are encoded into the query string rather than pasted into it
+    let service_token = client
+        .service_token("client id&with=reserved characters")
+        .await
+        .expect("unable to retrieve a service token");
+
+    // the tokens are owned, so they outlive the client that retrieved them
+    drop(client);
+    assert!(service_token.token.is_some());
+    assert!(nex_token.token.is_some());
+}
+
+#[tokio::test]
+async fn validate_email() {
+    let client = start(Store::default()).await;
+
+    client
+        .validate_email("ralsei@example.com")
+        .await
+        .expect("unable to validate a valid email address");
+    assert!(matches!(
+        client.validate_email("ralsei").await,
+        Err(ClientError::RegistrationError(
+            RegistrationError::InvalidMailAddress
+        ))
+    ));
+    assert!(matches!(
+        client.validate_email("ralsei@example").await,
+        Err(ClientError::RegistrationError(
+            RegistrationError::InvalidMailAddressDomainName
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn create_account() {
+    let client = start(store_with_account()).await;
+    let new_person = |nnid, password| NewPerson {
+        user_id: Some(Nnid(Cow::Borrowed(nnid))),
+        password: Some(Cow::Borrowed(password)),
+        country: Some(CountryCode::USA),
+        language: Some(Iso639_1::En),
+        email: Some(Email {
+            address: Some(Cow::Borrowed("ralsei@example.com")),
+            ..Email::default()
+        }),
+        ..NewPerson::default()
+    };
+
+    assert!(matches!(
+        client
+            .create_account(&new_person("SuperWhiskers", "password"))
+            .await,
+        Err(ClientError::RegistrationError(
+            RegistrationError::AccountIdExists
+        ))
+    ));
+    assert!(matches!(
+        client
+            .create_account(&new_person("ralsei!", "password"))
+            .await,
+        Err(ClientError::RegistrationError(
+            RegistrationError::AccountIdFormatInvalid
+        ))
+    ));
+    assert!(matches!(
+        client.create_account(&new_person("ralsei", "Ralsei")).await,
+        Err(ClientError::RegistrationError(
+            RegistrationError::AccountIdPasswordSame
+        ))
+    ));
+
+    let pid = client
+        .create_account(&new_person("ralsei", "password"))
+        .await
+        .expect("unable to create an account");
+    assert_eq!(pid, Pid(1_000_000_000));
+    assert_eq!(
+        client
+            .create_account(&new_person("noelle", "password"))
+            .await
+            .expect("unable to create an account"),
+        Pid(1_000_000_001)
+    );
+    assert!(client
+        .does_user_exist(Nnid(Cow::Borrowed("ralsei")))
+        .await
+        .expect("unable to check if the user exists"));
+
+    // the new account is able to log in
+    client
+        .login(
+            Nnid(Cow::Borrowed("ralsei")),
+            Password::Plain(Cow::Borrowed("password")),
+        )
+        .await
+        .expect("unable to log in to the new account");
+    let profile = client
+        .profile()
+        .await
+        .expect("unable to retrieve the profile");
+
+    // the profile is owned, so it outlives the client that retrieved it
+    drop(client);
+    assert_eq!(profile.pid, Some(pid));
+    assert_eq!(profile.user_id, Some(Nnid(Cow::Borrowed("ralsei"))));
+    assert_eq!(profile.country, Some(CountryCode::USA));
+    assert_eq!(
+        profile.email.and_then(|email| email.address),
+        Some(Cow::Borrowed("ralsei@example.com"))
+    );
+}
@@ -9,11 +9,13 @@
 
 use chrono::{
     offset::{TimeZone, Utc},
-    DateTime,
+    DateTime, Duration,
 };
 use futures::stream::TryStreamExt;
 use http::{
-    header::{HeaderMap, HeaderValue, ToStrError as HeaderValueToStrError},
+    header::{
+        self, HeaderMap, HeaderValue, InvalidHeaderValue, ToStrError as HeaderValueToStrError,
+    },
     uri::{Authority, InvalidUri, PathAndQuery},
     Error as HttpError, Request, Uri, Version as HttpVersion,
 };
@@ -29,9 +31,11 @@ use native_tls::{
 };
 use parking_lot::RwLock;
 use quick_xml::Reader as XmlReader;
+use sha2::{Digest, Sha256};
 use std::{
     borrow::Cow,
     convert::TryFrom,
+    fmt::Write,
     mem::MaybeUninit,
     num::ParseIntError,
     str::{self, FromStr},
@@ -40,45 +44,69 @@ use std::{
 use tokio_native_tls::TlsConnector;
 
 use crate::{
-    common::{account_api_endpoints, DEFAULT_ACCOUNT_SERVER_HOST},
+    common::{account_api_endpoints, DEFAULT_ACCOUNT_SERVER_HOST, XML_DECLARATION},
     xml::{
         agreement::{AgreementKindValue, Agreements},
         error as error_xml,
         errors::Error as XmlErrorExtension,
+        mapped_id::MappedIds,
+        new_person::NewPerson,
+        oauth::OAuth20,
+        person::Person,
         timezone::Timezones,
+        token::{NexToken, ServiceToken},
     },
 };
 use ralsei_keypairs::{CTR_COMMON_1, NINTENDO_CACERTS, WUP_ACCOUNT_1};
 use ralsei_model::{
     console::common::{Console, HeaderConstructionError, Kind as ConsoleKind},
-    network::Nnid,
+    network::{Identifier, IdentifierKind, Nnid, Pid},
     server::Kind as ServerKind,
 };
 use ralsei_util::xml::{
     errors::Error as XmlError,
-    framework::{BufferPool, FromXml},
+    framework::{self as xml_framework, BufferPool, FromXml},
     GLOBAL_BUFFER_POOL,
 };
 
-macro handle_error_xml($self:ident, $response:ident) {{
-    let mut error = error_xml::Errors::default();
-    error
-        .from_xml(
-            &mut XmlReader::from_reader(
-                $response
-                    .into_body()
-                    .try_fold(Vec::new(), |mut accumulator, chunk| async move {
-                        accumulator.extend_from_slice(&chunk);
-                        Ok(accumulator)
-                    })
-                    .await?
-                    .as_slice(),
-            ),
-            $self.pool.clone(),
-        )
-        .await?;
-    Err(error.into())
-}}
+macro handle_error_xml {
+    ($self:ident, $response:ident) => {
+        handle_error_xml!($self, $response, |error: error_xml::Errors<'static>| -> ClientError {
+            error.into()
+        })
+    },
+    ($self:ident, $response:ident, $map:expr) => {{
+        let mut error = error_xml::Errors::default();
+        error
+            .from_xml(
+                &mut XmlReader::from_reader(
+                    $response
+                        .into_body()
+                        .try_fold(Vec::new(), |mut accumulator, chunk| async move {
+                            accumulator.extend_from_slice(&chunk);
+                            Ok(accumulator)
+                        })
+                        .await?
+                        .as_slice(),
+                ),
+                $self.pool.clone(),
+            )
+            .await?;
+        Err($map(error))
+    }},
+}
+
+/// Maps an error xml document returned by a registration endpoint to a [`RegistrationError`] if
+/// it has a known error code
+fn registration_error(error: error_xml::Errors<'static>) -> ClientError {
+    match error
+        .first_code()
+        .and_then(RegistrationError::from_error_code)
+    {
+        Some(registration_error) => registration_error.into(),
+        None => error.into(),
+    }
+}
 
 /// A client for the Nintendo Network account servers
 ///
@@ -101,6 +129,15 @@ pub struct Client<'a, C: Console<'a> + Send + Clone> {
     /// removing the overhead of memory allocation
     pub pool: BufferPool,
 
+    /// The access token used to authenticate requests made on behalf of a user
+    ///
+    /// It is populated by [`login`] and [`refresh_login`], but it can also be set manually in
+    /// order to use an access token retrieved elsewhere
+    ///
+    /// [`login`]: #method.login
+    /// [`refresh_login`]: #method.refresh_login
+    pub access_token: RwLock<Option<Token>>,
+
     /// A cache of the headers to avoid recalling [`Console::http_headers`]
     pub(crate) cached_headers: RwLock<HeaderMap<HeaderValue>>,
 
@@ -141,6 +178,7 @@ impl<'a, C: Console<'a> + Send + Clone> Client<'a, C> {
             } else {
                 GLOBAL_BUFFER_POOL.clone()
             },
+            access_token: RwLock::new(None),
             cached_headers: RwLock::new(
                 console
                     .read()
@@ -196,6 +234,20 @@ impl<'a, C: Console<'a> + Send + Clone> Client<'a, C> {
         Ok(())
     }
 
+    /// Construct a [`Uri`] pointing to the provided path on the account server
+    ///
+    /// The read lock acquired on the [`host`] field is released before this method returns, so it
+    /// is not held across any await points while the request is being executed
+    ///
+    /// [`host`]: #structfield.host
+    fn uri(&self, path_and_query: &str) -> Result<Uri, ClientError> {
+        Ok(Uri::builder()
+            .scheme("https")
+            .authority(Authority::try_from(self.host.read().as_ref())?)
+            .path_and_query(PathAndQuery::try_from(path_and_query)?)
+            .build()?)
+    }
+
     /// Execute a request using the provided [`Request`]
     #[inline]
     pub fn request(&self, mut request: Request<Body>) -> ResponseFuture {
@@ -205,6 +257,135 @@ impl<'a, C: Console<'a> + Send + Clone> Client<'a, C> {
         self.http.request(request)
     }
 
+    /// Execute a request using the provided [`Request`], authenticating it using the stored
+    /// [`access_token`]
+    ///
+    /// If no access token is stored, an error of [`ClientError::MissingAccessToken`] is returned
+    ///
+    /// [`access_token`]: #structfield.access_token
+    pub fn authenticated_request(
+        &self,
+        mut request: Request<Body>,
+    ) -> Result<ResponseFuture, ClientError> {
+        let authorization = match &*self.access_token.read() {
+            Some(token) => HeaderValue::from_str(&format!("Bearer {}", token.access_token))?,
+            None => return Err(ClientError::MissingAccessToken),
+        };
+        request
+            .headers_mut()
+            .insert(header::AUTHORIZATION, authorization);
+        Ok(self.request(request))
+    }
+
+    /// Log in to the provided account server as the user with the given [`Nnid`] using the
+    /// provided [`Password`]
+    ///
+    /// Upon success, the resulting [`Token`] is stored in the [`access_token`] field, where it is
+    /// used to authenticate subsequent requests
+    ///
+    /// [`access_token`]: #structfield.access_token
+    pub async fn login(
+        &self,
+        nnid: Nnid<'_>,
+        password: Password<'_>,
+    ) -> Result<Token, ClientError> {
+        let mut body = form_urlencoded::Serializer::new(String::new());
+        body.append_pair("grant_type", "password")
+            .append_pair("user_id", &nnid.0);
+        match &password {
+            Password::Plain(password) => body.append_pair("password", password),
+            Password::Hashed(password) => body
+                .append_pair("password", password)
+                .append_pair("password_type", "hash"),
+        };
+        self.generate_access_token(body.finish()).await
+    }
+
+    /// Generate a new access token using the refresh token of the stored [`access_token`]
+    ///
+    /// Upon success, the resulting [`Token`] replaces the one stored in the [`access_token`] field
+    ///
+    /// [`access_token`]: #structfield.access_token
+    pub async fn refresh_login(&self) -> Result<Token, ClientError> {
+        let refresh_token = self
+            .access_token
+            .read()
+            .as_ref()
+            .ok_or(ClientError::MissingAccessToken)?
+            .refresh_token
+            .clone()
+            .ok_or(ClientError::MissingRefreshToken)?;
+        self.generate_access_token(
+            form_urlencoded::Serializer::new(String::new())
+                .append_pair("grant_type", "refresh_token")
+                .append_pair("refresh_token", &refresh_token)
+                .finish(),
+        )
+        .await
+    }
+
+    /// Perform an access token grant using the provided url-encoded form body, storing the
+    /// resulting [`Token`] in the [`access_token`] field
+    ///
+    /// [`access_token`]: #structfield.access_token
+    async fn generate_access_token(&self, body: String) -> Result<Token, ClientError> {
+        let response = self
+            .request(
+                Request::builder()
+                    .method("POST")
+                    .uri(self.uri(account_api_endpoints::ACCESS_TOKEN)?)
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .version(HttpVersion::HTTP_11)
+                    .body(Body::from(body))?,
+            )
+            .await?;
+        match response.status().as_u16() {
+            200 => {
+                let issued_at = Utc::now();
+                let mut oauth = OAuth20::default();
+                oauth
+                    .from_xml(
+                        &mut XmlReader::from_reader(
+                            response
+                                .into_body()
+                                .try_fold(Vec::new(), |mut accumulator, chunk| async move {
+                                    accumulator.extend_from_slice(&chunk);
+                                    Ok(accumulator)
+                                })
+                                .await?
+                                .as_slice(),
+                        ),
+                        self.pool.clone(),
+                    )
+                    .await?;
+                let access_token = oauth
+                    .access_token
+                    .ok_or(ClientError::MissingXmlField("access_token"))?;
+                let token = Token {
+                    access_token: Cow::Owned(
+                        access_token
+                            .token
+                            .ok_or(ClientError::MissingXmlField("token"))?
+                            .into_owned(),
+                    ),
+                    refresh_token: access_token
+                        .refresh_token
+                        .map(|refresh_token| Cow::Owned(refresh_token.into_owned())),
+                    expires_at: issued_at
+                        + Duration::seconds(i64::from(
+                            access_token
+                                .expires_in
+                                .ok_or(ClientError::MissingXmlField("expires_in"))?,
+                        )),
+                };
+                *self.access_token.write() = Some(token.clone());
+                Ok(token)
+            }
+            400 | 401 => handle_error_xml!(self, response),
+            status => Err(ClientError::UnexpectedStatusCode(status)),
+        }
+    }
+
     /// Check if a user with the given [`Nnid`] exists on the provided account server
     pub async fn does_user_exist(&self, nnid: Nnid<'_>) -> Result<bool, ClientError> {
         let mut path = nnid.0.into_owned();
@@ -213,13 +394,7 @@ impl<'a, C: Console<'a> + Send + Clone> Client<'a, C> {
             .request(
                 Request::builder()
                     .method("GET")
-                    .uri(
-                        Uri::builder()
-                            .scheme("https")
-                            .authority(Authority::try_from(self.host.read().as_ref())?)
-                            .path_and_query(PathAndQuery::try_from(path.as_str())?)
-                            .build()?,
-                    )
+                    .uri(self.uri(path.as_str())?)
                     .version(HttpVersion::HTTP_11)
                     .body(Body::empty())?,
             )
@@ -254,6 +429,207 @@ impl<'a, C: Console<'a> + Send + Clone> Client<'a, C> {
         }
     }
 
+    /// Check if the provided email address is able to be used to register an account on the
+    /// provided account server
+    ///
+    /// If the email address is unable to be used, an error of [`ClientError::RegistrationError`] is
+    /// returned describing why
+    pub async fn validate_email(&self, email: &str) -> Result<(), ClientError> {
+        let response = self
+            .request(
+                Request::builder()
+                    .method("POST")
+                    .uri(self.uri(account_api_endpoints::VALIDATE_EMAIL)?)
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .version(HttpVersion::HTTP_11)
+                    .body(Body::from(
+                        form_urlencoded::Serializer::new(String::new())
+                            .append_pair("email", email)
+                            .finish(),
+                    ))?,
+            )
+            .await?;
+        match response.status().as_u16() {
+            200 => Ok(()),
+            400 | 401 => handle_error_xml!(self, response, registration_error),
+            status => Err(ClientError::UnexpectedStatusCode(status)),
+        }
+    }
+
+    /// Register a new account described by the provided [`NewPerson`] on the provided account
+    /// server, returning the [`Pid`] it was assigned
+    ///
+    /// If the account server refuses to register the account for a known reason, an error of
+    /// [`ClientError::RegistrationError`] is returned describing why
+    pub async fn create_account(&self, person: &NewPerson<'_>) -> Result<Pid, ClientError> {
+        let mut body = String::from(XML_DECLARATION);
+        body.push_str(&xml_framework::to_string(person).await?);
+        let response = self
+            .request(
+                Request::builder()
+                    .method("POST")
+                    .uri(self.uri(account_api_endpoints::PEOPLE)?)
+                    .header(header::CONTENT_TYPE, "application/xml")
+                    .version(HttpVersion::HTTP_11)
+                    .body(Body::from(body))?,
+            )
+            .await?;
+        match response.status().as_u16() {
+            200 => {
+                let mut person = Person::default();
+                person
+                    .from_xml(
+                        &mut XmlReader::from_reader(
+                            response
+                                .into_body()
+                                .try_fold(Vec::new(), |mut accumulator, chunk| async move {
+                                    accumulator.extend_from_slice(&chunk);
+                                    Ok(accumulator)
+                                })
+                                .await?
+                                .as_slice(),
+                        ),
+                        self.pool.clone(),
+                    )
+                    .await?;
+                person.pid.ok_or(ClientError::MissingXmlField("pid"))
+            }
+            400 | 401 => handle_error_xml!(self, response, registration_error),
+            status => Err(ClientError::UnexpectedStatusCode(status)),
+        }
+    }
+
+    /// Retrieve the [`Person`] (profile) of the user the stored [`access_token`] belongs to from
+    /// the provided account server
+    ///
+    /// [`access_token`]: #structfield.access_token
+    pub async fn profile(&self) -> Result<Person<'static>, ClientError> {
+        let mut path = String::from(account_api_endpoints::PEOPLE);
+        path.push_str("@me/profile");
+        let response = self
+            .authenticated_request(
+                Request::builder()
+                    .method("GET")
+                    .uri(self.uri(path.as_str())?)
+                    .version(HttpVersion::HTTP_11)
+                    .body(Body::empty())?,
+            )?
+            .await?;
+        match response.status().as_u16() {
+            200 => {
+                let mut person = Person::default();
+                person
+                    .from_xml(
+                        &mut XmlReader::from_reader(
+                            response
+                                .into_body()
+                                .try_fold(Vec::new(), |mut accumulator, chunk| async move {
+                                    accumulator.extend_from_slice(&chunk);
+                                    Ok(accumulator)
+                                })
+                                .await?
+                                .as_slice(),
+                        ),
+                        self.pool.clone(),
+                    )
+                    .await?;
+                Ok(person)
+            }
+            400 | 401 => handle_error_xml!(self, response),
+            status => Err(ClientError::UnexpectedStatusCode(status)),
+        }
+    }
+
+    /// Retrieve a [`NexToken`] for the game server with the provided id from the provided account
+    /// server on behalf of the user the stored [`access_token`] belongs to
+    ///
+    /// [`access_token`]: #structfield.access_token
+    pub async fn nex_token(&self, game_server_id: u32) -> Result<NexToken<'static>, ClientError> {
+        let mut path = String::from(account_api_endpoints::NEX_TOKEN);
+        let _ = write!(path, "?game_server_id={:08X}", game_server_id);
+        let response = self
+            .authenticated_request(
+                Request::builder()
+                    .method("GET")
+                    .uri(self.uri(path.as_str())?)
+                    .version(HttpVersion::HTTP_11)
+                    .body(Body::empty())?,
+            )?
+            .await?;
+        match response.status().as_u16() {
+            200 => {
+                let mut nex_token = NexToken::default();
+                nex_token
+                    .from_xml(
+                        &mut XmlReader::from_reader(
+                            response
+                                .into_body()
+                                .try_fold(Vec::new(), |mut accumulator, chunk| async move {
+                                    accumulator.extend_from_slice(&chunk);
+                                    Ok(accumulator)
+                                })
+                                .await?
+                                .as_slice(),
+                        ),
+                        self.pool.clone(),
+                    )
+                    .await?;
+                Ok(nex_token)
+            }
+            400 | 401 => handle_error_xml!(self, response),
+            status => Err(ClientError::UnexpectedStatusCode(status)),
+        }
+    }
+
+    /// Retrieve a [`ServiceToken`] for the service with the provided client id from the provided
+    /// account server on behalf of the user the stored [`access_token`] belongs to
+    ///
+    /// [`access_token`]: #structfield.access_token
+    pub async fn service_token(
+        &self,
+        client_id: &str,
+    ) -> Result<ServiceToken<'static>, ClientError> {
+        let mut path = String::from(account_api_endpoints::SERVICE_TOKEN);
+        path.push('?');
+        path.push_str(
+            &form_urlencoded::Serializer::new(String::new())
+                .append_pair("client_id", client_id)
+                .finish(),
+        );
+        let response = self
+            .authenticated_request(
+                Request::builder()
+                    .method("GET")
+                    .uri(self.uri(path.as_str())?)
+                    .version(HttpVersion::HTTP_11)
+                    .body(Body::empty())?,
+            )?
+            .await?;
+        match response.status().as_u16() {
+            200 => {
+                let mut service_token = ServiceToken::default();
+                service_token
+                    .from_xml(
+                        &mut XmlReader::from_reader(
+                            response
+                                .into_body()
+                                .try_fold(Vec::new(), |mut accumulator, chunk| async move {
+                                    accumulator.extend_from_slice(&chunk);
+                                    Ok(accumulator)
+                                })
+                                .await?
+                                .as_slice(),
+                        ),
+                        self.pool.clone(),
+                    )
+                    .await?;
+                Ok(service_token)
+            }
+            400 | 401 => handle_error_xml!(self, response),
+            status => Err(ClientError::UnexpectedStatusCode(status)),
+        }
+    }
+
     /// Retrieve [`Agreements`] from the provided account server based on their
     /// [kind](AgreementKindValue), their associated [`CountryCode`], and
     /// [version](AgreementVersionParameter)
@@ -286,13 +662,7 @@ impl<'a, C: Console<'a> + Send + Clone> Client<'a, C> {
             .request(
                 Request::builder()
                     .method("GET")
-                    .uri(
-                        Uri::builder()
-                            .scheme("https")
-                            .authority(Authority::try_from(self.host.read().as_ref())?)
-                            .path_and_query(PathAndQuery::try_from(path.as_str())?)
-                            .build()?,
-                    )
+                    .uri(self.uri(path.as_str())?)
                     .version(HttpVersion::HTTP_11)
                     .body(Body::empty())?,
             )
@@ -345,17 +715,11 @@ impl<'a, C: Console<'a> + Send + Clone> Client<'a, C> {
             .request(
                 Request::builder()
                     .method("GET")
-                    .uri(
-                        Uri::builder()
-                            .scheme("https")
-                            .authority(Authority::try_from(self.host.read().as_ref())?)
-                            .path_and_query(PathAndQuery::try_from(unsafe {
-                                // SAFETY: `path` has been initialized
-                                // SAFETY: all bytes are written from valid utf8
-                                str::from_utf8_unchecked(MaybeUninit::slice_assume_init_ref(&path))
-                            })?)
-                            .build()?,
-                    )
+                    .uri(self.uri(unsafe {
+                        // SAFETY: `path` has been initialized
+                        // SAFETY: all bytes are written from valid utf8
+                        str::from_utf8_unchecked(MaybeUninit::slice_assume_init_ref(&path))
+                    })?)
                     .version(HttpVersion::HTTP_11)
                     .body(Body::empty())?,
             )
@@ -391,13 +755,7 @@ impl<'a, C: Console<'a> + Send + Clone> Client<'a, C> {
             .request(
                 Request::builder()
                     .method("GET")
-                    .uri(
-                        Uri::builder()
-                            .scheme("https")
-                            .authority(Authority::try_from(self.host.read().as_ref())?)
-                            .path_and_query(PathAndQuery::try_from(account_api_endpoints::TIME)?)
-                            .build()?,
-                    )
+                    .uri(self.uri(account_api_endpoints::TIME)?)
                     .version(HttpVersion::HTTP_11)
                     .body(Body::empty())?,
             )
@@ -421,8 +779,151 @@ impl<'a, C: Console<'a> + Send + Clone> Client<'a, C> {
         }
     }
 
-    /// Map one valid id on the network to another
-    pub async fn convert_id(&self) -> Result<(), ClientError> {
+    /// Map a batch of [`Identifier`]s of the `input` [`IdentifierKind`] to their corresponding
+    /// identifiers of the `output` [`IdentifierKind`]
+    ///
+    /// Each of the returned pairs contains an identifier that was provided alongside the identifier
+    /// it was mapped to. If the account server was unable to map an identifier, it is paired with
+    /// `None`
+    pub async fn convert_id(
+        &self,
+        ids: &[Identifier<'_>],
+        input: IdentifierKind,
+        output: IdentifierKind,
+    ) -> Result<Vec<(Identifier<'static>, Option<Identifier<'static>>)>, ClientError> {
+        if let Some(id) = ids.iter().find(|id| id.kind() != input) {
+            return Err(ClientError::MismatchedIdentifierKind(id.kind()));
+        }
+
+        let mut path = String::from(account_api_endpoints::MAPPED_IDS);
+        path.push_str("?input_type=");
+        path.push_str(mapped_id_kind(input));
+        path.push_str("&output_type=");
+        path.push_str(mapped_id_kind(output));
+        path.push_str("&input=");
+        for (index, id) in ids.iter().enumerate() {
+            if index != 0 {
+                path.push(',');
+            }
+            path.push_str(&id.to_cow());
+        }
+
+        let response = self
+            .request(
+                Request::builder()
+                    .method("GET")
+                    .uri(self.uri(path.as_str())?)
+                    .version(HttpVersion::HTTP_11)
+                    .body(Body::empty())?,
+            )
+            .await?;
+        match response.status().as_u16() {
+            200 => {
+                let mut mapped_ids = MappedIds::default();
+                mapped_ids
+                    .from_xml(
+                        &mut XmlReader::from_reader(
+                            response
+                                .into_body()
+                                .try_fold(Vec::new(), |mut accumulator, chunk| async move {
+                                    accumulator.extend_from_slice(&chunk);
+                                    Ok(accumulator)
+                                })
+                                .await?
+                                .as_slice(),
+                        ),
+                        self.pool.clone(),
+                    )
+                    .await?;
+                mapped_ids
+                    .mapped_ids
+                    .into_iter()
+                    .map(|mapped_id| {
+                        Ok((
+                            Identifier::from_kind(
+                                input,
+                                Cow::Owned(
+                                    mapped_id
+                                        .input
+                                        .ok_or(ClientError::MissingXmlField("in_id"))?
+                                        .into_owned(),
+                                ),
+                            )?,
+                            mapped_id
+                                .output
+                                .map(|id| {
+                                    Identifier::from_kind(output, Cow::Owned(id.into_owned()))
+                                })
+                                .transpose()?,
+                        ))
+                    })
+                    .collect()
+            }
+            400 | 401 => handle_error_xml!(self, response),
+            status => Err(ClientError::UnexpectedStatusCode(status)),
+        }
+    }
+}
+
+/// Returns the name used by the mapped id endpoint of an account server for the provided
+/// [`IdentifierKind`]
+fn mapped_id_kind(kind: IdentifierKind) -> &'static str {
+    match kind {
+        IdentifierKind::Nnid => "user",
+        IdentifierKind::Pid => "pid",
+    }
+}
+
+/// An access token issued by an account server, used to authenticate requests made on behalf of a
+/// user
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Token {
+    /// The access token itself, which is sent in the `Authorization` header
+    pub access_token: Cow<'static, str>,
+
+    /// The token used to generate a new access token once this one expires
+    pub refresh_token: Option<Cow<'static, str>>,
+
+    /// The point in time at which the access token expires
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Token {
+    /// Returns `true` if the access token has expired
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+/// An enumeration over the ways a password can be provided to the access token generation
+/// endpoint of an account server
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Password<'a> {
+    /// A plaintext password
+    Plain(Cow<'a, str>),
+
+    /// A password hashed in the same way that consoles hash them. See [`Password::hash`]
+    Hashed(Cow<'a, str>),
+}
+
+impl Password<'_> {
+    /// Hashes the provided plaintext password in the same way consoles do before sending it to an
+    /// account server
+    ///
+    /// The hash is the hexadecimal representation of the SHA-256 digest of the user's [`Pid`] (as
+    /// little-endian bytes), the bytes `02 65 43 46`, and the password itself
+    pub fn hash(pid: Pid, password: &str) -> Password<'static> {
+        let mut hasher = Sha256::new();
+        hasher.update(pid.0.to_le_bytes());
+        hasher.update([0x02, 0x65, 0x43, 0x46]);
+        hasher.update(password.as_bytes());
+
+        let mut hash = String::with_capacity(64);
+        for byte in hasher.finalize() {
+            // writing to a string cannot fail
+            let _ = write!(hash, "{:02x}", byte);
+        }
+        Password::Hashed(Cow::Owned(hash))
     }
 }
 
@@ -479,9 +980,9 @@ pub enum ClientError {
     #[error("An error was encountered while deserializing XML")]
     XmlError(#[from] XmlError<XmlErrorExtension>),
 
-    /// An error was encountered while deserializing XML
-    #[error("An error was encountered while deserializing XML")]
-    XmlError(#[from] XmlError<XmlErrorExtension>),
+    /// The Nintendo Network API returned an XML document that lacks an expected field
+    #[error("The Nintendo Network API returned an XML document lacking an expected field, `{0}`")]
+    MissingXmlField(&'static str),
 
     /// The Nintendo Network API returned an unexpected status code
     #[error("The Nintendo Network API returned an unexpected status code, `{0}`")]
@@ -495,9 +996,129 @@ pub enum ClientError {
     #[error("An error was encountered while trying to interpret a header value as a &str")]
     HeaderValueToStrError(#[from] HeaderValueToStrError),
 
+    /// An error encountered when a value is unable to be used as a header value
+    #[error("An error was encountered while trying to use a value as a header value")]
+    InvalidHeaderValue(#[from] InvalidHeaderValue),
+
+    /// An error encountered when a request requiring authentication is made without an access
+    /// token
+    #[error("No access token is available to authenticate the request with")]
+    MissingAccessToken,
+
+    /// An error encountered when an access token is to be refreshed without a refresh token
+    #[error("No refresh token is available to generate a new access token with")]
+    MissingRefreshToken,
+
     /// An error encountered when a string cannot be parsed as an integer
     #[error("An error was encountered while trying to parse a string as an integer")]
     IntegerParseError(#[from] ParseIntError),
 
-    // /// An error encountered when an integer timestamp does not have a single representation
+    /// An error encountered when an integer timestamp does not have a single representation
+    #[error("The timestamp `{0}` does not have a single representation")]
+    TimestampParseError(i64),
+
+    /// An error encountered when an [`Identifier`] does not match the [`IdentifierKind`] it was
+    /// expected to be
+    #[error("An identifier of the kind `{0:?}` was provided where another kind was expected")]
+    MismatchedIdentifierKind(IdentifierKind),
+
+    /// The account server refused to register an account or validate an email for a known reason
+    #[error(transparent)]
+    RegistrationError(#[from] RegistrationError),
+}
+
+/// An enumeration over the known reasons the account server may refuse to register an account
+#[non_exhaustive]
+#[derive(thiserror::Error, Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum RegistrationError {
+    /// The requested [`Nnid`] is already in use
+    #[error("The requested account id is already in use")]
+    AccountIdExists,
+
+    /// The requested [`Nnid`] is invalid
+    #[error("The requested account id is invalid")]
+    InvalidAccountId,
+
+    /// The requested [`Nnid`] is formatted incorrectly
+    #[error("The requested account id is formatted incorrectly")]
+    AccountIdFormatInvalid,
+
+    /// The requested [`Nnid`] and the provided password are the same
+    #[error("The requested account id and the provided password are the same")]
+    AccountIdPasswordSame,
+
+    /// The provided email address is invalid
+    #[error("The provided email address is invalid")]
+    InvalidMailAddress,
+
+    /// The domain name of the provided email address is invalid
+    #[error("The domain name of the provided email address is invalid")]
+    InvalidMailAddressDomainName,
+
+    /// The domain name of the provided email address could not be resolved
+    #[error("The domain name of the provided email address could not be resolved")]
+    UnresolvableMailAddressDomainName,
+
+    /// The device is unable to register any more accounts
+    #[error("The device is unable to register any more accounts")]
+    RegistrationLimitReached,
+
+    /// The requested country does not match the device's country
+    #[error("The requested country does not match the device's country")]
+    CountryMismatch,
+
+    /// The country of the accepted agreement does not match the device's country
+    #[error("The country of the accepted agreement does not match the device's country")]
+    DeviceEulaCountryMismatch,
+
+    /// No agreement was accepted
+    #[error("No agreement was accepted")]
+    EulaNotAccepted,
+
+    /// The COPPA agreement was not accepted
+    #[error("The COPPA agreement was not accepted")]
+    CoppaNotAccepted,
+
+    /// Parental controls are required to register the account
+    #[error("Parental controls are required to register the account")]
+    ParentalControlsRequired,
+}
+
+impl RegistrationError {
+    /// Returns the [`RegistrationError`] corresponding to the provided [`ErrorCode`], or `None` if
+    /// it is not specific to registration
+    ///
+    /// [`ErrorCode`]: error_xml::ErrorCode
+    pub fn from_error_code(code: &error_xml::ErrorCode) -> Option<Self> {
+        use error_xml::{ErrorCode, ErrorCodeValue};
+
+        Some(match code {
+            ErrorCode::Known(ErrorCodeValue::AccountIdExists) => Self::AccountIdExists,
+            ErrorCode::Known(ErrorCodeValue::InvalidAccountId) => Self::InvalidAccountId,
+            ErrorCode::Known(ErrorCodeValue::AccountIdFormatInvalid) => {
+                Self::AccountIdFormatInvalid
+            }
+            ErrorCode::Known(ErrorCodeValue::AccountIdPasswordSame) => Self::AccountIdPasswordSame,
+            ErrorCode::Known(ErrorCodeValue::InvalidMailAddress) => Self::InvalidMailAddress,
+            ErrorCode::Known(ErrorCodeValue::InvalidMailAddressDomainName) => {
+                Self::InvalidMailAddressDomainName
+            }
+            ErrorCode::Known(ErrorCodeValue::UnresolvableMailAddressDomainName) => {
+                Self::UnresolvableMailAddressDomainName
+            }
+            ErrorCode::Known(ErrorCodeValue::RegistrationLimitReached) => {
+                Self::RegistrationLimitReached
+            }
+            ErrorCode::Known(ErrorCodeValue::CountryMismatch) => Self::CountryMismatch,
+            ErrorCode::Known(ErrorCodeValue::DeviceEulaCountryMismatch) => {
+                Self::DeviceEulaCountryMismatch
+            }
+            ErrorCode::Known(ErrorCodeValue::EulaNotAccepted) => Self::EulaNotAccepted,
+            ErrorCode::Known(ErrorCodeValue::CoppaNotAccepted) => Self::CoppaNotAccepted,
+            ErrorCode::Known(ErrorCodeValue::ParentalControlsRequired) => {
+                Self::ParentalControlsRequired
+            }
+            _ => return None,
+        })
+    }
 }
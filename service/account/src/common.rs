@@ -10,6 +10,9 @@
 /// The default (official Nintendo) host for the account server
 pub const DEFAULT_ACCOUNT_SERVER_HOST: &str = "account.nintendo.net";
 
+/// The XML declaration prepended to XML documents sent to the account server
+pub const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+
 //TODO(superwhiskers): make a proper macro for this as the existing one doesn't work at all with
 //                     multiple endpoints
 /// A module containing paths to various endpoints of the Nintendo Network account server
@@ -18,4 +21,9 @@ pub mod account_api_endpoints {
     pub const AGREEMENTS: &str = "/v1/api/content/agreements/";
     pub const TIMEZONES: &str = "/v1/api/content/time_zones/";
     pub const TIME: &str = "/v1/api/admin/time";
+    pub const MAPPED_IDS: &str = "/v1/api/admin/mapped_ids";
+    pub const ACCESS_TOKEN: &str = "/v1/api/oauth20/access_token/generate";
+    pub const NEX_TOKEN: &str = "/v1/api/provider/nex_token/@me";
+    pub const SERVICE_TOKEN: &str = "/v1/api/provider/service_token/@me";
+    pub const VALIDATE_EMAIL: &str = "/v1/api/support/validate/email";
 }
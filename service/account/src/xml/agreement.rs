@@ -389,3 +389,111 @@ pub enum AgreementKindValue {
     Eula,
     //TODO(superwhiskers): figure out all possible agreements, if there even are more
 }
+
+/// A record of an [`Agreement`] having been accepted, as sent to the account server when
+/// registering an account
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct AcceptedAgreement<'a> {
+    /// The accepted agreement's id
+    pub id: Option<u32>,
+
+    /// The accepted agreement's intended country
+    pub country: Option<CountryCode>,
+
+    /// The location the accepted agreement was retrieved from
+    pub location: Option<Cow<'a, str>>,
+
+    /// The accepted agreement's kind
+    pub kind: AgreementKind<'a>,
+
+    /// The accepted agreement's version
+    pub version: Option<u16>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for AcceptedAgreement<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"agreement")))?;
+
+        // the agreement's id
+        if let Some(ref id) = &self.id {
+            generate_xml_field_write!(
+                b"agreement_id",
+                writer,
+                BytesText::from_plain_str(&id.to_string())
+            );
+        }
+
+        // the agreement's intended country
+        if let Some(ref country) = &self.country {
+            generate_xml_field_write!(
+                b"country",
+                writer,
+                BytesText::from_plain_str(country.alpha2())
+            );
+        }
+
+        // the agreement's location
+        if let Some(ref location) = &self.location {
+            generate_xml_field_write!(b"location", writer, BytesText::from_plain_str(location));
+        }
+
+        // the agreement's kind
+        generate_xml_field_write_by_propagation!(b"type", writer, self.kind);
+
+        // the agreement's version
+        if let Some(ref version) = &self.version {
+            generate_xml_field_write!(
+                b"version",
+                writer,
+                BytesText::from_escaped_str(Cow::Owned(format!("{:0>4}", version)))
+            );
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"agreement")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for AcceptedAgreement<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read!(
+            b"agreement",
+            reader, buffer_pool,
+            c,
+
+            // the agreement's id
+            b"agreement_id" => {
+                self.id = Some(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the agreement's intended country
+            b"country" => {
+                self.country = Some(CountryCode::for_alpha2(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?).map_err(|e| XmlError::CustomError(XmlErrorExtension::CountryCodeParseError(e)))?);
+            },
+
+            // the agreement's location
+            b"location" => {
+                self.location = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the agreement's kind
+            b"type" => {
+                generate_xml_field_read_by_propagation!(self.kind, reader, buffer_pool, b"type");
+            },
+
+            // the agreement's version
+            b"version" => {
+                self.version = Some(u16::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            }
+        )
+    }
+}
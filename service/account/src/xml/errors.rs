@@ -7,10 +7,12 @@
 // file, you can obtain one at http://mozilla.org/MPL/2.0/.
 //
 
+use base64::DecodeError as Base64DecodeError;
 use chrono::format::ParseError as DateTimeParseError;
 use iso::language::Error as LanguageCodeParseError;
 use isocountry::CountryCodeParseErr as CountryCodeParseError;
 use std::num::ParseIntError;
+use strum::ParseError as EnumParseError;
 
 use ralsei_util::xml::errors::ResultWithError;
 
@@ -41,4 +43,16 @@ pub enum Error {
     /// An error that indicates that the provided UTC offset is out of bounds
     #[error("The provided UTC offset, `{0}`, is out of bounds")]
     UtcOffsetOutOfBounds(i32),
+
+    /// An error that may arise while parsing an enumeration from its string representation
+    #[error("An error was encountered while parsing an enumeration")]
+    EnumParseError(#[from] EnumParseError),
+
+    /// An error that may arise while decoding base64-encoded data
+    #[error("An error was encountered while decoding base64-encoded data")]
+    Base64DecodeError(#[from] Base64DecodeError),
+
+    /// An error that indicates that the provided flag was neither `Y` nor `N`
+    #[error("The provided flag, `{0}`, is neither `Y` nor `N`")]
+    InvalidFlag(String),
 }
@@ -19,7 +19,6 @@ use std::{
 
 use crate::xml::errors::{Error as XmlErrorExtension, Result};
 use ralsei_util::xml::{
-    errors::Error as XmlError,
     framework::{BufferPool, FromXml, ToXml},
     helpers::{generate_xml_field_write, generate_xml_struct_read, generate_xml_struct_read_check},
 };
@@ -73,7 +72,10 @@ impl<'a> FromXml<XmlErrorExtension> for MappedIds<'a> {
 /// A Nintendo Network user identifier mapping
 ///
 /// Because of the lack of information provided during the deserialization phase, both of the
-/// fields are [`Cow<'a, str>`](Cow)s instead of being specialized types
+/// fields are [`Cow<'a, str>`](Cow)s instead of being specialized types. If the account server was
+/// unable to map the identifier, the [`output`] field is `None`
+///
+/// [`output`]: #structfield.output
 #[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
 pub struct MappedId<'a> {
     /// The identifier being mapped
@@ -98,12 +100,12 @@ impl<'a> ToXml<XmlErrorExtension> for MappedId<'a> {
 
         // the resulting identifier
         if let Some(ref output) = &self.output {
-            generate_xml_field_write!(b"out_id", writer, BytesText::from_plain_str(input));
+            generate_xml_field_write!(b"out_id", writer, BytesText::from_plain_str(output));
         } else {
             writer.write_event(Event::Empty(BytesStart::borrowed_name(b"out_id")))?;
         }
 
-        writer.write_event(Event::Start(BytesStart::borrowed_name(b"mapped_id")))?;
+        writer.write_event(Event::End(BytesEnd::borrowed(b"mapped_id")))?;
 
         Ok(())
     }
@@ -122,12 +124,17 @@ impl<'a> FromXml<XmlErrorExtension> for MappedId<'a> {
 
             // the identifier being mapped
             b"in_id" => {
-                self.input = Some(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?);
+                self.input = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
             },
 
-            // the resulting identifier
+            // the resulting identifier (which is empty if the identifier could not be mapped)
             b"out_id" => {
-                self.output = Some(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?);
+                let output = reader.read_text(c.name(), &mut *buffer_pool.get().await?)?;
+                self.output = if output.is_empty() {
+                    None
+                } else {
+                    Some(Cow::Owned(output))
+                };
             }
         )
     }
@@ -10,4 +10,9 @@
 pub mod agreement;
 pub mod error;
 pub mod errors;
+pub mod mapped_id;
+pub mod new_person;
+pub mod oauth;
+pub mod person;
 pub mod timezone;
+pub mod token;
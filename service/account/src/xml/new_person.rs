@@ -0,0 +1,408 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use async_trait::async_trait;
+use chrono::{
+    offset::{TimeZone, Utc},
+    DateTime, NaiveDate,
+};
+use iso::language::{Iso639_1, Language};
+use isocountry::CountryCode;
+use quick_xml::{
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use std::{
+    borrow::Cow,
+    io::{BufRead, Read, Write},
+    str::FromStr,
+};
+
+use crate::xml::{
+    agreement::AcceptedAgreement,
+    errors::{Error as XmlErrorExtension, Result},
+    person::{
+        flag, parse_flag, DeviceAttribute, Email, Gender, Mii, DATE_FORMAT, DATE_TIME_FORMAT,
+    },
+};
+use ralsei_model::network::Nnid;
+use ralsei_util::xml::{
+    errors::Error as XmlError,
+    framework::{BufferPool, FromXml, ToXml},
+    helpers::{generate_xml_field_write, generate_xml_struct_read, generate_xml_struct_read_check},
+};
+
+/// A representation of a Nintendo Network account registration document
+///
+/// Contained within is everything the account server needs to create an account: the user's
+/// identifiers and credentials, where they live, the agreement they accepted, their email, their
+/// Mii, and information about the device they are registering from
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct NewPerson<'a> {
+    /// The user's birth date
+    pub birth_date: Option<NaiveDate>,
+
+    /// The [`Nnid`] the user is registering
+    pub user_id: Option<Nnid<'a>>,
+
+    /// The user's password
+    pub password: Option<Cow<'a, str>>,
+
+    /// The country the user lives in
+    pub country: Option<CountryCode>,
+
+    /// The language the user prefers
+    pub language: Option<Iso639_1>,
+
+    /// The name of the timezone the user lives in (as used in zoneinfo)
+    pub timezone: Option<Cow<'a, str>>,
+
+    /// The agreement the user accepted
+    pub agreement: Option<AcceptedAgreement<'a>>,
+
+    /// The user's [`Email`]
+    pub email: Option<Email<'a>>,
+
+    /// The user's [`Mii`]
+    pub mii: Option<Mii<'a>>,
+
+    /// The consent given by the user's parent, if one was required
+    pub parental_consent: Option<ParentalConsent>,
+
+    /// The user's gender
+    pub gender: Option<Gender>,
+
+    /// The region the user lives in
+    pub region: Option<u32>,
+
+    /// Whether or not the user has opted into receiving marketing emails
+    pub marketing: Option<bool>,
+
+    /// Information about the device the account is being registered from
+    pub device_attributes: Vec<DeviceAttribute<'a>>,
+
+    /// Whether or not the user is allowed to use the account on devices other than their own
+    pub off_device: Option<bool>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for NewPerson<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"person")))?;
+
+        // the user's birth date
+        if let Some(ref birth_date) = &self.birth_date {
+            generate_xml_field_write!(
+                b"birth_date",
+                writer,
+                BytesText::from_plain_str(&birth_date.format(DATE_FORMAT).to_string())
+            );
+        }
+
+        // the user's nnid
+        if let Some(ref user_id) = &self.user_id {
+            generate_xml_field_write!(b"user_id", writer, BytesText::from_plain_str(&user_id.0));
+        }
+
+        // the user's password
+        if let Some(ref password) = &self.password {
+            generate_xml_field_write!(b"password", writer, BytesText::from_plain_str(password));
+        }
+
+        // the user's country
+        if let Some(ref country) = &self.country {
+            generate_xml_field_write!(
+                b"country",
+                writer,
+                BytesText::from_plain_str(country.alpha2())
+            );
+        }
+
+        // the user's preferred language
+        if let Some(ref language) = &self.language {
+            generate_xml_field_write!(
+                b"language",
+                writer,
+                BytesText::from_plain_str(language.code())
+            );
+        }
+
+        // the user's timezone
+        if let Some(ref timezone) = &self.timezone {
+            generate_xml_field_write!(b"tz_name", writer, BytesText::from_plain_str(timezone));
+        }
+
+        // the accepted agreement
+        if let Some(ref agreement) = &self.agreement {
+            agreement.to_xml(writer).await?;
+        }
+
+        // the user's email
+        if let Some(ref email) = &self.email {
+            email.to_xml(writer).await?;
+        }
+
+        // the user's mii
+        if let Some(ref mii) = &self.mii {
+            mii.to_xml(writer).await?;
+        }
+
+        // the consent given by the user's parent
+        if let Some(ref parental_consent) = &self.parental_consent {
+            parental_consent.to_xml(writer).await?;
+        }
+
+        // the user's gender
+        if let Some(ref gender) = &self.gender {
+            generate_xml_field_write!(
+                b"gender",
+                writer,
+                BytesText::from_plain_str(gender.as_ref())
+            );
+        }
+
+        // the user's region
+        if let Some(ref region) = &self.region {
+            generate_xml_field_write!(
+                b"region",
+                writer,
+                BytesText::from_plain_str(&region.to_string())
+            );
+        }
+
+        // whether or not the user receives marketing emails
+        if let Some(marketing) = self.marketing {
+            generate_xml_field_write!(
+                b"marketing_flag",
+                writer,
+                BytesText::from_plain_str(flag(marketing))
+            );
+        }
+
+        // the information about the device the account is being registered from
+        if !self.device_attributes.is_empty() {
+            writer.write_event(Event::Start(BytesStart::borrowed_name(
+                b"device_attributes",
+            )))?;
+
+            for device_attribute in &self.device_attributes {
+                device_attribute.to_xml(writer).await?;
+            }
+
+            writer.write_event(Event::End(BytesEnd::borrowed(b"device_attributes")))?;
+        }
+
+        // whether or not the account can be used on other devices
+        if let Some(off_device) = self.off_device {
+            generate_xml_field_write!(
+                b"off_device_flag",
+                writer,
+                BytesText::from_plain_str(flag(off_device))
+            );
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"person")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for NewPerson<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read_check!(b"person", reader, buffer_pool.clone());
+
+        generate_xml_struct_read!(
+            b"person",
+            reader, buffer_pool,
+            c,
+
+            // the user's birth date
+            b"birth_date" => {
+                self.birth_date = Some(NaiveDate::parse_from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str(), DATE_FORMAT).map_err(|e| XmlError::CustomError(XmlErrorExtension::DateTimeParseError(e)))?);
+            },
+
+            // the user's nnid
+            b"user_id" => {
+                self.user_id = Some(Nnid(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)));
+            },
+
+            // the user's password
+            b"password" => {
+                self.password = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the user's country
+            b"country" => {
+                self.country = Some(CountryCode::for_alpha2(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?).map_err(|e| XmlError::CustomError(XmlErrorExtension::CountryCodeParseError(e)))?);
+            },
+
+            // the user's preferred language
+            b"language" => {
+                self.language = Some(Iso639_1::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::LanguageCodeParseError(e)))?);
+            },
+
+            // the user's timezone
+            b"tz_name" => {
+                self.timezone = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the accepted agreement
+            b"agreement" => {
+                let mut agreement = AcceptedAgreement::default();
+                agreement.from_xml(reader, buffer_pool.clone()).await?;
+                self.agreement = Some(agreement);
+            },
+
+            // the user's email
+            b"email" => {
+                let mut email = Email::default();
+                email.from_xml(reader, buffer_pool.clone()).await?;
+                self.email = Some(email);
+            },
+
+            // the user's mii
+            b"mii" => {
+                let mut mii = Mii::default();
+                mii.from_xml(reader, buffer_pool.clone()).await?;
+                self.mii = Some(mii);
+            },
+
+            // the consent given by the user's parent
+            b"parental_consent" => {
+                let mut parental_consent = ParentalConsent::default();
+                parental_consent.from_xml(reader, buffer_pool.clone()).await?;
+                self.parental_consent = Some(parental_consent);
+            },
+
+            // the user's gender
+            b"gender" => {
+                self.gender = Some(Gender::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::EnumParseError(e)))?);
+            },
+
+            // the user's region
+            b"region" => {
+                self.region = Some(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // whether or not the user receives marketing emails
+            b"marketing_flag" => {
+                self.marketing = Some(parse_flag(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)?);
+            },
+
+            // the information about the device the account is being registered from
+            b"device_attributes" => {
+                let _: Result<()> = generate_xml_struct_read!(
+                    b"device_attributes",
+                    reader, buffer_pool,
+                    c,
+                    b"device_attribute" => {
+                        let mut device_attribute = DeviceAttribute::default();
+                        device_attribute.from_xml(reader, buffer_pool.clone()).await?;
+                        self.device_attributes.push(device_attribute)
+                    }
+                );
+            },
+
+            // whether or not the account can be used on other devices
+            b"off_device_flag" => {
+                self.off_device = Some(parse_flag(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)?);
+            }
+        )
+    }
+}
+
+/// A record of the consent given by a user's parent to register an account
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct ParentalConsent {
+    /// The scope of the consent
+    pub scope: Option<u32>,
+
+    /// The date at which consent was given
+    pub consent_date: Option<DateTime<Utc>>,
+
+    /// The id of the approval
+    pub approval_id: Option<u32>,
+}
+
+#[async_trait]
+impl ToXml<XmlErrorExtension> for ParentalConsent {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"parental_consent")))?;
+
+        // the scope of the consent
+        if let Some(ref scope) = &self.scope {
+            generate_xml_field_write!(
+                b"scope",
+                writer,
+                BytesText::from_plain_str(&scope.to_string())
+            );
+        }
+
+        // the date consent was given
+        if let Some(ref consent_date) = &self.consent_date {
+            generate_xml_field_write!(
+                b"consent_date",
+                writer,
+                BytesText::from_plain_str(&consent_date.format(DATE_TIME_FORMAT).to_string())
+            );
+        }
+
+        // the id of the approval
+        if let Some(ref approval_id) = &self.approval_id {
+            generate_xml_field_write!(
+                b"approval_id",
+                writer,
+                BytesText::from_plain_str(&approval_id.to_string())
+            );
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"parental_consent")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FromXml<XmlErrorExtension> for ParentalConsent {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read!(
+            b"parental_consent",
+            reader, buffer_pool,
+            c,
+
+            // the scope of the consent
+            b"scope" => {
+                self.scope = Some(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the date consent was given
+            b"consent_date" => {
+                self.consent_date = Some(Utc.datetime_from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str(), DATE_TIME_FORMAT).map_err(|e| XmlError::CustomError(XmlErrorExtension::DateTimeParseError(e)))?);
+            },
+
+            // the id of the approval
+            b"approval_id" => {
+                self.approval_id = Some(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            }
+        )
+    }
+}
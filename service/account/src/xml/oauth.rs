@@ -0,0 +1,155 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use async_trait::async_trait;
+use quick_xml::{
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use std::{
+    borrow::Cow,
+    io::{BufRead, Read, Write},
+    str::FromStr,
+};
+
+use crate::xml::errors::{Error as XmlErrorExtension, Result};
+use ralsei_util::xml::{
+    errors::Error as XmlError,
+    framework::{BufferPool, FromXml, ToXml},
+    helpers::{generate_xml_field_write, generate_xml_struct_read, generate_xml_struct_read_check},
+};
+
+/// A representation of a Nintendo Network OAuth 2.0 document
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct OAuth20<'a> {
+    /// The [`AccessToken`] contained within the document
+    pub access_token: Option<AccessToken<'a>>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for OAuth20<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"OAuth20")))?;
+
+        if let Some(ref access_token) = &self.access_token {
+            access_token.to_xml(writer).await?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"OAuth20")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for OAuth20<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read_check!(b"OAuth20", reader, buffer_pool.clone());
+
+        generate_xml_struct_read!(
+            b"OAuth20",
+            reader, buffer_pool,
+            c,
+            b"access_token" => {
+                let mut access_token = AccessToken::default();
+                access_token.from_xml(reader, buffer_pool.clone()).await?;
+                self.access_token = Some(access_token);
+            }
+        )
+    }
+}
+
+/// A Nintendo Network account server access token
+///
+/// Contained within is the token used to authenticate requests made on behalf of a user, the token
+/// that can be used to generate a new access token once it expires, and the number of seconds it
+/// is valid for
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct AccessToken<'a> {
+    /// The access token itself
+    pub token: Option<Cow<'a, str>>,
+
+    /// The token used to generate a new access token
+    pub refresh_token: Option<Cow<'a, str>>,
+
+    /// The number of seconds the access token is valid for after it was generated
+    pub expires_in: Option<u32>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for AccessToken<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"access_token")))?;
+
+        // the access token
+        if let Some(ref token) = &self.token {
+            generate_xml_field_write!(b"token", writer, BytesText::from_plain_str(token));
+        }
+
+        // the refresh token
+        if let Some(ref refresh_token) = &self.refresh_token {
+            generate_xml_field_write!(
+                b"refresh_token",
+                writer,
+                BytesText::from_plain_str(refresh_token)
+            );
+        }
+
+        // the lifetime of the access token
+        if let Some(ref expires_in) = &self.expires_in {
+            generate_xml_field_write!(
+                b"expires_in",
+                writer,
+                BytesText::from_plain_str(&expires_in.to_string())
+            );
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"access_token")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for AccessToken<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read!(
+            b"access_token",
+            reader, buffer_pool,
+            c,
+
+            // the access token
+            b"token" => {
+                self.token = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the refresh token
+            b"refresh_token" => {
+                self.refresh_token = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the lifetime of the access token
+            b"expires_in" => {
+                self.expires_in = Some(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            }
+        )
+    }
+}
@@ -0,0 +1,890 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use async_trait::async_trait;
+use chrono::{
+    offset::{FixedOffset, TimeZone, Utc},
+    DateTime, NaiveDate,
+};
+use iso::language::{Iso639_1, Language};
+use isocountry::CountryCode;
+use quick_xml::{
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use std::{
+    borrow::Cow,
+    io::{BufRead, Read, Write},
+    str::FromStr,
+};
+use strum_macros::{AsRefStr, Display, EnumString, IntoStaticStr};
+
+use crate::xml::errors::{Error as XmlErrorExtension, Result};
+use ralsei_model::network::{Nnid, Pid};
+use ralsei_util::xml::{
+    errors::Error as XmlError,
+    framework::{BufferPool, FromXml, ToXml},
+    helpers::{generate_xml_field_write, generate_xml_struct_read, generate_xml_struct_read_check},
+};
+
+/// The format used by the account server for dates
+pub(crate) const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The format used by the account server for dates with a time attached
+pub(crate) const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Parses a Nintendo Network flag (`Y` or `N`) into a [`bool`]
+pub(crate) fn parse_flag(value: &str) -> Result<bool> {
+    match value {
+        "Y" => Ok(true),
+        "N" => Ok(false),
+        value => Err(XmlError::CustomError(XmlErrorExtension::InvalidFlag(
+            value.to_string(),
+        ))),
+    }
+}
+
+/// Returns the Nintendo Network flag (`Y` or `N`) representation of a [`bool`]
+pub(crate) fn flag(value: bool) -> &'static str {
+    if value {
+        "Y"
+    } else {
+        "N"
+    }
+}
+
+/// A representation of a Nintendo Network account profile document
+///
+/// Contained within is the information the account server holds about a user, such as their
+/// identifiers, their birth date, where they live, their email, their Mii, and information about
+/// the devices linked to their account
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct Person<'a> {
+    /// Whether or not the account is active
+    pub active: Option<bool>,
+
+    /// The user's birth date
+    pub birth_date: Option<NaiveDate>,
+
+    /// The country the user lives in
+    pub country: Option<CountryCode>,
+
+    /// The date at which the account was created
+    pub create_date: Option<DateTime<Utc>>,
+
+    /// Information about the devices linked to the account
+    pub device_attributes: Vec<DeviceAttribute<'a>>,
+
+    /// The user's gender
+    pub gender: Option<Gender>,
+
+    /// The language the user prefers
+    pub language: Option<Iso639_1>,
+
+    /// The date at which the account was last updated
+    pub updated: Option<DateTime<Utc>>,
+
+    /// Whether or not the user has opted into receiving marketing emails
+    pub marketing: Option<bool>,
+
+    /// Whether or not the user is allowed to use the account on devices other than their own
+    pub off_device: Option<bool>,
+
+    /// The user's [`Pid`]
+    pub pid: Option<Pid>,
+
+    /// The user's [`Email`]
+    pub email: Option<Email<'a>>,
+
+    /// The user's [`Mii`]
+    pub mii: Option<Mii<'a>>,
+
+    /// The region the user lives in
+    ///
+    /// This is not the same as a console's region, and is instead a value specific to the
+    /// account server
+    pub region: Option<u32>,
+
+    /// The name of the timezone the user lives in (as used in zoneinfo)
+    pub timezone: Option<Cow<'a, str>>,
+
+    /// The user's [`Nnid`]
+    pub user_id: Option<Nnid<'a>>,
+
+    /// The UTC offset of the timezone the user lives in
+    pub utc_offset: Option<FixedOffset>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for Person<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"person")))?;
+
+        // whether or not the account is active
+        if let Some(active) = self.active {
+            generate_xml_field_write!(
+                b"active_flag",
+                writer,
+                BytesText::from_plain_str(flag(active))
+            );
+        }
+
+        // the user's birth date
+        if let Some(ref birth_date) = &self.birth_date {
+            generate_xml_field_write!(
+                b"birth_date",
+                writer,
+                BytesText::from_plain_str(&birth_date.format(DATE_FORMAT).to_string())
+            );
+        }
+
+        // the user's country
+        if let Some(ref country) = &self.country {
+            generate_xml_field_write!(
+                b"country",
+                writer,
+                BytesText::from_plain_str(country.alpha2())
+            );
+        }
+
+        // the account's creation date
+        if let Some(ref create_date) = &self.create_date {
+            generate_xml_field_write!(
+                b"create_date",
+                writer,
+                BytesText::from_plain_str(&create_date.format(DATE_TIME_FORMAT).to_string())
+            );
+        }
+
+        // the information about the devices linked to the account
+        if !self.device_attributes.is_empty() {
+            writer.write_event(Event::Start(BytesStart::borrowed_name(
+                b"device_attributes",
+            )))?;
+
+            for device_attribute in &self.device_attributes {
+                device_attribute.to_xml(writer).await?;
+            }
+
+            writer.write_event(Event::End(BytesEnd::borrowed(b"device_attributes")))?;
+        }
+
+        // the user's gender
+        if let Some(ref gender) = &self.gender {
+            generate_xml_field_write!(
+                b"gender",
+                writer,
+                BytesText::from_plain_str(gender.as_ref())
+            );
+        }
+
+        // the user's preferred language
+        if let Some(ref language) = &self.language {
+            generate_xml_field_write!(
+                b"language",
+                writer,
+                BytesText::from_plain_str(language.code())
+            );
+        }
+
+        // the date the account was last updated
+        if let Some(ref updated) = &self.updated {
+            generate_xml_field_write!(
+                b"updated",
+                writer,
+                BytesText::from_plain_str(&updated.format(DATE_TIME_FORMAT).to_string())
+            );
+        }
+
+        // whether or not the user receives marketing emails
+        if let Some(marketing) = self.marketing {
+            generate_xml_field_write!(
+                b"marketing_flag",
+                writer,
+                BytesText::from_plain_str(flag(marketing))
+            );
+        }
+
+        // whether or not the account can be used on other devices
+        if let Some(off_device) = self.off_device {
+            generate_xml_field_write!(
+                b"off_device_flag",
+                writer,
+                BytesText::from_plain_str(flag(off_device))
+            );
+        }
+
+        // the user's pid
+        if let Some(ref pid) = &self.pid {
+            generate_xml_field_write!(
+                b"pid",
+                writer,
+                BytesText::from_plain_str(&pid.0.to_string())
+            );
+        }
+
+        // the user's email
+        if let Some(ref email) = &self.email {
+            email.to_xml(writer).await?;
+        }
+
+        // the user's mii
+        if let Some(ref mii) = &self.mii {
+            mii.to_xml(writer).await?;
+        }
+
+        // the user's region
+        if let Some(ref region) = &self.region {
+            generate_xml_field_write!(
+                b"region",
+                writer,
+                BytesText::from_plain_str(&region.to_string())
+            );
+        }
+
+        // the user's timezone
+        if let Some(ref timezone) = &self.timezone {
+            generate_xml_field_write!(b"tz_name", writer, BytesText::from_plain_str(timezone));
+        }
+
+        // the user's nnid
+        if let Some(ref user_id) = &self.user_id {
+            generate_xml_field_write!(b"user_id", writer, BytesText::from_plain_str(&user_id.0));
+        }
+
+        // the utc offset of the user's timezone
+        if let Some(ref utc_offset) = &self.utc_offset {
+            generate_xml_field_write!(
+                b"utc_offset",
+                writer,
+                BytesText::from_plain_str(&utc_offset.local_minus_utc().to_string())
+            );
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"person")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for Person<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read_check!(b"person", reader, buffer_pool.clone());
+
+        generate_xml_struct_read!(
+            b"person",
+            reader, buffer_pool,
+            c,
+
+            // whether or not the account is active
+            b"active_flag" => {
+                self.active = Some(parse_flag(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)?);
+            },
+
+            // the user's birth date
+            b"birth_date" => {
+                self.birth_date = Some(NaiveDate::parse_from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str(), DATE_FORMAT).map_err(|e| XmlError::CustomError(XmlErrorExtension::DateTimeParseError(e)))?);
+            },
+
+            // the user's country
+            b"country" => {
+                self.country = Some(CountryCode::for_alpha2(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?).map_err(|e| XmlError::CustomError(XmlErrorExtension::CountryCodeParseError(e)))?);
+            },
+
+            // the account's creation date
+            b"create_date" => {
+                self.create_date = Some(Utc.datetime_from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str(), DATE_TIME_FORMAT).map_err(|e| XmlError::CustomError(XmlErrorExtension::DateTimeParseError(e)))?);
+            },
+
+            // the information about the devices linked to the account
+            b"device_attributes" => {
+                let _: Result<()> = generate_xml_struct_read!(
+                    b"device_attributes",
+                    reader, buffer_pool,
+                    c,
+                    b"device_attribute" => {
+                        let mut device_attribute = DeviceAttribute::default();
+                        device_attribute.from_xml(reader, buffer_pool.clone()).await?;
+                        self.device_attributes.push(device_attribute)
+                    }
+                );
+            },
+
+            // the user's gender
+            b"gender" => {
+                self.gender = Some(Gender::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::EnumParseError(e)))?);
+            },
+
+            // the user's preferred language
+            b"language" => {
+                self.language = Some(Iso639_1::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::LanguageCodeParseError(e)))?);
+            },
+
+            // the date the account was last updated
+            b"updated" => {
+                self.updated = Some(Utc.datetime_from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str(), DATE_TIME_FORMAT).map_err(|e| XmlError::CustomError(XmlErrorExtension::DateTimeParseError(e)))?);
+            },
+
+            // whether or not the user receives marketing emails
+            b"marketing_flag" => {
+                self.marketing = Some(parse_flag(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)?);
+            },
+
+            // whether or not the account can be used on other devices
+            b"off_device_flag" => {
+                self.off_device = Some(parse_flag(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)?);
+            },
+
+            // the user's pid
+            b"pid" => {
+                self.pid = Some(Pid(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?));
+            },
+
+            // the user's email
+            b"email" => {
+                let mut email = Email::default();
+                email.from_xml(reader, buffer_pool.clone()).await?;
+                self.email = Some(email);
+            },
+
+            // the user's mii
+            b"mii" => {
+                let mut mii = Mii::default();
+                mii.from_xml(reader, buffer_pool.clone()).await?;
+                self.mii = Some(mii);
+            },
+
+            // the user's region
+            b"region" => {
+                self.region = Some(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the user's timezone
+            b"tz_name" => {
+                self.timezone = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the user's nnid
+            b"user_id" => {
+                self.user_id = Some(Nnid(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)));
+            },
+
+            // the utc offset of the user's timezone
+            b"utc_offset" => {
+                let converted_offset = i32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?;
+                self.utc_offset = Some(FixedOffset::east_opt(converted_offset).ok_or(XmlError::CustomError(XmlErrorExtension::UtcOffsetOutOfBounds(converted_offset)))?);
+            }
+        )
+    }
+}
+
+/// An enumeration over the genders a Nintendo Network account can have
+#[non_exhaustive]
+#[derive(
+    IntoStaticStr,
+    AsRefStr,
+    EnumString,
+    Display,
+    Copy,
+    Clone,
+    Debug,
+    Eq,
+    Hash,
+    PartialEq,
+    PartialOrd,
+    Ord,
+)]
+pub enum Gender {
+    #[strum(to_string = "M")]
+    Male,
+    #[strum(to_string = "F")]
+    Female,
+}
+
+/// A Nintendo Network account's email
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct Email<'a> {
+    /// The email address itself
+    pub address: Option<Cow<'a, str>>,
+
+    /// The email's id
+    pub id: Option<u64>,
+
+    /// Whether or not the email is owned by the user
+    pub owned: Option<bool>,
+
+    /// Whether or not the email belongs to the user's parent
+    pub parent: Option<bool>,
+
+    /// Whether or not this is the user's primary email
+    pub primary: Option<bool>,
+
+    /// Whether or not the email is reachable
+    pub reachable: Option<bool>,
+
+    /// The kind of email (usually `DEFAULT`)
+    pub kind: Option<Cow<'a, str>>,
+
+    /// What last updated the email
+    pub updated_by: Option<Cow<'a, str>>,
+
+    /// Whether or not the email has been validated
+    pub validated: Option<bool>,
+
+    /// The date at which the email was validated
+    pub validated_date: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for Email<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"email")))?;
+
+        // the email address
+        if let Some(ref address) = &self.address {
+            generate_xml_field_write!(b"address", writer, BytesText::from_plain_str(address));
+        }
+
+        // the email's id
+        if let Some(ref id) = &self.id {
+            generate_xml_field_write!(b"id", writer, BytesText::from_plain_str(&id.to_string()));
+        }
+
+        // whether or not the email is owned by the user
+        if let Some(owned) = self.owned {
+            generate_xml_field_write!(b"owned", writer, BytesText::from_plain_str(flag(owned)));
+        }
+
+        // whether or not the email belongs to the user's parent
+        if let Some(parent) = self.parent {
+            generate_xml_field_write!(b"parent", writer, BytesText::from_plain_str(flag(parent)));
+        }
+
+        // whether or not the email is the user's primary one
+        if let Some(primary) = self.primary {
+            generate_xml_field_write!(b"primary", writer, BytesText::from_plain_str(flag(primary)));
+        }
+
+        // whether or not the email is reachable
+        if let Some(reachable) = self.reachable {
+            generate_xml_field_write!(
+                b"reachable",
+                writer,
+                BytesText::from_plain_str(flag(reachable))
+            );
+        }
+
+        // the kind of email
+        if let Some(ref kind) = &self.kind {
+            generate_xml_field_write!(b"type", writer, BytesText::from_plain_str(kind));
+        }
+
+        // what last updated the email
+        if let Some(ref updated_by) = &self.updated_by {
+            generate_xml_field_write!(b"updated_by", writer, BytesText::from_plain_str(updated_by));
+        }
+
+        // whether or not the email has been validated
+        if let Some(validated) = self.validated {
+            generate_xml_field_write!(
+                b"validated",
+                writer,
+                BytesText::from_plain_str(flag(validated))
+            );
+        }
+
+        // the date the email was validated
+        if let Some(ref validated_date) = &self.validated_date {
+            generate_xml_field_write!(
+                b"validated_date",
+                writer,
+                BytesText::from_plain_str(&validated_date.format(DATE_TIME_FORMAT).to_string())
+            );
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"email")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for Email<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read!(
+            b"email",
+            reader, buffer_pool,
+            c,
+
+            // the email address
+            b"address" => {
+                self.address = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the email's id
+            b"id" => {
+                self.id = Some(u64::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // whether or not the email is owned by the user
+            b"owned" => {
+                self.owned = Some(parse_flag(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)?);
+            },
+
+            // whether or not the email belongs to the user's parent
+            b"parent" => {
+                self.parent = Some(parse_flag(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)?);
+            },
+
+            // whether or not the email is the user's primary one
+            b"primary" => {
+                self.primary = Some(parse_flag(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)?);
+            },
+
+            // whether or not the email is reachable
+            b"reachable" => {
+                self.reachable = Some(parse_flag(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)?);
+            },
+
+            // the kind of email
+            b"type" => {
+                self.kind = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // what last updated the email
+            b"updated_by" => {
+                self.updated_by = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // whether or not the email has been validated
+            b"validated" => {
+                self.validated = Some(parse_flag(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)?);
+            },
+
+            // the date the email was validated
+            b"validated_date" => {
+                self.validated_date = Some(Utc.datetime_from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str(), DATE_TIME_FORMAT).map_err(|e| XmlError::CustomError(XmlErrorExtension::DateTimeParseError(e)))?);
+            }
+        )
+    }
+}
+
+/// A Nintendo Network account's Mii
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct Mii<'a> {
+    /// The status of the Mii (usually `COMPLETED`)
+    pub status: Option<Cow<'a, str>>,
+
+    /// The Mii's data
+    pub data: Option<Vec<u8>>,
+
+    /// The Mii's id
+    pub id: Option<u64>,
+
+    /// The hash used to refer to the Mii in the urls of its images
+    pub hash: Option<Cow<'a, str>>,
+
+    /// Rendered images of the Mii
+    pub images: Vec<MiiImage<'a>>,
+
+    /// The Mii's name
+    pub name: Option<Cow<'a, str>>,
+
+    /// Whether or not this is the user's primary Mii
+    pub primary: Option<bool>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for Mii<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"mii")))?;
+
+        // the mii's status
+        if let Some(ref status) = &self.status {
+            generate_xml_field_write!(b"status", writer, BytesText::from_plain_str(status));
+        }
+
+        // the mii's data
+        if let Some(ref data) = &self.data {
+            generate_xml_field_write!(
+                b"data",
+                writer,
+                BytesText::from_plain_str(&base64::encode(data))
+            );
+        }
+
+        // the mii's id
+        if let Some(ref id) = &self.id {
+            generate_xml_field_write!(b"id", writer, BytesText::from_plain_str(&id.to_string()));
+        }
+
+        // the mii's hash
+        if let Some(ref hash) = &self.hash {
+            generate_xml_field_write!(b"mii_hash", writer, BytesText::from_plain_str(hash));
+        }
+
+        // the rendered images of the mii
+        if !self.images.is_empty() {
+            writer.write_event(Event::Start(BytesStart::borrowed_name(b"mii_images")))?;
+
+            for image in &self.images {
+                image.to_xml(writer).await?;
+            }
+
+            writer.write_event(Event::End(BytesEnd::borrowed(b"mii_images")))?;
+        }
+
+        // the mii's name
+        if let Some(ref name) = &self.name {
+            generate_xml_field_write!(b"name", writer, BytesText::from_plain_str(name));
+        }
+
+        // whether or not the mii is the user's primary one
+        if let Some(primary) = self.primary {
+            generate_xml_field_write!(b"primary", writer, BytesText::from_plain_str(flag(primary)));
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"mii")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for Mii<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read!(
+            b"mii",
+            reader, buffer_pool,
+            c,
+
+            // the mii's status
+            b"status" => {
+                self.status = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the mii's data
+            b"data" => {
+                self.data = Some(base64::decode(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?).map_err(|e| XmlError::CustomError(XmlErrorExtension::Base64DecodeError(e)))?);
+            },
+
+            // the mii's id
+            b"id" => {
+                self.id = Some(u64::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the mii's hash
+            b"mii_hash" => {
+                self.hash = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the rendered images of the mii
+            b"mii_images" => {
+                let _: Result<()> = generate_xml_struct_read!(
+                    b"mii_images",
+                    reader, buffer_pool,
+                    c,
+                    b"mii_image" => {
+                        let mut image = MiiImage::default();
+                        image.from_xml(reader, buffer_pool.clone()).await?;
+                        self.images.push(image)
+                    }
+                );
+            },
+
+            // the mii's name
+            b"name" => {
+                self.name = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // whether or not the mii is the user's primary one
+            b"primary" => {
+                self.primary = Some(parse_flag(&reader.read_text(c.name(), &mut *buffer_pool.get().await?)?)?);
+            }
+        )
+    }
+}
+
+/// A rendered image of a Nintendo Network account's [`Mii`]
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct MiiImage<'a> {
+    /// The url of a cached copy of the image
+    pub cached_url: Option<Cow<'a, str>>,
+
+    /// The image's id
+    pub id: Option<u64>,
+
+    /// The url of the image
+    pub url: Option<Cow<'a, str>>,
+
+    /// The kind of image (usually `standard`)
+    pub kind: Option<Cow<'a, str>>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for MiiImage<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"mii_image")))?;
+
+        // the url of the cached image
+        if let Some(ref cached_url) = &self.cached_url {
+            generate_xml_field_write!(b"cached_url", writer, BytesText::from_plain_str(cached_url));
+        }
+
+        // the image's id
+        if let Some(ref id) = &self.id {
+            generate_xml_field_write!(b"id", writer, BytesText::from_plain_str(&id.to_string()));
+        }
+
+        // the url of the image
+        if let Some(ref url) = &self.url {
+            generate_xml_field_write!(b"url", writer, BytesText::from_plain_str(url));
+        }
+
+        // the kind of image
+        if let Some(ref kind) = &self.kind {
+            generate_xml_field_write!(b"type", writer, BytesText::from_plain_str(kind));
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"mii_image")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for MiiImage<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read!(
+            b"mii_image",
+            reader, buffer_pool,
+            c,
+
+            // the url of the cached image
+            b"cached_url" => {
+                self.cached_url = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the image's id
+            b"id" => {
+                self.id = Some(u64::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the url of the image
+            b"url" => {
+                self.url = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the kind of image
+            b"type" => {
+                self.kind = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            }
+        )
+    }
+}
+
+/// A piece of information about a device linked to a Nintendo Network account
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct DeviceAttribute<'a> {
+    /// The date at which the attribute was created
+    pub created_date: Option<DateTime<Utc>>,
+
+    /// The name of the attribute
+    pub name: Option<Cow<'a, str>>,
+
+    /// The value of the attribute
+    pub value: Option<Cow<'a, str>>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for DeviceAttribute<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"device_attribute")))?;
+
+        // the attribute's creation date
+        if let Some(ref created_date) = &self.created_date {
+            generate_xml_field_write!(
+                b"created_date",
+                writer,
+                BytesText::from_plain_str(&created_date.format(DATE_TIME_FORMAT).to_string())
+            );
+        }
+
+        // the attribute's name
+        if let Some(ref name) = &self.name {
+            generate_xml_field_write!(b"name", writer, BytesText::from_plain_str(name));
+        }
+
+        // the attribute's value
+        if let Some(ref value) = &self.value {
+            generate_xml_field_write!(b"value", writer, BytesText::from_plain_str(value));
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"device_attribute")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for DeviceAttribute<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read!(
+            b"device_attribute",
+            reader, buffer_pool,
+            c,
+
+            // the attribute's creation date
+            b"created_date" => {
+                self.created_date = Some(Utc.datetime_from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str(), DATE_TIME_FORMAT).map_err(|e| XmlError::CustomError(XmlErrorExtension::DateTimeParseError(e)))?);
+            },
+
+            // the attribute's name
+            b"name" => {
+                self.name = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the attribute's value
+            b"value" => {
+                self.value = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            }
+        )
+    }
+}
@@ -0,0 +1,188 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use async_trait::async_trait;
+use quick_xml::{
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use std::{
+    borrow::Cow,
+    io::{BufRead, Read, Write},
+    str::FromStr,
+};
+
+use crate::xml::errors::{Error as XmlErrorExtension, Result};
+use ralsei_model::network::Pid;
+use ralsei_util::xml::{
+    errors::Error as XmlError,
+    framework::{BufferPool, FromXml, ToXml},
+    helpers::{generate_xml_field_write, generate_xml_struct_read, generate_xml_struct_read_check},
+};
+
+/// A token used to authenticate with a NEX game server
+///
+/// Contained within is the address of the game server's authentication server, the credentials
+/// used to log in to it, and the token presented to it
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct NexToken<'a> {
+    /// The host of the game server's authentication server
+    pub host: Option<Cow<'a, str>>,
+
+    /// The password used to log in to the game server
+    pub nex_password: Option<Cow<'a, str>>,
+
+    /// The [`Pid`] of the user the token was generated for
+    pub pid: Option<Pid>,
+
+    /// The port of the game server's authentication server
+    pub port: Option<u16>,
+
+    /// The token itself
+    pub token: Option<Cow<'a, str>>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for NexToken<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"nex_token")))?;
+
+        // the authentication server's host
+        if let Some(ref host) = &self.host {
+            generate_xml_field_write!(b"host", writer, BytesText::from_plain_str(host));
+        }
+
+        // the password used to log in
+        if let Some(ref nex_password) = &self.nex_password {
+            generate_xml_field_write!(
+                b"nex_password",
+                writer,
+                BytesText::from_plain_str(nex_password)
+            );
+        }
+
+        // the user's pid
+        if let Some(ref pid) = &self.pid {
+            generate_xml_field_write!(
+                b"pid",
+                writer,
+                BytesText::from_plain_str(&pid.0.to_string())
+            );
+        }
+
+        // the authentication server's port
+        if let Some(ref port) = &self.port {
+            generate_xml_field_write!(
+                b"port",
+                writer,
+                BytesText::from_plain_str(&port.to_string())
+            );
+        }
+
+        // the token
+        if let Some(ref token) = &self.token {
+            generate_xml_field_write!(b"token", writer, BytesText::from_plain_str(token));
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"nex_token")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for NexToken<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read_check!(b"nex_token", reader, buffer_pool.clone());
+
+        generate_xml_struct_read!(
+            b"nex_token",
+            reader, buffer_pool,
+            c,
+
+            // the authentication server's host
+            b"host" => {
+                self.host = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the password used to log in
+            b"nex_password" => {
+                self.nex_password = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the user's pid
+            b"pid" => {
+                self.pid = Some(Pid(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?));
+            },
+
+            // the authentication server's port
+            b"port" => {
+                self.port = Some(u16::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the token
+            b"token" => {
+                self.token = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            }
+        )
+    }
+}
+
+/// A token used to authenticate with a service independent of the account server
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct ServiceToken<'a> {
+    /// The token itself
+    pub token: Option<Cow<'a, str>>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for ServiceToken<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"service_token")))?;
+
+        // the token
+        if let Some(ref token) = &self.token {
+            generate_xml_field_write!(b"token", writer, BytesText::from_plain_str(token));
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"service_token")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for ServiceToken<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read_check!(b"service_token", reader, buffer_pool.clone());
+
+        generate_xml_struct_read!(
+            b"service_token",
+            reader, buffer_pool,
+            c,
+
+            // the token
+            b"token" => {
+                self.token = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            }
+        )
+    }
+}
@@ -0,0 +1,517 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use http::{
+    header::{self, HeaderMap, HeaderValue},
+    uri::{Authority, InvalidUri, PathAndQuery},
+    Error as HttpError, Request, Uri, Version as HttpVersion,
+};
+use hyper::{
+    body,
+    client::{Client as HttpClient, HttpConnector, ResponseFuture},
+    Body, Error as HyperError,
+};
+use hyper_tls::HttpsConnector;
+use isocountry::CountryCode;
+use native_tls::{
+    Certificate, Error as NativeTlsError, Identity, TlsConnector as NativeTlsConnector,
+};
+use parking_lot::RwLock;
+use quick_xml::Reader as XmlReader;
+use std::{borrow::Cow, convert::TryFrom, sync::Arc};
+use tokio_native_tls::TlsConnector;
+
+use crate::{
+    common::{
+        nus_endpoints, DEFAULT_3DS_CCS_HOST, DEFAULT_3DS_NUS_HOST, DEFAULT_WIIU_CCS_HOST,
+        DEFAULT_WIIU_NUS_HOST, NUS_NAMESPACE, XML_DECLARATION,
+    },
+    xml::{
+        envelope::Envelope,
+        errors::Error as XmlErrorExtension,
+        system_title_hash::{SystemTitleHashRequest, SystemTitleHashResponse},
+        system_update::{SystemUpdateRequest, SystemUpdateResponse, UpdateTitle},
+    },
+};
+use ralsei_keypairs::{CTR_COMMON_1, NINTENDO_CACERTS, WUP_COMMON_1};
+use ralsei_model::{
+    console::common::{Console, HeaderConstructionError, Kind as ConsoleKind, Region},
+    server::Kind as ServerKind,
+    title::{
+        id::TitleId,
+        ticket::{Ticket, TicketError},
+        tmd::{TitleMetadata, TitleMetadataError},
+        version::TitleVersion,
+    },
+};
+use ralsei_util::xml::{
+    errors::Error as XmlError,
+    framework::{self as xml_framework, BufferPool, FromXml, ToXml},
+    GLOBAL_BUFFER_POOL,
+};
+
+/// A client for the network update and content servers, used to check for and download system
+/// updates along with any other title
+///
+/// The network update server (NUS) is a SOAP api that reports the titles making up the latest
+/// system update, while the content server (CCS) hosts the title metadata, common tickets and
+/// encrypted contents of every title. Both are reached over TLS using the client certificate of
+/// the [`Console`] the client was created with, and every request carries the headers that the
+/// console would send.
+///
+/// Contents are not buffered, but are instead returned as a [`Body`] that may be streamed into a
+/// [`ContentDecryptor`] or a file as it arrives. Hashed Wii U contents can be downloaded, but
+/// cannot be decrypted by a [`ContentDecryptor`].
+///
+/// [`ContentDecryptor`]: ralsei_model::title::content::ContentDecryptor
+pub struct Client<'a, C: Console<'a> + Send + Clone> {
+    /// The host of the network update server
+    ///
+    /// If no value is provided, it is initialized with [`DEFAULT_3DS_NUS_HOST`] or
+    /// [`DEFAULT_WIIU_NUS_HOST`], depending on the console.
+    pub host: RwLock<Cow<'a, str>>,
+
+    /// The host of the content server that titles are downloaded from
+    ///
+    /// If no value is provided, it is initialized with [`DEFAULT_3DS_CCS_HOST`] or
+    /// [`DEFAULT_WIIU_CCS_HOST`], depending on the console.
+    pub content_host: RwLock<Cow<'a, str>>,
+
+    /// The console data we are connecting to the servers with
+    ///
+    /// This field is used to generate a set of HTTP headers that are passed to the servers in
+    /// requests to mimic a real console.
+    pub console: Arc<RwLock<C>>,
+
+    /// The pool we are storing [`Vec<u8>`]s in.
+    ///
+    /// This is used to speed up XML deserialization by reusing memory as much as possible,
+    /// removing the overhead of memory allocation
+    pub pool: BufferPool,
+
+    /// A cache of the headers sent to the network update server to avoid recalling
+    /// [`Console::http_headers`]
+    pub(crate) cached_headers: RwLock<HeaderMap<HeaderValue>>,
+
+    /// A cache of the headers sent to the content server to avoid recalling
+    /// [`Console::http_headers`]
+    pub(crate) cached_content_headers: RwLock<HeaderMap<HeaderValue>>,
+
+    /// The HTTP client used to make requests to the servers
+    pub(crate) http: HttpClient<HttpsConnector<HttpConnector>, Body>,
+}
+
+impl<'a, C: Console<'a> + Send + Clone> Client<'a, C> {
+    /// Create a new Client using the provided [`Console`]
+    ///
+    /// If no value for the `host` or `content_host` parameters are provided, the corresponding
+    /// struct fields, [`host`] and [`content_host`], are initialized to the default (official
+    /// Nintendo) hosts for the console that the provided [`Console`] implementor reports itself
+    /// as.
+    ///
+    /// If no value for the `identity` parameter is provided, it is initialized to the default
+    /// (official Nintendo) client certificate for the console that the provided [`Console`]
+    /// implementor reports itself as.
+    ///
+    /// If no value for the `cacert_bundle` parameter is provided, it is initialized to the default
+    /// (official Nintendo) certificate authority bundle, [`NINTENDO_CACERTS`]
+    ///
+    /// If no value for the `pool` parameter is provided, it is initialized to a global pool of
+    /// vectors with a fixed capacity of 100
+    ///
+    /// [`host`]: #structfield.host
+    /// [`content_host`]: #structfield.content_host
+    pub fn new<'b>(
+        host: Option<Cow<'a, str>>,
+        content_host: Option<Cow<'a, str>>,
+        console: Arc<RwLock<C>>,
+        identity: Option<Identity>,
+        cacert_bundle: Option<Cow<'b, [Certificate]>>,
+        pool: Option<BufferPool>,
+    ) -> Result<Self, ClientError> {
+        let kind = console.read().kind();
+        let (default_host, default_content_host, default_identity) = match kind {
+            ConsoleKind::N3ds => (DEFAULT_3DS_NUS_HOST, DEFAULT_3DS_CCS_HOST, CTR_COMMON_1),
+            ConsoleKind::WiiU => (DEFAULT_WIIU_NUS_HOST, DEFAULT_WIIU_CCS_HOST, WUP_COMMON_1),
+            kind => return Err(ClientError::UnsupportedConsoleKind(kind)),
+        };
+        let host = host.unwrap_or(Cow::Borrowed(default_host));
+        let content_host = content_host.unwrap_or(Cow::Borrowed(default_content_host));
+        Ok(Client {
+            host: RwLock::new(host.clone()),
+            content_host: RwLock::new(content_host.clone()),
+            console: Arc::clone(&console),
+            pool: if let Some(pool) = pool {
+                pool
+            } else {
+                GLOBAL_BUFFER_POOL.clone()
+            },
+            cached_headers: RwLock::new(
+                console
+                    .read()
+                    .http_headers(ServerKind::Nus(Cow::Borrowed(&host)))?,
+            ),
+            cached_content_headers: RwLock::new(
+                console
+                    .read()
+                    .http_headers(ServerKind::Ccs(Cow::Borrowed(&content_host)))?,
+            ),
+            http: HttpClient::builder().build(HttpsConnector::from((
+                {
+                    let mut http = HttpConnector::new();
+                    http.enforce_http(false);
+                    http
+                },
+                TlsConnector::from({
+                    let mut builder = NativeTlsConnector::builder();
+
+                    builder.identity(if let Some(identity) = identity {
+                        identity
+                    } else {
+                        Identity::from_pkcs12(default_identity, "ralsei")?
+                    });
+
+                    if let Some(cacert_bundle) = cacert_bundle {
+                        for cert in cacert_bundle.into_owned() {
+                            builder.add_root_certificate(cert);
+                        }
+                    } else {
+                        for cert in &NINTENDO_CACERTS {
+                            builder.add_root_certificate(Certificate::from_der(cert)?);
+                        }
+                    }
+
+                    builder.build()?
+                }),
+            ))),
+        })
+    }
+
+    /// Refresh the cached http headers
+    ///
+    /// This method blocks until a read lock can be acquired on the [`console`], [`host`], and
+    /// [`content_host`] fields, and a write lock can be acquired on the fields caching the
+    /// headers.
+    ///
+    /// [`console`]: #structfield.console
+    /// [`host`]: #structfield.host
+    /// [`content_host`]: #structfield.content_host
+    pub fn refresh_header(&self) -> Result<(), ClientError> {
+        let console = self.console.read();
+        *self.cached_headers.write() =
+            console.http_headers(ServerKind::Nus(Cow::Borrowed(&self.host.read())))?;
+        *self.cached_content_headers.write() =
+            console.http_headers(ServerKind::Ccs(Cow::Borrowed(&self.content_host.read())))?;
+        Ok(())
+    }
+
+    /// Construct a [`Uri`] pointing to the provided path on the provided host
+    fn uri(host: &RwLock<Cow<'a, str>>, path_and_query: &str) -> Result<Uri, ClientError> {
+        Ok(Uri::builder()
+            .scheme("https")
+            .authority(Authority::try_from(host.read().as_ref())?)
+            .path_and_query(PathAndQuery::try_from(path_and_query)?)
+            .build()?)
+    }
+
+    /// Execute a request to the network update server using the provided [`Request`]
+    #[inline]
+    pub fn request(&self, mut request: Request<Body>) -> ResponseFuture {
+        request
+            .headers_mut()
+            .extend(self.cached_headers.read().clone());
+        self.http.request(request)
+    }
+
+    /// Execute a request to the content server using the provided [`Request`]
+    #[inline]
+    pub fn content_request(&self, mut request: Request<Body>) -> ResponseFuture {
+        request
+            .headers_mut()
+            .extend(self.cached_content_headers.read().clone());
+        self.http.request(request)
+    }
+
+    /// Call the provided method of the network update server's SOAP api with the provided message,
+    /// returning the message it responds with
+    async fn call<M, R>(&self, method: &str, message: M) -> Result<R, ClientError>
+    where
+        M: ToXml<XmlErrorExtension> + Sync,
+        R: FromXml<XmlErrorExtension> + Default + Send,
+    {
+        let mut body = String::from(XML_DECLARATION);
+        body.push_str(&xml_framework::to_string(&Envelope { body: message }).await?);
+        let response = self
+            .request(
+                Request::builder()
+                    .method("POST")
+                    .uri(Self::uri(&self.host, nus_endpoints::NET_UPDATE_SOAP)?)
+                    .header(header::CONTENT_TYPE, "text/xml; charset=utf-8")
+                    .header("SOAPAction", format!("{}/{}", NUS_NAMESPACE, method))
+                    .version(HttpVersion::HTTP_11)
+                    .body(Body::from(body))?,
+            )
+            .await?;
+        match response.status().as_u16() {
+            200 => {
+                let mut envelope = Envelope::<R>::default();
+                envelope
+                    .from_xml(
+                        &mut XmlReader::from_reader(
+                            &body::to_bytes(response.into_body()).await?[..],
+                        ),
+                        self.pool.clone(),
+                    )
+                    .await?;
+                Ok(envelope.body)
+            }
+            status => Err(ClientError::UnexpectedStatusCode(status)),
+        }
+    }
+
+    /// Retrieve the hash identifying the latest system update available to the provided [`Device`]
+    /// from the network update server
+    ///
+    /// The hash changes whenever a new system update is released, so it can be used to cheaply
+    /// check for one before calling [`system_update`]
+    ///
+    /// [`system_update`]: #method.system_update
+    pub async fn system_title_hash(&self, device: &Device) -> Result<String, ClientError> {
+        let response: SystemTitleHashResponse<'static> = self
+            .call(
+                "GetSystemTitleHash",
+                SystemTitleHashRequest {
+                    version: Some(Cow::Borrowed("1.0")),
+                    message_id: Some(Cow::Borrowed("1")),
+                    device_id: Some(device.id),
+                    region_id: Some(Cow::Borrowed(region_id(device.region))),
+                    country_code: Some(device.country),
+                },
+            )
+            .await?;
+        check_error_code(response.error_code)?;
+        response
+            .title_hash
+            .map(Cow::into_owned)
+            .ok_or(ClientError::MissingXmlField("TitleHash"))
+    }
+
+    /// Retrieve the latest system update available to the provided [`Device`] from the network
+    /// update server
+    ///
+    /// The titles provided are reported as being installed on the device, and real consoles
+    /// provide none, causing every title of the update to be listed in the response
+    pub async fn system_update(
+        &self,
+        device: &Device,
+        installed_titles: &[UpdateTitle],
+    ) -> Result<SystemUpdateResponse<'static>, ClientError> {
+        // only the 3ds is known to send this
+        let attribute = match self.console.read().kind() {
+            ConsoleKind::N3ds => Some(2),
+            _ => None,
+        };
+        let response: SystemUpdateResponse<'static> = self
+            .call(
+                "GetSystemUpdate",
+                SystemUpdateRequest {
+                    version: Some(Cow::Borrowed("1.0")),
+                    message_id: Some(Cow::Borrowed("1")),
+                    device_id: Some(device.id),
+                    region_id: Some(Cow::Borrowed(region_id(device.region))),
+                    country_code: Some(device.country),
+                    titles: installed_titles.to_vec(),
+                    attribute,
+                    audit_data: Some(1),
+                },
+            )
+            .await?;
+        check_error_code(response.error_code)?;
+        Ok(response)
+    }
+
+    /// Request the file at the provided path below the download endpoint of the content server,
+    /// returning its body without reading it
+    async fn download(&self, path: &str) -> Result<Body, ClientError> {
+        let mut path_and_query = String::from(nus_endpoints::CCS_DOWNLOAD);
+        path_and_query.push_str(path);
+        let response = self
+            .content_request(
+                Request::builder()
+                    .method("GET")
+                    .uri(Self::uri(&self.content_host, &path_and_query)?)
+                    .version(HttpVersion::HTTP_11)
+                    .body(Body::empty())?,
+            )
+            .await?;
+        match response.status().as_u16() {
+            200 => Ok(response.into_body()),
+            status => Err(ClientError::UnexpectedStatusCode(status)),
+        }
+    }
+
+    /// Download the title metadata of the title with the provided [`TitleId`] from the content
+    /// server, without parsing it
+    ///
+    /// If no [`TitleVersion`] is provided, the metadata of the latest version of the title is
+    /// downloaded. The certificates it is signed with are appended to it.
+    pub async fn raw_title_metadata(
+        &self,
+        title_id: TitleId,
+        version: Option<TitleVersion>,
+    ) -> Result<Vec<u8>, ClientError> {
+        let body = self
+            .download(&match version {
+                Some(version) => format!("{:016x}/tmd.{}", title_id.0, version.0),
+                None => format!("{:016x}/tmd", title_id.0),
+            })
+            .await?;
+        Ok(body::to_bytes(body).await?.to_vec())
+    }
+
+    /// Download the [`TitleMetadata`] of the title with the provided [`TitleId`] from the
+    /// content server
+    ///
+    /// If no [`TitleVersion`] is provided, the metadata of the latest version of the title is
+    /// downloaded
+    pub async fn title_metadata(
+        &self,
+        title_id: TitleId,
+        version: Option<TitleVersion>,
+    ) -> Result<TitleMetadata<'static>, ClientError> {
+        Ok(TitleMetadata::try_from(
+            &self.raw_title_metadata(title_id, version).await?[..],
+        )?)
+    }
+
+    /// Download the common ticket (`cetk`) of the title with the provided [`TitleId`] from the
+    /// content server, without parsing it
+    ///
+    /// Only system titles and titles that are free have a common ticket available. The
+    /// certificates it is signed with are appended to it.
+    pub async fn raw_ticket(&self, title_id: TitleId) -> Result<Vec<u8>, ClientError> {
+        let body = self.download(&format!("{:016x}/cetk", title_id.0)).await?;
+        Ok(body::to_bytes(body).await?.to_vec())
+    }
+
+    /// Download the common [`Ticket`] (`cetk`) of the title with the provided [`TitleId`] from
+    /// the content server
+    ///
+    /// Only system titles and titles that are free have a common ticket available
+    pub async fn ticket(&self, title_id: TitleId) -> Result<Ticket<'static>, ClientError> {
+        Ok(Ticket::try_from(&self.raw_ticket(title_id).await?[..])?)
+    }
+
+    /// Download the content with the provided id belonging to the title with the provided
+    /// [`TitleId`] from the content server
+    ///
+    /// The content is returned as it is stored on the content server, which is encrypted using
+    /// the title key of the title's ticket. As contents can be several gigabytes in size, the
+    /// [`Body`] of the response is returned so that it can be read a chunk at a time, such as
+    /// into [`ContentDecryptor::update`]
+    ///
+    /// Hashed Wii U contents are returned as they are stored as well, but cannot be decrypted by a
+    /// [`ContentDecryptor`], which rejects them
+    ///
+    /// [`ContentDecryptor`]: ralsei_model::title::content::ContentDecryptor
+    /// [`ContentDecryptor::update`]: ralsei_model::title::content::ContentDecryptor::update
+    pub async fn content(&self, title_id: TitleId, content_id: u32) -> Result<Body, ClientError> {
+        self.download(&format!("{:016x}/{:08x}", title_id.0, content_id))
+            .await
+    }
+}
+
+/// The details of a device that are sent to the network update server
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Device {
+    /// The id of the device, as it is sent in requests to the network update server
+    pub id: u64,
+
+    /// The region of the device
+    pub region: Region,
+
+    /// The country of the device
+    pub country: CountryCode,
+}
+
+/// Returns the identifier of the provided [`Region`] used by the network update server
+fn region_id(region: Region) -> &'static str {
+    match region {
+        Region::Japan => "JPN",
+        Region::UnitedStates => "USA",
+        Region::Europe => "EUR",
+        Region::Australia => "AUS",
+        Region::China => "CHN",
+        Region::Korea => "KOR",
+        Region::Taiwan => "TWN",
+    }
+}
+
+/// Converts the error code of a response from the network update server into an error if it does
+/// not indicate success
+fn check_error_code(error_code: Option<i32>) -> Result<(), ClientError> {
+    match error_code {
+        Some(0) => Ok(()),
+        Some(error_code) => Err(ClientError::ErrorCode(error_code)),
+        None => Err(ClientError::MissingXmlField("ErrorCode")),
+    }
+}
+
+/// An enumeration over errors that can occur while using the client
+#[non_exhaustive]
+#[derive(thiserror::Error, Debug)]
+pub enum ClientError {
+    /// An error encountered when the provided [`Kind`](ConsoleKind) of console is not supported
+    #[error("`{0}` is an unsupported console Kind")]
+    UnsupportedConsoleKind(ConsoleKind),
+
+    /// An error encountered when the header values provided by the [`Console`] are invalid
+    #[error("An error was encountered while constructing headers")]
+    HeaderConstructionError(#[from] HeaderConstructionError),
+
+    /// An error encountered while using the native tls implementation
+    #[error("An error was encountered while using the native tls implementation")]
+    NativeTlsError(#[from] NativeTlsError),
+
+    /// An error was encountered while using hyper
+    #[error("An error was encountered while using the `hyper` library")]
+    HyperError(#[from] HyperError),
+
+    /// An error was encountered while using the http library
+    #[error("An error was encountered while using the `http` library")]
+    HttpError(#[from] HttpError),
+
+    /// An error was encountered while constructing a Uri
+    #[error("An error was encountered while constructing a Uri")]
+    UriConstructionError(#[from] InvalidUri),
+
+    /// An error was encountered while (de)serializing XML
+    #[error("An error was encountered while (de)serializing XML")]
+    XmlError(#[from] XmlError<XmlErrorExtension>),
+
+    /// The network update server returned an XML document that lacks an expected field
+    #[error("The network update server returned an XML document lacking an expected field, `{0}`")]
+    MissingXmlField(&'static str),
+
+    /// The network update server responded with an error code other than `0`
+    #[error("The network update server responded with the error code `{0}`")]
+    ErrorCode(i32),
+
+    /// The network update or content server returned an unexpected status code
+    #[error("The server returned an unexpected status code, `{0}`")]
+    UnexpectedStatusCode(u16),
+
+    /// An error was encountered while parsing title metadata
+    #[error("An error was encountered while parsing title metadata")]
+    TitleMetadataError(#[from] TitleMetadataError),
+
+    /// An error was encountered while parsing a ticket
+    #[error("An error was encountered while parsing a ticket")]
+    TicketError(#[from] TicketError),
+}
@@ -0,0 +1,35 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+/// The default (official Nintendo) host for the 3ds' network update server
+pub const DEFAULT_3DS_NUS_HOST: &str = "nus.c.shop.nintendowifi.net";
+
+/// The default (official Nintendo) host for the wii u's network update server
+pub const DEFAULT_WIIU_NUS_HOST: &str = "nus.wup.shop.nintendo.net";
+
+/// The default (official Nintendo) host for the 3ds' uncached content server
+pub const DEFAULT_3DS_CCS_HOST: &str = "ccs.c.shop.nintendowifi.net";
+
+/// The default (official Nintendo) host for the wii u's uncached content server
+pub const DEFAULT_WIIU_CCS_HOST: &str = "ccs.wup.shop.nintendo.net";
+
+/// The XML declaration prepended to SOAP requests sent to the network update server
+pub const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+/// The namespace of SOAP envelopes
+pub const SOAP_ENVELOPE_NAMESPACE: &str = "http://schemas.xmlsoap.org/soap/envelope/";
+
+/// The namespace of the network update server's SOAP messages
+pub const NUS_NAMESPACE: &str = "urn:nus.wsapi.broadon.com";
+
+/// A module containing paths to various endpoints of the network update and content servers
+pub mod nus_endpoints {
+    pub const NET_UPDATE_SOAP: &str = "/nus/services/NetUpdateSOAP";
+    pub const CCS_DOWNLOAD: &str = "/ccs/download/";
+}
@@ -0,0 +1,88 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use async_trait::async_trait;
+use quick_xml::{
+    events::{BytesEnd, BytesStart, Event},
+    Reader, Writer,
+};
+use std::io::{BufRead, Read, Write};
+
+use crate::{
+    common::SOAP_ENVELOPE_NAMESPACE,
+    xml::errors::{Error as XmlErrorExtension, Result},
+};
+use ralsei_util::xml::{
+    framework::{BufferPool, FromXml, ToXml},
+    helpers::{
+        generate_xml_field_write_by_propagation, generate_xml_struct_read,
+        generate_xml_struct_read_check,
+    },
+};
+
+/// A SOAP envelope, carrying a single message in its body
+///
+/// Every request made to and response received from the network update server is wrapped in one
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct Envelope<T> {
+    /// The message carried in the envelope's body
+    pub body: T,
+}
+
+#[async_trait]
+impl<T> ToXml<XmlErrorExtension> for Envelope<T>
+where
+    T: ToXml<XmlErrorExtension> + Sync,
+{
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(
+            BytesStart::borrowed_name(b"soapenv:Envelope").with_attributes(vec![
+                ("xmlns:soapenv", SOAP_ENVELOPE_NAMESPACE),
+                ("xmlns:xsd", "http://www.w3.org/2001/XMLSchema"),
+                ("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"),
+            ]),
+        ))?;
+
+        generate_xml_field_write_by_propagation!(b"soapenv:Body", writer, self.body);
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"soapenv:Envelope")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T> FromXml<XmlErrorExtension> for Envelope<T>
+where
+    T: FromXml<XmlErrorExtension> + Send,
+{
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read_check!(b"soapenv:Envelope", reader, buffer_pool.clone());
+
+        generate_xml_struct_read!(
+            b"soapenv:Envelope",
+            reader, buffer_pool,
+            c,
+
+            // the body, which holds nothing but the message itself
+            b"soapenv:Body" => {
+                self.body.from_xml(reader, buffer_pool.clone()).await?;
+                let rest_of_body: Result<()> =
+                    generate_xml_struct_read!(b"soapenv:Body", reader, buffer_pool, c,);
+                rest_of_body?;
+            }
+        )
+    }
+}
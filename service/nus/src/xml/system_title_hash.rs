@@ -0,0 +1,270 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use async_trait::async_trait;
+use isocountry::CountryCode;
+use quick_xml::{
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use std::{
+    borrow::Cow,
+    io::{BufRead, Read, Write},
+    str::FromStr,
+};
+
+use crate::{
+    common::NUS_NAMESPACE,
+    xml::errors::{Error as XmlErrorExtension, Result},
+};
+use ralsei_util::xml::{
+    errors::Error as XmlError,
+    framework::{BufferPool, FromXml, ToXml},
+    helpers::{generate_xml_field_write, generate_xml_struct_read, generate_xml_struct_read_check},
+};
+
+/// A request for the hash identifying the latest system update, used to check whether a console
+/// is up to date without listing every title of the update
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct SystemTitleHashRequest<'a> {
+    /// The version of the SOAP api being used (always `1.0`)
+    pub version: Option<Cow<'a, str>>,
+
+    /// An identifier for the request, which is echoed back in the response
+    pub message_id: Option<Cow<'a, str>>,
+
+    /// The id of the device making the request
+    pub device_id: Option<u64>,
+
+    /// The region of the device making the request (`JPN`, `USA`, `EUR`, ...)
+    pub region_id: Option<Cow<'a, str>>,
+
+    /// The country of the device making the request
+    pub country_code: Option<CountryCode>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for SystemTitleHashRequest<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(
+            BytesStart::borrowed_name(b"GetSystemTitleHashRequest")
+                .with_attributes(vec![("xmlns", NUS_NAMESPACE)]),
+        ))?;
+
+        // the version of the api
+        if let Some(ref version) = &self.version {
+            generate_xml_field_write!(b"Version", writer, BytesText::from_plain_str(version));
+        }
+
+        // the request's identifier
+        if let Some(ref message_id) = &self.message_id {
+            generate_xml_field_write!(b"MessageId", writer, BytesText::from_plain_str(message_id));
+        }
+
+        // the device's id
+        if let Some(device_id) = self.device_id {
+            generate_xml_field_write!(
+                b"DeviceId",
+                writer,
+                BytesText::from_plain_str(device_id.to_string().as_str())
+            );
+        }
+
+        // the device's region
+        if let Some(ref region_id) = &self.region_id {
+            generate_xml_field_write!(b"RegionId", writer, BytesText::from_plain_str(region_id));
+        }
+
+        // the device's country
+        if let Some(country_code) = self.country_code {
+            generate_xml_field_write!(
+                b"CountryCode",
+                writer,
+                BytesText::from_plain_str(country_code.alpha2())
+            );
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"GetSystemTitleHashRequest")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for SystemTitleHashRequest<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read_check!(b"GetSystemTitleHashRequest", reader, buffer_pool.clone());
+
+        generate_xml_struct_read!(
+            b"GetSystemTitleHashRequest",
+            reader, buffer_pool,
+            c,
+
+            // the version of the api
+            b"Version" => {
+                self.version = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the request's identifier
+            b"MessageId" => {
+                self.message_id = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the device's id
+            b"DeviceId" => {
+                self.device_id = Some(u64::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the device's region
+            b"RegionId" => {
+                self.region_id = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the device's country
+            b"CountryCode" => {
+                self.country_code = Some(CountryCode::for_alpha2(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::CountryCodeParseError(e)))?);
+            }
+        )
+    }
+}
+
+/// The network update server's response to a [`SystemTitleHashRequest`]
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct SystemTitleHashResponse<'a> {
+    /// The version of the SOAP api being used (always `1.0`)
+    pub version: Option<Cow<'a, str>>,
+
+    /// The id of the device that made the request
+    pub device_id: Option<u64>,
+
+    /// The identifier of the request being responded to
+    pub message_id: Option<Cow<'a, str>>,
+
+    /// The time the response was made at, in milliseconds since the unix epoch
+    pub timestamp: Option<u64>,
+
+    /// The result of the request, which is `0` if it succeeded
+    pub error_code: Option<i32>,
+
+    /// The hash identifying the latest system update
+    pub title_hash: Option<Cow<'a, str>>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for SystemTitleHashResponse<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(
+            BytesStart::borrowed_name(b"GetSystemTitleHashResponse")
+                .with_attributes(vec![("xmlns", NUS_NAMESPACE)]),
+        ))?;
+
+        // the version of the api
+        if let Some(ref version) = &self.version {
+            generate_xml_field_write!(b"Version", writer, BytesText::from_plain_str(version));
+        }
+
+        // the device's id
+        if let Some(device_id) = self.device_id {
+            generate_xml_field_write!(
+                b"DeviceId",
+                writer,
+                BytesText::from_plain_str(device_id.to_string().as_str())
+            );
+        }
+
+        // the request's identifier
+        if let Some(ref message_id) = &self.message_id {
+            generate_xml_field_write!(b"MessageId", writer, BytesText::from_plain_str(message_id));
+        }
+
+        // the time of the response
+        if let Some(timestamp) = self.timestamp {
+            generate_xml_field_write!(
+                b"TimeStamp",
+                writer,
+                BytesText::from_plain_str(timestamp.to_string().as_str())
+            );
+        }
+
+        // the result of the request
+        if let Some(error_code) = self.error_code {
+            generate_xml_field_write!(
+                b"ErrorCode",
+                writer,
+                BytesText::from_plain_str(error_code.to_string().as_str())
+            );
+        }
+
+        // the hash of the latest system update
+        if let Some(ref title_hash) = &self.title_hash {
+            generate_xml_field_write!(b"TitleHash", writer, BytesText::from_plain_str(title_hash));
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(
+            b"GetSystemTitleHashResponse",
+        )))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for SystemTitleHashResponse<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read_check!(b"GetSystemTitleHashResponse", reader, buffer_pool.clone());
+
+        generate_xml_struct_read!(
+            b"GetSystemTitleHashResponse",
+            reader, buffer_pool,
+            c,
+
+            // the version of the api
+            b"Version" => {
+                self.version = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the device's id
+            b"DeviceId" => {
+                self.device_id = Some(u64::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the request's identifier
+            b"MessageId" => {
+                self.message_id = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the time of the response
+            b"TimeStamp" => {
+                self.timestamp = Some(u64::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the result of the request
+            b"ErrorCode" => {
+                self.error_code = Some(i32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the hash of the latest system update
+            b"TitleHash" => {
+                self.title_hash = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            }
+        )
+    }
+}
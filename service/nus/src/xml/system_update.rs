@@ -0,0 +1,507 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use async_trait::async_trait;
+use isocountry::CountryCode;
+use quick_xml::{
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use std::{
+    borrow::Cow,
+    io::{BufRead, Read, Write},
+    str::FromStr,
+};
+
+use crate::{
+    common::NUS_NAMESPACE,
+    xml::errors::{Error as XmlErrorExtension, Result},
+};
+use ralsei_model::title::{id::TitleId, version::TitleVersion};
+use ralsei_util::xml::{
+    errors::Error as XmlError,
+    framework::{BufferPool, FromXml, ToXml},
+    helpers::{generate_xml_field_write, generate_xml_struct_read, generate_xml_struct_read_check},
+};
+
+/// A request for the titles making up the latest system update
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct SystemUpdateRequest<'a> {
+    /// The version of the SOAP api being used (always `1.0`)
+    pub version: Option<Cow<'a, str>>,
+
+    /// An identifier for the request, which is echoed back in the response
+    pub message_id: Option<Cow<'a, str>>,
+
+    /// The id of the device making the request
+    pub device_id: Option<u64>,
+
+    /// The region of the device making the request (`JPN`, `USA`, `EUR`, ...)
+    pub region_id: Option<Cow<'a, str>>,
+
+    /// The country of the device making the request
+    pub country_code: Option<CountryCode>,
+
+    /// The titles installed on the device making the request
+    ///
+    /// Real consoles leave this empty, causing every title of the update to be listed in the
+    /// response
+    pub titles: Vec<UpdateTitle>,
+
+    /// An unknown attribute, sent as `2` by the 3ds
+    pub attribute: Option<u32>,
+
+    /// Whether the device is sending audit data (`1`) or not
+    pub audit_data: Option<u32>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for SystemUpdateRequest<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(
+            BytesStart::borrowed_name(b"GetSystemUpdateRequest")
+                .with_attributes(vec![("xmlns", NUS_NAMESPACE)]),
+        ))?;
+
+        // the version of the api
+        if let Some(ref version) = &self.version {
+            generate_xml_field_write!(b"Version", writer, BytesText::from_plain_str(version));
+        }
+
+        // the request's identifier
+        if let Some(ref message_id) = &self.message_id {
+            generate_xml_field_write!(b"MessageId", writer, BytesText::from_plain_str(message_id));
+        }
+
+        // the device's id
+        if let Some(device_id) = self.device_id {
+            generate_xml_field_write!(
+                b"DeviceId",
+                writer,
+                BytesText::from_plain_str(device_id.to_string().as_str())
+            );
+        }
+
+        // the device's region
+        if let Some(ref region_id) = &self.region_id {
+            generate_xml_field_write!(b"RegionId", writer, BytesText::from_plain_str(region_id));
+        }
+
+        // the device's country
+        if let Some(country_code) = self.country_code {
+            generate_xml_field_write!(
+                b"CountryCode",
+                writer,
+                BytesText::from_plain_str(country_code.alpha2())
+            );
+        }
+
+        // the titles installed on the device
+        for title in &self.titles {
+            title.to_xml(writer).await?;
+        }
+
+        // the unknown attribute
+        if let Some(attribute) = self.attribute {
+            generate_xml_field_write!(
+                b"Attribute",
+                writer,
+                BytesText::from_plain_str(attribute.to_string().as_str())
+            );
+        }
+
+        // whether audit data is being sent
+        if let Some(audit_data) = self.audit_data {
+            generate_xml_field_write!(
+                b"AuditData",
+                writer,
+                BytesText::from_plain_str(audit_data.to_string().as_str())
+            );
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"GetSystemUpdateRequest")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for SystemUpdateRequest<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read_check!(b"GetSystemUpdateRequest", reader, buffer_pool.clone());
+
+        generate_xml_struct_read!(
+            b"GetSystemUpdateRequest",
+            reader, buffer_pool,
+            c,
+
+            // the version of the api
+            b"Version" => {
+                self.version = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the request's identifier
+            b"MessageId" => {
+                self.message_id = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the device's id
+            b"DeviceId" => {
+                self.device_id = Some(u64::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the device's region
+            b"RegionId" => {
+                self.region_id = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the device's country
+            b"CountryCode" => {
+                self.country_code = Some(CountryCode::for_alpha2(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::CountryCodeParseError(e)))?);
+            },
+
+            // a title installed on the device
+            b"TitleVersion" => {
+                let mut title = UpdateTitle::default();
+                title.from_xml(reader, buffer_pool.clone()).await?;
+                self.titles.push(title)
+            },
+
+            // the unknown attribute
+            b"Attribute" => {
+                self.attribute = Some(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // whether audit data is being sent
+            b"AuditData" => {
+                self.audit_data = Some(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            }
+        )
+    }
+}
+
+/// The network update server's response to a [`SystemUpdateRequest`]
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct SystemUpdateResponse<'a> {
+    /// The version of the SOAP api being used (always `1.0`)
+    pub version: Option<Cow<'a, str>>,
+
+    /// The id of the device that made the request
+    pub device_id: Option<u64>,
+
+    /// The identifier of the request being responded to
+    pub message_id: Option<Cow<'a, str>>,
+
+    /// The time the response was made at, in milliseconds since the unix epoch
+    pub timestamp: Option<u64>,
+
+    /// The result of the request, which is `0` if it succeeded
+    pub error_code: Option<i32>,
+
+    /// The url of the cached content server that the update's titles can be downloaded from
+    pub content_prefix_url: Option<Cow<'a, str>>,
+
+    /// The url of the uncached content server that the update's titles can be downloaded from
+    pub uncached_content_prefix_url: Option<Cow<'a, str>>,
+
+    /// The titles making up the update
+    pub titles: Vec<UpdateTitle>,
+
+    /// Whether the device should upload audit data (`1`) or not
+    pub upload_audit_data: Option<u32>,
+
+    /// The hash identifying the update, as returned by a [`SystemTitleHashRequest`]
+    ///
+    /// [`SystemTitleHashRequest`]: crate::xml::system_title_hash::SystemTitleHashRequest
+    pub title_hash: Option<Cow<'a, str>>,
+}
+
+#[async_trait]
+impl<'a> ToXml<XmlErrorExtension> for SystemUpdateResponse<'a> {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(
+            BytesStart::borrowed_name(b"GetSystemUpdateResponse")
+                .with_attributes(vec![("xmlns", NUS_NAMESPACE)]),
+        ))?;
+
+        // the version of the api
+        if let Some(ref version) = &self.version {
+            generate_xml_field_write!(b"Version", writer, BytesText::from_plain_str(version));
+        }
+
+        // the device's id
+        if let Some(device_id) = self.device_id {
+            generate_xml_field_write!(
+                b"DeviceId",
+                writer,
+                BytesText::from_plain_str(device_id.to_string().as_str())
+            );
+        }
+
+        // the request's identifier
+        if let Some(ref message_id) = &self.message_id {
+            generate_xml_field_write!(b"MessageId", writer, BytesText::from_plain_str(message_id));
+        }
+
+        // the time of the response
+        if let Some(timestamp) = self.timestamp {
+            generate_xml_field_write!(
+                b"TimeStamp",
+                writer,
+                BytesText::from_plain_str(timestamp.to_string().as_str())
+            );
+        }
+
+        // the result of the request
+        if let Some(error_code) = self.error_code {
+            generate_xml_field_write!(
+                b"ErrorCode",
+                writer,
+                BytesText::from_plain_str(error_code.to_string().as_str())
+            );
+        }
+
+        // the cached content server
+        if let Some(ref content_prefix_url) = &self.content_prefix_url {
+            generate_xml_field_write!(
+                b"ContentPrefixURL",
+                writer,
+                BytesText::from_plain_str(content_prefix_url)
+            );
+        }
+
+        // the uncached content server
+        if let Some(ref uncached_content_prefix_url) = &self.uncached_content_prefix_url {
+            generate_xml_field_write!(
+                b"UncachedContentPrefixURL",
+                writer,
+                BytesText::from_plain_str(uncached_content_prefix_url)
+            );
+        }
+
+        // the titles of the update
+        for title in &self.titles {
+            title.to_xml(writer).await?;
+        }
+
+        // whether audit data should be uploaded
+        if let Some(upload_audit_data) = self.upload_audit_data {
+            generate_xml_field_write!(
+                b"UploadAuditData",
+                writer,
+                BytesText::from_plain_str(upload_audit_data.to_string().as_str())
+            );
+        }
+
+        // the hash of the update
+        if let Some(ref title_hash) = &self.title_hash {
+            generate_xml_field_write!(b"TitleHash", writer, BytesText::from_plain_str(title_hash));
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"GetSystemUpdateResponse")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> FromXml<XmlErrorExtension> for SystemUpdateResponse<'a> {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read_check!(b"GetSystemUpdateResponse", reader, buffer_pool.clone());
+
+        generate_xml_struct_read!(
+            b"GetSystemUpdateResponse",
+            reader, buffer_pool,
+            c,
+
+            // the version of the api
+            b"Version" => {
+                self.version = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the device's id
+            b"DeviceId" => {
+                self.device_id = Some(u64::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the request's identifier
+            b"MessageId" => {
+                self.message_id = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the time of the response
+            b"TimeStamp" => {
+                self.timestamp = Some(u64::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the result of the request
+            b"ErrorCode" => {
+                self.error_code = Some(i32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the cached content server
+            b"ContentPrefixURL" => {
+                self.content_prefix_url = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // the uncached content server
+            b"UncachedContentPrefixURL" => {
+                self.uncached_content_prefix_url = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            },
+
+            // a title of the update
+            b"TitleVersion" => {
+                let mut title = UpdateTitle::default();
+                title.from_xml(reader, buffer_pool.clone()).await?;
+                self.titles.push(title)
+            },
+
+            // whether audit data should be uploaded
+            b"UploadAuditData" => {
+                self.upload_audit_data = Some(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the hash of the update
+            b"TitleHash" => {
+                self.title_hash = Some(Cow::Owned(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?));
+            }
+        )
+    }
+}
+
+/// A title of a system update, or one installed on the device requesting it
+///
+/// The sizes are only present in the responses of the network update server
+#[derive(Clone, Default, Debug, Eq, Hash, PartialEq)]
+pub struct UpdateTitle {
+    /// The title's id
+    pub title_id: Option<TitleId>,
+
+    /// The title's version
+    pub version: Option<TitleVersion>,
+
+    /// The size of the title once installed
+    pub fs_size: Option<u64>,
+
+    /// The size of the title's ticket
+    pub ticket_size: Option<u32>,
+
+    /// The size of the title's metadata
+    pub tmd_size: Option<u32>,
+}
+
+#[async_trait]
+impl ToXml<XmlErrorExtension> for UpdateTitle {
+    async fn to_xml<W>(&self, writer: &mut Writer<W>) -> Result<()>
+    where
+        W: Write + Send + Sync,
+    {
+        writer.write_event(Event::Start(BytesStart::borrowed_name(b"TitleVersion")))?;
+
+        // the title's id
+        if let Some(title_id) = self.title_id {
+            generate_xml_field_write!(
+                b"TitleId",
+                writer,
+                BytesText::from_plain_str(format!("{:016X}", title_id.0).as_str())
+            );
+        }
+
+        // the title's version
+        if let Some(version) = self.version {
+            generate_xml_field_write!(
+                b"Version",
+                writer,
+                BytesText::from_plain_str(version.0.to_string().as_str())
+            );
+        }
+
+        // the size of the installed title
+        if let Some(fs_size) = self.fs_size {
+            generate_xml_field_write!(
+                b"FsSize",
+                writer,
+                BytesText::from_plain_str(fs_size.to_string().as_str())
+            );
+        }
+
+        // the size of the title's ticket
+        if let Some(ticket_size) = self.ticket_size {
+            generate_xml_field_write!(
+                b"TicketSize",
+                writer,
+                BytesText::from_plain_str(ticket_size.to_string().as_str())
+            );
+        }
+
+        // the size of the title's metadata
+        if let Some(tmd_size) = self.tmd_size {
+            generate_xml_field_write!(
+                b"TMDSize",
+                writer,
+                BytesText::from_plain_str(tmd_size.to_string().as_str())
+            );
+        }
+
+        writer.write_event(Event::End(BytesEnd::borrowed(b"TitleVersion")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FromXml<XmlErrorExtension> for UpdateTitle {
+    async fn from_xml<R>(&mut self, reader: &mut Reader<R>, buffer_pool: BufferPool) -> Result<()>
+    where
+        R: Read + BufRead + Send + Sync,
+    {
+        generate_xml_struct_read!(
+            b"TitleVersion",
+            reader, buffer_pool,
+            c,
+
+            // the title's id, which is written in hexadecimal
+            b"TitleId" => {
+                self.title_id = Some(TitleId(u64::from_str_radix(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str(), 16).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?));
+            },
+
+            // the title's version
+            b"Version" => {
+                self.version = Some(TitleVersion(u16::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?));
+            },
+
+            // the size of the installed title
+            b"FsSize" => {
+                self.fs_size = Some(u64::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the size of the title's ticket
+            b"TicketSize" => {
+                self.ticket_size = Some(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            },
+
+            // the size of the title's metadata
+            b"TMDSize" => {
+                self.tmd_size = Some(u32::from_str(reader.read_text(c.name(), &mut *buffer_pool.get().await?)?.as_str()).map_err(|e| XmlError::CustomError(XmlErrorExtension::IntegerParseError(e)))?);
+            }
+        )
+    }
+}
@@ -0,0 +1,384 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+use hyper::{body, server::conn::Http, service::service_fn, Body, Request, Response, StatusCode};
+use isocountry::CountryCode;
+use native_tls::{Certificate, Identity, TlsAcceptor};
+use parking_lot::RwLock;
+use quick_xml::Reader as XmlReader;
+use std::{
+    borrow::Cow,
+    convert::{Infallible, TryFrom},
+    sync::Arc,
+};
+use tokio::net::TcpListener;
+use tokio_native_tls::TlsAcceptor as AsyncTlsAcceptor;
+
+use ralsei_model::{
+    console::{
+        common::{Console, Region},
+        n3ds::Console3ds,
+        wiiu::ConsoleWiiU,
+    },
+    title::{id::TitleId, ticket::Ticket, tmd::TitleMetadata, version::TitleVersion},
+};
+use ralsei_service_nus::{
+    client::{Client, ClientError, Device},
+    common::{DEFAULT_WIIU_CCS_HOST, DEFAULT_WIIU_NUS_HOST},
+    xml::{
+        envelope::Envelope,
+        system_title_hash::{SystemTitleHashRequest, SystemTitleHashResponse},
+        system_update::{SystemUpdateRequest, SystemUpdateResponse, UpdateTitle},
+    },
+};
+use ralsei_util::xml::{
+    framework::{self as xml_framework, FromXml},
+    GLOBAL_BUFFER_POOL,
+};
+
+// a title's metadata and ticket, generated for testing
+const TITLE_METADATA: &[u8] = include_bytes!("data/title.tmd");
+const TICKET: &[u8] = include_bytes!("data/ticket.tik");
+
+const TITLE_ID: TitleId = TitleId(0x0004000000030800);
+const CONTENT: &[u8] = b"\x00some encrypted content\xFF";
+const TITLE_HASH: &str = "0123456789ABCDEF0123456789ABCDEF";
+
+const N3DS_USER_AGENT: &str = "CTR NUP 040600 Mar 14 2012 13:32:39";
+const WIIU_USER_AGENT: &str = "wii libnup/1.0";
+
+/// Produce the response of the mock network update and content servers to the provided
+/// [`Request`]
+async fn handle(request: Request<Body>) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    let wiiu = match request.headers().get(hyper::header::USER_AGENT) {
+        Some(user_agent) if user_agent == N3DS_USER_AGENT => false,
+        Some(user_agent) if user_agent == WIIU_USER_AGENT => true,
+        _ => {
+            *response.status_mut() = StatusCode::FORBIDDEN;
+            return response;
+        }
+    };
+
+    let title_id = format!("{:016x}", TITLE_ID.0);
+    let path = request.uri().path().to_string();
+    match (
+        request.method().as_str(),
+        path.split('/').collect::<Vec<_>>().as_slice(),
+    ) {
+        ("POST", ["", "nus", "services", "NetUpdateSOAP"]) => {
+            let action = request.headers()["SOAPAction"]
+                .to_str()
+                .unwrap()
+                .to_string();
+            let body = body::to_bytes(request.into_body()).await.unwrap();
+            let mut reader = XmlReader::from_reader(&body[..]);
+            let xml = match action.as_str() {
+                "urn:nus.wsapi.broadon.com/GetSystemTitleHash" => {
+                    let mut request = Envelope::<SystemTitleHashRequest>::default();
+                    request
+                        .from_xml(&mut reader, GLOBAL_BUFFER_POOL.clone())
+                        .await
+                        .unwrap();
+                    xml_framework::to_string(&Envelope {
+                        body: SystemTitleHashResponse {
+                            version: Some(Cow::Borrowed("1.0")),
+                            device_id: request.body.device_id,
+                            message_id: request.body.message_id,
+                            timestamp: Some(1616161616161),
+                            error_code: Some(0),
+                            title_hash: Some(Cow::Borrowed(TITLE_HASH)),
+                        },
+                    })
+                    .await
+                }
+                "urn:nus.wsapi.broadon.com/GetSystemUpdate" => {
+                    let mut request = Envelope::<SystemUpdateRequest>::default();
+                    request
+                        .from_xml(&mut reader, GLOBAL_BUFFER_POOL.clone())
+                        .await
+                        .unwrap();
+
+                    // only the 3ds sends an attribute
+                    if request.body.attribute != if wiiu { None } else { Some(2) } {
+                        *response.status_mut() = StatusCode::BAD_REQUEST;
+                        return response;
+                    }
+
+                    // updates are only available to devices in the united states
+                    if request.body.region_id.as_deref() != Some("USA")
+                        || request.body.country_code != Some(CountryCode::USA)
+                    {
+                        xml_framework::to_string(&Envelope {
+                            body: SystemUpdateResponse {
+                                error_code: Some(619),
+                                ..SystemUpdateResponse::default()
+                            },
+                        })
+                        .await
+                    } else {
+                        xml_framework::to_string(&Envelope {
+                            body: SystemUpdateResponse {
+                                version: Some(Cow::Borrowed("1.0")),
+                                device_id: request.body.device_id,
+                                message_id: request.body.message_id,
+                                timestamp: Some(1616161616161),
+                                error_code: Some(0),
+                                content_prefix_url: Some(Cow::Borrowed(
+                                    "http://localhost/ccs/download",
+                                )),
+                                uncached_content_prefix_url: Some(Cow::Borrowed(
+                                    "https://localhost/ccs/download",
+                                )),
+                                titles: vec![UpdateTitle {
+                                    title_id: Some(TITLE_ID),
+                                    version: Some(TitleVersion(0x0400)),
+                                    fs_size: Some(0x40000),
+                                    ticket_size: Some(TICKET.len() as u32),
+                                    tmd_size: Some(TITLE_METADATA.len() as u32),
+                                }],
+                                upload_audit_data: Some(1),
+                                title_hash: Some(Cow::Borrowed(TITLE_HASH)),
+                            },
+                        })
+                        .await
+                    }
+                }
+                _ => {
+                    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                    return response;
+                }
+            };
+            *response.body_mut() = Body::from(xml.unwrap());
+        }
+        ("GET", ["", "ccs", "download", id, file]) if *id == title_id => match *file {
+            "tmd" | "tmd.1024" => *response.body_mut() = Body::from(TITLE_METADATA),
+            "cetk" => *response.body_mut() = Body::from(TICKET),
+            "00000000" => *response.body_mut() = Body::from(CONTENT),
+            _ => *response.status_mut() = StatusCode::NOT_FOUND,
+        },
+        _ => *response.status_mut() = StatusCode::NOT_FOUND,
+    }
+    response
+}
+
+/// Start the mock network update and content servers over TLS using a freshly generated
+/// certificate, returning a client for the provided console connected to them
+async fn start<C: Console<'static> + Send + Clone>(console: C) -> Client<'static, C> {
+    let certificate = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+        .expect("unable to generate a certificate");
+    let certificate_pem = certificate
+        .serialize_pem()
+        .expect("unable to serialize the certificate");
+    let private_key_pem = certificate.serialize_private_key_pem();
+    let identity = || {
+        Identity::from_pkcs8(certificate_pem.as_bytes(), private_key_pem.as_bytes())
+            .expect("unable to construct an identity")
+    };
+    let acceptor = AsyncTlsAcceptor::from(
+        TlsAcceptor::new(identity()).expect("unable to construct a tls acceptor"),
+    );
+
+    let listener = TcpListener::bind("localhost:0")
+        .await
+        .expect("unable to bind a listener");
+    let port = listener
+        .local_addr()
+        .expect("unable to get the listener's address")
+        .port();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                if let Ok(stream) = acceptor.accept(stream).await {
+                    let _ = Http::new()
+                        .serve_connection(
+                            stream,
+                            service_fn(|request| async {
+                                Ok::<_, Infallible>(handle(request).await)
+                            }),
+                        )
+                        .await;
+                }
+            });
+        }
+    });
+
+    // both servers are served by the same mock
+    let host = format!("localhost:{}", port);
+    Client::new(
+        Some(Cow::Owned(host.clone())),
+        Some(Cow::Owned(host)),
+        Arc::new(RwLock::new(console)),
+        // the server does not verify client certificates, so the one it uses is reused here
+        Some(identity()),
+        Some(Cow::Owned(vec![Certificate::from_pem(
+            certificate_pem.as_bytes(),
+        )
+        .expect("unable to parse the certificate")])),
+        None,
+    )
+    .expect("unable to construct a client")
+}
+
+fn console_3ds() -> Console3ds<'static> {
+    Console3ds::new(|b| Ok(b.device_id(1))).expect("unable to construct a console")
+}
+
+fn console_wiiu() -> ConsoleWiiU<'static> {
+    ConsoleWiiU::new(|b| Ok(b.device_id(1))).expect("unable to construct a console")
+}
+
+const DEVICE: Device = Device {
+    id: 0x0000000400000001,
+    region: Region::UnitedStates,
+    country: CountryCode::USA,
+};
+
+async fn system_title_hash<C: Console<'static> + Send + Clone>(client: Client<'static, C>) {
+    assert_eq!(
+        client
+            .system_title_hash(&DEVICE)
+            .await
+            .expect("unable to retrieve the system title hash"),
+        TITLE_HASH
+    );
+}
+
+async fn system_update<C: Console<'static> + Send + Clone>(client: Client<'static, C>) {
+    let update = client
+        .system_update(&DEVICE, &[])
+        .await
+        .expect("unable to retrieve the system update");
+    assert_eq!(update.device_id, Some(DEVICE.id));
+    assert_eq!(update.title_hash.as_deref(), Some(TITLE_HASH));
+    assert_eq!(
+        update.titles,
+        vec![UpdateTitle {
+            title_id: Some(TITLE_ID),
+            version: Some(TitleVersion(0x0400)),
+            fs_size: Some(0x40000),
+            ticket_size: Some(TICKET.len() as u32),
+            tmd_size: Some(TITLE_METADATA.len() as u32),
+        }]
+    );
+
+    assert!(matches!(
+        client
+            .system_update(
+                &Device {
+                    region: Region::Japan,
+                    country: CountryCode::JPN,
+                    ..DEVICE
+                },
+                &[]
+            )
+            .await,
+        Err(ClientError::ErrorCode(619))
+    ));
+}
+
+async fn downloads<C: Console<'static> + Send + Clone>(client: Client<'static, C>) {
+    assert_eq!(
+        client
+            .title_metadata(TITLE_ID, None)
+            .await
+            .expect("unable to download the title metadata"),
+        TitleMetadata::try_from(TITLE_METADATA).unwrap()
+    );
+    assert_eq!(
+        client
+            .raw_title_metadata(TITLE_ID, Some(TitleVersion(1024)))
+            .await
+            .expect("unable to download the title metadata"),
+        TITLE_METADATA
+    );
+    assert_eq!(
+        client
+            .ticket(TITLE_ID)
+            .await
+            .expect("unable to download the ticket"),
+        Ticket::try_from(TICKET).unwrap()
+    );
+    assert_eq!(
+        body::to_bytes(
+            client
+                .content(TITLE_ID, 0)
+                .await
+                .expect("unable to download the content")
+        )
+        .await
+        .expect("unable to read the content"),
+        CONTENT
+    );
+
+    assert!(matches!(
+        client.content(TITLE_ID, 1).await,
+        Err(ClientError::UnexpectedStatusCode(404))
+    ));
+}
+
+#[tokio::test]
+async fn system_title_hash_n3ds() {
+    system_title_hash(start(console_3ds()).await).await;
+}
+
+#[tokio::test]
+async fn system_title_hash_wiiu() {
+    system_title_hash(start(console_wiiu()).await).await;
+}
+
+#[tokio::test]
+async fn system_update_n3ds() {
+    system_update(start(console_3ds()).await).await;
+}
+
+#[tokio::test]
+async fn system_update_wiiu() {
+    system_update(start(console_wiiu()).await).await;
+}
+
+#[tokio::test]
+async fn downloads_n3ds() {
+    downloads(start(console_3ds()).await).await;
+}
+
+#[tokio::test]
+async fn downloads_wiiu() {
+    downloads(start(console_wiiu()).await).await;
+}
+
+#[tokio::test]
+async fn wiiu_default_hosts() {
+    // the bundled client certificates are encrypted using rc2, which newer versions of openssl
+    // do not load by default, so a generated one is used instead
+    let certificate = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+        .expect("unable to generate a certificate");
+    let client = Client::new(
+        None,
+        None,
+        Arc::new(RwLock::new(console_wiiu())),
+        Some(
+            Identity::from_pkcs8(
+                certificate
+                    .serialize_pem()
+                    .expect("unable to serialize the certificate")
+                    .as_bytes(),
+                certificate.serialize_private_key_pem().as_bytes(),
+            )
+            .expect("unable to construct an identity"),
+        ),
+        None,
+        None,
+    )
+    .expect("unable to construct a client");
+    assert_eq!(*client.host.read(), DEFAULT_WIIU_NUS_HOST);
+    assert_eq!(*client.content_host.read(), DEFAULT_WIIU_CCS_HOST);
+}
@@ -42,4 +42,7 @@
 
 pub mod builder;
 pub mod misc;
+pub mod prudp;
+pub mod rc4;
+pub mod transport;
 pub mod xml;
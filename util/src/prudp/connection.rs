@@ -0,0 +1,784 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! A reliable, ordered PRUDP connection, built on top of a [`Codec`] and a [`Link`]
+//!
+//! A connection's [`State`] is created by a version's handshake, which uses it to exchange
+//! packets with the peer before handing it off to a task using [`State::spawn`]. The task takes
+//! care of acknowledging, retransmitting, fragmenting, encrypting and ordering the packets sent
+//! over each of the connection's substreams, leaving the returned [`Handle`] to send and receive
+//! whole messages
+
+use async_trait::async_trait;
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    io::Error as IoError,
+    mem,
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt, WriteHalf},
+    net::UdpSocket,
+    sync::{mpsc, oneshot},
+    time::{self, Instant},
+};
+
+use crate::{
+    prudp::packet::{Codec, Packet, PacketFlags, PacketKind},
+    rc4::Rc4,
+    transport::TransportError,
+};
+
+/// The largest datagram that can be received
+pub const MAX_DATAGRAM_LENGTH: usize = 0x10000;
+
+/// The shortest interval at which a connection checks for packets to retransmit
+const MIN_TICK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The number of times a disconnect packet is acknowledged over a lossy [`Link`], as the
+/// acknowledgement cannot be retransmitted once the connection has been closed
+const DISCONNECT_ACKNOWLEDGEMENTS: usize = 3;
+
+/// The channel that the packets received from the peer are passed into a connection through
+pub type Packets<C> =
+    mpsc::UnboundedReceiver<Result<<C as Codec>::Packet, ConnectionError<<C as Codec>::Error>>>;
+
+/// A message received from the peer, along with the id of the substream it was received on, or the
+/// error that closed the connection
+type Message<E> = Result<(u8, Vec<u8>), ConnectionError<E>>;
+
+/// The channel that the messages received from the peer are passed out of a connection through
+type Messages<E> = mpsc::UnboundedSender<Message<E>>;
+
+/// The channel that the outcome of a disconnection is reported over
+type DisconnectWaiter<E> = oneshot::Sender<Result<(), ConnectionError<E>>>;
+
+/// The settings governing how a connection delivers messages
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Reliability {
+    /// The largest payload placed in a single data packet before a message is fragmented
+    pub fragment_size: usize,
+
+    /// The time to wait for an acknowledgement before retransmitting a packet, or before giving
+    /// up on the peer if the [`Link`] is not lossy
+    pub resend_timeout: Duration,
+
+    /// The number of times a packet is retransmitted before the connection is considered to have
+    /// timed out
+    pub max_resends: u8,
+
+    /// The time to wait without sending anything before sending a ping to keep the connection
+    /// alive
+    pub ping_interval: Duration,
+}
+
+/// The medium that a connection's packets are sent over
+#[async_trait]
+pub trait Link: Send + 'static {
+    /// Whether packets sent over the link may be lost, and so must be retransmitted
+    const LOSSY: bool;
+
+    /// Sends the provided encoded packet to the peer
+    async fn send(&mut self, data: &[u8]) -> Result<(), IoError>;
+
+    /// Closes the link once the connection has been closed
+    async fn close(&mut self) {}
+}
+
+/// A [`Link`] to a single peer over a UDP socket, which may be shared with other connections
+#[derive(Debug)]
+pub struct Datagram {
+    socket: Arc<UdpSocket>,
+    peer: SocketAddr,
+}
+
+impl Datagram {
+    /// Creates a new [`Datagram`] link to the provided peer over the provided socket
+    pub fn new(socket: Arc<UdpSocket>, peer: SocketAddr) -> Self {
+        Self { socket, peer }
+    }
+}
+
+#[async_trait]
+impl Link for Datagram {
+    const LOSSY: bool = true;
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), IoError> {
+        let _ = self.socket.send_to(data, self.peer).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S> Link for WriteHalf<S>
+where
+    S: AsyncWrite + Send + 'static,
+{
+    const LOSSY: bool = false;
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), IoError> {
+        self.write_all(data).await?;
+        self.flush().await
+    }
+
+    async fn close(&mut self) {
+        let _ = self.shutdown().await;
+    }
+}
+
+/// Reads packets sent by the provided peer from the provided socket, decoding them using the
+/// provided function and passing them into the provided channel until it is closed
+pub async fn read_datagrams<P, E, F>(
+    socket: Arc<UdpSocket>,
+    peer: SocketAddr,
+    decode: F,
+    packets: mpsc::UnboundedSender<Result<P, ConnectionError<E>>>,
+) where
+    F: Fn(&[u8]) -> Result<P, E>,
+{
+    let mut buffer = vec![0; MAX_DATAGRAM_LENGTH];
+    loop {
+        let (length, address) = tokio::select! {
+            received = socket.recv_from(&mut buffer) => match received {
+                Ok(received) => received,
+                Err(_) => return,
+            },
+            _ = packets.closed() => return,
+        };
+
+        if address != peer {
+            continue;
+        }
+
+        // packets that cannot be decoded are treated as if they were lost
+        if let Ok(packet) = decode(&buffer[..length]) {
+            if packets.send(Ok(packet)).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// A request made of a connection's task by its [`Handle`]
+#[derive(Debug)]
+enum Command<E> {
+    /// Send the contained message on the contained substream
+    Send(u8, Vec<u8>),
+
+    /// Disconnect once everything sent has been acknowledged, reporting the outcome over the
+    /// contained channel
+    Disconnect(DisconnectWaiter<E>),
+}
+
+/// A handle to the task driving an established connection
+///
+/// Dropping it disconnects from the peer once everything sent has been acknowledged
+#[derive(Debug)]
+pub struct Handle<E> {
+    max_substream_id: u8,
+    commands: mpsc::UnboundedSender<Command<E>>,
+    messages: mpsc::UnboundedReceiver<Message<E>>,
+}
+
+impl<E> Handle<E> {
+    /// Returns the highest substream id that may be used on the connection
+    pub fn max_substream_id(&self) -> u8 {
+        self.max_substream_id
+    }
+
+    /// Sends the provided message to the peer on the provided substream, fragmenting it if
+    /// necessary
+    ///
+    /// The message is queued to be sent by the connection's task, so this only fails if the
+    /// substream does not exist or the connection has been closed
+    pub fn send(&self, substream_id: u8, message: &[u8]) -> Result<(), ConnectionError<E>> {
+        if substream_id > self.max_substream_id {
+            return Err(ConnectionError::InvalidSubstream(substream_id));
+        }
+
+        self.commands
+            .send(Command::Send(substream_id, message.to_vec()))
+            .map_err(|_| ConnectionError::Closed)
+    }
+
+    /// Receives the next message sent by the peer, along with the id of the substream it was sent
+    /// on
+    ///
+    /// Once the connection has been closed, this returns the error that closed it (if any), and
+    /// [`ConnectionError::Closed`] after that
+    pub async fn recv(&mut self) -> Result<(u8, Vec<u8>), ConnectionError<E>> {
+        self.messages.recv().await.ok_or(ConnectionError::Closed)?
+    }
+
+    /// Disconnects from the peer once everything sent has been acknowledged
+    pub async fn disconnect(self) -> Result<(), ConnectionError<E>> {
+        let (sender, receiver) = oneshot::channel();
+        self.commands
+            .send(Command::Disconnect(sender))
+            .map_err(|_| ConnectionError::Closed)?;
+        receiver.await.map_err(|_| ConnectionError::Closed)?
+    }
+}
+
+/// A packet that has been sent but not yet acknowledged
+#[derive(Debug)]
+struct Unacknowledged {
+    data: Vec<u8>,
+    sent: Instant,
+    resends: u8,
+}
+
+/// Whether a connection remains open after handling a packet
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Flow {
+    Continue,
+    Closed,
+}
+
+/// The state of a single substream of a connection
+///
+/// Every substream has its own sequence of packets and its own RC4 keystreams, although all of
+/// them are keyed with the same encryption key
+struct Substream<P> {
+    next_sequence_id: u16,
+    expected_sequence_id: u16,
+    encryption: Option<Rc4>,
+    decryption: Option<Rc4>,
+    received: BTreeMap<u16, P>,
+    fragments: Vec<u8>,
+}
+
+/// The state of a connection, owned by its handshake and then by its task
+pub struct State<C, L>
+where
+    C: Codec,
+{
+    codec: C,
+    link: L,
+    reliability: Reliability,
+    substreams: Vec<Substream<C::Packet>>,
+    next_ping_id: u16,
+    unacknowledged: HashMap<(PacketKind, u8, u16), Unacknowledged>,
+    last_sent: Instant,
+    connect_response: Vec<u8>,
+}
+
+impl<C, L> State<C, L>
+where
+    C: Codec,
+    L: Link,
+{
+    /// Creates a new [`State`] with substreams up to the provided id, all of which start at the
+    /// provided sequence ids
+    ///
+    /// The payloads of data packets are encrypted using RC4 if an encryption key is provided
+    pub fn new(
+        codec: C,
+        link: L,
+        reliability: Reliability,
+        encryption_key: Option<&[u8]>,
+        max_substream_id: u8,
+        next_sequence_id: u16,
+        expected_sequence_id: u16,
+    ) -> Result<Self, ConnectionError<C::Error>> {
+        if matches!(encryption_key, Some(key) if key.is_empty()) {
+            return Err(ConnectionError::EmptyEncryptionKey);
+        }
+
+        Ok(Self {
+            codec,
+            link,
+            reliability,
+            substreams: (0..=max_substream_id)
+                .map(|_| Substream {
+                    next_sequence_id,
+                    expected_sequence_id,
+                    encryption: encryption_key.map(Rc4::new),
+                    decryption: encryption_key.map(Rc4::new),
+                    received: BTreeMap::new(),
+                    fragments: Vec::new(),
+                })
+                .collect(),
+            next_ping_id: 0,
+            unacknowledged: HashMap::new(),
+            last_sent: Instant::now(),
+            connect_response: Vec::new(),
+        })
+    }
+
+    /// Returns a reference to the [`State`]'s [`Codec`]
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    /// Returns a mutable reference to the [`State`]'s [`Codec`]
+    pub fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    /// Returns the highest substream id that may be used
+    pub fn max_substream_id(&self) -> u8 {
+        (self.substreams.len() - 1) as u8
+    }
+
+    /// Lowers the highest substream id that may be used to the provided one, if it is lower
+    pub fn truncate_substreams(&mut self, max_substream_id: u8) {
+        self.substreams.truncate(usize::from(max_substream_id) + 1);
+    }
+
+    /// Returns the next sequence id of the provided substream, advancing past it
+    ///
+    /// This is used to place the packets sent during the handshake in the reliable stream
+    pub fn next_sequence_id(&mut self, substream_id: u8) -> u16 {
+        let substream = &mut self.substreams[usize::from(substream_id)];
+        let sequence_id = substream.next_sequence_id;
+        substream.next_sequence_id = sequence_id.wrapping_add(1);
+        sequence_id
+    }
+
+    /// Sets the sequence id that the peer's reliable stream is expected to continue from on every
+    /// substream
+    pub fn expect_sequence_id(&mut self, sequence_id: u16) {
+        for substream in &mut self.substreams {
+            substream.expected_sequence_id = sequence_id;
+        }
+    }
+
+    /// Sets the payload of the acknowledgement of the peer's connect packet, which is empty by
+    /// default
+    ///
+    /// This is used by servers to respond to the data passed in the payload of the connect packet
+    pub fn respond_to_connect(&mut self, payload: Vec<u8>) {
+        self.connect_response = payload;
+    }
+
+    /// Creates a packet addressed to the peer
+    pub fn packet(
+        &self,
+        kind: PacketKind,
+        flags: PacketFlags,
+        substream_id: u8,
+        sequence_id: u16,
+        payload: Vec<u8>,
+    ) -> C::Packet {
+        self.codec
+            .packet(kind, flags, substream_id, sequence_id, 0, payload)
+    }
+
+    /// Sends the provided packet to the peer, keeping track of it until it has been acknowledged
+    /// if necessary
+    pub async fn send(&mut self, packet: C::Packet) -> Result<(), ConnectionError<C::Error>> {
+        let key = (packet.kind(), packet.substream_id(), packet.sequence_id());
+        let need_ack = packet.flags().contains(PacketFlags::NEED_ACK);
+        let data = self
+            .codec
+            .encode(packet)
+            .map_err(ConnectionError::PacketError)?;
+        self.link.send(&data).await?;
+        self.last_sent = Instant::now();
+
+        if need_ack {
+            let _ = self.unacknowledged.insert(
+                key,
+                Unacknowledged {
+                    data,
+                    sent: self.last_sent,
+                    resends: 0,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Sends the provided packet until a properly signed packet satisfying the provided predicate
+    /// is received, returning it
+    ///
+    /// This is used during the handshake, before the connection's task has been started
+    pub async fn exchange<F>(
+        &mut self,
+        packets: &mut Packets<C>,
+        packet: C::Packet,
+        predicate: F,
+    ) -> Result<C::Packet, ConnectionError<C::Error>>
+    where
+        F: Fn(&C::Packet) -> bool,
+    {
+        let data = self
+            .codec
+            .encode(packet)
+            .map_err(ConnectionError::PacketError)?;
+        let attempts = if L::LOSSY {
+            self.reliability.max_resends
+        } else {
+            0
+        };
+        for _ in 0..=attempts {
+            self.link.send(&data).await?;
+            let deadline = Instant::now() + self.reliability.resend_timeout;
+            while let Ok(received) = time::timeout_at(deadline, packets.recv()).await {
+                match received {
+                    Some(Ok(received))
+                        if predicate(&received) && self.codec.is_signed(&received) =>
+                    {
+                        return Ok(received)
+                    }
+                    Some(Ok(_)) => (),
+                    Some(Err(error)) => return Err(error),
+                    None => return Err(ConnectionError::Closed),
+                }
+            }
+        }
+
+        Err(ConnectionError::TimedOut)
+    }
+
+    /// Acknowledges the provided packet
+    pub async fn acknowledge(
+        &mut self,
+        packet: &C::Packet,
+    ) -> Result<(), ConnectionError<C::Error>> {
+        let payload = if packet.kind() == PacketKind::Connect {
+            self.connect_response.clone()
+        } else {
+            Vec::new()
+        };
+        let ack = self.codec.packet(
+            packet.kind(),
+            PacketFlags::ACK,
+            packet.substream_id(),
+            packet.sequence_id(),
+            packet.fragment_id(),
+            payload,
+        );
+        self.send(ack).await
+    }
+
+    /// Starts the connection's task, returning a [`Handle`] to it
+    pub fn spawn(self, packets: Packets<C>) -> Handle<C::Error> {
+        let (messages_sender, messages) = mpsc::unbounded_channel();
+        let (commands_sender, commands) = mpsc::unbounded_channel();
+        let max_substream_id = self.max_substream_id();
+        tokio::spawn(self.run(packets, commands, messages_sender));
+
+        Handle {
+            max_substream_id,
+            commands: commands_sender,
+            messages,
+        }
+    }
+
+    /// Fragments, encrypts and sends the provided message on the provided substream
+    async fn send_message(
+        &mut self,
+        substream_id: u8,
+        message: &[u8],
+    ) -> Result<(), ConnectionError<C::Error>> {
+        let mut fragments = message
+            .chunks(self.reliability.fragment_size.max(1))
+            .enumerate()
+            .peekable();
+
+        // empty messages are still sent as a single empty fragment
+        if fragments.peek().is_none() {
+            return self.send_fragment(substream_id, Vec::new(), 0).await;
+        }
+
+        while let Some((i, fragment)) = fragments.next() {
+            let fragment_id = if fragments.peek().is_some() {
+                (i % 0xFF) as u8 + 1
+            } else {
+                0
+            };
+            self.send_fragment(substream_id, fragment.to_vec(), fragment_id)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Encrypts and sends a single fragment of a message on the provided substream
+    async fn send_fragment(
+        &mut self,
+        substream_id: u8,
+        mut payload: Vec<u8>,
+        fragment_id: u8,
+    ) -> Result<(), ConnectionError<C::Error>> {
+        if let Some(encryption) = &mut self.substreams[usize::from(substream_id)].encryption {
+            encryption.apply(&mut payload);
+        }
+        let sequence_id = self.next_sequence_id(substream_id);
+
+        let packet = self.codec.packet(
+            PacketKind::Data,
+            PacketFlags::RELIABLE | PacketFlags::NEED_ACK | PacketFlags::HAS_SIZE,
+            substream_id,
+            sequence_id,
+            fragment_id,
+            payload,
+        );
+        self.send(packet).await
+    }
+
+    /// Handles a packet received from the peer
+    async fn handle(
+        &mut self,
+        packet: C::Packet,
+        messages: &Messages<C::Error>,
+    ) -> Result<Flow, ConnectionError<C::Error>> {
+        // packets that are not signed properly or belong to a substream that was not negotiated
+        // are treated as if they were lost
+        if !self.codec.is_signed(&packet) || packet.substream_id() > self.max_substream_id() {
+            return Ok(Flow::Continue);
+        }
+
+        if packet.flags().contains(PacketFlags::ACK) {
+            let _ = self.unacknowledged.remove(&(
+                packet.kind(),
+                packet.substream_id(),
+                packet.sequence_id(),
+            ));
+            return Ok(Flow::Continue);
+        }
+
+        if packet.kind() == PacketKind::Disconnect {
+            let acknowledgements = if L::LOSSY {
+                DISCONNECT_ACKNOWLEDGEMENTS
+            } else {
+                1
+            };
+            for _ in 0..acknowledgements {
+                self.acknowledge(&packet).await?;
+            }
+            return Ok(Flow::Closed);
+        }
+
+        if packet.flags().contains(PacketFlags::NEED_ACK) {
+            self.acknowledge(&packet).await?;
+        }
+
+        if packet.flags().contains(PacketFlags::RELIABLE) {
+            self.receive(packet, messages);
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    /// Places the provided reliable packet into the stream of received packets of its substream,
+    /// delivering any messages that have been completed in order
+    fn receive(&mut self, packet: C::Packet, messages: &Messages<C::Error>) {
+        let substream_id = packet.substream_id();
+        let substream = &mut self.substreams[usize::from(substream_id)];
+
+        // packets from before the expected one have already been delivered
+        if packet
+            .sequence_id()
+            .wrapping_sub(substream.expected_sequence_id)
+            >= 0x8000
+        {
+            return;
+        }
+        let _ = substream
+            .received
+            .entry(packet.sequence_id())
+            .or_insert(packet);
+
+        while let Some(mut packet) = substream.received.remove(&substream.expected_sequence_id) {
+            substream.expected_sequence_id = substream.expected_sequence_id.wrapping_add(1);
+            if packet.kind() != PacketKind::Data {
+                continue;
+            }
+
+            let payload = packet.payload_mut();
+            if let Some(decryption) = &mut substream.decryption {
+                decryption.apply(payload);
+            }
+            substream.fragments.append(payload);
+            if packet.fragment_id() == 0 {
+                let _ = messages.send(Ok((substream_id, mem::take(&mut substream.fragments))));
+            }
+        }
+    }
+
+    /// Retransmits any packets that have gone unacknowledged for too long, and pings the peer if
+    /// nothing has been sent in a while
+    async fn tick(&mut self) -> Result<(), ConnectionError<C::Error>> {
+        let now = Instant::now();
+        for unacknowledged in self.unacknowledged.values_mut() {
+            if now.duration_since(unacknowledged.sent) < self.reliability.resend_timeout {
+                continue;
+            }
+            if !L::LOSSY || unacknowledged.resends >= self.reliability.max_resends {
+                return Err(ConnectionError::TimedOut);
+            }
+
+            unacknowledged.resends += 1;
+            unacknowledged.sent = now;
+            self.link.send(&unacknowledged.data).await?;
+        }
+
+        if now.duration_since(self.last_sent) >= self.reliability.ping_interval {
+            let ping = self.packet(
+                PacketKind::Ping,
+                PacketFlags::NEED_ACK,
+                0,
+                self.next_ping_id,
+                Vec::new(),
+            );
+            self.next_ping_id = self.next_ping_id.wrapping_add(1);
+            self.send(ping).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Drives the connection until it is closed, reporting the reason it was closed to whoever is
+    /// waiting on it
+    async fn run(
+        mut self,
+        mut packets: Packets<C>,
+        mut commands: mpsc::UnboundedReceiver<Command<C::Error>>,
+        messages: Messages<C::Error>,
+    ) {
+        let mut disconnect_waiter = None;
+        let result = self
+            .drive(
+                &mut packets,
+                &mut commands,
+                &messages,
+                &mut disconnect_waiter,
+            )
+            .await;
+        self.link.close().await;
+
+        match disconnect_waiter {
+            Some(waiter) => {
+                let _ = waiter.send(result);
+            }
+            None => {
+                if let Err(error) = result {
+                    let _ = messages.send(Err(error));
+                }
+            }
+        }
+    }
+
+    /// Handles packets, commands and timers until the connection is closed
+    async fn drive(
+        &mut self,
+        packets: &mut Packets<C>,
+        commands: &mut mpsc::UnboundedReceiver<Command<C::Error>>,
+        messages: &Messages<C::Error>,
+        disconnect_waiter: &mut Option<DisconnectWaiter<C::Error>>,
+    ) -> Result<(), ConnectionError<C::Error>> {
+        let mut interval =
+            time::interval((self.reliability.resend_timeout / 4).max(MIN_TICK_INTERVAL));
+        let mut disconnecting = false;
+        let mut disconnect_id = None;
+
+        loop {
+            tokio::select! {
+                packet = packets.recv() => match packet {
+                    Some(Ok(packet)) => if self.handle(packet, messages).await? == Flow::Closed {
+                        return Ok(());
+                    },
+                    Some(Err(error)) => return Err(error),
+                    None => return Err(ConnectionError::Closed),
+                },
+                command = commands.recv(), if !disconnecting => match command {
+                    Some(Command::Send(substream_id, message)) => {
+                        self.send_message(substream_id, &message).await?
+                    }
+                    Some(Command::Disconnect(waiter)) => {
+                        *disconnect_waiter = Some(waiter);
+                        disconnecting = true;
+                    }
+                    None => disconnecting = true,
+                },
+                _ = interval.tick() => self.tick().await?,
+            }
+
+            // the disconnect packet is only sent once everything else has been acknowledged
+            match disconnect_id {
+                Some(id) => {
+                    if !self
+                        .unacknowledged
+                        .contains_key(&(PacketKind::Disconnect, 0, id))
+                    {
+                        return Ok(());
+                    }
+                }
+                None if disconnecting && self.unacknowledged.is_empty() => {
+                    let id = self.substreams[0].next_sequence_id;
+                    let disconnect = self.packet(
+                        PacketKind::Disconnect,
+                        PacketFlags::RELIABLE | PacketFlags::NEED_ACK,
+                        0,
+                        id,
+                        Vec::new(),
+                    );
+                    disconnect_id = Some(id);
+                    self.send(disconnect).await?;
+                }
+                None => (),
+            }
+        }
+    }
+}
+
+/// An enumeration over the errors that may occur while using a PRUDP connection, where `E` is the
+/// error returned when one of its packets cannot be encoded or decoded
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum ConnectionError<E> {
+    /// An error was encountered while performing io
+    #[error("An error was encountered while performing io")]
+    IoError(#[from] IoError),
+
+    /// An error was encountered while encoding or decoding a packet
+    #[error("An error was encountered while encoding or decoding a packet")]
+    PacketError(#[source] E),
+
+    /// An error returned when the provided address did not resolve to anything
+    #[error("The provided address did not resolve to anything")]
+    NoAddress,
+
+    /// An error returned when the provided encryption key is empty
+    #[error("The encryption key must not be empty")]
+    EmptyEncryptionKey,
+
+    /// An error returned when a message is sent on a substream that was not negotiated
+    #[error("Substream `{0}` was not negotiated during the handshake")]
+    InvalidSubstream(u8),
+
+    /// An error returned when the peer stopped acknowledging packets
+    #[error("The peer stopped acknowledging packets")]
+    TimedOut,
+
+    /// An error returned when the connection has been closed
+    #[error("The connection has been closed")]
+    Closed,
+}
+
+impl<E> From<ConnectionError<E>> for TransportError
+where
+    E: Error + Send + Sync + 'static,
+{
+    fn from(error: ConnectionError<E>) -> Self {
+        match error {
+            ConnectionError::Closed => Self::Closed,
+            ConnectionError::TimedOut => Self::TimedOut,
+            error => Self::Other(Box::new(error)),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The server end of PRUDP connections made over UDP
+//!
+//! A listener owns a UDP socket that is shared by every connection it accepts. The datagrams it
+//! receives are routed by [`dispatch`] to the connection established with their sender, or, if
+//! there is none, to the version's [`Handshake`], which answers them and decides when a connection
+//! has been made
+
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use tokio::{net::UdpSocket, sync::mpsc};
+
+use crate::prudp::{
+    connection::{ConnectionError, MAX_DATAGRAM_LENGTH},
+    packet::{Packet, PacketFlags},
+};
+
+/// The channel that the packets received from a peer are passed into its connection through
+pub type Peer<P, E> = mpsc::UnboundedSender<Result<P, ConnectionError<E>>>;
+
+/// The function used to respond to the data a client passes in the payload of its connect
+/// packet, returning [`None`] if the client is not to be accepted
+pub type Respond = Box<dyn Fn(&[u8]) -> Option<Vec<u8>> + Send + Sync>;
+
+/// What a [`Handshake`] makes of a packet received from an address without a connection
+#[derive(Debug)]
+pub enum Reply<P, E, T> {
+    /// Nothing is sent in response to the packet
+    Ignore,
+
+    /// The contained encoded packet is sent back to the address
+    Respond(Vec<u8>),
+
+    /// The contained connection has been established, and the packet, along with every packet
+    /// that follows it, is passed into it through the contained channel
+    Accept(T, Peer<P, E>),
+}
+
+/// The version-specific half of a listener, which decodes the datagrams it receives and answers
+/// the handshakes of clients
+pub trait Handshake: Send + 'static {
+    /// The packets of the version of PRUDP
+    type Packet: Packet;
+
+    /// The error returned when a packet cannot be encoded or decoded
+    type Error: Send + 'static;
+
+    /// The connections that are accepted
+    type Connection: Send + 'static;
+
+    /// Decodes the provided datagram, returning [`None`] if it cannot be decoded or is not
+    /// addressed to the listener
+    fn decode(&self, data: &[u8]) -> Option<Self::Packet>;
+
+    /// Answers the provided packet, which was received over the provided socket from the provided
+    /// address without a connection
+    ///
+    /// Acknowledgements are never passed to this, as there is nothing to answer them with
+    fn handshake(
+        &self,
+        socket: &Arc<UdpSocket>,
+        address: SocketAddr,
+        packet: &Self::Packet,
+    ) -> Reply<Self::Packet, Self::Error, Self::Connection>;
+}
+
+/// Reads packets from the provided socket, answering handshakes using the provided [`Handshake`]
+/// and routing the packets of established connections to their tasks
+///
+/// Accepted connections are passed into the provided channel. Once it has been closed and every
+/// connection that was accepted has been closed as well, this returns
+pub async fn dispatch<H>(
+    socket: Arc<UdpSocket>,
+    handshake: H,
+    connections: mpsc::UnboundedSender<H::Connection>,
+) where
+    H: Handshake,
+{
+    let mut peers: HashMap<SocketAddr, Peer<H::Packet, H::Error>> = HashMap::new();
+
+    // each connection reports the address of its peer through this channel once it is closed
+    let (closed_sender, mut closed) = mpsc::unbounded_channel();
+
+    let mut buffer = vec![0; MAX_DATAGRAM_LENGTH];
+    loop {
+        let (length, address) = tokio::select! {
+            received = socket.recv_from(&mut buffer) => match received {
+                Ok(received) => received,
+                Err(_) => return,
+            },
+            Some(address) = closed.recv() => {
+                // the address may have connected again since
+                if matches!(peers.get(&address), Some(peer) if peer.is_closed()) {
+                    let _ = peers.remove(&address);
+                }
+                continue;
+            }
+            // once the listener has been dropped, there is nothing left to do after the last
+            // connection has been closed
+            _ = connections.closed(), if peers.is_empty() => return,
+        };
+
+        // packets that cannot be decoded are treated as if they were lost
+        let packet = match handshake.decode(&buffer[..length]) {
+            Some(packet) => packet,
+            None => continue,
+        };
+
+        // the packets of a closed connection are handled as if they came from a new client
+        if let Some(peer) = peers.get(&address) {
+            if !peer.is_closed() {
+                let _ = peer.send(Ok(packet));
+                continue;
+            }
+            let _ = peers.remove(&address);
+        }
+
+        if packet.flags().contains(PacketFlags::ACK) {
+            continue;
+        }
+
+        match handshake.handshake(&socket, address, &packet) {
+            Reply::Ignore => (),
+            Reply::Respond(data) => {
+                let _ = socket.send_to(&data, address).await;
+            }
+            Reply::Accept(connection, peer) => {
+                let _ = peer.send(Ok(packet));
+
+                let (watched, closed_sender) = (peer.clone(), closed_sender.clone());
+                tokio::spawn(async move {
+                    watched.closed().await;
+                    let _ = closed_sender.send(address);
+                });
+                let _ = peers.insert(address, peer);
+
+                // if the listener has been dropped, the connection is dropped as well, which
+                // disconnects it
+                let _ = connections.send(connection);
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The machinery shared by the PRUDP transports
+//!
+//! Each version of PRUDP has its own wire format, signing scheme and handshake, which live in
+//! their own crates. Once a connection has been established, however, all of them acknowledge,
+//! retransmit, fragment, encrypt and order packets in the same way, which is implemented once in
+//! the [`connection`] module in terms of a version's [`Codec`](packet::Codec). Likewise, the
+//! routing of datagrams done by a server over UDP is implemented once in the [`listener`] module
+//! in terms of a version's [`Handshake`](listener::Handshake)
+
+pub mod connection;
+pub mod listener;
+pub mod packet;
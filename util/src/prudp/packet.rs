@@ -0,0 +1,104 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! The parts of a PRUDP packet that every version agrees on
+//!
+//! The kinds and flags of packets are shared as-is, while everything else about a version's
+//! packets is reached through the [`Packet`] and [`Codec`] traits
+
+use bitflags::bitflags;
+use num_derive::{FromPrimitive, ToPrimitive};
+use std::error::Error;
+
+/// Enumeration of the kinds of PRUDP packets
+#[derive(FromPrimitive, ToPrimitive, Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum PacketKind {
+    Syn = 0,
+    Connect = 1,
+    Data = 2,
+    Disconnect = 3,
+    Ping = 4,
+}
+
+bitflags! {
+    /// The flags that may be set on a PRUDP packet
+    #[derive(Default)]
+    pub struct PacketFlags: u16 {
+        /// The packet acknowledges another packet with the same kind, substream and sequence id
+        const ACK = 0x001;
+
+        /// The packet is a part of the reliable stream of packets
+        const RELIABLE = 0x002;
+
+        /// The packet must be acknowledged by its recipient
+        const NEED_ACK = 0x004;
+
+        /// The packet's header contains the size of its payload
+        ///
+        /// Only PRUDPv0 omits the size when this is not set. Every other version always includes
+        /// it, making the flag informational
+        const HAS_SIZE = 0x008;
+
+        /// The packet acknowledges several packets at once
+        const MULTI_ACK = 0x200;
+    }
+}
+
+/// A packet of some version of PRUDP, as seen by the [`connection`](super::connection) machinery
+pub trait Packet: Send + Sync + 'static {
+    /// Returns the kind of packet
+    fn kind(&self) -> PacketKind;
+
+    /// Returns the packet's flags
+    fn flags(&self) -> PacketFlags;
+
+    /// Returns the substream the packet is a part of
+    ///
+    /// This is always zero for versions without substreams
+    fn substream_id(&self) -> u8 {
+        0
+    }
+
+    /// Returns the packet's position in the sender's stream of packets on its substream
+    fn sequence_id(&self) -> u16;
+
+    /// Returns the packet's position within a fragmented message, where zero marks the last
+    /// fragment
+    fn fragment_id(&self) -> u8;
+
+    /// Returns a mutable reference to the packet's payload
+    fn payload_mut(&mut self) -> &mut Vec<u8>;
+}
+
+/// The version-specific half of one end of a connection, which creates, signs and encodes the
+/// packets it sends and checks the signatures of the packets it receives
+pub trait Codec: Send + Sync + 'static {
+    /// The packets of the version of PRUDP
+    type Packet: Packet;
+
+    /// The error returned when a packet cannot be encoded
+    type Error: Error + Send + Sync + 'static;
+
+    /// Creates a packet addressed to the peer, carrying whatever this end adds to every packet
+    fn packet(
+        &self,
+        kind: PacketKind,
+        flags: PacketFlags,
+        substream_id: u8,
+        sequence_id: u16,
+        fragment_id: u8,
+        payload: Vec<u8>,
+    ) -> Self::Packet;
+
+    /// Signs the provided packet and encodes it into bytes
+    fn encode(&self, packet: Self::Packet) -> Result<Vec<u8>, Self::Error>;
+
+    /// Returns `true` if the provided packet carries the signature expected of it
+    fn is_signed(&self, packet: &Self::Packet) -> bool;
+}
@@ -0,0 +1,94 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! An implementation of the RC4 stream cipher
+//!
+//! While it is long broken, it is still used by the PRUDP transports to encrypt the payloads of
+//! data packets, and is shared between them here
+
+use std::fmt;
+
+/// An instance of the RC4 stream cipher
+///
+/// PRUDP keeps a single keystream for each direction of a (sub)stream, so the payloads of data
+/// packets must be passed through it in the order of their sequence ids
+#[derive(Clone)]
+pub struct Rc4 {
+    state: [u8; 256],
+    i: u8,
+    j: u8,
+}
+
+impl Rc4 {
+    /// Creates a new [`Rc4`] instance using the provided key
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the provided key is empty
+    pub fn new(key: &[u8]) -> Self {
+        assert!(!key.is_empty(), "an rc4 key must not be empty");
+
+        let mut state = [0_u8; 256];
+        for (i, b) in state.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+
+        let mut j = 0_u8;
+        for i in 0..256 {
+            j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
+            state.swap(i, usize::from(j));
+        }
+
+        Self { state, i: 0, j: 0 }
+    }
+
+    /// Encrypts or decrypts the provided data in place, advancing the keystream
+    pub fn apply(&mut self, data: &mut [u8]) {
+        for b in data {
+            self.i = self.i.wrapping_add(1);
+            self.j = self.j.wrapping_add(self.state[usize::from(self.i)]);
+            self.state.swap(usize::from(self.i), usize::from(self.j));
+            *b ^= self.state[usize::from(
+                self.state[usize::from(self.i)].wrapping_add(self.state[usize::from(self.j)]),
+            )];
+        }
+    }
+}
+
+impl fmt::Debug for Rc4 {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the internal state is omitted to avoid leaking the keystream
+        formatter.debug_struct("Rc4").finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rc4_known_answer() {
+        let mut data = *b"Plaintext";
+        Rc4::new(b"Key").apply(&mut data);
+        assert_eq!(data, [0xBB, 0xF3, 0x16, 0xE8, 0xD9, 0x40, 0xAF, 0x0A, 0xD3]);
+    }
+
+    #[test]
+    fn rc4_is_a_stream() {
+        let mut whole = *b"Attack at dawn";
+        Rc4::new(b"Secret").apply(&mut whole);
+
+        let mut split = *b"Attack at dawn";
+        let mut rc4 = Rc4::new(b"Secret");
+        let (left, right) = split.split_at_mut(6);
+        rc4.apply(left);
+        rc4.apply(right);
+        assert_eq!(whole, split);
+    }
+}
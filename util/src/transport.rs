@@ -0,0 +1,49 @@
+//
+// ralsei - fast nintendo library in rust
+//
+// copyright (c) 2020-2021 superwhiskers <whiskerdev@protonmail.com>
+// this source code form is subject to the terms of the mozilla public
+// license, v. 2.0. if a copy of the mpl was not distributed with this
+// file, you can obtain one at http://mozilla.org/MPL/2.0/.
+//
+
+//! A transport-agnostic interface to an established connection
+//!
+//! NEX is spoken over a different transport depending on the console (PRUDPv0 on the 3ds, PRUDPv1
+//! on the Wii U, and PRUDP Lite elsewhere), each of which is implemented in its own crate. The
+//! [`Transport`] trait is implemented by the connections of all of them, allowing code built on
+//! top of them to be written once and handed whichever is appropriate as a `Box<dyn Transport>`.
+
+use async_trait::async_trait;
+use std::error::Error;
+use thiserror::Error;
+
+/// An established connection that whole messages can be sent and received over
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Sends the provided message to the peer
+    async fn send(&self, message: &[u8]) -> Result<(), TransportError>;
+
+    /// Receives the next message sent by the peer
+    async fn recv(&mut self) -> Result<Vec<u8>, TransportError>;
+
+    /// Disconnects from the peer once everything sent has been delivered
+    async fn disconnect(self: Box<Self>) -> Result<(), TransportError>;
+}
+
+/// An enumeration over the errors that may be returned by a [`Transport`]
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum TransportError {
+    /// An error returned when the connection has been closed
+    #[error("The connection has been closed")]
+    Closed,
+
+    /// An error returned when the peer stopped responding
+    #[error("The peer stopped responding")]
+    TimedOut,
+
+    /// An error encountered by the underlying transport
+    #[error("An error was encountered by the underlying transport")]
+    Other(#[source] Box<dyn Error + Send + Sync>),
+}
@@ -176,6 +176,10 @@ pub macro generate_xml_struct_read($name:expr, $reader:ident, $buffer_pool:expr,
                     }
                 }
                 Event::Text(_) => continue,
+
+                // empty elements contain no data, so they are handled as if they were absent
+                Event::Empty(_) => continue,
+
                 e => {
                     return Err(Error::Formatting(FormattingError::UnexpectedEvent(
                         format!("{:?}", e),